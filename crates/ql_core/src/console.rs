@@ -0,0 +1,78 @@
+//! Talking to a running server through its console (stdin).
+
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+
+use crate::{LaunchedProcess, info};
+
+#[derive(Debug, Error)]
+pub enum ConsoleError {
+    #[error("server console isn't available\n(enable logging for this server to use it)")]
+    NoStdin,
+    #[error("while writing to server console:\n{0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl LaunchedProcess {
+    /// Sends a command to the server console,
+    /// as if it was typed in the terminal.
+    ///
+    /// A leading `/` is optional (the console ignores it).
+    ///
+    /// # Errors
+    /// - The process wasn't started with a piped stdin
+    ///   (logging disabled), or it's a client
+    /// - The process has exited (broken pipe)
+    pub async fn send_command(&self, command: &str) -> Result<(), ConsoleError> {
+        let mut child = self.child.lock().await;
+        let stdin = child.stdin.as_mut().ok_or(ConsoleError::NoStdin)?;
+        stdin
+            .write_all(format!("{}\n", command.trim_end()).as_bytes())
+            .await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    /// Gracefully stops the server with the `stop` command
+    /// (`end` for proxies), killing it if it doesn't exit within `timeout`.
+    ///
+    /// Classic servers (and servers without console access)
+    /// are killed right away, since they don't have a `stop` command.
+    ///
+    /// # Errors
+    /// If the process couldn't be killed.
+    pub async fn stop(&self, timeout: Duration) -> Result<(), ConsoleError> {
+        let command = if self.is_proxy { "end" } else { "stop" };
+        if self.is_classic_server || self.send_command(command).await.is_err() {
+            return self.kill().await;
+        }
+
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            if self.child.lock().await.try_wait()?.is_some() {
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+
+        info!(
+            "Server didn't stop within {}s, killing it",
+            timeout.as_secs()
+        );
+        self.kill().await
+    }
+
+    /// Forcefully kills the process.
+    ///
+    /// # Errors
+    /// If the process couldn't be killed.
+    pub async fn kill(&self) -> Result<(), ConsoleError> {
+        let mut child = self.child.lock().await;
+        if child.try_wait()?.is_none() {
+            child.start_kill()?;
+        }
+        Ok(())
+    }
+}
@@ -63,6 +63,20 @@ pub struct InstanceConfigJson {
     pub is_classic_server: Option<bool>,
     /// Whether this is a server, not a client
     is_server: Option<bool>,
+    /// **Server only**: who accepted the Minecraft EULA and when.
+    /// `None` if it was never accepted in the launcher
+    pub eula_accepted: Option<EulaAcceptance>,
+    /// **Server only, Linux only**: CPU, memory, priority
+    /// and filesystem limits for the server process.
+    /// `None` runs it unrestricted (besides `ram_in_mb`)
+    pub resource_limits: Option<ResourceLimits>,
+    /// **Server only**: announce the server on the local network,
+    /// like singleplayer worlds opened to LAN (default: `false`)
+    pub lan_broadcast: Option<bool>,
+    /// **Server only**: a jar in the server folder to run, instead
+    /// of the one the launcher uses for [`Self::mod_type`].
+    /// Set for imported servers (eg: `paper-1.21.1-123.jar`)
+    pub server_jar: Option<String>,
 
     /// Close launcher after client starts, **deprecated**
     // Since: v0.4
@@ -117,6 +131,10 @@ impl InstanceConfigJson {
 
             is_server: Some(kind.is_server()),
             is_classic_server: Some(is_classic_server),
+            eula_accepted: None,
+            resource_limits: None,
+            lan_broadcast: None,
+            server_jar: None,
 
             omniarchive: None,
             close_on_start: None,
@@ -466,6 +484,62 @@ impl CustomJarConfig {
     }
 }
 
+/// A record of the Minecraft EULA
+/// (<https://aka.ms/MinecraftEULA>) being accepted for a server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EulaAcceptance {
+    /// User account (on this computer) that accepted it
+    pub by: String,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Limits for a server process, for hosting several
+/// servers on one machine. Only applied on Linux.
+///
+/// Whatever the system doesn't support (eg: cgroups not being
+/// delegated to the user) is skipped with a warning,
+/// instead of stopping the server from starting.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// CPU time in percent of one core (eg: `200` for two cores).
+    /// Needs the cgroup v2 `cpu` controller
+    pub cpu_percent: Option<u32>,
+    /// Memory ceiling for the whole process (heap and everything
+    /// else Java uses), in MB. Should be well above `ram_in_mb`.
+    /// Needs the cgroup v2 `memory` controller
+    pub memory_mb: Option<usize>,
+    /// Scheduling priority from -20 (highest) to 19 (lowest),
+    /// as with `nice`. Going below 0 needs root
+    pub nice: Option<i8>,
+    /// Disk access priority, as with `ionice`
+    pub io_class: Option<IoClass>,
+    /// Priority within `io_class` from 0 (highest) to 7 (lowest)
+    pub io_priority: Option<u8>,
+    /// Only let the server write inside its own folder
+    /// (and a private `/tmp`). Needs `bwrap` (bubblewrap)
+    pub restrict_dir: bool,
+}
+
+impl ResourceLimits {
+    #[must_use]
+    pub fn has_cgroup_limits(&self) -> bool {
+        self.cpu_percent.is_some() || self.memory_mb.is_some()
+    }
+}
+
+/// `ionice` scheduling class
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IoClass {
+    /// Goes first, needs root
+    Realtime,
+    /// The default
+    BestEffort,
+    /// Only when no other program needs the disk
+    Idle,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MainClassMode {
     SafeFallback,
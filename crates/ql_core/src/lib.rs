@@ -33,13 +33,18 @@ use std::{
 use tokio::process::Child;
 
 pub mod clean;
+mod console;
 pub mod constants;
 mod error;
 /// Common utilities for working with files.
 pub mod file_utils;
+/// Hex encoding and MD5.
+pub mod hash;
 pub mod jarmod;
 /// JSON structs for version, instance config, Fabric, Forge, Optifine, Quilt, Neoforge, etc.
 pub mod json;
+/// Reading and editing NBT files (`level.dat`, `servers.dat`, region files).
+pub mod nbt;
 /// Logging macros.
 pub mod print;
 mod progress;
@@ -48,6 +53,7 @@ pub mod request;
 mod structs;
 
 pub use crate::json::InstanceConfigJson;
+pub use console::ConsoleError;
 pub use constants::*;
 pub use error::{
     DownloadFileError, IntoIoError, IntoJsonError, IntoStringError, IoError, JsonDownloadError,
@@ -325,6 +331,17 @@ impl Instance {
     pub const fn is_server(&self) -> bool {
         self.kind.is_server()
     }
+
+    /// Deletes the instance when setting it up failed
+    /// partway (eg: while installing a loader or mods),
+    /// so a broken one isn't left behind.
+    pub async fn remove_unfinished(&self) {
+        let path = self.get_instance_path();
+        info!("Removing unfinished {}", self.name);
+        if let Err(error) = tokio::fs::remove_dir_all(&path).await {
+            err!("Couldn't remove {path:?}: {error}");
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -651,6 +668,9 @@ pub struct LaunchedProcess {
     /// - Downloaded and extracted from zip
     /// - Don't have a stop command (?), need to be killed
     pub is_classic_server: bool,
+    /// Velocity/BungeeCord proxies have no worlds,
+    /// and are stopped with `end` instead of `stop`
+    pub is_proxy: bool,
 }
 
 type ReadLogOut = Result<(ExitStatus, Instance, Option<Diagnostic>), ReadError>;
@@ -682,6 +702,18 @@ impl LaunchedProcess {
     }
 }
 
+/// Lowercase hex string of bytes (eg: of a hash).
+#[must_use]
+pub fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut out, n| {
+            _ = write!(out, "{n:02x}");
+            out
+        })
+}
+
 #[must_use]
 pub fn sanitize_instance_name(mut name: String) -> String {
     let mut disallowed = vec![
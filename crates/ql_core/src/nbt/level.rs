@@ -0,0 +1,296 @@
+//! World info from `level.dat`, and editing its common fields.
+//!
+//! See <https://minecraft.wiki/w/Java_Edition_level_format>
+
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{Instance, IntoIoError, err, file_utils};
+
+use super::{Compound, NbtError, NbtFile, Tag, get_path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameMode {
+    #[default]
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl GameMode {
+    pub const ALL: &[Self] = &[
+        Self::Survival,
+        Self::Creative,
+        Self::Adventure,
+        Self::Spectator,
+    ];
+
+    fn from_id(id: i64) -> Self {
+        match id {
+            1 => Self::Creative,
+            2 => Self::Adventure,
+            3 => Self::Spectator,
+            _ => Self::Survival,
+        }
+    }
+
+    fn id(self) -> i32 {
+        match self {
+            Self::Survival => 0,
+            Self::Creative => 1,
+            Self::Adventure => 2,
+            Self::Spectator => 3,
+        }
+    }
+}
+
+impl Display for GameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Survival => "Survival",
+                Self::Creative => "Creative",
+                Self::Adventure => "Adventure",
+                Self::Spectator => "Spectator",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: &[Self] = &[Self::Peaceful, Self::Easy, Self::Normal, Self::Hard];
+
+    fn from_id(id: i64) -> Self {
+        match id {
+            0 => Self::Peaceful,
+            1 => Self::Easy,
+            3 => Self::Hard,
+            _ => Self::Normal,
+        }
+    }
+
+    fn id(self) -> i8 {
+        match self {
+            Self::Peaceful => 0,
+            Self::Easy => 1,
+            Self::Normal => 2,
+            Self::Hard => 3,
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Peaceful => "Peaceful",
+                Self::Easy => "Easy",
+                Self::Normal => "Normal",
+                Self::Hard => "Hard",
+            }
+        )
+    }
+}
+
+/// Info about a world, read from its `level.dat`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldInfo {
+    /// The world's folder
+    pub folder: PathBuf,
+    /// The name shown in-game (`LevelName`)
+    pub name: String,
+    /// `None` if the file doesn't have one (very old versions)
+    pub seed: Option<i64>,
+    pub game_mode: GameMode,
+    pub hardcore: bool,
+    pub difficulty: Difficulty,
+    pub allow_commands: bool,
+    /// The game version that last saved the world
+    /// (`None` before 1.9)
+    pub version: Option<String>,
+    pub last_played: Option<DateTime<Utc>>,
+}
+
+impl WorldInfo {
+    /// Reads `level.dat` in the world folder.
+    ///
+    /// # Errors
+    /// If the file couldn't be read or is corrupted.
+    pub async fn load(folder: &Path) -> Result<Self, NbtError> {
+        let file = NbtFile::load(&folder.join("level.dat")).await?;
+        let data = data(&file.root)?;
+        Ok(Self::from_data(folder, data))
+    }
+
+    fn from_data(folder: &Path, data: &Compound) -> Self {
+        let int = |path: &[&str]| get_path(data, path).and_then(Tag::as_i64);
+        Self {
+            folder: folder.to_owned(),
+            name: data
+                .get("LevelName")
+                .and_then(Tag::as_str)
+                .map_or_else(|| folder_name(folder), str::to_owned),
+            // 1.16+ moved the seed into `WorldGenSettings`
+            seed: int(&["WorldGenSettings", "seed"]).or_else(|| int(&["RandomSeed"])),
+            game_mode: GameMode::from_id(int(&["GameType"]).unwrap_or_default()),
+            hardcore: int(&["hardcore"]).is_some_and(|n| n != 0),
+            difficulty: Difficulty::from_id(int(&["Difficulty"]).unwrap_or(2)),
+            allow_commands: int(&["allowCommands"]).is_some_and(|n| n != 0),
+            version: get_path(data, &["Version", "Name"])
+                .and_then(Tag::as_str)
+                .map(str::to_owned),
+            last_played: int(&["LastPlayed"]).and_then(DateTime::from_timestamp_millis),
+        }
+    }
+
+    /// Writes the editable fields (name, game mode, hardcore,
+    /// difficulty, cheats) back to `level.dat`, keeping
+    /// everything else. The previous file is kept as `level.dat_old`,
+    /// like the game does.
+    ///
+    /// Don't call this while the world is open,
+    /// the game will overwrite it.
+    ///
+    /// # Errors
+    /// If the file couldn't be read/written or is corrupted.
+    pub async fn save(&self) -> Result<(), NbtError> {
+        let path = self.folder.join("level.dat");
+        let mut file = NbtFile::load(&path).await?;
+        let data = file
+            .root
+            .get_mut("Data")
+            .and_then(Tag::as_compound_mut)
+            .ok_or_else(|| NbtError::MissingField("Data".to_owned()))?;
+
+        data.insert("LevelName".to_owned(), Tag::String(self.name.clone()));
+        data.insert("GameType".to_owned(), Tag::Int(self.game_mode.id()));
+        data.insert("hardcore".to_owned(), Tag::Byte(self.hardcore.into()));
+        data.insert("Difficulty".to_owned(), Tag::Byte(self.difficulty.id()));
+        data.insert(
+            "allowCommands".to_owned(),
+            Tag::Byte(self.allow_commands.into()),
+        );
+
+        let old = self.folder.join("level.dat_old");
+        tokio::fs::copy(&path, &old).await.path(&old)?;
+        file.save(&path).await
+    }
+
+    /// Player data files (`playerdata/<uuid>.dat`) of this world.
+    ///
+    /// # Errors
+    /// If the folder couldn't be read.
+    pub async fn player_data_files(&self) -> Result<Vec<PathBuf>, NbtError> {
+        let dir = self.folder.join("playerdata");
+        if !file_utils::exists(&dir).await {
+            return Ok(Vec::new());
+        }
+        let mut files = Vec::new();
+        let mut entries = tokio::fs::read_dir(&dir).await.dir(&dir)?;
+        while let Some(entry) = entries.next_entry().await.dir(&dir)? {
+            let path = entry.path();
+            if path.extension().is_some_and(|n| n == "dat") {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+fn data(root: &Compound) -> Result<&Compound, NbtError> {
+    root.get("Data")
+        .and_then(Tag::as_compound)
+        .ok_or_else(|| NbtError::MissingField("Data".to_owned()))
+}
+
+fn folder_name(folder: &Path) -> String {
+    folder
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Lists the worlds of an instance, most recently played first.
+///
+/// - Clients: every folder in `.minecraft/saves/`
+/// - Servers: every folder in the server directory with
+///   a `level.dat` (the main world, and for Spigot-based
+///   servers, the separate nether/end worlds)
+///
+/// Worlds whose `level.dat` can't be read are skipped.
+///
+/// # Errors
+/// If the folder couldn't be read.
+pub async fn list_worlds(instance: &Instance) -> Result<Vec<WorldInfo>, NbtError> {
+    let dir = if instance.is_server() {
+        instance.get_dot_minecraft_path()
+    } else {
+        instance.get_dot_minecraft_path().join("saves")
+    };
+    if !file_utils::exists(&dir).await {
+        return Ok(Vec::new());
+    }
+
+    let mut worlds = Vec::new();
+    let mut entries = tokio::fs::read_dir(&dir).await.dir(&dir)?;
+    while let Some(entry) = entries.next_entry().await.dir(&dir)? {
+        let path = entry.path();
+        if !file_utils::exists(&path.join("level.dat")).await {
+            continue;
+        }
+        match WorldInfo::load(&path).await {
+            Ok(info) => worlds.push(info),
+            Err(err) => err!("Couldn't read world {path:?}: {err}"),
+        }
+    }
+    worlds.sort_by_key(|n| std::cmp::Reverse(n.last_played));
+    Ok(worlds)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{Compound, Difficulty, GameMode, Tag, WorldInfo};
+
+    #[test]
+    fn reads_modern_and_legacy_seed() {
+        let mut settings = Compound::new();
+        settings.insert("seed".to_owned(), Tag::Long(-42));
+        let mut data = Compound::new();
+        data.insert("WorldGenSettings".to_owned(), Tag::Compound(settings));
+        data.insert("GameType".to_owned(), Tag::Int(1));
+        data.insert("LastPlayed".to_owned(), Tag::Long(1_700_000_000_000));
+
+        let info = WorldInfo::from_data(Path::new("saves/New World"), &data);
+        assert_eq!(info.name, "New World");
+        assert_eq!(info.seed, Some(-42));
+        assert_eq!(info.game_mode, GameMode::Creative);
+        assert_eq!(info.difficulty, Difficulty::Normal);
+        assert_eq!(info.last_played.unwrap().timestamp(), 1_700_000_000);
+
+        let mut data = Compound::new();
+        data.insert("RandomSeed".to_owned(), Tag::Long(7));
+        let info = WorldInfo::from_data(Path::new("world"), &data);
+        assert_eq!(info.seed, Some(7));
+    }
+}
@@ -0,0 +1,616 @@
+//! Reading and writing Minecraft's
+//! [NBT](https://minecraft.wiki/w/NBT_format) format
+//! (Java edition, big-endian).
+//!
+//! - [`NbtFile`]: a whole file (`level.dat`, `servers.dat`,
+//!   player data, ...), compressed with gzip, zlib or not at all
+//! - [`region::Region`]: chunks in region files (`.mca`)
+//! - [`level`]: world info from `level.dat`
+//! - [`servers_dat`]: the multiplayer server list of a client
+
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
+
+use flate2::{
+    Compression as Level,
+    read::{GzDecoder, ZlibDecoder},
+    write::{GzEncoder, ZlibEncoder},
+};
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use crate::{IntoIoError, IoError};
+
+pub mod level;
+pub mod region;
+pub mod servers_dat;
+
+/// Named tags, in the order they were read.
+pub type Compound = IndexMap<String, Tag>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// All elements have the same type
+    List(Vec<Tag>),
+    Compound(Compound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+const TAG_END: u8 = 0;
+
+impl Tag {
+    fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+
+    /// The value of any integer tag (`Byte`, `Short`, `Int`, `Long`).
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Tag::Byte(n) => Some(i64::from(*n)),
+            Tag::Short(n) => Some(i64::from(*n)),
+            Tag::Int(n) => Some(i64::from(*n)),
+            Tag::Long(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// `Byte` tags are used as booleans.
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_i64().map(|n| n != 0)
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        if let Tag::String(s) = self {
+            Some(s)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub fn as_compound(&self) -> Option<&Compound> {
+        if let Tag::Compound(c) = self {
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub fn as_compound_mut(&mut self) -> Option<&mut Compound> {
+        if let Tag::Compound(c) = self {
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        if let Tag::List(l) = self {
+            Some(l)
+        } else {
+            None
+        }
+    }
+}
+
+/// Follows `path` through nested compounds.
+#[must_use]
+pub fn get_path<'a>(compound: &'a Compound, path: &[&str]) -> Option<&'a Tag> {
+    let (last, parents) = path.split_last()?;
+    let mut current = compound;
+    for key in parents {
+        current = current.get(*key)?.as_compound()?;
+    }
+    current.get(*last)
+}
+
+/// Follows `path` through nested compounds, for editing.
+#[must_use]
+pub fn get_path_mut<'a>(compound: &'a mut Compound, path: &[&str]) -> Option<&'a mut Tag> {
+    let (last, parents) = path.split_last()?;
+    let mut current = compound;
+    for key in parents {
+        current = current.get_mut(*key)?.as_compound_mut()?;
+    }
+    current.get_mut(*last)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// Used by most files (`level.dat`, player data)
+    #[default]
+    Gzip,
+    /// Used by chunks in region files
+    Zlib,
+    /// Used by `servers.dat`
+    None,
+}
+
+impl Compression {
+    /// Guesses the compression from the first bytes.
+    #[must_use]
+    pub fn detect(data: &[u8]) -> Self {
+        match data {
+            [0x1f, 0x8b, ..] => Compression::Gzip,
+            [0x78, ..] => Compression::Zlib,
+            _ => Compression::None,
+        }
+    }
+
+    /// # Errors
+    /// If the data isn't validly compressed.
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, NbtError> {
+        let mut out = Vec::new();
+        match self {
+            Compression::Gzip => GzDecoder::new(data).read_to_end(&mut out),
+            Compression::Zlib => ZlibDecoder::new(data).read_to_end(&mut out),
+            Compression::None => return Ok(data.to_vec()),
+        }
+        .map_err(NbtError::Compression)?;
+        Ok(out)
+    }
+
+    /// # Errors
+    /// If compression fails (shouldn't happen).
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>, NbtError> {
+        Ok(match self {
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+                encoder.write_all(data).map_err(NbtError::Compression)?;
+                encoder.finish().map_err(NbtError::Compression)?
+            }
+            Compression::Zlib => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Level::default());
+                encoder.write_all(data).map_err(NbtError::Compression)?;
+                encoder.finish().map_err(NbtError::Compression)?
+            }
+            Compression::None => data.to_vec(),
+        })
+    }
+}
+
+/// An NBT file: a named root compound.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NbtFile {
+    /// Usually empty
+    pub name: String,
+    pub root: Compound,
+    /// Kept to save the file the same way
+    pub compression: Compression,
+}
+
+impl NbtFile {
+    /// Parses a (possibly compressed) NBT file.
+    ///
+    /// # Errors
+    /// If the data isn't valid (compressed) NBT.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, NbtError> {
+        let compression = Compression::detect(data);
+        let data = compression.decompress(data)?;
+        let (name, root) = read_root(&data)?;
+        Ok(Self {
+            name,
+            root,
+            compression,
+        })
+    }
+
+    /// # Errors
+    /// If compression fails (shouldn't happen).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, NbtError> {
+        self.compression
+            .compress(&write_root(&self.name, &self.root))
+    }
+
+    /// # Errors
+    /// If the file couldn't be read, or isn't valid NBT.
+    pub async fn load(path: &Path) -> Result<Self, NbtError> {
+        let data = tokio::fs::read(path).await.path(path)?;
+        Self::from_bytes(&data)
+    }
+
+    /// Saves the file, first writing to a temporary
+    /// file so it can't be left half-written.
+    ///
+    /// # Errors
+    /// If the file couldn't be written.
+    pub async fn save(&self, path: &Path) -> Result<(), NbtError> {
+        let data = self.to_bytes()?;
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = Path::new(&temp);
+        tokio::fs::write(temp, data).await.path(temp)?;
+        tokio::fs::rename(temp, path).await.path(path)?;
+        Ok(())
+    }
+}
+
+/// Reads an uncompressed root tag (must be a compound).
+///
+/// # Errors
+/// If the data isn't valid NBT.
+pub fn read_root(data: &[u8]) -> Result<(String, Compound), NbtError> {
+    let mut reader = Reader { data, pos: 0 };
+    let id = reader.u8()?;
+    if id != 10 {
+        return Err(NbtError::RootNotCompound(id));
+    }
+    let name = reader.string()?;
+    let Tag::Compound(root) = reader.tag(id, 0)? else {
+        unreachable!("read a compound")
+    };
+    Ok((name, root))
+}
+
+/// Writes an uncompressed root compound.
+#[must_use]
+pub fn write_root(name: &str, root: &Compound) -> Vec<u8> {
+    let mut out = vec![10];
+    write_string(&mut out, name);
+    write_compound(&mut out, root);
+    out
+}
+
+/// Real files never go this deep, this
+/// stops malicious files from overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], NbtError> {
+        let end = self.pos.checked_add(len).ok_or(NbtError::UnexpectedEnd)?;
+        let bytes = self
+            .data
+            .get(self.pos..end)
+            .ok_or(NbtError::UnexpectedEnd)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], NbtError> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, NbtError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn len(&mut self) -> Result<usize, NbtError> {
+        let len = i32::from_be_bytes(self.array()?);
+        // Each element takes at least a byte, so
+        // this can't be more than what's left
+        usize::try_from(len)
+            .ok()
+            .filter(|n| *n <= self.data.len() - self.pos)
+            .ok_or(NbtError::InvalidLength(len))
+    }
+
+    fn string(&mut self) -> Result<String, NbtError> {
+        let len = u16::from_be_bytes(self.array()?);
+        decode_mutf8(self.take(len.into())?)
+    }
+
+    fn tag(&mut self, id: u8, depth: usize) -> Result<Tag, NbtError> {
+        if depth > MAX_DEPTH {
+            return Err(NbtError::TooDeep);
+        }
+        Ok(match id {
+            1 => Tag::Byte(i8::from_be_bytes(self.array()?)),
+            2 => Tag::Short(i16::from_be_bytes(self.array()?)),
+            3 => Tag::Int(i32::from_be_bytes(self.array()?)),
+            4 => Tag::Long(i64::from_be_bytes(self.array()?)),
+            5 => Tag::Float(f32::from_be_bytes(self.array()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.array()?)),
+            7 => {
+                let len = self.len()?;
+                Tag::ByteArray(
+                    self.take(len)?
+                        .iter()
+                        .map(|n| i8::from_be_bytes([*n]))
+                        .collect(),
+                )
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let element = self.u8()?;
+                let len = self.len()?;
+                if element == TAG_END && len > 0 {
+                    return Err(NbtError::InvalidTagType(element));
+                }
+                let list = (0..len)
+                    .map(|_| self.tag(element, depth + 1))
+                    .collect::<Result<_, _>>()?;
+                Tag::List(list)
+            }
+            10 => {
+                let mut compound = Compound::new();
+                loop {
+                    let id = self.u8()?;
+                    if id == TAG_END {
+                        break;
+                    }
+                    let name = self.string()?;
+                    compound.insert(name, self.tag(id, depth + 1)?);
+                }
+                Tag::Compound(compound)
+            }
+            11 => {
+                let len = self.len()?;
+                Tag::IntArray(
+                    (0..len)
+                        .map(|_| self.array().map(i32::from_be_bytes))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            12 => {
+                let len = self.len()?;
+                Tag::LongArray(
+                    (0..len)
+                        .map(|_| self.array().map(i64::from_be_bytes))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            _ => return Err(NbtError::InvalidTagType(id)),
+        })
+    }
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    let len = i32::try_from(len).unwrap_or(i32::MAX);
+    out.extend_from_slice(&len.to_be_bytes());
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    // Strings longer than this can't be stored, so they're
+    // cut off before the first character that doesn't fit
+    let mut encoded_len = 0;
+    let end = s
+        .char_indices()
+        .find_map(|(i, c)| {
+            encoded_len += mutf8_len(c);
+            (encoded_len > usize::from(u16::MAX)).then_some(i)
+        })
+        .unwrap_or(s.len());
+    let bytes = encode_mutf8(&s[..end]);
+    let len = u16::try_from(bytes.len()).unwrap_or(u16::MAX);
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(&bytes);
+}
+
+fn write_compound(out: &mut Vec<u8>, compound: &Compound) {
+    for (name, tag) in compound {
+        out.push(tag.id());
+        write_string(out, name);
+        write_tag(out, tag);
+    }
+    out.push(TAG_END);
+}
+
+fn write_tag(out: &mut Vec<u8>, tag: &Tag) {
+    match tag {
+        Tag::Byte(n) => out.extend_from_slice(&n.to_be_bytes()),
+        Tag::Short(n) => out.extend_from_slice(&n.to_be_bytes()),
+        Tag::Int(n) => out.extend_from_slice(&n.to_be_bytes()),
+        Tag::Long(n) => out.extend_from_slice(&n.to_be_bytes()),
+        Tag::Float(n) => out.extend_from_slice(&n.to_be_bytes()),
+        Tag::Double(n) => out.extend_from_slice(&n.to_be_bytes()),
+        Tag::ByteArray(arr) => {
+            write_len(out, arr.len());
+            out.extend(arr.iter().map(|n| n.to_be_bytes()[0]));
+        }
+        Tag::String(s) => write_string(out, s),
+        Tag::List(list) => {
+            out.push(list.first().map_or(TAG_END, Tag::id));
+            write_len(out, list.len());
+            for tag in list {
+                write_tag(out, tag);
+            }
+        }
+        Tag::Compound(compound) => write_compound(out, compound),
+        Tag::IntArray(arr) => {
+            write_len(out, arr.len());
+            for n in arr {
+                out.extend_from_slice(&n.to_be_bytes());
+            }
+        }
+        Tag::LongArray(arr) => {
+            write_len(out, arr.len());
+            for n in arr {
+                out.extend_from_slice(&n.to_be_bytes());
+            }
+        }
+    }
+}
+
+/// Java's "modified UTF-8": `\0` is two bytes, and characters
+/// outside the BMP are stored as UTF-16 surrogate pairs
+/// (each encoded like a 3-byte character).
+fn decode_mutf8(bytes: &[u8]) -> Result<String, NbtError> {
+    // Fast path, almost all strings
+    if !bytes.iter().any(|n| *n == 0xC0 || *n == 0xED || *n == 0) {
+        if let Ok(s) = std::str::from_utf8(bytes) {
+            return Ok(s.to_owned());
+        }
+    }
+
+    let mut units: Vec<u16> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = u16::from(bytes[i]);
+        let cont = |n: usize| {
+            bytes
+                .get(i + n)
+                .map(|n| u16::from(*n))
+                .filter(|n| n & 0xC0 == 0x80)
+                .ok_or(NbtError::InvalidString)
+        };
+        if b & 0x80 == 0 {
+            units.push(b);
+            i += 1;
+        } else if b & 0xE0 == 0xC0 {
+            units.push(((b & 0x1F) << 6) | (cont(1)? & 0x3F));
+            i += 2;
+        } else if b & 0xF0 == 0xE0 {
+            units.push(((b & 0x0F) << 12) | ((cont(1)? & 0x3F) << 6) | (cont(2)? & 0x3F));
+            i += 3;
+        } else {
+            return Err(NbtError::InvalidString);
+        }
+    }
+    String::from_utf16(&units).map_err(|_| NbtError::InvalidString)
+}
+
+fn encode_mutf8(s: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(s.len());
+    for unit in s.encode_utf16() {
+        match unit {
+            0x0001..=0x007F => out.push(unit.to_be_bytes()[1]),
+            0x0000 | 0x0080..=0x07FF => {
+                out.push(0xC0 | ((unit >> 6) & 0x1F).to_be_bytes()[1]);
+                out.push(0x80 | (unit & 0x3F).to_be_bytes()[1]);
+            }
+            _ => {
+                out.push(0xE0 | (unit >> 12).to_be_bytes()[1]);
+                out.push(0x80 | ((unit >> 6) & 0x3F).to_be_bytes()[1]);
+                out.push(0x80 | (unit & 0x3F).to_be_bytes()[1]);
+            }
+        }
+    }
+    out
+}
+
+/// How many bytes `c` takes up in [`encode_mutf8`].
+fn mutf8_len(c: char) -> usize {
+    match u32::from(c) {
+        0x0001..=0x007F => 1,
+        0x0000 | 0x0080..=0x07FF => 2,
+        0x0800..=0xFFFF => 3,
+        // Surrogate pair
+        _ => 6,
+    }
+}
+
+const NBT_ERR_PREFIX: &str = "while reading/writing NBT file:\n";
+
+#[derive(Debug, Error)]
+pub enum NbtError {
+    #[error("{NBT_ERR_PREFIX}{0}")]
+    Io(#[from] IoError),
+    #[error("{NBT_ERR_PREFIX}(de)compression error: {0}")]
+    Compression(std::io::Error),
+    #[error("{NBT_ERR_PREFIX}file ended unexpectedly (corrupted?)")]
+    UnexpectedEnd,
+    #[error("{NBT_ERR_PREFIX}invalid tag type: {0}")]
+    InvalidTagType(u8),
+    #[error("{NBT_ERR_PREFIX}root tag must be a compound, found type {0}")]
+    RootNotCompound(u8),
+    #[error("{NBT_ERR_PREFIX}invalid length: {0}")]
+    InvalidLength(i32),
+    #[error("{NBT_ERR_PREFIX}invalid string encoding")]
+    InvalidString,
+    #[error("{NBT_ERR_PREFIX}tags are nested too deeply")]
+    TooDeep,
+    #[error("{NBT_ERR_PREFIX}missing field: {0}")]
+    MissingField(String),
+    #[error("{NBT_ERR_PREFIX}unsupported chunk compression: {0}")]
+    UnsupportedCompression(u8),
+    #[error("{NBT_ERR_PREFIX}chunk {0}, {1} isn't in this region (must be 0-31)")]
+    ChunkOutOfRange(usize, usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Compound, Compression, NbtFile, Tag, decode_mutf8, encode_mutf8, mutf8_len, write_string,
+    };
+
+    #[test]
+    fn roundtrip() {
+        let mut inner = Compound::new();
+        inner.insert("name".to_owned(), Tag::String("Bananrama".to_owned()));
+        inner.insert("longs".to_owned(), Tag::LongArray(vec![1, -2, i64::MAX]));
+
+        let mut root = Compound::new();
+        root.insert("byte".to_owned(), Tag::Byte(-1));
+        root.insert("double".to_owned(), Tag::Double(0.5));
+        root.insert(
+            "list".to_owned(),
+            Tag::List(vec![Tag::Compound(inner.clone()), Tag::Compound(inner)]),
+        );
+        root.insert("empty".to_owned(), Tag::List(Vec::new()));
+        root.insert("bytes".to_owned(), Tag::ByteArray(vec![1, -128, 127]));
+
+        for compression in [Compression::Gzip, Compression::Zlib, Compression::None] {
+            let file = NbtFile {
+                name: "hello world".to_owned(),
+                root: root.clone(),
+                compression,
+            };
+            let bytes = file.to_bytes().unwrap();
+            assert_eq!(NbtFile::from_bytes(&bytes).unwrap(), file);
+        }
+    }
+
+    #[test]
+    fn modified_utf8() {
+        for s in ["plain", "nul\0byte", "ümlaut", "emoji 🎉"] {
+            assert_eq!(decode_mutf8(&encode_mutf8(s)).unwrap(), s);
+        }
+        assert_eq!(encode_mutf8("\0"), [0xC0, 0x80]);
+        // Surrogate pair, not 4-byte UTF-8
+        assert_eq!(encode_mutf8("🎉").len(), 6);
+    }
+
+    #[test]
+    fn long_strings_cut_at_characters() {
+        for s in [
+            "é".repeat(40_000),
+            format!("{}🎉", "a".repeat(65_532)),
+            "a".repeat(70_000),
+        ] {
+            let mut out = Vec::new();
+            write_string(&mut out, &s);
+            let len = usize::from(u16::from_be_bytes([out[0], out[1]]));
+            assert_eq!(len, out.len() - 2);
+            let decoded = decode_mutf8(&out[2..]).unwrap();
+            assert!(s.starts_with(&decoded));
+            assert!(len + s[decoded.len()..].chars().next().map_or(0, mutf8_len) > 65_535);
+        }
+        for c in ['\0', 'a', 'ü', '€', '🎉'] {
+            assert_eq!(mutf8_len(c), encode_mutf8(&c.to_string()).len());
+        }
+    }
+}
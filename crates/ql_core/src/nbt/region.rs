@@ -0,0 +1,228 @@
+//! Region files (`region/r.<x>.<z>.mca`), each storing
+//! 32x32 chunks as compressed NBT.
+//!
+//! See <https://minecraft.wiki/w/Region_file_format>
+
+use std::path::{Path, PathBuf};
+
+use crate::IntoIoError;
+
+use super::{Compression, NbtError, NbtFile};
+
+const SECTOR: usize = 4096;
+const HEADER_LEN: usize = SECTOR * 2;
+/// Set in the compression byte when the chunk is
+/// too big and stored in a separate `c.<x>.<z>.mcc` file
+const EXTERNAL_FLAG: u8 = 0x80;
+
+/// A region file, loaded into memory.
+///
+/// Chunk coordinates are relative to the region (0-31).
+pub struct Region {
+    path: PathBuf,
+    data: Vec<u8>,
+}
+
+impl Region {
+    /// # Errors
+    /// If the file couldn't be read.
+    pub async fn load(path: &Path) -> Result<Self, NbtError> {
+        let mut data = tokio::fs::read(path).await.path(path)?;
+        if data.len() < HEADER_LEN {
+            // Empty region files are valid (no chunks generated yet)
+            data.resize(HEADER_LEN, 0);
+        }
+        Ok(Self {
+            path: path.to_owned(),
+            data,
+        })
+    }
+
+    /// # Errors
+    /// If the file couldn't be written.
+    pub async fn save(&self) -> Result<(), NbtError> {
+        let mut temp = self.path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let temp = Path::new(&temp);
+        tokio::fs::write(temp, &self.data).await.path(temp)?;
+        tokio::fs::rename(temp, &self.path).await.path(&self.path)?;
+        Ok(())
+    }
+
+    /// The region file's path for the chunk at
+    /// absolute chunk coordinates `x`, `z`.
+    #[must_use]
+    pub fn path_for_chunk(world: &Path, x: i32, z: i32) -> PathBuf {
+        world
+            .join("region")
+            .join(format!("r.{}.{}.mca", x >> 5, z >> 5))
+    }
+
+    fn index(x: usize, z: usize) -> Result<usize, NbtError> {
+        if x < 32 && z < 32 {
+            Ok(x + z * 32)
+        } else {
+            Err(NbtError::ChunkOutOfRange(x, z))
+        }
+    }
+
+    fn location(&self, index: usize) -> (usize, usize) {
+        let entry = &self.data[index * 4..index * 4 + 4];
+        let offset = u32::from_be_bytes([0, entry[0], entry[1], entry[2]]);
+        (offset as usize * SECTOR, usize::from(entry[3]) * SECTOR)
+    }
+
+    /// Coordinates of all chunks stored in this region.
+    #[must_use]
+    pub fn chunks(&self) -> Vec<(usize, usize)> {
+        (0..1024)
+            .filter(|i| self.location(*i).0 != 0)
+            .map(|i| (i % 32, i / 32))
+            .collect()
+    }
+
+    /// When the chunk was last saved (seconds since epoch).
+    ///
+    /// # Errors
+    /// If `x` or `z` are out of range.
+    pub fn timestamp(&self, x: usize, z: usize) -> Result<u32, NbtError> {
+        let i = SECTOR + Self::index(x, z)? * 4;
+        Ok(u32::from_be_bytes(
+            self.data[i..i + 4].try_into().expect("4 bytes"),
+        ))
+    }
+
+    /// Reads a chunk, or `None` if it hasn't been generated.
+    ///
+    /// # Errors
+    /// - `x` or `z` are out of range
+    /// - the chunk is corrupted
+    /// - the chunk uses LZ4 or custom compression
+    pub async fn read_chunk(&self, x: usize, z: usize) -> Result<Option<NbtFile>, NbtError> {
+        let (offset, _) = self.location(Self::index(x, z)?);
+        if offset == 0 {
+            return Ok(None);
+        }
+        let header = self
+            .data
+            .get(offset..offset + 5)
+            .ok_or(NbtError::UnexpectedEnd)?;
+        let len = u32::from_be_bytes(header[..4].try_into().expect("4 bytes")) as usize;
+        let kind = header[4];
+
+        let compression = match kind & !EXTERNAL_FLAG {
+            1 => Compression::Gzip,
+            2 => Compression::Zlib,
+            3 => Compression::None,
+            n => return Err(NbtError::UnsupportedCompression(n)),
+        };
+
+        let data = if kind & EXTERNAL_FLAG == 0 {
+            self.data
+                .get(offset + 5..offset + 4 + len)
+                .ok_or(NbtError::UnexpectedEnd)?
+                .to_vec()
+        } else {
+            let (rx, rz) = self.region_coords();
+            let path = self.path.with_file_name(format!(
+                "c.{}.{}.mcc",
+                rx * 32 + x as i32,
+                rz * 32 + z as i32
+            ));
+            tokio::fs::read(&path).await.path(&path)?
+        };
+
+        let (name, root) = super::read_root(&compression.decompress(&data)?)?;
+        Ok(Some(NbtFile {
+            name,
+            root,
+            compression,
+        }))
+    }
+
+    /// Replaces a chunk. The new data is appended to the end
+    /// of the file (the old sectors are left unused, like the game does).
+    ///
+    /// Call [`Region::save`] afterwards.
+    ///
+    /// # Errors
+    /// - `x` or `z` are out of range
+    /// - the chunk is too big to fit in a region file (over 1 MiB compressed)
+    pub fn write_chunk(&mut self, x: usize, z: usize, chunk: &NbtFile) -> Result<(), NbtError> {
+        let index = Self::index(x, z)?;
+        let compression = match chunk.compression {
+            Compression::Gzip => 1,
+            Compression::Zlib => 2,
+            Compression::None => 3,
+        };
+        let data = chunk.to_bytes()?;
+
+        let sectors = (data.len() + 5).div_ceil(SECTOR);
+        let sector_count = u8::try_from(sectors)
+            .map_err(|_| NbtError::InvalidLength(i32::try_from(data.len()).unwrap_or(i32::MAX)))?;
+
+        let offset = self.data.len().div_ceil(SECTOR);
+        self.data.resize(offset * SECTOR, 0);
+        let len = u32::try_from(data.len() + 1).expect("fits in 255 sectors");
+        self.data.extend_from_slice(&len.to_be_bytes());
+        self.data.push(compression);
+        self.data.extend_from_slice(&data);
+        self.data.resize((offset + sectors) * SECTOR, 0);
+
+        let offset = u32::try_from(offset)
+            .expect("region files are small")
+            .to_be_bytes();
+        self.data[index * 4..index * 4 + 4].copy_from_slice(&[
+            offset[1],
+            offset[2],
+            offset[3],
+            sector_count,
+        ]);
+
+        let now = u32::try_from(chrono::Utc::now().timestamp()).unwrap_or_default();
+        let i = SECTOR + index * 4;
+        self.data[i..i + 4].copy_from_slice(&now.to_be_bytes());
+        Ok(())
+    }
+
+    /// Region coordinates from the file name (`r.<x>.<z>.mca`).
+    fn region_coords(&self) -> (i32, i32) {
+        let name = self
+            .path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        let mut parts = name.split('.').skip(1).map(|n| n.parse().unwrap_or(0));
+        (
+            parts.next().unwrap_or_default(),
+            parts.next().unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::Tag, NbtFile, Region};
+    use crate::nbt::{Compound, Compression};
+
+    #[tokio::test]
+    async fn write_then_read_chunk() {
+        let mut region = Region {
+            path: "r.0.0.mca".into(),
+            data: vec![0; super::HEADER_LEN],
+        };
+        let mut root = Compound::new();
+        root.insert("xPos".to_owned(), Tag::Int(3));
+        let chunk = NbtFile {
+            name: String::new(),
+            root,
+            compression: Compression::Zlib,
+        };
+
+        region.write_chunk(3, 5, &chunk).unwrap();
+        assert_eq!(region.chunks(), [(3, 5)]);
+        assert_eq!(region.read_chunk(3, 5).await.unwrap(), Some(chunk));
+        assert_eq!(region.read_chunk(0, 0).await.unwrap(), None);
+        assert!(region.read_chunk(32, 0).await.is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! The multiplayer server list of a client
+//! (`.minecraft/servers.dat`, uncompressed NBT).
+
+use std::path::PathBuf;
+
+use crate::{Instance, file_utils};
+
+use super::{Compound, Compression, NbtError, NbtFile, Tag};
+
+/// An entry in the multiplayer server list.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ServerEntry {
+    pub name: String,
+    /// Address, with an optional `:port`
+    pub ip: String,
+    /// Whether to download the server's resource pack
+    /// (`None`: ask the player)
+    pub accept_textures: Option<bool>,
+    /// Base64-encoded PNG, cached by the game
+    pub icon: Option<String>,
+    /// Any other fields, kept as-is
+    pub other: Compound,
+}
+
+impl ServerEntry {
+    #[must_use]
+    pub fn new(name: String, ip: String) -> Self {
+        Self {
+            name,
+            ip,
+            ..Default::default()
+        }
+    }
+
+    fn from_compound(mut compound: Compound) -> Self {
+        let mut take_str = |key: &str| match compound.shift_remove(key) {
+            Some(Tag::String(s)) => Some(s),
+            _ => None,
+        };
+        let name = take_str("name").unwrap_or_default();
+        let ip = take_str("ip").unwrap_or_default();
+        let icon = take_str("icon");
+        let accept_textures = compound
+            .shift_remove("acceptTextures")
+            .and_then(|n| n.as_bool());
+        Self {
+            name,
+            ip,
+            accept_textures,
+            icon,
+            other: compound,
+        }
+    }
+
+    fn into_compound(self) -> Compound {
+        let mut compound = Compound::new();
+        compound.insert("name".to_owned(), Tag::String(self.name));
+        compound.insert("ip".to_owned(), Tag::String(self.ip));
+        if let Some(icon) = self.icon {
+            compound.insert("icon".to_owned(), Tag::String(icon));
+        }
+        if let Some(accept) = self.accept_textures {
+            compound.insert("acceptTextures".to_owned(), Tag::Byte(accept.into()));
+        }
+        compound.extend(self.other);
+        compound
+    }
+}
+
+fn path(instance: &Instance) -> PathBuf {
+    instance.get_dot_minecraft_path().join("servers.dat")
+}
+
+/// Reads the server list of a client instance
+/// (empty if it doesn't exist yet).
+///
+/// # Errors
+/// If the file couldn't be read or is corrupted.
+pub async fn load(instance: &Instance) -> Result<Vec<ServerEntry>, NbtError> {
+    let path = path(instance);
+    if !file_utils::exists(&path).await {
+        return Ok(Vec::new());
+    }
+    let file = NbtFile::load(&path).await?;
+    Ok(from_root(file.root))
+}
+
+/// Overwrites the server list of a client instance.
+///
+/// # Errors
+/// If the file couldn't be written.
+pub async fn save(instance: &Instance, servers: Vec<ServerEntry>) -> Result<(), NbtError> {
+    let file = NbtFile {
+        name: String::new(),
+        root: to_root(servers),
+        compression: Compression::None,
+    };
+    file.save(&path(instance)).await
+}
+
+fn from_root(mut root: Compound) -> Vec<ServerEntry> {
+    match root.shift_remove("servers") {
+        Some(Tag::List(list)) => list
+            .into_iter()
+            .filter_map(|n| match n {
+                Tag::Compound(c) => Some(ServerEntry::from_compound(c)),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn to_root(servers: Vec<ServerEntry>) -> Compound {
+    let mut root = Compound::new();
+    root.insert(
+        "servers".to_owned(),
+        Tag::List(
+            servers
+                .into_iter()
+                .map(|n| Tag::Compound(n.into_compound()))
+                .collect(),
+        ),
+    );
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ServerEntry, Tag, from_root, to_root};
+
+    #[test]
+    fn keeps_unknown_fields() {
+        let mut entry = ServerEntry::new("Test".to_owned(), "localhost:25566".to_owned());
+        entry.accept_textures = Some(false);
+        entry.other.insert("hidden".to_owned(), Tag::Byte(0));
+
+        let servers = vec![entry, ServerEntry::new("B".to_owned(), "b.net".to_owned())];
+        assert_eq!(from_root(to_root(servers.clone())), servers);
+    }
+}
@@ -293,6 +293,34 @@ pub enum Diagnostic {
     OutOfStackSpace,
     #[error("Your mac's graphics drivers aren't working!\nThis is normal in virtual machines")]
     MacOSPixelFormat,
+    #[error(
+        "The world is locked (session.lock) by another server!\nIf none is running, the lock is left over from a crash"
+    )]
+    SessionLocked,
+    #[error("{}", port_in_use_msg(*.0))]
+    PortInUse(Option<u16>),
+    #[error("You need to accept the Minecraft EULA (eula.txt) to run the server")]
+    EulaNotAccepted,
+    #[error("{}", unsupported_java_msg(*.required))]
+    UnsupportedJavaVersion {
+        /// Minimum Java version needed,
+        /// if it could be found from the log
+        required: Option<usize>,
+    },
+    #[error("Out of memory! Allocate more memory (RAM) in the Edit tab")]
+    OutOfMemory,
+}
+
+fn port_in_use_msg(port: Option<u16>) -> String {
+    let port = port.map(|n| format!(" {n}")).unwrap_or_default();
+    format!("Port{port} is already in use!\nAnother server (or program) is probably using it")
+}
+
+fn unsupported_java_msg(required: Option<usize>) -> String {
+    let required = required
+        .map(|n| format!(" (Java {n}+)"))
+        .unwrap_or_default();
+    format!("This needs a newer version of Java{required}!")
 }
 
 impl Diagnostic {
@@ -325,12 +353,50 @@ impl Diagnostic {
                 || c(log, "Failed to find a suitable pixel format"))
         {
             Some(Diagnostic::MacOSPixelFormat)
+        } else if c(log, "session.lock: already locked")
+            || c(log, "Failed to check session lock")
+            || c(log, "The save is being accessed from another location")
+        {
+            Some(Diagnostic::SessionLocked)
+        } else if c(log, "FAILED TO BIND TO PORT") {
+            Some(Diagnostic::PortInUse(find_server_port(log)))
+        } else if c(log, "You need to agree to the EULA") {
+            Some(Diagnostic::EulaNotAccepted)
+        } else if c(log, "java.lang.UnsupportedClassVersionError") {
+            Some(Diagnostic::UnsupportedJavaVersion {
+                required: find_required_java(log),
+            })
+        } else if c(log, "java.lang.OutOfMemoryError: Java heap space")
+            || c(
+                log,
+                "java.lang.OutOfMemoryError: GC overhead limit exceeded",
+            )
+        {
+            Some(Diagnostic::OutOfMemory)
         } else {
             None
         }
     }
 }
 
+/// From `Starting Minecraft server on *:25565`
+fn find_server_port(log: &[String]) -> Option<u16> {
+    log.iter().find_map(|line| {
+        let (_, addr) = line.split_once("Starting Minecraft server on ")?;
+        addr.trim().rsplit(':').next()?.parse().ok()
+    })
+}
+
+/// From `... (class file version 65.0), this version of the Java Runtime ...`
+fn find_required_java(log: &[String]) -> Option<usize> {
+    log.iter().find_map(|line| {
+        let (_, rest) = line.split_once("class file version ")?;
+        let class_version: usize = rest.split('.').next()?.parse().ok()?;
+        // Class file version 52 is Java 8, 53 is Java 9, ...
+        class_version.checked_sub(44)
+    })
+}
+
 /// Represents a log event.
 /// Contains advanced information about the log line
 /// like the timestamp, class name, level and thread.
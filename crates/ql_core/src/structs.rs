@@ -20,6 +20,14 @@ pub enum Loader {
     OptiFine,
     #[serde(rename = "Paper")]
     Paper,
+    Folia,
+    Purpur,
+    /// Proxy server, not a loader (but installed like Paper)
+    Velocity,
+    /// Proxy server
+    BungeeCord,
+    Spigot,
+    Bukkit,
 
     // The launcher doesn't currently support these:
     #[serde(rename = "LiteLoader")]
@@ -58,6 +66,12 @@ impl Loader {
         Self::NeoForge,
         Self::OptiFine,
         Self::Paper,
+        Self::Folia,
+        Self::Purpur,
+        Self::Velocity,
+        Self::BungeeCord,
+        Self::Spigot,
+        Self::Bukkit,
         Self::Liteloader,
         Self::Modloader,
         Self::Rift,
@@ -73,6 +87,13 @@ impl Loader {
         matches!(self, Loader::Vanilla)
     }
 
+    /// Whether this is a proxy (connecting players to other
+    /// servers) rather than a server running a world.
+    #[must_use]
+    pub fn is_proxy(self) -> bool {
+        matches!(self, Loader::Velocity | Loader::BungeeCord)
+    }
+
     #[must_use]
     pub fn to_modrinth_str(self) -> &'static str {
         match self {
@@ -85,6 +106,12 @@ impl Loader {
             Loader::NeoForge => "neoforge",
             Loader::OptiFine => "optifine",
             Loader::Paper => "paper",
+            Loader::Folia => "folia",
+            Loader::Purpur => "purpur",
+            Loader::Velocity => "velocity",
+            Loader::BungeeCord => "bungeecord",
+            Loader::Spigot => "spigot",
+            Loader::Bukkit => "bukkit",
             Loader::Vanilla => " ",
         }
     }
@@ -99,6 +126,12 @@ impl Loader {
             Loader::Liteloader => "3",
             Loader::Rift
             | Loader::Paper
+            | Loader::Folia
+            | Loader::Purpur
+            | Loader::Velocity
+            | Loader::BungeeCord
+            | Loader::Spigot
+            | Loader::Bukkit
             | Loader::Modloader
             | Loader::OptiFine
             | Loader::Vanilla => {
@@ -108,6 +108,7 @@ pub async fn launch(
         child: Arc::new(Mutex::new(child)),
         instance: Instance::client(&instance_name),
         is_classic_server: false,
+        is_proxy: false,
     })
 }
 
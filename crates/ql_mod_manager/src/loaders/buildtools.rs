@@ -0,0 +1,297 @@
+//! Spigot and CraftBukkit (Bukkit) servers.
+//!
+//! Unlike other loaders, these can't be downloaded directly
+//! (for legal reasons), they have to be built from source with
+//! SpigotMC's [BuildTools](https://www.spigotmc.org/wiki/buildtools/).
+//!
+//! Building takes a few minutes, so built jars are cached in
+//! `QuantumLauncher/buildtools/cache/` for reuse by other servers
+//! of the same version.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{LazyLock, mpsc::Sender},
+};
+
+use ql_core::{
+    GenericProgress, Instance, IntoIoError, IoError, JsonError, LAUNCHER_DIR, Loader, RequestError,
+    download,
+    file_utils::exists,
+    impl_3_errs_jri, info,
+    json::{VersionDetails, instance_config::ModTypeInfo},
+    no_window, pt,
+};
+use ql_java_handler::{JavaInstallError, JavaVersion, get_java_binary};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    process::Command,
+    sync::Mutex,
+};
+
+use crate::loaders::{change_instance_type, paper::migrate_worlds_to_vanilla};
+
+const BUILDTOOLS_URL: &str = "https://hub.spigotmc.org/jenkins/job/BuildTools/lastSuccessfulBuild/artifact/target/BuildTools.jar";
+
+/// How many lines of BuildTools output to
+/// show in the error if the build fails
+const ERROR_LOG_LINES: usize = 15;
+
+/// Name of the server jar in the server directory
+#[must_use]
+pub fn server_jar_name(loader: Loader) -> Option<&'static str> {
+    match loader {
+        Loader::Spigot => Some("spigot_server.jar"),
+        Loader::Bukkit => Some("craftbukkit_server.jar"),
+        _ => None,
+    }
+}
+
+/// What BuildTools calls it (`--compile` argument,
+/// and prefix of the output jar)
+fn buildtools_name(loader: Loader) -> &'static str {
+    if let Loader::Bukkit = loader {
+        "craftbukkit"
+    } else {
+        "spigot"
+    }
+}
+
+/// Installs Spigot or CraftBukkit ([`Loader::Spigot`] or
+/// [`Loader::Bukkit`]) for a server, building it with BuildTools
+/// if there's no cached build for the server's version.
+///
+/// # Errors
+/// - BuildTools or Java couldn't be downloaded
+/// - The build failed (unsupported version, `git` not installed, ...)
+/// - Files couldn't be read or written
+pub async fn install(
+    instance: Instance,
+    loader: Loader,
+    progress: Option<&Sender<GenericProgress>>,
+    j_progress: Option<&Sender<GenericProgress>>,
+) -> Result<(), BuildToolsError> {
+    let Some(jar_name) = server_jar_name(loader) else {
+        return Err(BuildToolsError::UnsupportedLoader(loader));
+    };
+    if !instance.is_server() {
+        return Err(BuildToolsError::NotAServer);
+    }
+    info!("Installing {loader}");
+
+    let server_dir = instance.get_instance_path();
+    let details = VersionDetails::load(&instance).await?;
+    let version = details.get_id().to_owned();
+
+    let cached_jar = get_or_build(loader, &version, &details, progress, j_progress).await?;
+
+    send(progress, 3, "Copying server jar".to_owned());
+    let jar_path = server_dir.join(jar_name);
+    tokio::fs::copy(&cached_jar, &jar_path)
+        .await
+        .path(&cached_jar)?;
+
+    change_instance_type(&server_dir, loader, Some(ModTypeInfo::new_regular(version))).await?;
+
+    pt!("Done");
+    Ok(())
+}
+
+/// Returns the cached jar for this loader and version, building it first if needed.
+async fn get_or_build(
+    loader: Loader,
+    version: &str,
+    details: &VersionDetails,
+    progress: Option<&Sender<GenericProgress>>,
+    j_progress: Option<&Sender<GenericProgress>>,
+) -> Result<PathBuf, BuildToolsError> {
+    let buildtools_dir = LAUNCHER_DIR.join("buildtools");
+    let cache_dir = buildtools_dir.join("cache");
+    let name = buildtools_name(loader);
+    let cached_jar = cache_dir.join(format!("{name}-{version}.jar"));
+
+    // Builds share the work and output dirs
+    static BUILD_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+    let _guard = if let Ok(g) = BUILD_LOCK.try_lock() {
+        g
+    } else {
+        info!("Another server is already being built... Waiting...");
+        BUILD_LOCK.lock().await
+    };
+
+    // Might've been built while waiting
+    if exists(&cached_jar).await {
+        pt!("Using cached build");
+        return Ok(cached_jar);
+    }
+
+    send(progress, 0, "Downloading BuildTools".to_owned());
+    let buildtools_jar = buildtools_dir.join("BuildTools.jar");
+    download(BUILDTOOLS_URL).path(&buildtools_jar).await?;
+
+    let java_version = details
+        .javaVersion
+        .clone()
+        .map_or(JavaVersion::Java8, Into::into);
+    let java = get_java_binary(java_version, "java", j_progress).await?;
+
+    // BuildTools downloads a lot into its working directory
+    // (git repos, Maven), kept to make later builds faster
+    let work_dir = buildtools_dir.join("work");
+    let output_dir = buildtools_dir.join("output");
+    if exists(&output_dir).await {
+        tokio::fs::remove_dir_all(&output_dir)
+            .await
+            .path(&output_dir)?;
+    }
+    tokio::fs::create_dir_all(&work_dir).await.path(&work_dir)?;
+    tokio::fs::create_dir_all(&cache_dir)
+        .await
+        .path(&cache_dir)?;
+
+    pt!("Running BuildTools (this might take a while)");
+    send(progress, 1, "Building (this might take a while)".to_owned());
+    run_buildtools(
+        &java,
+        &buildtools_jar,
+        &work_dir,
+        &output_dir,
+        name,
+        version,
+        progress,
+    )
+    .await?;
+
+    let built_jar = find_output_jar(&output_dir, name)
+        .await?
+        .ok_or(BuildToolsError::NoOutputJar)?;
+    tokio::fs::rename(&built_jar, &cached_jar)
+        .await
+        .path(&built_jar)?;
+    Ok(cached_jar)
+}
+
+async fn run_buildtools(
+    java: &Path,
+    buildtools_jar: &Path,
+    work_dir: &Path,
+    output_dir: &Path,
+    name: &str,
+    version: &str,
+    progress: Option<&Sender<GenericProgress>>,
+) -> Result<(), BuildToolsError> {
+    let mut command = Command::new(java);
+    command
+        .arg("-jar")
+        .arg(buildtools_jar)
+        .args(["--rev", version, "--compile", name, "--output-dir"])
+        .arg(output_dir)
+        .current_dir(work_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    no_window!(command);
+
+    let mut child = command.spawn().path(java)?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    let stderr_task = tokio::spawn(async move {
+        let mut lines = Vec::new();
+        if let Some(stderr) = stderr {
+            let mut stderr = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = stderr.next_line().await {
+                lines.push(line);
+            }
+        }
+        lines
+    });
+
+    let mut log = Vec::new();
+    if let Some(stdout) = stdout {
+        let mut stdout = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = stdout.next_line().await {
+            send(progress, 1, line.clone());
+            log.push(line);
+        }
+    }
+
+    let status = child.wait().await.path(java)?;
+    log.extend(stderr_task.await.unwrap_or_default());
+    if status.success() {
+        Ok(())
+    } else {
+        let start = log.len().saturating_sub(ERROR_LOG_LINES);
+        Err(BuildToolsError::BuildFailed(log[start..].join("\n")))
+    }
+}
+
+async fn find_output_jar(output_dir: &Path, name: &str) -> Result<Option<PathBuf>, IoError> {
+    let mut entries = tokio::fs::read_dir(output_dir).await.dir(output_dir)?;
+    while let Some(entry) = entries.next_entry().await.dir(output_dir)? {
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if file_name.starts_with(name) && file_name.ends_with(".jar") {
+            return Ok(Some(entry.path()));
+        }
+    }
+    Ok(None)
+}
+
+fn send(progress: Option<&Sender<GenericProgress>>, done: usize, message: String) {
+    if let Some(progress) = progress {
+        _ = progress.send(GenericProgress {
+            done,
+            total: 4,
+            message: Some(message),
+            has_finished: false,
+        });
+    }
+}
+
+/// Goes back to the vanilla server.
+///
+/// # Errors
+/// If the server jar or config couldn't be modified.
+pub async fn uninstall(instance: Instance, loader: Loader) -> Result<(), BuildToolsError> {
+    let server_dir = instance.get_instance_path();
+    if let Some(jar_name) = server_jar_name(loader) {
+        let jar_path = server_dir.join(jar_name);
+        if exists(&jar_path).await {
+            tokio::fs::remove_file(&jar_path).await.path(jar_path)?;
+        }
+    }
+
+    // Bukkit stores the Nether and End in separate worlds
+    migrate_worlds_to_vanilla(&server_dir).await?;
+
+    change_instance_type(&server_dir, Loader::Vanilla, None).await?;
+    Ok(())
+}
+
+const BUILDTOOLS_ERR_PREFIX: &str = "while installing Spigot/Bukkit for Minecraft server:\n";
+
+#[derive(Debug, Error)]
+pub enum BuildToolsError {
+    #[error("{BUILDTOOLS_ERR_PREFIX}{0}")]
+    Request(#[from] RequestError),
+    #[error("{BUILDTOOLS_ERR_PREFIX}{0}")]
+    Io(#[from] IoError),
+    #[error("{BUILDTOOLS_ERR_PREFIX}json error: {0}")]
+    Json(#[from] JsonError),
+    #[error("{BUILDTOOLS_ERR_PREFIX}{0}")]
+    JavaInstall(#[from] JavaInstallError),
+    #[error(
+        "{BUILDTOOLS_ERR_PREFIX}BuildTools failed! Is this version supported, and is git installed?\n\n{0}"
+    )]
+    BuildFailed(String),
+    #[error("{BUILDTOOLS_ERR_PREFIX}BuildTools didn't produce a server jar")]
+    NoOutputJar,
+    #[error("{BUILDTOOLS_ERR_PREFIX}{0} isn't built with BuildTools")]
+    UnsupportedLoader(Loader),
+    #[error("{BUILDTOOLS_ERR_PREFIX}only servers are supported")]
+    NotAServer,
+}
+
+impl_3_errs_jri!(BuildToolsError, Json, Request, Io);
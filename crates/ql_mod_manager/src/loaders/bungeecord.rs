@@ -0,0 +1,85 @@
+//! [BungeeCord](https://www.spigotmc.org/wiki/bungeecord/),
+//! a proxy for connecting multiple servers together.
+//!
+//! Downloaded from SpigotMC's Jenkins (latest successful build).
+
+use ql_core::{
+    Instance, IntoIoError, IoError, JsonError, Loader, RequestError, download, file_utils::exists,
+    impl_3_errs_jri, info, json::instance_config::ModTypeInfo, pt,
+};
+use thiserror::Error;
+
+use crate::loaders::change_instance_type;
+
+const JOB_URL: &str = "https://ci.md-5.net/job/BungeeCord/lastSuccessfulBuild";
+
+/// Name of the proxy jar in the server directory
+pub const JAR_NAME: &str = "bungeecord_server.jar";
+
+/// Installs the latest BungeeCord build for a server.
+///
+/// # Errors
+/// - The build couldn't be downloaded
+/// - Files couldn't be read or written
+pub async fn install(instance: Instance) -> Result<(), BungeeCordError> {
+    if !instance.is_server() {
+        return Err(BungeeCordError::NotAServer);
+    }
+    info!("Installing BungeeCord");
+    let server_dir = instance.get_instance_path();
+
+    let build = download(&format!("{JOB_URL}/buildNumber"))
+        .user_agent_ql()
+        .string()
+        .await?;
+    let build = build.trim().to_owned();
+    pt!("Downloading build {build}");
+
+    let jar_path = server_dir.join(JAR_NAME);
+    download(&format!(
+        "{JOB_URL}/artifact/bootstrap/target/BungeeCord.jar"
+    ))
+    .user_agent_ql()
+    .path(&jar_path)
+    .await?;
+
+    change_instance_type(
+        &server_dir,
+        Loader::BungeeCord,
+        Some(ModTypeInfo::new_regular(build)),
+    )
+    .await?;
+
+    pt!("Done");
+    Ok(())
+}
+
+/// Goes back to the vanilla server.
+///
+/// # Errors
+/// If the proxy jar or config couldn't be modified.
+pub async fn uninstall(instance: Instance) -> Result<(), BungeeCordError> {
+    let server_dir = instance.get_instance_path();
+    let jar_path = server_dir.join(JAR_NAME);
+    if exists(&jar_path).await {
+        tokio::fs::remove_file(&jar_path).await.path(jar_path)?;
+    }
+    change_instance_type(&server_dir, Loader::Vanilla, None).await?;
+    Ok(())
+}
+
+const BUNGEECORD_ERR_PREFIX: &str = "while installing BungeeCord:\n";
+
+#[derive(Debug, Error)]
+pub enum BungeeCordError {
+    #[error("{BUNGEECORD_ERR_PREFIX}{0}")]
+    Request(#[from] RequestError),
+    #[error("{BUNGEECORD_ERR_PREFIX}{0}")]
+    Io(#[from] IoError),
+    #[error("{BUNGEECORD_ERR_PREFIX}json error: {0}")]
+    Json(#[from] JsonError),
+    #[error("{BUNGEECORD_ERR_PREFIX}only servers are supported")]
+    NotAServer,
+}
+
+impl_3_errs_jri!(BungeeCordError, Json, Request, Io);
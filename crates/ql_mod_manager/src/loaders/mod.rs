@@ -6,13 +6,15 @@ use std::{
     },
 };
 
-use crate::loaders::paper::PaperVer;
+use crate::loaders::paper::{BuildChannel, PaperProject, PaperVer};
 use forge::ForgeInstallProgress;
 use ql_core::{
     GenericProgress, Instance, IntoStringError, JsonFileError, Loader, Progress,
     json::{InstanceConfigJson, instance_config::ModTypeInfo},
 };
 
+pub mod buildtools;
+pub mod bungeecord;
 pub mod fabric;
 pub mod forge;
 pub mod neoforge;
@@ -99,20 +101,39 @@ pub async fn install_specified_loader(
                 .strerr()?;
         }
 
-        Loader::Paper => {
+        Loader::Paper | Loader::Folia | Loader::Purpur | Loader::Velocity => {
+            let Some(project) = PaperProject::from_loader(loader).filter(|_| instance.is_server())
+            else {
+                return Ok(LoaderInstallResult::Unsupported);
+            };
+            // Either a build number, or a channel ("stable"/"experimental")
+            // to install the latest build from
+            let version = match specified_version {
+                Some(s) => match BuildChannel::from_name(&s) {
+                    Some(channel) => PaperVer::Latest(channel),
+                    None => PaperVer::Id(s),
+                },
+                None => PaperVer::Latest(BuildChannel::Stable),
+            };
+            paper::install(instance.get_name().to_owned(), project, version)
+                .await
+                .strerr()?;
+        }
+
+        Loader::BungeeCord => {
             if !instance.is_server() {
                 return Ok(LoaderInstallResult::Unsupported);
             }
-            paper::install(
-                instance.get_name().to_owned(),
-                if let Some(s) = specified_version {
-                    PaperVer::Id(s)
-                } else {
-                    PaperVer::None
-                },
-            )
-            .await
-            .strerr()?;
+            bungeecord::install(instance).await.strerr()?;
+        }
+
+        Loader::Spigot | Loader::Bukkit => {
+            if !instance.is_server() {
+                return Ok(LoaderInstallResult::Unsupported);
+            }
+            buildtools::install(instance, loader, progress.as_deref(), None)
+                .await
+                .strerr()?;
         }
 
         Loader::OptiFine => {
@@ -145,9 +166,16 @@ pub async fn uninstall_loader(instance: Instance) -> Result<(), String> {
         Loader::OptiFine => optifine::uninstall(instance.get_name().to_owned(), true)
             .await
             .strerr(),
-        Loader::Paper => paper::uninstall(instance.get_name().to_owned())
-            .await
-            .strerr(),
+        Loader::Paper | Loader::Folia | Loader::Purpur | Loader::Velocity => {
+            let Some(project) = PaperProject::from_loader(loader) else {
+                return Ok(());
+            };
+            paper::uninstall(instance.get_name().to_owned(), project)
+                .await
+                .strerr()
+        }
+        Loader::BungeeCord => bungeecord::uninstall(instance).await.strerr(),
+        Loader::Spigot | Loader::Bukkit => buildtools::uninstall(instance, loader).await.strerr(),
         // Not yet supported
         Loader::Liteloader | Loader::Modloader | Loader::Rift | Loader::Vanilla => Ok(()),
     }
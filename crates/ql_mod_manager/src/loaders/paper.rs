@@ -1,6 +1,14 @@
+//! Paper and other server software from PaperMC's
+//! [Fill API](https://fill.papermc.io) (Folia, Velocity),
+//! as well as [Purpur](https://purpurmc.org).
+//!
+//! Downloaded builds are verified against the checksum
+//! provided by the API before being installed.
+
 use std::fmt::{Display, Formatter};
 use std::path::Path;
 
+use indexmap::IndexMap;
 use ql_core::file_utils::exists;
 use ql_core::{
     IntoIoError, IntoJsonError, IoError, JsonError, LAUNCHER_DIR, Loader, RequestError, file_utils,
@@ -9,6 +17,7 @@ use ql_core::{
     pt,
 };
 use ql_core::{download, impl_3_errs_jri};
+use ql_core::{hash::md5, to_hex};
 use serde::Deserialize;
 use thiserror::Error;
 
@@ -25,60 +34,169 @@ async fn move_dir(old_path: &Path, new_path: &Path) -> Result<(), IoError> {
     Ok(())
 }
 
+/// Server software installed through this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaperProject {
+    #[default]
+    Paper,
+    /// Paper with regionized multithreading
+    Folia,
+    /// Proxy, for connecting multiple servers together
+    Velocity,
+    /// Paper fork with extra gameplay options
+    Purpur,
+}
+
+impl PaperProject {
+    pub const ALL: &[Self] = &[Self::Paper, Self::Folia, Self::Velocity, Self::Purpur];
+
+    #[must_use]
+    pub fn from_loader(loader: Loader) -> Option<Self> {
+        Some(match loader {
+            Loader::Paper => Self::Paper,
+            Loader::Folia => Self::Folia,
+            Loader::Velocity => Self::Velocity,
+            Loader::Purpur => Self::Purpur,
+            _ => return None,
+        })
+    }
+
+    #[must_use]
+    pub fn loader(self) -> Loader {
+        match self {
+            PaperProject::Paper => Loader::Paper,
+            PaperProject::Folia => Loader::Folia,
+            PaperProject::Velocity => Loader::Velocity,
+            PaperProject::Purpur => Loader::Purpur,
+        }
+    }
+
+    /// Name of the server jar in the server directory
+    #[must_use]
+    pub fn jar_name(self) -> &'static str {
+        match self {
+            PaperProject::Paper => "paper_server.jar",
+            PaperProject::Folia => "folia_server.jar",
+            PaperProject::Velocity => "velocity_server.jar",
+            PaperProject::Purpur => "purpur_server.jar",
+        }
+    }
+
+    /// Whether builds are tied to a Minecraft version.
+    /// Velocity (a proxy) has its own versioning.
+    #[must_use]
+    pub fn follows_game_version(self) -> bool {
+        !matches!(self, PaperProject::Velocity)
+    }
+}
+
+impl Display for PaperProject {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.loader())
+    }
+}
+
+/// Which builds to offer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BuildChannel {
+    /// Only builds marked as stable (or recommended)
+    #[default]
+    Stable,
+    /// All builds, including alpha/beta ones
+    Experimental,
+}
+
+impl BuildChannel {
+    pub const ALL: &[Self] = &[Self::Stable, Self::Experimental];
+
+    /// Parses `"stable"` or `"experimental"` (case-insensitive).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "stable" => Some(Self::Stable),
+            "experimental" => Some(Self::Experimental),
+            _ => None,
+        }
+    }
+}
+
+impl Display for BuildChannel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildChannel::Stable => write!(f, "Stable"),
+            BuildChannel::Experimental => write!(f, "Experimental"),
+        }
+    }
+}
+
 pub enum PaperVer {
     Full(PaperVersion),
+    /// A build number
     Id(String),
-    None,
+    /// The newest build in the channel
+    Latest(BuildChannel),
 }
 
 impl From<Option<PaperVersion>> for PaperVer {
     fn from(v: Option<PaperVersion>) -> Self {
         match v {
             Some(v) => Self::Full(v),
-            None => Self::None,
+            None => Self::Latest(BuildChannel::Stable),
         }
     }
 }
 
 impl PaperVer {
-    async fn get(&self, version: &str) -> Result<PaperVersion, PaperInstallerError> {
-        if let PaperVer::Full(n) = self {
-            return Ok(n.clone());
-        }
+    async fn get(
+        &self,
+        project: PaperProject,
+        version: &str,
+    ) -> Result<PaperVersion, PaperInstallerError> {
+        let channel = match self {
+            PaperVer::Full(n) => return Ok(n.clone()),
+            PaperVer::Id(_) => BuildChannel::Experimental,
+            PaperVer::Latest(channel) => *channel,
+        };
 
-        let list = get_list_of_versions(version.to_owned()).await?;
-        Ok(match self {
-            PaperVer::Full(_) => unreachable!(),
-            PaperVer::Id(id) => list.into_iter().find(|n| n.id.to_string() == *id).ok_or(
-                PaperInstallerError::NoMatchingVersionFound(version.to_owned()),
-            )?,
-            PaperVer::None => list
-                .first()
-                .ok_or(PaperInstallerError::NoMatchingVersionFound(
-                    version.to_owned(),
-                ))?
-                .clone(),
-        })
+        let list = get_list_of_versions(project, version.to_owned(), channel).await?;
+        let not_found = || PaperInstallerError::NoMatchingVersionFound(project, version.to_owned());
+        match self {
+            PaperVer::Id(id) => list
+                .into_iter()
+                .find(|n| n.id.to_string() == *id)
+                .ok_or_else(not_found),
+            _ => list.into_iter().next().ok_or_else(not_found),
+        }
     }
 }
 
-pub async fn install(instance_name: String, version: PaperVer) -> Result<(), PaperInstallerError> {
-    info!("Installing Paper");
+/// Installs Paper, or another [`PaperProject`], for a server.
+///
+/// # Errors
+/// - The build couldn't be found or downloaded
+/// - The download didn't match its checksum
+/// - Files couldn't be read or written
+pub async fn install(
+    instance_name: String,
+    project: PaperProject,
+    version: PaperVer,
+) -> Result<(), PaperInstallerError> {
+    info!("Installing {project}");
     let server_dir = LAUNCHER_DIR.join("servers").join(&instance_name);
     let json = VersionDetails::load_from_path(&server_dir).await?;
 
-    let version = version.get(json.get_id()).await?;
+    let version = version.get(project, json.get_id()).await?;
+
+    pt!("Downloading jar ({version})");
+    let jar = download(&version.url).user_agent_ql().bytes().await?;
+    version.checksum.verify(&jar, &version.url)?;
 
-    pt!("Downloading jar");
-    let jar_path = server_dir.join("paper_server.jar");
-    download(&version.downloads.server.url)
-        .user_agent_ql()
-        .path(&jar_path)
-        .await?;
+    let jar_path = server_dir.join(project.jar_name());
+    tokio::fs::write(&jar_path, &jar).await.path(&jar_path)?;
 
     change_instance_type(
         &server_dir,
-        Loader::Paper,
+        project.loader(),
         Some(ModTypeInfo::new_regular(version.id.to_string())),
     )
     .await?;
@@ -87,82 +205,282 @@ pub async fn install(instance_name: String, version: PaperVer) -> Result<(), Pap
     Ok(())
 }
 
+/// Lists the builds of `project` for Minecraft `version`
+/// (ignored for Velocity), newest first.
+///
+/// # Errors
+/// - The version isn't supported by the project
+/// - The API couldn't be reached, or returned invalid data
 pub async fn get_list_of_versions(
+    project: PaperProject,
     version: String,
+    channel: BuildChannel,
 ) -> Result<Vec<PaperVersion>, PaperInstallerError> {
-    let url = format!("https://fill.papermc.io/v3/projects/paper/versions/{version}/builds");
-    let json = download(&url).string().await?;
+    let mut list = match project {
+        PaperProject::Purpur => get_purpur_builds(&version).await?,
+        PaperProject::Velocity => get_velocity_builds(channel).await?,
+        PaperProject::Paper | PaperProject::Folia => get_fill_builds(project, &version).await?,
+    };
+    if let BuildChannel::Stable = channel {
+        list.retain(|n| n.channel == BuildChannel::Stable);
+    }
+    Ok(list)
+}
+
+async fn get_fill_builds(
+    project: PaperProject,
+    version: &str,
+) -> Result<Vec<PaperVersion>, PaperInstallerError> {
+    let url = format!(
+        "https://fill.papermc.io/v3/projects/{}/versions/{version}/builds",
+        project.to_string().to_lowercase()
+    );
+    let json = match download(&url).user_agent_ql().string().await {
+        Ok(n) => n,
+        Err(RequestError::DownloadError { code, .. }) if code == reqwest::StatusCode::NOT_FOUND => {
+            return Err(PaperInstallerError::NoMatchingVersionFound(
+                project,
+                version.to_owned(),
+            ));
+        }
+        Err(err) => return Err(err.into()),
+    };
 
     let not_found = json.contains("\"version_not_found\"");
-    let json: Vec<PaperVersion> = match serde_json::from_str(&json).json(json) {
+    let builds: Vec<FillBuild> = match serde_json::from_str(&json).json(json) {
         Ok(n) => n,
         Err(e) => {
-            let result = Err(if not_found {
-                PaperInstallerError::NoMatchingVersionFound(version)
+            return Err(if not_found {
+                PaperInstallerError::NoMatchingVersionFound(project, version.to_owned())
             } else {
                 e.into()
             });
-            return result;
         }
     };
 
-    Ok(json)
+    Ok(builds
+        .into_iter()
+        .map(|build| PaperVersion {
+            id: build.id,
+            channel: if matches!(build.channel.as_str(), "STABLE" | "RECOMMENDED") {
+                BuildChannel::Stable
+            } else {
+                BuildChannel::Experimental
+            },
+            url: build.downloads.server.url,
+            checksum: Checksum::Sha256(build.downloads.server.checksums.sha256),
+        })
+        .collect())
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq)]
+/// Velocity versions aren't tied to Minecraft versions,
+/// so these are the builds of the newest one with builds in `channel`.
+///
+/// Velocity is developed as `-SNAPSHOT` versions, and their builds
+/// are what's marked stable, so the version name doesn't tell.
+async fn get_velocity_builds(
+    channel: BuildChannel,
+) -> Result<Vec<PaperVersion>, PaperInstallerError> {
+    const URL: &str = "https://fill.papermc.io/v3/projects/velocity";
+    let project: FillProject = download(URL).user_agent_ql().json().await?;
+
+    // Grouped by major version, newest first
+    for version in project.versions.into_values().flatten() {
+        let builds = get_fill_builds(PaperProject::Velocity, &version).await?;
+        if builds
+            .iter()
+            .any(|n| channel == BuildChannel::Experimental || n.channel == BuildChannel::Stable)
+        {
+            return Ok(builds);
+        }
+    }
+    Err(PaperInstallerError::NoMatchingVersionFound(
+        PaperProject::Velocity,
+        channel.to_string(),
+    ))
+}
+
+async fn get_purpur_builds(version: &str) -> Result<Vec<PaperVersion>, PaperInstallerError> {
+    // With the checksum of every build
+    let url = format!("https://api.purpurmc.org/v2/purpur/{version}?detailed=true");
+    let info: PurpurVersion = match download(&url).user_agent_ql().json().await {
+        Ok(n) => n,
+        Err(ql_core::JsonDownloadError::RequestError(RequestError::DownloadError {
+            code, ..
+        })) if code == reqwest::StatusCode::NOT_FOUND => {
+            return Err(PaperInstallerError::NoMatchingVersionFound(
+                PaperProject::Purpur,
+                version.to_owned(),
+            ));
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    // Newest first
+    Ok(info
+        .builds
+        .all
+        .into_iter()
+        .rev()
+        .filter(|n| n.result == "SUCCESS")
+        .filter_map(|build| {
+            Some(PaperVersion {
+                id: build.build.parse().ok()?,
+                // Purpur doesn't have channels, but marks
+                // builds of snapshot versions as experimental
+                channel: if build.version.contains('w') || build.version.contains('-') {
+                    BuildChannel::Experimental
+                } else {
+                    BuildChannel::Stable
+                },
+                url: format!(
+                    "https://api.purpurmc.org/v2/purpur/{}/{}/download",
+                    build.version, build.build
+                ),
+                checksum: Checksum::Md5(build.md5),
+            })
+        })
+        .collect())
+}
+
+/// A build of a [`PaperProject`].
+#[derive(Clone, Debug, PartialEq)]
 pub struct PaperVersion {
-    id: isize,
-    downloads: PaperDownloads,
+    pub id: isize,
+    pub channel: BuildChannel,
+    url: String,
+    checksum: Checksum,
 }
 
 impl Display for PaperVersion {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "version {}", self.id)
+        write!(f, "build {}", self.id)?;
+        if let BuildChannel::Experimental = self.channel {
+            write!(f, " (experimental)")?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq)]
-pub struct PaperDownloads {
+#[derive(Clone, Debug, PartialEq)]
+enum Checksum {
+    Sha256(String),
+    Md5(String),
+}
+
+impl Checksum {
+    fn verify(&self, data: &[u8], url: &str) -> Result<(), PaperInstallerError> {
+        let (expected, got) = match self {
+            Checksum::Sha256(expected) => (
+                expected,
+                to_hex(ring::digest::digest(&ring::digest::SHA256, data).as_ref()),
+            ),
+            Checksum::Md5(expected) => (expected, to_hex(&md5(data))),
+        };
+        if expected.eq_ignore_ascii_case(&got) {
+            Ok(())
+        } else {
+            Err(PaperInstallerError::ChecksumMismatch {
+                url: url.to_owned(),
+                expected: expected.clone(),
+                got,
+            })
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct FillProject {
+    versions: IndexMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct FillBuild {
+    id: isize,
+    channel: String,
+    downloads: FillDownloads,
+}
+
+#[derive(Deserialize)]
+struct FillDownloads {
     #[serde(rename = "server:default")]
-    server: PaperDownloadsInner,
+    server: FillDownload,
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq)]
-pub struct PaperDownloadsInner {
+#[derive(Deserialize)]
+struct FillDownload {
     url: String,
+    checksums: FillChecksums,
 }
 
-pub async fn uninstall(instance_name: String) -> Result<(), PaperInstallerError> {
-    let server_dir = LAUNCHER_DIR.join("servers").join(instance_name);
+#[derive(Deserialize)]
+struct FillChecksums {
+    sha256: String,
+}
 
-    let jar_path = server_dir.join("paper_server.jar");
-    tokio::fs::remove_file(&jar_path).await.path(jar_path)?;
+#[derive(Deserialize)]
+struct PurpurVersion {
+    builds: PurpurBuilds,
+}
 
-    // Paper stores Nether and End dimension worlds
-    // in a separate directory, so we migrate it back.
+#[derive(Deserialize)]
+struct PurpurBuilds {
+    all: Vec<PurpurBuild>,
+}
 
-    move_dir(
-        &server_dir.join("world_nether/DIM-1"),
-        &server_dir.join("world/DIM-1"),
-    )
-    .await?;
-    move_dir(
-        &server_dir.join("world_the_end/DIM1"),
-        &server_dir.join("world/DIM1"),
-    )
-    .await?;
+#[derive(Deserialize)]
+struct PurpurBuild {
+    build: String,
+    version: String,
+    md5: String,
+    result: String,
+}
+
+/// Goes back to the vanilla server.
+///
+/// # Errors
+/// If the server jar or config couldn't be modified.
+pub async fn uninstall(
+    instance_name: String,
+    project: PaperProject,
+) -> Result<(), PaperInstallerError> {
+    let server_dir = LAUNCHER_DIR.join("servers").join(instance_name);
 
-    let path = server_dir.join("world_nether");
-    tokio::fs::remove_dir_all(&path).await.path(path)?;
-    let path = server_dir.join("world_the_end");
-    tokio::fs::remove_dir_all(&path).await.path(path)?;
+    let jar_path = server_dir.join(project.jar_name());
+    if exists(&jar_path).await {
+        tokio::fs::remove_file(&jar_path).await.path(jar_path)?;
+    }
+
+    migrate_worlds_to_vanilla(&server_dir).await?;
 
     change_instance_type(&server_dir, Loader::Vanilla, None).await?;
 
     Ok(())
 }
 
-const PAPER_INSTALL_ERR_PREFIX: &str = "while installing Paper for Minecraft server:\n";
+/// Bukkit-based servers (Paper, Spigot, ...) store Nether and End
+/// dimension worlds in separate directories, so this migrates them
+/// back into `world/` for vanilla.
+///
+/// Does nothing for dimensions that don't exist
+/// (eg: the server was never started).
+pub(crate) async fn migrate_worlds_to_vanilla(server_dir: &Path) -> Result<(), IoError> {
+    for (world, dim) in [("world_nether", "DIM-1"), ("world_the_end", "DIM1")] {
+        let world_dir = server_dir.join(world);
+        let dim_dir = world_dir.join(dim);
+        if exists(&dim_dir).await {
+            move_dir(&dim_dir, &server_dir.join("world").join(dim)).await?;
+        }
+        if exists(&world_dir).await {
+            tokio::fs::remove_dir_all(&world_dir)
+                .await
+                .path(world_dir)?;
+        }
+    }
+    Ok(())
+}
+
+const PAPER_INSTALL_ERR_PREFIX: &str = "while installing Paper (or fork) for Minecraft server:\n";
 
 #[derive(Debug, Error)]
 pub enum PaperInstallerError {
@@ -172,8 +490,16 @@ pub enum PaperInstallerError {
     Io(#[from] IoError),
     #[error("{PAPER_INSTALL_ERR_PREFIX}json error: {0}")]
     Json(#[from] JsonError),
-    #[error("{PAPER_INSTALL_ERR_PREFIX}no matching paper version found for {0}")]
-    NoMatchingVersionFound(String),
+    #[error("{PAPER_INSTALL_ERR_PREFIX}no matching {0} version found for {1}")]
+    NoMatchingVersionFound(PaperProject, String),
+    #[error(
+        "{PAPER_INSTALL_ERR_PREFIX}checksum mismatch for downloaded jar (corrupted download?)\nUrl: {url}\nExpected: {expected}\nGot: {got}"
+    )]
+    ChecksumMismatch {
+        url: String,
+        expected: String,
+        got: String,
+    },
 }
 
 impl_3_errs_jri!(PaperInstallerError, Json, Request, Io);
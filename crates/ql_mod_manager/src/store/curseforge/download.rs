@@ -1,18 +1,19 @@
 use std::{
     collections::{HashMap, HashSet},
+    path::Path,
     sync::{Arc, mpsc::Sender},
 };
 
 use owo_colors::OwoColorize;
 use ql_core::{
-    GenericProgress, Instance, InstanceConfigJson, download, err, file_utils, info,
+    GenericProgress, Instance, InstanceConfigJson, IntoIoError, download, err, file_utils, info,
     json::VersionDetails, pt,
 };
 
 use crate::store::{
     CurseforgeNotAllowed, DirStructure, ModConfig, ModError, ModFile, ModId, ModIndex, QueryType,
     StoreBackendType,
-    curseforge::{ModQuery, get_query_type},
+    curseforge::{CurseforgeFileQuery, ModQuery, get_query_type},
     install_modpack,
 };
 
@@ -147,7 +148,9 @@ impl<'a> ModDownloader<'a> {
                 slug: response.slug.clone(),
                 filename,
                 project_type: query_type,
+                project_id: Arc::from(response.id.to_string()),
                 file_id: file_id as usize,
+                fingerprint: file_query.data.fileFingerprint,
             });
             return Ok(());
         };
@@ -206,6 +209,59 @@ impl<'a> ModDownloader<'a> {
         Ok(())
     }
 
+    /// Moves a manually downloaded file (see [`CurseforgeNotAllowed`])
+    /// into the right content dir, and registers it in the mod index.
+    ///
+    /// Doesn't save the index, call [`ModIndex::save`] after this.
+    ///
+    /// # Errors
+    /// - The file is a modpack ([`ModError::ManualModpackDownload`])
+    /// - Mod info couldn't be fetched from CurseForge
+    /// - The file couldn't be moved
+    pub async fn add_manual_download(
+        &mut self,
+        entry: &CurseforgeNotAllowed,
+        file: &Path,
+    ) -> Result<(), ModError> {
+        let Some(dest) = self
+            .dirs
+            .get(entry.project_type)
+            .map(|n| n.join(&entry.filename))
+        else {
+            return Err(ModError::ManualModpackDownload);
+        };
+        // Before moving, so the file is left
+        // where it was if these fail
+        let response = self.get_query(&entry.project_id).await?;
+        let file_query = CurseforgeFileQuery::load(&entry.project_id, entry.file_id as i32).await?;
+
+        move_file(file, &dest).await?;
+
+        // No direct link for blocked files, so point to the download page
+        let url = format!(
+            "https://www.curseforge.com/minecraft/{}/{}/download/{}",
+            entry.project_type.to_curseforge_str(),
+            entry.slug,
+            entry.file_id
+        );
+        let id_mod = mid(&entry.project_id);
+        self.add_to_index(
+            None,
+            &response,
+            entry.project_type,
+            file_query,
+            url,
+            &id_mod,
+        );
+
+        pt!(
+            "Added manually downloaded {}: {}",
+            entry.project_type,
+            entry.name
+        );
+        Ok(())
+    }
+
     fn add_to_index(
         &mut self,
         dependent: Option<&str>,
@@ -284,3 +340,14 @@ impl<'a> ModDownloader<'a> {
 fn mid(id: &str) -> ModId {
     ModId::Curseforge(Arc::from(id))
 }
+
+/// Renames `src` to `dest`, falling back to copying
+/// if they're on different drives/filesystems.
+async fn move_file(src: &Path, dest: &Path) -> Result<(), ModError> {
+    if tokio::fs::rename(src, dest).await.is_ok() {
+        return Ok(());
+    }
+    tokio::fs::copy(src, dest).await.path(dest)?;
+    tokio::fs::remove_file(src).await.path(src)?;
+    Ok(())
+}
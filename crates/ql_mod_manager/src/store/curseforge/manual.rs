@@ -0,0 +1,136 @@
+//! Finishing CurseForge downloads that were blocked
+//! for third-party launchers (see [`CurseforgeNotAllowed`]).
+//!
+//! The user downloads these through their browser,
+//! and we pick them up from their downloads folder.
+
+use std::path::Path;
+
+use ql_core::{Instance, IntoIoError, err, info, pt};
+
+use super::download::ModDownloader;
+use crate::store::{CurseforgeNotAllowed, ModError};
+
+/// Looks for the files of `pending` inside `downloads_dir`,
+/// moving any matches into the instance and registering
+/// them in the mod index.
+///
+/// A file matches if it has the expected filename and
+/// (if known) the expected CurseForge fingerprint.
+/// Partially downloaded or unrelated files with the same name
+/// are left alone.
+///
+/// Returns the entries that were installed.
+///
+/// # Errors
+/// - `downloads_dir` couldn't be read
+/// - Moving the file into the instance failed
+/// - Mod info couldn't be fetched from CurseForge
+/// - The mod index couldn't be loaded/saved
+pub async fn add_manual_downloads(
+    instance: Instance,
+    downloads_dir: &Path,
+    pending: Vec<CurseforgeNotAllowed>,
+) -> Result<Vec<CurseforgeNotAllowed>, ModError> {
+    let mut found = Vec::new();
+    for entry in pending {
+        let path = downloads_dir.join(&entry.filename);
+        if !path.is_file() {
+            continue;
+        }
+        if let Some(expected) = entry.fingerprint {
+            let bytes = tokio::fs::read(&path).await.path(&path)?;
+            let fingerprint = get_fingerprint(&bytes);
+            if u64::from(fingerprint) != expected {
+                pt!(
+                    "{} doesn't match the expected file (fingerprint {fingerprint}, expected {expected}), skipping",
+                    entry.filename
+                );
+                continue;
+            }
+        }
+        found.push((entry, path));
+    }
+
+    if found.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    info!("Found {} manually downloaded CurseForge files", found.len());
+    let mut downloader = ModDownloader::new(instance.clone(), None).await?;
+    let mut installed = Vec::new();
+    for (entry, path) in found {
+        if let Err(error) = downloader.add_manual_download(&entry, &path).await {
+            err!("Couldn't add manually downloaded {}: {error}", entry.name);
+            continue;
+        }
+        installed.push(entry);
+    }
+    downloader.index.save(&instance).await?;
+
+    Ok(installed)
+}
+
+/// CurseForge's file fingerprint: 32-bit MurmurHash2 (seed 1)
+/// of the file, with all whitespace bytes stripped out.
+#[must_use]
+fn get_fingerprint(bytes: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1_e995;
+    const SEED: u32 = 1;
+
+    let data: Vec<u8> = bytes
+        .iter()
+        .copied()
+        .filter(|n| !matches!(n, 9 | 10 | 13 | 32))
+        .collect();
+
+    #[allow(clippy::cast_possible_truncation)]
+    let mut h = SEED ^ (data.len() as u32);
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> 24;
+        k = k.wrapping_mul(M);
+        h = h.wrapping_mul(M) ^ k;
+    }
+
+    let rest = chunks.remainder();
+    if !rest.is_empty() {
+        for (i, byte) in rest.iter().enumerate() {
+            h ^= u32::from(*byte) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^ (h >> 15)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_fingerprint;
+
+    #[test]
+    fn fingerprints() {
+        // From the reference MurmurHash2 (seed 1) on the stripped bytes
+        assert_eq!(get_fingerprint(b""), 1_540_447_798);
+        assert_eq!(get_fingerprint(b"abcd"), 3_376_380_438);
+        // 1 to 3 bytes after the last 4-byte block
+        assert_eq!(get_fingerprint(b"a"), 626_045_324);
+        assert_eq!(get_fingerprint(b"ab"), 1_692_487_918);
+        assert_eq!(get_fingerprint(b"abc"), 1_621_425_345);
+        assert_eq!(get_fingerprint(b"fabric.mod.json"), 3_774_118_014);
+
+        // Tabs, newlines, carriage returns and spaces are left out
+        assert_eq!(get_fingerprint(b"hello world"), 2_824_650_221);
+        assert_eq!(get_fingerprint(b"helloworld"), 2_824_650_221);
+        assert_eq!(
+            get_fingerprint(b"Hello,\tworld!\r\n"),
+            get_fingerprint(b"Hello,world!")
+        );
+        assert_eq!(get_fingerprint(b"Hello,world!"), 2_173_663_876);
+    }
+}
@@ -27,6 +27,9 @@ use ql_core::request::check_for_success;
 
 mod categories;
 mod download;
+mod manual;
+
+pub use manual::add_manual_downloads;
 
 const NOT_LOADED: i32 = -1;
 pub static MC_ID: AtomicI32 = AtomicI32::new(NOT_LOADED);
@@ -205,6 +208,8 @@ pub struct CurseforgeFile {
     pub fileDate: DateTime<chrono::FixedOffset>,
     pub displayName: String,
     pub fileLength: u64,
+    #[serde(default)]
+    pub fileFingerprint: Option<u64>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
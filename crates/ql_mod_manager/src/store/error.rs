@@ -22,6 +22,10 @@ pub enum ModError {
     NoCompatibleVersionFound(Arc<str>),
     #[error("{MOD_ERR_PREFIX}no valid files found for mod")]
     NoFilesFound,
+    #[error(
+        "{MOD_ERR_PREFIX}manually downloaded modpacks can't be added here\nimport the modpack file instead"
+    )]
+    ManualModpackDownload,
     #[error(
         "{MOD_ERR_PREFIX}unknown project_type while downloading from store: {0}\n\nThis is a bug, please report in discord!"
     )]
@@ -48,6 +52,12 @@ pub enum ModError {
     Pack(#[from] Box<PackError>),
     #[error("{MOD_ERR_PREFIX}not a valid modpack or QMP preset!")]
     NotValidPack,
+    #[error("{MOD_ERR_PREFIX}keyring error (Modrinth token):\n{0}")]
+    Keyring(#[from] keyring::Error),
+    #[error(
+        "{MOD_ERR_PREFIX}no Modrinth token saved!\nAdd a personal access token to import followed projects"
+    )]
+    NoModrinthToken,
     #[error("{MOD_ERR_PREFIX}API Error: {error_id}\n{description}")]
     ApiError {
         error_id: String,
@@ -16,19 +16,26 @@ mod local_json;
 mod modpack;
 mod modrinth;
 pub mod recommended;
+mod side;
 mod toggle;
 mod types;
 mod update;
 
 pub use add_file::add_files;
-pub use curseforge::CurseforgeBackend;
+pub use curseforge::{CurseforgeBackend, add_manual_downloads};
 pub use delete::delete_mods;
 pub use error::{GameExpectation, ModError};
 pub use id::ModId;
 pub use local_json::{ModConfig, ModFile, ModIndex};
-pub use modpack::{PackError, install_modpack};
-pub use modrinth::ModrinthBackend;
+pub use modpack::{PackError, PackInfo, export_modrinth_pack, install_modpack, read_pack_info};
+pub use modrinth::{
+    ModListImport, ModrinthBackend, ModrinthList, get_modrinth_token, import_modrinth_list,
+    parse_collection_id, set_modrinth_token,
+};
 pub use recommended::{RECOMMENDED_MODS, RecommendedMod};
+pub use side::{
+    ModCopyReport, ModSide, client_configs, copy_mods, disable_unneeded_mods, mod_sides,
+};
 pub use toggle::{flip_filename, toggle_mods, toggle_mods_local};
 pub use types::{
     Category, CurseforgeNotAllowed, DirStructure, LocalMod, Query, QueryType, SearchMod,
@@ -17,7 +17,7 @@ use crate::store::{
     curseforge::{self, CFSearchResult, CurseforgeFileQuery, ModQuery, get_query_type},
 };
 
-use super::PackError;
+use super::{PackError, PackInfo};
 
 #[derive(Deserialize)]
 pub struct PackIndex {
@@ -50,6 +50,35 @@ pub struct PackFile {
     required: bool,
 }
 
+impl PackIndex {
+    pub fn info(&self) -> Result<PackInfo, PackError> {
+        // eg: `forge-47.2.0`, `fabric-0.16.10`
+        let (loader, version) = self
+            .minecraft
+            .modLoaders
+            .iter()
+            .find_map(|n| {
+                let (name, version) = n.id.split_once('-')?;
+                let loader = match name {
+                    "forge" => Loader::Forge,
+                    "neoforge" => Loader::NeoForge,
+                    "fabric" => Loader::Fabric,
+                    "quilt" => Loader::Quilt,
+                    _ => return None,
+                };
+                Some((loader, version))
+            })
+            .ok_or(PackError::NoLoadersSpecified)?;
+
+        Ok(PackInfo {
+            name: self.name.clone(),
+            minecraft: self.minecraft.version.clone(),
+            loader,
+            loader_version: Some(version.to_owned()),
+        })
+    }
+}
+
 impl PackFile {
     async fn download(
         &self,
@@ -119,7 +148,9 @@ impl PackFile {
             slug: mod_info.slug,
             file_id: self.fileID,
             project_type: query_type,
+            project_id: Arc::from(self.projectID.to_string()),
             filename: query.data.fileName,
+            fingerprint: query.data.fileFingerprint,
         });
     }
 }
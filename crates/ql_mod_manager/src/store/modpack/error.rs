@@ -31,6 +31,18 @@ pub enum PackError {
         "{PACK_ERR_PREFIX}This modpack doesn't have any mod loaders specified.\nIt may be corrupt, unsupported or invalid.\nPlease report this bug in discord."
     )]
     NoLoadersSpecified,
+    #[error(
+        "{PACK_ERR_PREFIX}This modpack doesn't say which Minecraft version it's for.\nIt may be corrupt or invalid."
+    )]
+    NoGameVersionSpecified,
+    #[error(
+        "{0} instances can't be exported as Modrinth modpacks\n(only Fabric, Quilt, Forge and NeoForge)"
+    )]
+    CantExport(Loader),
+    #[error(
+        "{PACK_ERR_PREFIX}couldn't find which version of {0} is installed.\nTry reinstalling it."
+    )]
+    NoLoaderVersion(Loader),
     #[error("{PACK_ERR_PREFIX}{0}")]
     Mod(#[from] ModError),
     #[error("{PACK_ERR_PREFIX}found modpack inside modpack!")]
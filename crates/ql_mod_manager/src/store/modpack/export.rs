@@ -0,0 +1,251 @@
+use std::{
+    collections::HashMap,
+    io::{Cursor, Write},
+};
+
+use ql_core::{
+    Instance, IntoIoError, IntoJsonError, Loader, download, info,
+    json::{InstanceConfigJson, VersionDetails},
+    pt, to_hex,
+};
+use serde::Serialize;
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+use crate::store::{
+    ModId, ModIndex, ModSide, QueryType, StoreBackendType, client_configs, get_download_link,
+    mod_sides,
+};
+
+use super::PackError;
+
+/// Only files from here may be linked in a
+/// `.mrpack` (rest have to be included)
+const ALLOWED_HOSTS: &[&str] = &[
+    "https://cdn.modrinth.com/",
+    "https://github.com/",
+    "https://raw.githubusercontent.com/",
+    "https://gitlab.com/",
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportIndex {
+    format_version: u32,
+    game: &'static str,
+    version_id: String,
+    name: String,
+    files: Vec<ExportFile>,
+    dependencies: HashMap<&'static str, String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportFile {
+    path: String,
+    hashes: HashMap<&'static str, String>,
+    env: HashMap<&'static str, &'static str>,
+    downloads: Vec<String>,
+    file_size: usize,
+}
+
+impl ExportFile {
+    fn new(path: String, bytes: &[u8], url: String, side: ModSide) -> Self {
+        let hash = |algorithm| to_hex(ring::digest::digest(algorithm, bytes).as_ref());
+        let env = |needed| if needed { "required" } else { "unsupported" };
+        Self {
+            path,
+            hashes: HashMap::from([
+                ("sha1", hash(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY)),
+                ("sha512", hash(&ring::digest::SHA512)),
+            ]),
+            env: HashMap::from([
+                ("client", env(side != ModSide::Server)),
+                ("server", env(side != ModSide::Client)),
+            ]),
+            downloads: vec![url],
+            file_size: bytes.len(),
+        }
+    }
+}
+
+/// Exports the mods and configs of an instance (client or server)
+/// as a Modrinth modpack (`.mrpack`), eg: to hand out to the
+/// players of a server.
+///
+/// - Mods downloaded from Modrinth are linked,
+///   the rest are included in the pack.
+/// - Mods only needed on one side (see [`mod_sides`])
+///   are marked as such, so importing the pack
+///   on the other side skips them.
+/// - `companions` (Modrinth project IDs or slugs) are added as
+///   client-only mods (eg: minimaps to go with a server).
+///
+/// Returns the bytes of the `.mrpack` file.
+///
+/// # Errors
+/// - The instance's loader can't be used for Modrinth modpacks
+///   (only Fabric, Quilt, Forge and NeoForge)
+/// - The instance or its mods couldn't be read
+/// - A companion mod isn't compatible, or couldn't be downloaded
+pub async fn export_modrinth_pack(
+    instance: &Instance,
+    companions: &[String],
+) -> Result<Vec<u8>, PackError> {
+    info!("Exporting {} as Modrinth modpack", instance.name);
+    let config = InstanceConfigJson::read(instance).await?;
+    let details = VersionDetails::load(instance).await?;
+
+    let loader_key = match config.mod_type {
+        Loader::Fabric => "fabric-loader",
+        Loader::Quilt => "quilt-loader",
+        Loader::Forge => "forge",
+        Loader::NeoForge => "neoforge",
+        loader => return Err(PackError::CantExport(loader)),
+    };
+    let loader_version = match config.mod_type_info.and_then(|n| n.version) {
+        Some(n) => n,
+        None => find_forge_version(instance, details.get_id())
+            .await
+            .ok_or(PackError::NoLoaderVersion(config.mod_type))?,
+    };
+
+    let dot_mc = instance.get_dot_minecraft_path();
+    let mods_dir = dot_mc.join("mods");
+    let mod_index = ModIndex::load(instance).await?;
+    let urls: HashMap<&str, &str> = mod_index
+        .mods
+        .values()
+        .filter(|n| n.project_source == StoreBackendType::Modrinth)
+        .flat_map(|n| &n.files)
+        .filter(|n| ALLOWED_HOSTS.iter().any(|host| n.url.starts_with(host)))
+        .map(|n| (n.filename.as_str(), n.url.as_str()))
+        .collect();
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+    let mut files = Vec::new();
+
+    for (filename, side) in mod_sides(instance).await? {
+        let path = mods_dir.join(&filename);
+        let bytes = tokio::fs::read(&path).await.path(&path)?;
+        if let Some(url) = urls.get(filename.as_str()) {
+            files.push(ExportFile::new(
+                format!("mods/{filename}"),
+                &bytes,
+                (*url).to_owned(),
+                side,
+            ));
+        } else {
+            let dir = match side {
+                ModSide::Client => "client-overrides",
+                ModSide::Server => "server-overrides",
+                ModSide::Both => "overrides",
+            };
+            pt!("Including {filename} ({side})");
+            let name = format!("{dir}/mods/{filename}");
+            zip.start_file(&name, options)?;
+            zip.write_all(&bytes)
+                .map_err(|n| PackError::ZipIoError(n, name))?;
+        }
+    }
+
+    for id in companions {
+        pt!("Adding companion mod {id}");
+        let id = ModId::Modrinth(id.as_str().into());
+        let url = get_download_link(instance, &id, QueryType::Mods).await?;
+        let bytes = download(&url).user_agent_ql().bytes().await?;
+        let filename = url_filename(&url);
+        files.push(ExportFile::new(
+            format!("mods/{filename}"),
+            &bytes,
+            url,
+            ModSide::Client,
+        ));
+    }
+
+    // Without proxy secrets and other server-only configs
+    for (path, contents) in client_configs(instance).await? {
+        let name = format!("overrides/config/{path}");
+        zip.start_file(&name, options)?;
+        zip.write_all(&contents)
+            .map_err(|n| PackError::ZipIoError(n, name))?;
+    }
+
+    let index = ExportIndex {
+        format_version: 1,
+        game: "minecraft",
+        version_id: chrono::Local::now().format("%Y.%m.%d").to_string(),
+        name: instance.name.to_string(),
+        files,
+        dependencies: HashMap::from([
+            ("minecraft", details.get_id().to_owned()),
+            (loader_key, loader_version),
+        ]),
+    };
+    zip.start_file("modrinth.index.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&index).json_to()?.as_bytes())
+        .map_err(|n| PackError::ZipIoError(n, "modrinth.index.json".to_owned()))?;
+
+    let file = zip.finish()?.into_inner();
+    pt!("Done! Size: {} bytes", file.len());
+    Ok(file)
+}
+
+/// Forge servers don't save their version in `config.json`,
+/// but it's in their libraries (`net/minecraftforge/forge/<MC>-<FORGE>/`).
+async fn find_forge_version(instance: &Instance, minecraft: &str) -> Option<String> {
+    let dir = instance
+        .get_dot_minecraft_path()
+        .join("libraries/net/minecraftforge/forge");
+    let mut entries = tokio::fs::read_dir(&dir).await.ok()?;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name();
+        if let Some(version) = name
+            .to_str()
+            .and_then(|n| n.strip_prefix(&format!("{minecraft}-")))
+        {
+            return Some(version.to_owned());
+        }
+    }
+    None
+}
+
+/// Last part of the URL, with `%XX` escapes decoded
+fn url_filename(url: &str) -> String {
+    let name = url.rsplit('/').next().unwrap_or(url);
+    let mut out = Vec::new();
+    let mut bytes = name.bytes();
+    while let Some(b) = bytes.next() {
+        if b == b'%' {
+            let hex: Vec<u8> = bytes.by_ref().take(2).collect();
+            if let Some(n) = std::str::from_utf8(&hex)
+                .ok()
+                .and_then(|n| u8::from_str_radix(n, 16).ok())
+            {
+                out.push(n);
+                continue;
+            }
+            out.push(b);
+            out.extend(hex);
+        } else {
+            out.push(b);
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::url_filename;
+
+    #[test]
+    fn filename_from_url() {
+        assert_eq!(
+            url_filename(
+                "https://cdn.modrinth.com/data/a/versions/b/sodium-fabric-0.6.0%2Bmc1.21.1.jar"
+            ),
+            "sodium-fabric-0.6.0+mc1.21.1.jar"
+        );
+        assert_eq!(url_filename("https://a.com/x%2.jar"), "x%2.jar");
+    }
+}
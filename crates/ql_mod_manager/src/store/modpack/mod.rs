@@ -5,21 +5,55 @@ use std::{
 };
 
 use ql_core::{
-    GenericProgress, Instance, IntoIoError, IntoJsonError, err, info,
+    GenericProgress, Instance, InstanceKind, IntoIoError, IntoJsonError, Loader, err, info,
     json::{InstanceConfigJson, VersionDetails},
     pt,
 };
 
 mod curseforge;
 mod error;
+mod export;
 mod modrinth;
 
 pub use error::PackError;
+pub use export::export_modrinth_pack;
 
 use crate::{Preset, store::download_mods_bulk};
 
 use super::CurseforgeNotAllowed;
 
+/// The game version and mod loader a modpack is made for.
+#[derive(Debug, Clone)]
+pub struct PackInfo {
+    pub name: String,
+    pub minecraft: String,
+    pub loader: Loader,
+    /// `None` if the pack doesn't say (use the latest)
+    pub loader_version: Option<String>,
+}
+
+/// Reads what a Curseforge or Modrinth modpack needs
+/// (see [`PackInfo`]), without installing anything.
+/// Useful for creating an instance to install it in.
+///
+/// # Errors
+/// - The file isn't a valid Curseforge/Modrinth modpack
+/// - It doesn't say which loader or game version it needs
+pub fn read_pack_info(file: &[u8]) -> Result<PackInfo, PackError> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(file))?;
+
+    if let Some(index) = read_json_from_zip::<modrinth::PackIndex>(&mut zip, "modrinth.index.json")?
+    {
+        index.info()
+    } else if let Some(index) =
+        read_json_from_zip::<curseforge::PackIndex>(&mut zip, "manifest.json")?
+    {
+        index.info()
+    } else {
+        Err(PackError::NoBackendFound)
+    }
+}
+
 /// Installs a Curseforge or Modrinth modpack.
 ///
 /// Unlike [`crate::Preset`] (`.qmp`) which are QuantumLauncher-only,
@@ -88,6 +122,13 @@ pub async fn install_modpack(
     let overrides = index_json_curseforge
         .as_ref()
         .map_or("overrides".to_owned(), |n| n.overrides.clone());
+    // Modrinth packs can have files for only one side,
+    // which replace the ones in `overrides`
+    let (side_overrides, other_side_overrides) = match instance.kind {
+        InstanceKind::Client => ("client-overrides", "server-overrides"),
+        InstanceKind::Server => ("server-overrides", "client-overrides"),
+    };
+    let file_names: HashSet<String> = zip.file_names().map(str::to_owned).collect();
 
     let mc_dir = instance.get_dot_minecraft_path();
     let config = InstanceConfigJson::read(&instance).await?;
@@ -130,10 +171,12 @@ pub async fn install_modpack(
             });
         }
 
-        if let Some(name) = name
-            .strip_prefix(&format!("{overrides}/"))
-            .or(name.strip_prefix(&format!("{overrides}\\")))
-        {
+        let side_name = strip_dir(&name, side_overrides);
+        if let Some(name) = strip_dir(&name, &overrides).or(side_name) {
+            if side_name.is_none() && file_names.contains(&format!("{side_overrides}/{name}")) {
+                continue;
+            }
+
             let path = mc_dir.join(name);
             let parent = if file.is_dir() {
                 &path
@@ -152,7 +195,7 @@ pub async fn install_modpack(
 
                 tokio::fs::write(&path, &buf).await.path(&path)?;
             }
-        } else {
+        } else if strip_dir(&name, other_side_overrides).is_none() {
             err!("Unrecognised file: {name}");
         }
     }
@@ -162,6 +205,12 @@ pub async fn install_modpack(
     Ok(Some(not_allowed))
 }
 
+/// `dir/name` (or `dir\\name`) -> `name`
+fn strip_dir<'a>(name: &'a str, dir: &str) -> Option<&'a str> {
+    let rest = name.strip_prefix(dir)?;
+    rest.strip_prefix('/').or_else(|| rest.strip_prefix('\\'))
+}
+
 async fn write_regular_file(
     file: &[u8],
     name: Option<String>,
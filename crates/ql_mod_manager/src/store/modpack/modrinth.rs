@@ -8,7 +8,7 @@ use ql_core::{
 use serde::Deserialize;
 use tokio::sync::Mutex;
 
-use super::PackError;
+use super::{PackError, PackInfo};
 
 #[derive(Deserialize)]
 pub struct PackIndex {
@@ -39,6 +39,31 @@ pub struct PackEnv {
     server: String,
 }
 
+impl PackIndex {
+    pub fn info(&self) -> Result<PackInfo, PackError> {
+        let minecraft = self
+            .dependencies
+            .get("minecraft")
+            .ok_or(PackError::NoGameVersionSpecified)?;
+        let (loader, version) = [
+            ("fabric-loader", Loader::Fabric),
+            ("quilt-loader", Loader::Quilt),
+            ("forge", Loader::Forge),
+            ("neoforge", Loader::NeoForge),
+        ]
+        .into_iter()
+        .find_map(|(key, loader)| self.dependencies.get(key).map(|n| (loader, n)))
+        .ok_or(PackError::NoLoadersSpecified)?;
+
+        Ok(PackInfo {
+            name: self.name.clone(),
+            minecraft: minecraft.clone(),
+            loader,
+            loader_version: Some(version.clone()),
+        })
+    }
+}
+
 pub async fn install(
     instance: &Instance,
     mc_dir: &Path,
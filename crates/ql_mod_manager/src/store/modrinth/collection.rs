@@ -0,0 +1,214 @@
+//! Importing lists of Modrinth projects into an instance:
+//! collections, and projects the user follows.
+
+use std::sync::{Arc, mpsc::Sender};
+
+use ql_core::{CLIENT, GenericProgress, Instance, IntoJsonError, RequestError, info, pt};
+use reqwest::header::{AUTHORIZATION, HeaderValue};
+use serde::{Deserialize, de::DeserializeOwned};
+
+use crate::{
+    rate_limiter::RATE_LIMITER,
+    store::{Backend, ModError, ModId, ModIndex, download_mods_bulk},
+};
+
+use super::ModrinthBackend;
+
+const KEYRING_SERVICE: &str = "QuantumLauncher";
+const KEYRING_USER: &str = "#modrinth-token";
+
+/// A list of Modrinth projects to import.
+#[derive(Debug, Clone)]
+pub enum ModrinthList {
+    /// A collection, by its ID (see [`parse_collection_id`])
+    Collection(String),
+    /// Projects followed by the user whose personal
+    /// access token is saved (see [`set_modrinth_token`])
+    Followed,
+}
+
+/// Result of [`import_modrinth_list`].
+#[derive(Debug, Clone)]
+pub struct ModListImport {
+    /// Name of the collection (or "Followed projects")
+    pub name: String,
+    pub installed: Vec<Arc<str>>,
+    /// Projects with no version for this instance's
+    /// Minecraft version and loader (or modpacks,
+    /// which can't be installed into an existing instance)
+    pub incompatible: Vec<Arc<str>>,
+}
+
+/// Gets the collection ID from either a plain ID
+/// or a link like `https://modrinth.com/collection/<id>`.
+#[must_use]
+pub fn parse_collection_id(input: &str) -> Option<String> {
+    let input = input.trim();
+    let id = match input.split_once("/collection/") {
+        Some((_, rest)) => rest.split(['/', '?', '#']).next().unwrap_or_default(),
+        None => input,
+    };
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric())).then(|| id.to_owned())
+}
+
+/// Installs all projects of a Modrinth collection
+/// (or the user's followed projects) into `instance`.
+///
+/// Only versions compatible with the instance's Minecraft
+/// version and loader are installed, the rest are
+/// returned in [`ModListImport::incompatible`].
+///
+/// # Errors
+/// - The collection doesn't exist (or is private)
+/// - For [`ModrinthList::Followed`]: no token is saved,
+///   or the token is invalid/lacks the `USER_READ` scope
+/// - Any error while downloading the mods
+pub async fn import_modrinth_list(
+    list: ModrinthList,
+    instance: Instance,
+    sender: Option<Sender<GenericProgress>>,
+) -> Result<ModListImport, ModError> {
+    let (name, ids) = match list {
+        ModrinthList::Collection(id) => get_collection(&id).await?,
+        ModrinthList::Followed => ("Followed projects".to_owned(), get_followed().await?),
+    };
+    info!("Importing Modrinth list: {name} ({} projects)", ids.len());
+
+    let mut result = ModListImport {
+        name,
+        installed: Vec::new(),
+        incompatible: Vec::new(),
+    };
+    if ids.is_empty() {
+        return Ok(result);
+    }
+
+    let projects = ModrinthBackend::get_info_bulk(&ids).await?;
+    let (modpacks, projects): (Vec<_>, Vec<_>) = projects
+        .into_iter()
+        .partition(|n| n.project_type == "modpack");
+    for modpack in modpacks {
+        pt!("Skipping modpack: {}", modpack.title);
+        result.incompatible.push(modpack.title);
+    }
+
+    download_mods_bulk(
+        projects
+            .iter()
+            .map(|n| ModId::Modrinth(n.id.clone()))
+            .collect(),
+        instance.clone(),
+        sender,
+    )
+    .await?;
+
+    // Incompatible mods are silently skipped while downloading,
+    // so check what actually ended up installed
+    let index = ModIndex::load(&instance).await?;
+    for project in projects {
+        if index.mods.contains_key(&ModId::Modrinth(project.id)) {
+            result.installed.push(project.title);
+        } else {
+            result.incompatible.push(project.title);
+        }
+    }
+    Ok(result)
+}
+
+async fn get_collection(id: &str) -> Result<(String, Vec<Arc<str>>), ModError> {
+    #[derive(Deserialize)]
+    struct Collection {
+        name: String,
+        projects: Vec<Arc<str>>,
+    }
+
+    // Collections are only in the v3 API
+    let collection: Collection = get_json(
+        &format!("https://api.modrinth.com/v3/collection/{id}"),
+        None,
+    )
+    .await?;
+    Ok((collection.name, collection.projects))
+}
+
+async fn get_followed() -> Result<Vec<Arc<str>>, ModError> {
+    #[derive(Deserialize)]
+    struct User {
+        id: String,
+    }
+    #[derive(Deserialize)]
+    struct Project {
+        id: Arc<str>,
+    }
+
+    let token = get_modrinth_token()?.ok_or(ModError::NoModrinthToken)?;
+    let user: User = get_json("https://api.modrinth.com/v2/user", Some(&token)).await?;
+    let follows: Vec<Project> = get_json(
+        &format!("https://api.modrinth.com/v2/user/{}/follows", user.id),
+        Some(&token),
+    )
+    .await?;
+    Ok(follows.into_iter().map(|n| n.id).collect())
+}
+
+async fn get_json<T: DeserializeOwned>(url: &str, token: Option<&str>) -> Result<T, ModError> {
+    RATE_LIMITER.lock().await;
+    let mut request = CLIENT.get(url);
+    if let Some(token) = token {
+        request = request.header(
+            AUTHORIZATION,
+            HeaderValue::from_str(token).map_err(RequestError::from)?,
+        );
+    }
+    let response = request.send().await?;
+    ql_core::request::check_for_success(&response)?;
+    let text = response.text().await?;
+    Ok(serde_json::from_str(&text).json(text)?)
+}
+
+/// Gets the saved Modrinth personal access token, if any.
+///
+/// # Errors
+/// If the system keyring couldn't be accessed.
+pub fn get_modrinth_token() -> Result<Option<String>, ModError> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?;
+    match entry.get_password() {
+        Ok(n) => Ok(Some(n)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Saves a Modrinth personal access token (needs the `USER_READ` scope)
+/// to the system keyring, or removes it if `None`.
+///
+/// # Errors
+/// If the system keyring couldn't be accessed.
+pub fn set_modrinth_token(token: Option<&str>) -> Result<(), ModError> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?;
+    match token {
+        Some(token) => entry.set_password(token)?,
+        None => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(err) => return Err(err.into()),
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_collection_id;
+
+    #[test]
+    fn collection_id_from_link() {
+        assert_eq!(parse_collection_id("bRxHLnR3").as_deref(), Some("bRxHLnR3"));
+        assert_eq!(
+            parse_collection_id(" https://modrinth.com/collection/bRxHLnR3/mods?g=1.21 ")
+                .as_deref(),
+            Some("bRxHLnR3")
+        );
+        assert_eq!(parse_collection_id("https://modrinth.com/mod/sodium"), None);
+        assert_eq!(parse_collection_id(""), None);
+    }
+}
@@ -17,8 +17,11 @@ pub struct ProjectInfo {
     pub project_type: String,
     pub slug: String,
     // pub categories: Vec<String>,
-    // pub client_side: String,
-    // pub server_side: String,
+    /// `required`, `optional`, `unsupported` or `unknown`
+    #[serde(default)]
+    pub client_side: String,
+    #[serde(default)]
+    pub server_side: String,
     // pub status: String,
     // pub requested_status: Option<String>,
     // pub additional_categories: Vec<String>,
@@ -6,7 +6,7 @@ use std::{
 
 use chrono::DateTime;
 use indexmap::IndexMap;
-use info::ProjectInfo;
+pub(crate) use info::ProjectInfo;
 use ql_core::{GenericProgress, Instance, Loader, download, pt};
 use serde::Deserialize;
 use versions::ModVersion;
@@ -18,11 +18,17 @@ use crate::{
 
 use super::{Backend, CurseforgeNotAllowed, ModError, Query, SearchResult};
 
+mod collection;
 mod download;
 mod info;
 mod search;
 mod versions;
 
+pub use collection::{
+    ModListImport, ModrinthList, get_modrinth_token, import_modrinth_list, parse_collection_id,
+    set_modrinth_token,
+};
+
 pub struct ModrinthBackend;
 
 impl Backend for ModrinthBackend {
@@ -0,0 +1,452 @@
+//! Which side (client or server) mods are needed on,
+//! for turning a client instance into a server and back.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Cursor, Read},
+    sync::Arc,
+};
+
+use ql_core::{Instance, InstanceKind, IntoIoError, err, file_utils, info, pt};
+use serde::Deserialize;
+
+use crate::store::{ModError, ModId, ModIndex, QueryType, modrinth::ProjectInfo};
+
+/// Where a mod has to be installed for it to do anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModSide {
+    /// Only on the client (eg: minimaps, shaders, HUD mods)
+    Client,
+    /// Only on the server (eg: permissions, anti-cheat)
+    Server,
+    /// Both, or unknown
+    Both,
+}
+
+impl std::fmt::Display for ModSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ModSide::Client => "client-only",
+                ModSide::Server => "server-only",
+                ModSide::Both => "client and server",
+            }
+        )
+    }
+}
+
+impl ModSide {
+    #[must_use]
+    pub fn is_needed_on(self, kind: InstanceKind) -> bool {
+        matches!(
+            (self, kind),
+            (ModSide::Both, _)
+                | (ModSide::Client, InstanceKind::Client)
+                | (ModSide::Server, InstanceKind::Server)
+        )
+    }
+
+    /// From a Modrinth project's `client_side` and `server_side`
+    /// (`required`, `optional`, `unsupported` or `unknown`).
+    fn from_modrinth(client: &str, server: &str) -> Self {
+        match (client, server) {
+            (_, "unsupported") => ModSide::Client,
+            ("unsupported", _) => ModSide::Server,
+            _ => ModSide::Both,
+        }
+    }
+
+    /// From the metadata inside a mod jar:
+    /// - `environment` in `fabric.mod.json`
+    /// - `minecraft.environment` in `quilt.mod.json`
+    /// - `clientSideOnly` in Forge/NeoForge's `mods.toml`
+    ///
+    /// Returns `None` if the jar has no metadata
+    /// (or isn't a valid jar).
+    #[must_use]
+    pub fn from_jar(jar: &[u8]) -> Option<Self> {
+        let mut zip = zip::ZipArchive::new(Cursor::new(jar)).ok()?;
+
+        if let Some(json) = read_zip_text(&mut zip, "fabric.mod.json") {
+            let meta: FabricModJson = serde_json::from_str(&json).ok()?;
+            return Some(Self::from_environment(meta.environment.as_deref()));
+        }
+        if let Some(json) = read_zip_text(&mut zip, "quilt.mod.json") {
+            let meta: QuiltModJson = serde_json::from_str(&json).ok()?;
+            let environment = meta.minecraft.and_then(|n| n.environment);
+            return Some(Self::from_environment(environment.as_deref()));
+        }
+        for name in ["META-INF/neoforge.mods.toml", "META-INF/mods.toml"] {
+            if let Some(toml) = read_zip_text(&mut zip, name) {
+                return Some(if is_client_side_only(&toml) {
+                    ModSide::Client
+                } else {
+                    ModSide::Both
+                });
+            }
+        }
+        None
+    }
+
+    fn from_environment(environment: Option<&str>) -> Self {
+        match environment {
+            Some("client") => ModSide::Client,
+            // Quilt calls it `dedicated_server`
+            Some("server" | "dedicated_server") => ModSide::Server,
+            _ => ModSide::Both,
+        }
+    }
+
+    /// If either source says a mod is one-sided, it is
+    /// (mod authors often leave the defaults in one of them).
+    fn combine(self, other: Self) -> Self {
+        if self == ModSide::Both { other } else { self }
+    }
+}
+
+#[derive(Deserialize)]
+struct FabricModJson {
+    environment: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct QuiltModJson {
+    minecraft: Option<QuiltMinecraft>,
+}
+
+#[derive(Deserialize)]
+struct QuiltMinecraft {
+    environment: Option<String>,
+}
+
+fn read_zip_text(zip: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str) -> Option<String> {
+    let mut file = zip.by_name(name).ok()?;
+    let mut text = String::new();
+    file.read_to_string(&mut text).ok()?;
+    Some(text)
+}
+
+/// `clientSideOnly=true` at the top level of `mods.toml`
+fn is_client_side_only(toml: &str) -> bool {
+    toml.lines()
+        .map(str::trim)
+        // Only the top level, before any `[table]`
+        .take_while(|line| !line.starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .any(|(key, value)| {
+            key.trim() == "clientSideOnly"
+                && value.split('#').next().is_some_and(|n| n.trim() == "true")
+        })
+}
+
+/// Works out which side each enabled mod in `mods/` is needed on
+/// (see [`ModSide`]), using the sides listed on Modrinth for mods
+/// downloaded from there, and the metadata inside the jars.
+///
+/// Returns a map of jar file names to sides.
+///
+/// Instances without a `mods/` folder (eg: clients that
+/// were never launched) have no mods.
+///
+/// # Errors
+/// If the mods folder or mod index couldn't be read.
+/// Modrinth being unreachable isn't an error,
+/// only the jar metadata is used then.
+pub async fn mod_sides(instance: &Instance) -> Result<BTreeMap<String, ModSide>, ModError> {
+    let mods_dir = instance.get_dot_minecraft_path().join("mods");
+    if !file_utils::exists(&mods_dir).await {
+        return Ok(BTreeMap::new());
+    }
+    let index = ModIndex::load(instance).await?;
+
+    let mut modrinth_ids: HashMap<String, Arc<str>> = HashMap::new();
+    for (id, config) in &index.mods {
+        if let ModId::Modrinth(id) = id {
+            for file in &config.files {
+                modrinth_ids.insert(file.filename.clone(), id.clone());
+            }
+        }
+    }
+
+    let modrinth_sides: HashMap<Arc<str>, ModSide> = if modrinth_ids.is_empty() {
+        HashMap::new()
+    } else {
+        let ids: Vec<Arc<str>> = modrinth_ids.values().cloned().collect();
+        match ProjectInfo::download_bulk(&ids).await {
+            Ok(projects) => projects
+                .into_iter()
+                .map(|n| {
+                    let side = ModSide::from_modrinth(&n.client_side, &n.server_side);
+                    (n.id, side)
+                })
+                .collect(),
+            Err(error) => {
+                err!("Couldn't get mod sides from Modrinth, only checking jars: {error}");
+                HashMap::new()
+            }
+        }
+    };
+
+    let mut sides = BTreeMap::new();
+    let mut entries = tokio::fs::read_dir(&mods_dir).await.path(&mods_dir)?;
+    while let Some(entry) = entries.next_entry().await.path(&mods_dir)? {
+        let path = entry.path();
+        let Some(filename) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if !filename.ends_with(".jar") || !path.is_file() {
+            continue;
+        }
+
+        let modrinth_side = modrinth_ids
+            .get(filename)
+            .and_then(|id| modrinth_sides.get(id))
+            .copied()
+            .unwrap_or(ModSide::Both);
+        let jar = tokio::fs::read(&path).await.path(&path)?;
+        let jar_side = ModSide::from_jar(&jar).unwrap_or(ModSide::Both);
+
+        sides.insert(filename.to_owned(), modrinth_side.combine(jar_side));
+    }
+    Ok(sides)
+}
+
+/// What [`copy_mods`] did.
+#[derive(Debug, Clone, Default)]
+pub struct ModCopyReport {
+    pub copied: Vec<String>,
+    /// Not needed on the other side, with the reason
+    pub skipped: Vec<(String, ModSide)>,
+}
+
+/// Copies the enabled mods of `from` into `to`, leaving out the
+/// ones not needed on `to`'s side (see [`mod_sides`]).
+/// Their entries in the mod index are copied too,
+/// so that they can still be updated.
+///
+/// # Errors
+/// If the mods couldn't be read or copied,
+/// or the mod index couldn't be read or saved.
+pub async fn copy_mods(from: &Instance, to: &Instance) -> Result<ModCopyReport, ModError> {
+    info!("Copying mods from {} to {}", from.name, to.name);
+    let sides = mod_sides(from).await?;
+
+    let from_dir = from.get_dot_minecraft_path().join("mods");
+    let to_dir = to.get_dot_minecraft_path().join("mods");
+    tokio::fs::create_dir_all(&to_dir).await.path(&to_dir)?;
+
+    let mut report = ModCopyReport::default();
+    for (filename, side) in sides {
+        if side.is_needed_on(to.kind) {
+            let dest = to_dir.join(&filename);
+            tokio::fs::copy(from_dir.join(&filename), &dest)
+                .await
+                .path(dest)?;
+            report.copied.push(filename);
+        } else {
+            pt!("Skipping {filename} ({side})");
+            report.skipped.push((filename, side));
+        }
+    }
+
+    if report.copied.is_empty() {
+        return Ok(report);
+    }
+    let from_index = ModIndex::load(from).await?;
+    let mut to_index = ModIndex::load(to).await?;
+    for (id, config) in from_index.mods {
+        if config.project_type == QueryType::Mods
+            && config
+                .files
+                .iter()
+                .any(|file| report.copied.contains(&file.filename))
+        {
+            to_index.mods.insert(id, config);
+        }
+    }
+    to_index.save(to).await?;
+
+    Ok(report)
+}
+
+/// Disables (renames to `.disabled`) the enabled mods
+/// that aren't needed on this instance's side,
+/// such as client-only mods from a modpack on a server.
+///
+/// Returns the disabled mods.
+///
+/// # Errors
+/// If the mods couldn't be read or renamed,
+/// or the mod index couldn't be read or saved.
+pub async fn disable_unneeded_mods(
+    instance: &Instance,
+) -> Result<Vec<(String, ModSide)>, ModError> {
+    let sides = mod_sides(instance).await?;
+    let mods_dir = instance.get_dot_minecraft_path().join("mods");
+    let mut index = ModIndex::load(instance).await?;
+
+    let mut disabled = Vec::new();
+    for (filename, side) in sides {
+        if side.is_needed_on(instance.kind) {
+            continue;
+        }
+        pt!("Disabling {filename} ({side})");
+        let path = mods_dir.join(&filename);
+        let disabled_path = mods_dir.join(format!("{filename}.disabled"));
+        tokio::fs::rename(&path, &disabled_path).await.path(path)?;
+
+        for config in index.mods.values_mut() {
+            if config.files.iter().any(|file| file.filename == filename) {
+                config.enabled = false;
+            }
+        }
+        disabled.push((filename, side));
+    }
+    index.save(instance).await?;
+
+    Ok(disabled)
+}
+
+/// Config files (by name, lowercase) that only servers use:
+/// proxy forwarding (with the secret that lets anyone log in
+/// as any player) and server-only mods.
+const SERVER_ONLY_CONFIGS: &[&str] = &[
+    "fabricproxy-lite",
+    "fabricproxy",
+    "crossstitch",
+    "proxy-compatible-forge",
+    "velocity",
+    "bungeeforward",
+    "luckperms",
+    "spark",
+    "ledger",
+    "chunky",
+];
+
+/// Keys with these in their name hold credentials
+const SECRET_KEYS: &[&str] = &[
+    "secret", "password", "token", "apikey", "api_key", "webhook",
+];
+
+/// The files in `config/` of a server that are fine to hand
+/// out to players, as `(path inside config/, contents)`.
+///
+/// Left out are configs of proxy forwarding and other server-only
+/// mods ([`SERVER_ONLY_CONFIGS`]), and any config that has
+/// a credential in it (a key like `secret = "..."`).
+///
+/// # Errors
+/// If the config folder couldn't be read.
+pub async fn client_configs(server: &Instance) -> Result<Vec<(String, Vec<u8>)>, ModError> {
+    let config_dir = server.get_dot_minecraft_path().join("config");
+    let mut configs = Vec::new();
+    if !config_dir.is_dir() {
+        return Ok(configs);
+    }
+    let mut dirs = vec![config_dir.clone()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await.dir(&dir)?;
+        while let Some(entry) = entries.next_entry().await.dir(&dir)? {
+            let path = entry.path();
+            let Ok(relative) = path.strip_prefix(&config_dir) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if is_server_only_config(&relative) {
+                pt!("Leaving out config/{relative} (server-only)");
+                continue;
+            }
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let contents = tokio::fs::read(&path).await.path(&path)?;
+            if has_secret(&contents) {
+                pt!("Leaving out config/{relative} (has credentials)");
+                continue;
+            }
+            configs.push((relative, contents));
+        }
+    }
+    configs.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(configs)
+}
+
+fn is_server_only_config(relative: &str) -> bool {
+    relative.split('/').any(|part| {
+        let part = part.to_lowercase();
+        let stem = part.split_once('.').map_or(part.as_str(), |(n, _)| n);
+        SERVER_ONLY_CONFIGS.contains(&stem)
+    })
+}
+
+/// Whether a (text) config sets a credential,
+/// as `key = value`, `key: value` or `"key": value`
+fn has_secret(contents: &[u8]) -> bool {
+    String::from_utf8_lossy(contents).lines().any(|line| {
+        let Some((key, value)) = line.split_once(['=', ':']) else {
+            return false;
+        };
+        let key = key.trim().trim_matches(['"', '\'']).to_lowercase();
+        let value = value.trim().trim_end_matches(',').trim_matches(['"', '\'']);
+        !key.starts_with('#') && SECRET_KEYS.iter().any(|n| key.contains(n)) && !value.is_empty()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn jar(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn jar_metadata() {
+        let fabric = jar(&[("fabric.mod.json", r#"{"id": "a", "environment": "client"}"#)]);
+        assert_eq!(ModSide::from_jar(&fabric), Some(ModSide::Client));
+        let fabric = jar(&[("fabric.mod.json", r#"{"id": "a", "environment": "*"}"#)]);
+        assert_eq!(ModSide::from_jar(&fabric), Some(ModSide::Both));
+
+        let quilt = jar(&[(
+            "quilt.mod.json",
+            r#"{"minecraft": {"environment": "dedicated_server"}}"#,
+        )]);
+        assert_eq!(ModSide::from_jar(&quilt), Some(ModSide::Server));
+
+        let forge = jar(&[(
+            "META-INF/mods.toml",
+            "modLoader=\"javafml\"\nclientSideOnly = true # no server\n[[mods]]\nmodId=\"a\"",
+        )]);
+        assert_eq!(ModSide::from_jar(&forge), Some(ModSide::Client));
+        let forge = jar(&[(
+            "META-INF/neoforge.mods.toml",
+            "modLoader=\"javafml\"\n[[mods]]\nclientSideOnly=true",
+        )]);
+        assert_eq!(ModSide::from_jar(&forge), Some(ModSide::Both));
+
+        assert_eq!(ModSide::from_jar(&jar(&[("a.class", "")])), None);
+    }
+
+    #[test]
+    fn server_only_configs() {
+        assert!(is_server_only_config("FabricProxy-Lite.toml"));
+        assert!(is_server_only_config("luckperms/luckperms.conf"));
+        assert!(!is_server_only_config("sodium-options.json"));
+
+        assert!(has_secret(b"hackOnlineMode = true\nsecret = \"abc\"\n"));
+        assert!(has_secret(b"{\n  \"apiToken\": \"abc\",\n}"));
+        assert!(!has_secret(b"secret = \"\"\n"));
+        assert!(!has_secret(b"# secret = abc\nenabled = true\n"));
+    }
+}
@@ -64,7 +64,11 @@ pub struct CurseforgeNotAllowed {
     pub slug: String,
     pub filename: String,
     pub project_type: QueryType,
+    pub project_id: Arc<str>,
     pub file_id: usize,
+    /// CurseForge's murmur2 fingerprint of the file,
+    /// used to verify manual downloads
+    pub fingerprint: Option<u64>,
 }
 
 #[derive(
@@ -0,0 +1,424 @@
+//! Streaming zip reading/writing for instance packages.
+//!
+//! Files are read from (or written to) disk one at a time,
+//! so huge instances (multi-GB worlds) don't need to fit in
+//! memory or be copied to a temporary folder first.
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+};
+
+use ql_core::{GenericProgress, IntoIoError};
+use walkdir::WalkDir;
+use zip::{CompressionMethod, ZipArchive, ZipWriter, write::SimpleFileOptions};
+
+use crate::InstancePackageError;
+
+/// Only report progress after this many bytes,
+/// to avoid flooding the channel.
+const PROGRESS_INTERVAL: u64 = 1024 * 1024;
+
+/// Compression used for exported instances.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportCompression {
+    /// Slower and bigger, but supported by everything
+    #[default]
+    Deflate,
+    /// Faster and smaller, but older launcher
+    /// versions and other tools may not read it
+    Zstd,
+}
+
+impl ExportCompression {
+    pub const ALL: &[Self] = &[Self::Deflate, Self::Zstd];
+
+    fn get_method(self) -> CompressionMethod {
+        match self {
+            ExportCompression::Deflate => CompressionMethod::Deflated,
+            ExportCompression::Zstd => CompressionMethod::Zstd,
+        }
+    }
+}
+
+impl Display for ExportCompression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ExportCompression::Deflate => "Deflate",
+            ExportCompression::Zstd => "Zstd",
+        })
+    }
+}
+
+/// Byte-level progress across a whole archive.
+pub(crate) struct ByteProgress {
+    sender: Option<Sender<GenericProgress>>,
+    message: &'static str,
+    done: u64,
+    total: u64,
+    last_sent: u64,
+}
+
+impl ByteProgress {
+    pub fn new(sender: Option<Sender<GenericProgress>>, message: &'static str, total: u64) -> Self {
+        Self {
+            sender,
+            message,
+            done: 0,
+            total: total.max(1),
+            last_sent: 0,
+        }
+    }
+
+    // In KB, so it fits in a 32-bit `usize`
+    #[allow(clippy::cast_possible_truncation)]
+    fn add(&mut self, bytes: u64) {
+        self.done += bytes;
+        if self.done - self.last_sent < PROGRESS_INTERVAL && self.done < self.total {
+            return;
+        }
+        self.last_sent = self.done;
+        if let Some(sender) = &self.sender {
+            _ = sender.send(GenericProgress {
+                done: (self.done / 1024) as usize,
+                total: (self.total / 1024).max(1) as usize,
+                message: Some(format!(
+                    "{} ({} / {} MB)",
+                    self.message,
+                    self.done / (1024 * 1024),
+                    self.total / (1024 * 1024)
+                )),
+                has_finished: false,
+            });
+        }
+    }
+
+    /// Copies `reader` into `writer`, reporting progress along the way.
+    fn copy(&mut self, reader: &mut impl Read, writer: &mut impl Write) -> std::io::Result<()> {
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                return Ok(());
+            }
+            writer.write_all(&buf[..n])?;
+            self.add(n as u64);
+        }
+    }
+}
+
+/// Walks `dir`, skipping anything in `exceptions` (and everything inside them).
+fn walk<'a>(dir: &Path, exceptions: &'a [PathBuf]) -> impl Iterator<Item = walkdir::DirEntry> + 'a {
+    WalkDir::new(dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| !exceptions.iter().any(|n| n == entry.path()))
+        // Unreadable entries are skipped, like the old temp-copy did
+        .filter_map(Result::ok)
+}
+
+/// Total size of all files in `dir` (minus `exceptions`), in bytes.
+pub(crate) fn dir_size(dir: &Path, exceptions: &[PathBuf]) -> u64 {
+    walk(dir, exceptions)
+        .filter(|n| n.file_type().is_file())
+        .filter_map(|n| n.metadata().ok())
+        .map(|n| n.len())
+        .sum()
+}
+
+/// Writes a zip straight to a file on disk.
+///
+/// All methods are blocking, so use this
+/// inside [`tokio::task::spawn_blocking`].
+pub(crate) struct ArchiveWriter {
+    zip: ZipWriter<BufWriter<File>>,
+    options: SimpleFileOptions,
+}
+
+impl ArchiveWriter {
+    pub fn create(
+        path: &Path,
+        compression: ExportCompression,
+    ) -> Result<Self, InstancePackageError> {
+        let file = File::create(path).path(path)?;
+        Ok(Self {
+            zip: ZipWriter::new(BufWriter::new(file)),
+            options: SimpleFileOptions::default()
+                .compression_method(compression.get_method())
+                .unix_permissions(0o755)
+                .large_file(true),
+        })
+    }
+
+    /// Adds an in-memory file (metadata, configs, etc).
+    pub fn add_bytes(&mut self, name: &str, bytes: &[u8]) -> Result<(), InstancePackageError> {
+        self.zip.start_file(name, self.options)?;
+        self.zip
+            .write_all(bytes)
+            .map_err(InstancePackageError::ZipIo)?;
+        Ok(())
+    }
+
+    /// Adds a single file from disk as `name`.
+    pub fn add_file(
+        &mut self,
+        path: &Path,
+        name: &str,
+        progress: &mut ByteProgress,
+    ) -> Result<(), InstancePackageError> {
+        let mut file = BufReader::new(File::open(path).path(path)?);
+        self.zip.start_file(name, self.options)?;
+        progress
+            .copy(&mut file, &mut self.zip)
+            .map_err(InstancePackageError::ZipIo)?;
+        Ok(())
+    }
+
+    /// Adds everything inside `dir` under `prefix` in the zip
+    /// (empty prefix for the root), skipping `exceptions`
+    /// (absolute paths) while walking.
+    pub fn add_dir(
+        &mut self,
+        dir: &Path,
+        prefix: &str,
+        exceptions: &[PathBuf],
+        progress: &mut ByteProgress,
+    ) -> Result<(), InstancePackageError> {
+        for entry in walk(dir, exceptions) {
+            let path = entry.path();
+            let relative = path
+                .strip_prefix(dir)
+                .map_err(|_| InstancePackageError::PathBufParent(path.to_owned()))?;
+            // Zip paths always use `/`, even on Windows
+            let relative = relative
+                .components()
+                .map(|n| n.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let name = format!("{prefix}{relative}");
+
+            if entry.file_type().is_dir() {
+                // Directory entries are needed for Java jar loading
+                self.zip.add_directory(name, self.options)?;
+            } else if entry.file_type().is_file() {
+                self.add_file(path, &name, progress)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<(), InstancePackageError> {
+        let mut writer = self.zip.finish()?;
+        writer.flush().map_err(InstancePackageError::ZipIo)?;
+        Ok(())
+    }
+}
+
+/// Reads an instance package directly from the zip file,
+/// without extracting everything to a temporary folder.
+///
+/// Packages may have everything inside one top-level
+/// folder (eg: Prism exports). This is detected and
+/// handled transparently: all names are relative to it.
+pub(crate) struct ArchiveReader {
+    zip: ZipArchive<BufReader<File>>,
+    root: String,
+}
+
+impl ArchiveReader {
+    /// Opens a zip, detecting the package root by
+    /// looking for any of the `markers` files.
+    pub fn open(path: &Path, markers: &[&str]) -> Result<Self, InstancePackageError> {
+        let file = File::open(path).path(path)?;
+        let zip = ZipArchive::new(BufReader::new(file))?;
+
+        let root = zip
+            .file_names()
+            .filter_map(|name| {
+                let (dir, file) = name.rsplit_once('/').unwrap_or(("", name));
+                markers.contains(&file).then(|| {
+                    if dir.is_empty() {
+                        String::new()
+                    } else {
+                        format!("{dir}/")
+                    }
+                })
+            })
+            .min_by_key(String::len)
+            .unwrap_or_default();
+
+        Ok(Self { zip, root })
+    }
+
+    /// Reads a (small) file from the package root, if it exists.
+    pub fn read_string(&mut self, name: &str) -> Result<Option<String>, InstancePackageError> {
+        let mut file = match self.zip.by_name(&format!("{}{name}", self.root)) {
+            Ok(n) => n,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut out = String::new();
+        file.read_to_string(&mut out)
+            .map_err(InstancePackageError::ZipIo)?;
+        Ok(Some(out))
+    }
+
+    /// Extracts files straight to their final location.
+    ///
+    /// `map` takes a path relative to the package root
+    /// (with `/` separators) and returns where it should go,
+    /// or `None` to skip it.
+    pub async fn extract(
+        mut self,
+        map: impl Fn(&str) -> Option<PathBuf> + Send + 'static,
+        progress: Option<Sender<GenericProgress>>,
+    ) -> Result<(), InstancePackageError> {
+        tokio::task::spawn_blocking(move || {
+            let mut files = Vec::new();
+            let mut total = 0;
+            for i in 0..self.zip.len() {
+                let file = self.zip.by_index_raw(i)?;
+                // Guards against `../` zip-slip attacks
+                if file.enclosed_name().is_none() {
+                    continue;
+                }
+                let Some(name) = file.name().strip_prefix(&self.root) else {
+                    continue;
+                };
+                let Some(dest) = map(name.trim_end_matches('/')) else {
+                    continue;
+                };
+                total += file.size();
+                files.push((i, dest, file.is_dir()));
+            }
+
+            let mut progress = ByteProgress::new(progress, "Extracting files...", total);
+            for (i, dest, is_dir) in files {
+                if is_dir {
+                    std::fs::create_dir_all(&dest).path(&dest)?;
+                    continue;
+                }
+                if let Some(parent) = dest.parent() {
+                    std::fs::create_dir_all(parent).path(parent)?;
+                }
+                let mut file = self.zip.by_index(i)?;
+                let mut out = BufWriter::new(File::create(&dest).path(&dest)?);
+                progress
+                    .copy(&mut file, &mut out)
+                    .map_err(InstancePackageError::ZipIo)?;
+                out.flush().map_err(InstancePackageError::ZipIo)?;
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|n| InstancePackageError::ZipIo(std::io::Error::other(n)))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty temporary folder
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ql-archive-test-{name}"));
+        _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Extracts everything under `prefix` into `dir`
+    async fn extract_into(reader: ArchiveReader, prefix: &'static str, dir: &Path) {
+        let dir = dir.to_owned();
+        reader
+            .extract(
+                move |name| name.strip_prefix(prefix).map(|n| dir.join(n)),
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn roundtrip() {
+        for compression in ExportCompression::ALL {
+            let dir = temp_dir(&format!("roundtrip-{compression}"));
+            let src = dir.join("src");
+            std::fs::create_dir_all(src.join("saves/world")).unwrap();
+            std::fs::create_dir_all(src.join("logs")).unwrap();
+            std::fs::write(src.join("options.txt"), "fov:90").unwrap();
+            let level: Vec<u8> = (0..200_000_u32).map(|n| (n % 251) as u8).collect();
+            std::fs::write(src.join("saves/world/level.dat"), &level).unwrap();
+            std::fs::write(src.join("logs/latest.log"), "skipped").unwrap();
+            std::fs::write(src.join("secret.txt"), "skipped").unwrap();
+
+            let zip_path = dir.join("package.zip");
+            let exceptions = [src.join("logs"), src.join("secret.txt")];
+            let mut writer = ArchiveWriter::create(&zip_path, *compression).unwrap();
+            writer.add_bytes("meta.json", b"{}").unwrap();
+            let mut progress = ByteProgress::new(None, "", dir_size(&src, &exceptions));
+            writer
+                .add_dir(&src, "files/", &exceptions, &mut progress)
+                .unwrap();
+            writer.finish().unwrap();
+            assert_eq!(dir_size(&src, &exceptions), 6 + level.len() as u64);
+
+            let mut zip = ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+            let method = zip.by_name("files/options.txt").unwrap().compression();
+            assert_eq!(method, compression.get_method());
+
+            let mut reader = ArchiveReader::open(&zip_path, &["meta.json"]).unwrap();
+            assert_eq!(reader.read_string("meta.json").unwrap().unwrap(), "{}");
+            assert_eq!(reader.read_string("missing.json").unwrap(), None);
+
+            let out = dir.join("out");
+            extract_into(reader, "files/", &out).await;
+            assert_eq!(
+                std::fs::read_to_string(out.join("options.txt")).unwrap(),
+                "fov:90"
+            );
+            assert_eq!(
+                std::fs::read(out.join("saves/world/level.dat")).unwrap(),
+                level
+            );
+            assert!(!out.join("logs").exists());
+            assert!(!out.join("secret.txt").exists());
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn root_folder_and_zip_slip() {
+        let dir = temp_dir("zip-slip");
+        let zip_path = dir.join("package.zip");
+        let mut zip = ZipWriter::new(File::create(&zip_path).unwrap());
+        for (name, contents) in [
+            ("Pack/instance.cfg", "name=Pack"),
+            ("Pack/.minecraft/options.txt", "fov:90"),
+            ("Pack/../../evil.txt", "escaped"),
+        ] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        // Everything is inside `Pack/`
+        let mut reader = ArchiveReader::open(&zip_path, &["instance.cfg"]).unwrap();
+        assert_eq!(
+            reader.read_string("instance.cfg").unwrap().unwrap(),
+            "name=Pack"
+        );
+
+        let out = dir.join("a/b");
+        extract_into(reader, "", &out).await;
+        assert!(out.join(".minecraft/options.txt").is_file());
+        assert!(!dir.join("a/evil.txt").exists());
+        assert!(!dir.join("evil.txt").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
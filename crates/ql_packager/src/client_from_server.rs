@@ -0,0 +1,151 @@
+use std::{collections::HashSet, sync::mpsc::Sender};
+
+use ql_core::{
+    DownloadProgress, Instance, IntoIoError, ListEntry, Loader, info,
+    json::{InstanceConfigJson, VersionDetails},
+    pt,
+};
+use ql_mod_manager::{
+    loaders::LoaderInstallResult,
+    store::{CurseforgeNotAllowed, ModId, ModSide},
+};
+
+use crate::InstancePackageError;
+
+/// What [`create_client_from_server`] did.
+#[derive(Debug, Clone)]
+pub struct ClientFromServer {
+    /// Name of the new instance (sanitized)
+    pub name: String,
+    /// Server-only mods that were left out
+    pub skipped_mods: Vec<(String, ModSide)>,
+    /// Companion mods blocked by CurseForge,
+    /// that have to be downloaded manually
+    pub not_allowed: HashSet<CurseforgeNotAllowed>,
+}
+
+/// Creates a client instance that can join `server`: the same
+/// Minecraft version, the exact same loader version, its mods
+/// (except server-only ones) and its configs.
+///
+/// `companions` are extra (usually client-only) mods to install,
+/// as Modrinth project IDs or slugs (eg: a minimap).
+///
+/// To hand the same thing out to players instead,
+/// see [`ql_mod_manager::store::export_modrinth_pack`].
+///
+/// Plugin servers (Paper, etc.) and proxies get
+/// a vanilla client, as their plugins are server-only.
+///
+/// If anything fails after the instance was created,
+/// it's deleted again.
+///
+/// # Errors
+/// - The server couldn't be read
+/// - The instance couldn't be created
+///   (see [`ql_instances::create_instance`])
+/// - The loader couldn't be installed
+/// - The mods couldn't be copied or downloaded
+pub async fn create_client_from_server(
+    server: &Instance,
+    name: String,
+    companions: Vec<String>,
+    progress: Option<Sender<DownloadProgress>>,
+) -> Result<ClientFromServer, InstancePackageError> {
+    let config = InstanceConfigJson::read(server).await?;
+    let details = VersionDetails::load(server).await?;
+    let loader = match config.mod_type {
+        n @ (Loader::Fabric | Loader::Quilt | Loader::Forge | Loader::NeoForge) => n,
+        _ => Loader::Vanilla,
+    };
+    let loader_version = config.mod_type_info.and_then(|n| n.version);
+
+    let name = ql_instances::create_instance(
+        name,
+        ListEntry::new(details.get_id().to_owned()),
+        progress,
+        true,
+    )
+    .await?;
+    let client = Instance::client(&name);
+    info!("Setting up {name} to join {}", server.name);
+
+    let mut created = ClientFromServer {
+        name,
+        skipped_mods: Vec::new(),
+        not_allowed: HashSet::new(),
+    };
+    if loader.is_vanilla() {
+        return Ok(created);
+    }
+    let result = set_up_for_server(
+        server,
+        &client,
+        loader,
+        loader_version,
+        details.get_id(),
+        companions,
+        &mut created,
+    )
+    .await;
+    if result.is_err() {
+        client.remove_unfinished().await;
+    }
+    result?;
+
+    pt!("Finished");
+    Ok(created)
+}
+
+async fn set_up_for_server(
+    server: &Instance,
+    client: &Instance,
+    loader: Loader,
+    loader_version: Option<String>,
+    minecraft: &str,
+    companions: Vec<String>,
+    created: &mut ClientFromServer,
+) -> Result<(), InstancePackageError> {
+    match ql_mod_manager::loaders::install_specified_loader(
+        client.clone(),
+        loader,
+        None,
+        loader_version,
+    )
+    .await
+    .map_err(InstancePackageError::Loader)?
+    {
+        LoaderInstallResult::Ok => {}
+        LoaderInstallResult::NeedsOptifine | LoaderInstallResult::Unsupported => {
+            return Err(InstancePackageError::Loader(format!(
+                "{loader} can't be installed automatically for Minecraft {minecraft}"
+            )));
+        }
+    }
+
+    created.skipped_mods = ql_mod_manager::store::copy_mods(server, client)
+        .await?
+        .skipped;
+
+    pt!("Copying config");
+    // Without proxy secrets and other server-only configs
+    let config_dir = client.get_dot_minecraft_path().join("config");
+    for (path, contents) in ql_mod_manager::store::client_configs(server).await? {
+        let path = config_dir.join(path);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.path(parent)?;
+        }
+        tokio::fs::write(&path, contents).await.path(path)?;
+    }
+
+    if !companions.is_empty() {
+        pt!("Installing companion mods");
+        let ids = companions
+            .into_iter()
+            .map(|n| ModId::Modrinth(n.into()))
+            .collect();
+        created.not_allowed =
+            ql_mod_manager::store::download_mods_bulk(ids, client.clone(), None).await?;
+    }
+    Ok(())
+}
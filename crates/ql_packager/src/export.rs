@@ -1,10 +1,10 @@
-use ql_core::{GenericProgress, file_utils};
-use ql_core::{Instance, IntoIoError, IntoJsonError, info, pt};
+use ql_core::GenericProgress;
+use ql_core::{Instance, IntoJsonError, info, pt};
 use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::mpsc::Sender;
-use tokio::fs;
 
+use crate::archive::{ArchiveWriter, ByteProgress, ExportCompression, dir_size};
 use crate::{InstanceInfo, InstancePackageError};
 
 pub const EXCEPTIONS: &[&str] = &[
@@ -24,87 +24,71 @@ fn create_instance_info(instance: &Instance, mut exceptions: HashSet<String>) ->
     }
 }
 
-/// Exports a Minecraft instance by copying its files to a temporary directory,
-/// removing specified exceptions, generating a metadata JSON, and zipping the result.
+/// Exports a Minecraft instance to a `.zip` file at `output`.
+///
+/// Files are streamed straight from the instance directory
+/// into the zip (no temporary copy), skipping exceptions
+/// while walking, so this works fine even with multi-GB worlds.
 ///
 /// # Arguments
 ///
 /// - `instance` - the selected instance to export
-/// - `exceptions` - An optional vector of paths to exclude from the export.
-///   By default, it will contain the contents of [`EXCEPTIONS`]. If you
-///   don't want any extra exceptions, just pass an empty `Vec`.
+/// - `exceptions` - Paths to exclude from the export
+///   (eg: `.minecraft/saves` to leave out worlds).
+///   The contents of [`EXCEPTIONS`] are always excluded.
+///   If you don't want any extra exceptions, just pass an empty set.
 ///   **Note: All exception paths are relative to instance dir
 ///   (parent dir of `.minecraft`)**
-///
-/// # Returns
-///
-/// Returns `Ok(Vec<u8>)` (bytes of the packaged file)
-/// if the export succeeds, or an error if any step fails.
-///
-/// # Process
-///
-/// 2. Constructs a new `InstanceInfo` with exceptions.
-/// 3. Copies the instance files into a temporary directory.
-/// 4. Writes the `InstanceInfo` to a `quantum-config.json` inside temp folder.
-/// 5. Deletes the excluded directories/files from the temp copy.
-/// 6. Compresses the temp folder into a `.zip` archive at the given destination.
+/// - `output` - Where to write the `.zip` file
+/// - `compression` - See [`ExportCompression`]
+/// - `progress` - Byte-level progress of the export
 ///
 /// # Errors
 ///
 /// Returns an error if:
-/// - The instance version can't be found.
 /// - The instance directory doesn't exist.
-/// - File I/O operations (copying, deleting, zipping) fail.
+/// - File I/O operations (reading, zipping) fail.
+///
+/// On error, the partially written `output` file is removed.
 pub async fn export_instance(
     instance: Instance,
     exceptions: HashSet<String>,
+    output: PathBuf,
+    compression: ExportCompression,
     progress: Option<Sender<GenericProgress>>,
-) -> Result<Vec<u8>, InstancePackageError> {
+) -> Result<(), InstancePackageError> {
     info!("Exporting instance...");
     let export_config = create_instance_info(&instance, exceptions);
-    // println!("{:?}",export_config);
-
     pt!(
         "Exceptions (not included in export): {:?}",
         export_config.exceptions
     );
-    if let Some(prog) = &progress {
-        _ = prog.send(GenericProgress {
-            done: 0,
-            total: 2,
-            message: Some("Copying data...".to_owned()),
-            has_finished: false,
-        });
-    }
-    let dir = tempfile::TempDir::new().map_err(InstancePackageError::TempDir)?;
+    let config = serde_json::to_string_pretty(&export_config).json_to()?;
+
     let instance_path = instance.get_instance_path();
-    let collect: Vec<PathBuf> = export_config
+    let exceptions: Vec<PathBuf> = export_config
         .exceptions
         .iter()
         .map(|n| instance_path.join(n))
         .collect();
-    file_utils::copy_dir_recursive_ext(&instance_path, dir.path(), &collect).await?;
-    let folder_path = dir.path();
 
-    // pt!("{:?}",temp_instance_path);
-    pt!("Creating metadata");
-    let config = serde_json::to_string_pretty(&export_config).json_to()?;
-    let config_path = folder_path.join("quantum-config.json");
-    fs::write(&config_path, config).await.path(&config_path)?;
+    let out = output.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let total = dir_size(&instance_path, &exceptions);
+        let mut progress = ByteProgress::new(progress, "Zipping files...", total);
 
-    pt!("Packaging the instance into zip");
-    if let Some(prog) = &progress {
-        _ = prog.send(GenericProgress {
-            done: 1,
-            total: 2,
-            message: Some("Zipping files...".to_owned()),
-            has_finished: false,
-        });
-    }
-    let bytes = file_utils::zip_directory_to_bytes(folder_path)
-        .await
-        .map_err(InstancePackageError::ZipIo)?;
-    pt!("Done!");
+        let mut writer = ArchiveWriter::create(&out, compression)?;
+        writer.add_dir(&instance_path, "", &exceptions, &mut progress)?;
+        writer.add_bytes("quantum-config.json", config.as_bytes())?;
+        writer.finish()
+    })
+    .await
+    .map_err(|n| InstancePackageError::ZipIo(std::io::Error::other(n)))?;
 
-    Ok(bytes)
+    if result.is_err() {
+        _ = tokio::fs::remove_file(&output).await;
+    } else {
+        pt!("Done!");
+    }
+    result
 }
@@ -1,11 +1,12 @@
 use ql_core::{
-    GenericProgress, Instance, InstanceKind, IntoIoError, IntoJsonError, ListEntry, Progress,
-    file_utils, info,
+    GenericProgress, Instance, InstanceKind, IntoIoError, IntoJsonError, LAUNCHER_DIR, ListEntry,
+    Progress, info,
     json::{InstanceConfigJson, VersionDetails},
     pt,
 };
+use serde::de::DeserializeOwned;
 use std::{
-    path::{Path, PathBuf},
+    path::PathBuf,
     sync::{
         Arc,
         mpsc::{Receiver, Sender},
@@ -13,24 +14,26 @@ use std::{
 };
 use tokio::fs;
 
-use crate::InstanceInfo;
+use crate::{InstanceInfo, archive::ArchiveReader};
 
 use super::InstancePackageError;
 
 pub const OUT_OF: usize = 4;
 
-/// Imports a Minecraft instance from a `.zip` file exported by the launcher.
+/// Imports a Minecraft instance from a `.zip` file exported by the launcher
+/// (or by MultiMC/Prism Launcher).
 ///
 /// This function performs the following:
-/// 1. Extracts the ZIP archive to a temporary directory.
-/// 2. Reads the `quantum-config.json` from the extracted directory to get instance metadata.
-/// 3. Creates a new instance using the extracted configuration.
-/// 4. Copies the extracted files to the main instances directory.
+/// 1. Reads the metadata (`quantum-config.json` or `mmc-pack.json`)
+///    straight out of the zip.
+/// 2. Creates a new instance based on it.
+/// 3. Extracts the packaged files directly into the new instance
+///    (no temporary copy, so large worlds import quickly).
 ///
-/// Finally, it returns a bool indicating whether the file
+/// Finally, it returns an `Option` indicating whether the file
 /// was an actual packaged instance or not. You can use this
 /// for fuzzy file detection, running this function and running
-/// something else if it's `false`.
+/// something else if it's `None`.
 ///
 /// # Parameters
 /// - `zip_path`: The path to the `.zip` archive to import. It must contain a `quantum-config.json` file inside the root of the zipped instance folder.
@@ -48,63 +51,45 @@ pub async fn import_instance(
     download_assets: bool,
     sender: Option<Sender<GenericProgress>>,
 ) -> Result<Option<Instance>, InstancePackageError> {
-    let temp_dir_obj = tempfile::TempDir::new().map_err(InstancePackageError::TempDir)?;
-    let temp_dir = temp_dir_obj.path();
-
-    pt!("Extracting zip to {temp_dir:?}");
-    let zip_file = std::fs::File::open(&zip_path).path(&zip_path)?;
+    pt!("Reading zip {zip_path:?}");
     if let Some(sender) = &sender {
         _ = sender.send(GenericProgress {
             done: 0,
             total: OUT_OF,
-            message: Some("Extracting Archive...".to_owned()),
+            message: Some("Reading Archive...".to_owned()),
             has_finished: false,
         });
     }
-    file_utils::extract_zip_archive(std::io::BufReader::new(zip_file), temp_dir, true).await?;
-
-    let try_ql = temp_dir.join("quantum-config.json");
-    let try_mmc = temp_dir.join("mmc-pack.json");
+    let mut reader = ArchiveReader::open(&zip_path, &["quantum-config.json", "mmc-pack.json"])?;
 
-    let instance = if let Ok(instance_info) = fs::read_to_string(&try_ql).await {
+    let instance = if let Some(instance_info) = reader.read_string("quantum-config.json")? {
         Some(
-            import_quantumlauncher(
-                download_assets,
-                temp_dir,
-                instance_info,
-                sender.map(Arc::new),
-            )
-            .await?,
+            import_quantumlauncher(download_assets, reader, instance_info, sender.map(Arc::new))
+                .await?,
         )
-    } else if let Ok(mmc_pack) = fs::read_to_string(&try_mmc).await {
+    } else if let Some(mmc_pack) = reader.read_string("mmc-pack.json")? {
         Some(
-            crate::multimc::import(download_assets, temp_dir, &mmc_pack, sender.map(Arc::new))
+            crate::multimc::import(download_assets, reader, &mmc_pack, sender.map(Arc::new))
                 .await?,
         )
     } else {
         None
     };
 
-    fs::remove_dir_all(&temp_dir).await.path(temp_dir)?;
-
     Ok(instance)
 }
 
 async fn import_quantumlauncher(
     download_assets: bool,
-    temp_dir: &Path,
+    mut reader: ArchiveReader,
     instance_info: String,
     sender: Option<Arc<Sender<GenericProgress>>>,
 ) -> Result<Instance, InstancePackageError> {
     info!("Importing QuantumLauncher instance...");
 
     let instance_info: InstanceInfo = serde_json::from_str(&instance_info).json(instance_info)?;
-    let version_json: VersionDetails = VersionDetails::load_from_path(temp_dir).await?;
-    let config_json: InstanceConfigJson = {
-        let path = temp_dir.join("config.json");
-        let file = fs::read_to_string(&path).await.path(&path)?;
-        serde_json::from_str(&file).json(file)?
-    };
+    let version_json: VersionDetails = read_json(&mut reader, "details.json")?;
+    let config_json: InstanceConfigJson = read_json(&mut reader, "config.json")?;
 
     let instance = Instance::new(
         &instance_info.instance_name,
@@ -150,20 +135,44 @@ async fn import_quantumlauncher(
     .await
     .map_err(InstancePackageError::Loader)?;
 
-    pt!("Copying packaged files");
-    if let Some(sender) = &sender {
-        _ = sender.send(GenericProgress {
-            done: 2,
-            total: OUT_OF,
-            message: Some("Copying files...".to_owned()),
-            has_finished: false,
-        });
-    }
-    file_utils::copy_dir_recursive(temp_dir, &instance_path).await?;
+    pt!("Extracting packaged files");
+    reader
+        .extract(
+            move |name| (name != "quantum-config.json").then(|| instance_path.join(name)),
+            sender.as_deref().cloned(),
+        )
+        .await?;
     info!("Finished importing QuantumLauncher instance");
     Ok(instance)
 }
 
+fn read_json<T: DeserializeOwned>(
+    reader: &mut ArchiveReader,
+    name: &str,
+) -> Result<T, InstancePackageError> {
+    let file = reader
+        .read_string(name)?
+        .ok_or_else(|| InstancePackageError::ZipFileMissing(name.to_owned()))?;
+    Ok(serde_json::from_str(&file).json(file)?)
+}
+
+/// Picks a name for a new client instance that doesn't clash
+/// with existing ones: if `MyInstance` exists, tries
+/// `MyInstance (1)`, `MyInstance (2)`...
+pub(crate) async fn get_unique_name(instance_name: &str) -> Result<String, InstancePackageError> {
+    let instance_dir = LAUNCHER_DIR.join("instances");
+    let mut name = instance_name.to_owned();
+    let mut path = instance_dir.join(&name);
+
+    let mut name_i = 1;
+    while fs::try_exists(&path).await.path(&path)? {
+        name = format!("{instance_name} ({name_i})");
+        path = instance_dir.join(&name);
+        name_i += 1;
+    }
+    Ok(name)
+}
+
 pub fn pipe_progress<T: Progress>(rec: Receiver<T>, snd: &Sender<GenericProgress>) {
     for item in rec {
         _ = snd.send(item.into_generic());
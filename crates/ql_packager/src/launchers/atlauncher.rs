@@ -0,0 +1,199 @@
+use std::{path::Path, sync::Arc};
+
+use chrono::{DateTime, FixedOffset};
+use ql_core::{IntoIoError, IntoJsonError, Loader, err, file_utils};
+use ql_mod_manager::store::{ModId, QueryType};
+use serde::Deserialize;
+use tokio::fs;
+
+use super::{
+    ForeignInstance, ImportRecipe, KnownMod, OtherLauncher, is_launcher_file, parse_java_args,
+};
+use crate::InstancePackageError;
+
+/// ATLauncher's `instance.json`: a Minecraft version JSON
+/// with an extra `launcher` field for ATLauncher's own stuff.
+#[derive(Deserialize)]
+struct AtlInstance {
+    /// Minecraft version
+    id: String,
+    launcher: AtlLauncher,
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct AtlLauncher {
+    name: String,
+    loaderVersion: Option<AtlLoaderVersion>,
+    maximumMemory: Option<usize>,
+    javaArguments: Option<String>,
+    #[serde(default)]
+    mods: Vec<AtlMod>,
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct AtlLoaderVersion {
+    version: String,
+    r#type: String,
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct AtlMod {
+    name: String,
+    version: Option<String>,
+    file: String,
+    r#type: Option<String>,
+    #[serde(default)]
+    disabled: bool,
+    curseForgeProjectId: Option<u64>,
+    curseForgeFileId: Option<u64>,
+    modrinthProject: Option<AtlModrinthProject>,
+    modrinthVersion: Option<AtlModrinthVersion>,
+}
+
+#[derive(Deserialize)]
+struct AtlModrinthProject {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct AtlModrinthVersion {
+    version_number: Option<String>,
+    date_published: Option<DateTime<FixedOffset>>,
+    #[serde(default)]
+    game_versions: Vec<String>,
+}
+
+impl AtlLoaderVersion {
+    fn get_loader(&self) -> Loader {
+        match self.r#type.as_str() {
+            "Fabric" => Loader::Fabric,
+            "Quilt" => Loader::Quilt,
+            "Forge" => Loader::Forge,
+            "NeoForge" => Loader::NeoForge,
+            n => {
+                err!("Unsupported ATLauncher loader: {n}");
+                Loader::Vanilla
+            }
+        }
+    }
+}
+
+impl AtlMod {
+    fn get_project_type(&self) -> Option<QueryType> {
+        match self.r#type.as_deref() {
+            Some("mods") | None => Some(QueryType::Mods),
+            Some("resourcepack") => Some(QueryType::ResourcePacks),
+            Some("shaderpack") => Some(QueryType::Shaders),
+            _ => None,
+        }
+    }
+
+    fn into_known(self) -> Option<KnownMod> {
+        let project_type = self.get_project_type()?;
+        let (id, release_time, supported_versions, version) =
+            if let Some(project) = &self.modrinthProject {
+                let version = self.modrinthVersion.as_ref();
+                (
+                    ModId::Modrinth(Arc::from(project.id.as_str())),
+                    version.and_then(|n| n.date_published),
+                    version.map(|n| n.game_versions.clone()).unwrap_or_default(),
+                    version.and_then(|n| n.version_number.clone()),
+                )
+            } else if let Some(project_id) = self.curseForgeProjectId {
+                (
+                    ModId::Curseforge(Arc::from(project_id.to_string())),
+                    None,
+                    Vec::new(),
+                    self.curseForgeFileId.map(|n| n.to_string()),
+                )
+            } else {
+                return None;
+            };
+
+        Some(KnownMod {
+            id,
+            name: self.name,
+            version: version.or(self.version).unwrap_or_default(),
+            release_time,
+            url: None,
+            filename: self.file,
+            project_type,
+            enabled: !self.disabled,
+            supported_versions,
+        })
+    }
+}
+
+async fn read_instance(dir: &Path) -> Result<AtlInstance, InstancePackageError> {
+    let path = dir.join("instance.json");
+    let file = fs::read_to_string(&path).await.path(path)?;
+    Ok(serde_json::from_str(&file).json(file)?)
+}
+
+pub async fn list(dir: &Path) -> Result<Vec<ForeignInstance>, InstancePackageError> {
+    let instances_dir = dir.join("instances");
+    if !file_utils::exists(&instances_dir).await {
+        return Ok(Vec::new());
+    }
+
+    let mut instances = Vec::new();
+    for entry in file_utils::read_filenames_from_dir(&instances_dir).await? {
+        if entry.is_file {
+            continue;
+        }
+        let path = instances_dir.join(&entry.name);
+        match read_instance(&path).await {
+            Ok(instance) => instances.push(ForeignInstance {
+                launcher: OtherLauncher::ATLauncher,
+                name: instance.launcher.name,
+                version: instance.id,
+                loader: instance
+                    .launcher
+                    .loaderVersion
+                    .as_ref()
+                    .map_or(Loader::Vanilla, AtlLoaderVersion::get_loader),
+                path,
+                profile_id: None,
+            }),
+            Err(error) => err!("Skipping ATLauncher instance {}: {error}", entry.name),
+        }
+    }
+    Ok(instances)
+}
+
+pub async fn get_recipe(foreign: &ForeignInstance) -> Result<ImportRecipe, InstancePackageError> {
+    let instance = read_instance(&foreign.path).await?;
+    let launcher = instance.launcher;
+
+    let (ram_in_mb, java_args) = launcher
+        .javaArguments
+        .as_deref()
+        .map(parse_java_args)
+        .unwrap_or_default();
+
+    Ok(ImportRecipe {
+        name: launcher.name,
+        mc_version: instance.id,
+        loader: launcher
+            .loaderVersion
+            .as_ref()
+            .map_or(Loader::Vanilla, AtlLoaderVersion::get_loader),
+        loader_version: launcher.loaderVersion.map(|n| n.version),
+        ram_in_mb: launcher.maximumMemory.or(ram_in_mb),
+        java_args,
+        window_size: None,
+        game_dir: foreign.path.clone(),
+        disabled_mods_dir: Some(foreign.path.join("disabledmods")),
+        skip: |name| {
+            is_launcher_file(name) || matches!(name, "instance.json" | "loaders" | "disabledmods")
+        },
+        mods: launcher
+            .mods
+            .into_iter()
+            .filter_map(AtlMod::into_known)
+            .collect(),
+    })
+}
@@ -0,0 +1,189 @@
+use std::{path::Path, sync::Arc};
+
+use chrono::{DateTime, FixedOffset};
+use ql_core::{IntoIoError, IntoJsonError, Loader, err, file_utils};
+use ql_mod_manager::store::{ModId, QueryType};
+use serde::Deserialize;
+use tokio::fs;
+
+use super::{
+    ForeignInstance, ImportRecipe, KnownMod, OtherLauncher, is_launcher_file, parse_java_args,
+};
+use crate::InstancePackageError;
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct CfInstance {
+    name: String,
+    gameVersion: String,
+    baseModLoader: Option<CfModLoader>,
+    /// In MB
+    allocatedMemory: Option<usize>,
+    javaArgsOverride: Option<String>,
+    #[serde(default)]
+    installedAddons: Vec<CfAddon>,
+}
+
+#[derive(Deserialize)]
+struct CfModLoader {
+    /// eg: `forge-47.2.0`, `fabric-0.15.0-1.20.1`
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct CfAddon {
+    addonID: u64,
+    name: String,
+    installedFile: Option<CfFile>,
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct CfFile {
+    fileName: String,
+    displayName: Option<String>,
+    fileDate: Option<DateTime<FixedOffset>>,
+    downloadUrl: Option<String>,
+    #[serde(default)]
+    gameVersion: Vec<String>,
+}
+
+impl CfModLoader {
+    fn get_loader(&self, mc_version: &str) -> (Loader, Option<String>) {
+        let Some((kind, version)) = self.name.split_once('-') else {
+            return (Loader::Vanilla, None);
+        };
+        let loader = match kind {
+            "forge" => Loader::Forge,
+            "neoforge" => Loader::NeoForge,
+            "fabric" => Loader::Fabric,
+            "quilt" => Loader::Quilt,
+            n => {
+                err!("Unsupported CurseForge loader: {n}");
+                return (Loader::Vanilla, None);
+            }
+        };
+        // Fabric/Quilt have the Minecraft version at the end
+        let version = version
+            .strip_suffix(mc_version)
+            .map_or(version, |n| n.trim_end_matches('-'));
+        (loader, Some(version.to_owned()))
+    }
+}
+
+async fn read_instance(dir: &Path) -> Result<CfInstance, InstancePackageError> {
+    let path = dir.join("minecraftinstance.json");
+    let file = fs::read_to_string(&path).await.path(path)?;
+    Ok(serde_json::from_str(&file).json(file)?)
+}
+
+pub async fn list(dir: &Path) -> Result<Vec<ForeignInstance>, InstancePackageError> {
+    let instances_dir = dir.join("Instances");
+    if !file_utils::exists(&instances_dir).await {
+        return Ok(Vec::new());
+    }
+
+    let mut instances = Vec::new();
+    for entry in file_utils::read_filenames_from_dir(&instances_dir).await? {
+        if entry.is_file {
+            continue;
+        }
+        let path = instances_dir.join(&entry.name);
+        match read_instance(&path).await {
+            Ok(instance) => instances.push(ForeignInstance {
+                launcher: OtherLauncher::CurseForge,
+                loader: instance
+                    .baseModLoader
+                    .as_ref()
+                    .map_or(Loader::Vanilla, |n| n.get_loader(&instance.gameVersion).0),
+                name: instance.name,
+                version: instance.gameVersion,
+                path,
+                profile_id: None,
+            }),
+            Err(error) => err!("Skipping CurseForge instance {}: {error}", entry.name),
+        }
+    }
+    Ok(instances)
+}
+
+pub async fn get_recipe(foreign: &ForeignInstance) -> Result<ImportRecipe, InstancePackageError> {
+    let instance = read_instance(&foreign.path).await?;
+    let (loader, loader_version) = instance
+        .baseModLoader
+        .as_ref()
+        .map_or((Loader::Vanilla, None), |n| {
+            n.get_loader(&instance.gameVersion)
+        });
+
+    let (ram_in_mb, java_args) = instance
+        .javaArgsOverride
+        .as_deref()
+        .map(parse_java_args)
+        .unwrap_or_default();
+
+    let mut mods = Vec::new();
+    for addon in instance.installedAddons {
+        let Some(file) = addon.installedFile else {
+            continue;
+        };
+        // CurseForge doesn't say what kind of addon it is,
+        // so see where the file ended up
+        let mut found = None;
+        for (project_type, dir) in [
+            (QueryType::Mods, "mods"),
+            (QueryType::ResourcePacks, "resourcepacks"),
+            (QueryType::Shaders, "shaderpacks"),
+        ] {
+            let dir = foreign.path.join(dir);
+            if file_utils::exists(&dir.join(&file.fileName)).await {
+                found = Some((project_type, true));
+            } else if file_utils::exists(&dir.join(format!("{}.disabled", file.fileName))).await {
+                found = Some((project_type, false));
+            }
+            if found.is_some() {
+                break;
+            }
+        }
+        let Some((project_type, enabled)) = found else {
+            continue;
+        };
+
+        mods.push(KnownMod {
+            id: ModId::Curseforge(Arc::from(addon.addonID.to_string())),
+            name: addon.name,
+            version: file.displayName.unwrap_or_else(|| file.fileName.clone()),
+            release_time: file.fileDate,
+            url: file.downloadUrl,
+            filename: file.fileName,
+            project_type,
+            enabled,
+            supported_versions: file
+                .gameVersion
+                .into_iter()
+                .filter(|n| n.contains('.'))
+                .collect(),
+        });
+    }
+
+    Ok(ImportRecipe {
+        name: instance.name,
+        mc_version: instance.gameVersion,
+        loader,
+        loader_version,
+        ram_in_mb: instance.allocatedMemory.or(ram_in_mb),
+        java_args,
+        window_size: None,
+        game_dir: foreign.path.clone(),
+        disabled_mods_dir: None,
+        skip: |name| {
+            is_launcher_file(name)
+                || matches!(
+                    name,
+                    "minecraftinstance.json" | ".curseclient" | "profileImage"
+                )
+        },
+        mods,
+    })
+}
@@ -0,0 +1,456 @@
+//! Importing instances from other launchers, installed
+//! on the same system:
+//! - Official Minecraft Launcher (`launcher_profiles.json` + `versions/`)
+//! - ATLauncher (`instance.json`)
+//! - CurseForge App (`minecraftinstance.json`)
+//!
+//! Use [`discover_instances`] to find instances in the default
+//! install locations (or [`list_instances_in`] for custom ones),
+//! then [`import_from_launcher`] to import one of them.
+
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::{Arc, mpsc::Sender},
+};
+
+use chrono::{DateTime, FixedOffset};
+use ql_core::{
+    GenericProgress, Instance, IntoIoError, ListEntry, Loader, err, file_utils, info,
+    json::InstanceConfigJson, pt,
+};
+use ql_mod_manager::{
+    loaders::LoaderInstallResult,
+    store::{ModConfig, ModFile, ModId, ModIndex, QueryType},
+};
+use tokio::fs;
+
+use crate::{
+    InstancePackageError,
+    import::{OUT_OF, get_unique_name, pipe_progress},
+};
+
+mod atlauncher;
+mod curseforge;
+mod official;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OtherLauncher {
+    Official,
+    ATLauncher,
+    CurseForge,
+}
+
+impl OtherLauncher {
+    pub const ALL: &[Self] = &[Self::Official, Self::ATLauncher, Self::CurseForge];
+
+    /// Where this launcher stores its data by default.
+    /// Not every path is guaranteed to exist.
+    #[must_use]
+    pub fn default_locations(self) -> Vec<PathBuf> {
+        let home = dirs::home_dir();
+        let data = dirs::data_dir();
+        match self {
+            OtherLauncher::Official => [
+                #[cfg(target_os = "windows")]
+                data.map(|n| n.join(".minecraft")),
+                #[cfg(target_os = "macos")]
+                data.map(|n| n.join("minecraft")),
+                #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+                home.as_ref().map(|n| n.join(".minecraft")),
+                #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+                home.map(|n| n.join(".var/app/com.mojang.Minecraft/.minecraft")),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            OtherLauncher::ATLauncher => [
+                data.map(|n| n.join("ATLauncher")),
+                #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+                home.map(|n| n.join(".var/app/com.atlauncher.ATLauncher/data/ATLauncher")),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            OtherLauncher::CurseForge => [
+                #[cfg(target_os = "macos")]
+                dirs::document_dir().map(|n| n.join("curseforge/minecraft")),
+                home.map(|n| n.join("curseforge/minecraft")),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        }
+    }
+}
+
+impl Display for OtherLauncher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            OtherLauncher::Official => "Minecraft Launcher",
+            OtherLauncher::ATLauncher => "ATLauncher",
+            OtherLauncher::CurseForge => "CurseForge",
+        })
+    }
+}
+
+/// An instance (or profile) found in another launcher.
+#[derive(Debug, Clone)]
+pub struct ForeignInstance {
+    pub launcher: OtherLauncher,
+    pub name: String,
+    /// Minecraft version. For the official launcher this
+    /// may be `latest-release` or `latest-snapshot`,
+    /// which get resolved while importing.
+    pub version: String,
+    pub loader: Loader,
+    /// The instance directory (ATLauncher, CurseForge),
+    /// or the launcher's root directory (Official).
+    pub path: PathBuf,
+    /// The key in `launcher_profiles.json` (Official only)
+    profile_id: Option<String>,
+}
+
+/// Everything needed to recreate a foreign instance.
+struct ImportRecipe {
+    name: String,
+    mc_version: String,
+    loader: Loader,
+    loader_version: Option<String>,
+    ram_in_mb: Option<usize>,
+    java_args: Vec<String>,
+    window_size: Option<(u32, u32)>,
+    /// The folder that becomes our `.minecraft`
+    game_dir: PathBuf,
+    /// Where disabled mods are moved to (if not kept in `mods/`)
+    disabled_mods_dir: Option<PathBuf>,
+    /// Top-level items of `game_dir` that shouldn't be copied
+    skip: fn(&str) -> bool,
+    mods: Vec<KnownMod>,
+}
+
+/// A mod/resource pack/shader whose store
+/// project ID is known by the other launcher.
+struct KnownMod {
+    id: ModId,
+    name: String,
+    version: String,
+    release_time: Option<DateTime<FixedOffset>>,
+    url: Option<String>,
+    filename: String,
+    project_type: QueryType,
+    enabled: bool,
+    supported_versions: Vec<String>,
+}
+
+/// Scans the default install locations of all supported
+/// launchers (see [`OtherLauncher::default_locations`])
+/// and lists the instances found in them.
+///
+/// Locations that fail to load are logged and skipped.
+pub async fn discover_instances() -> Vec<ForeignInstance> {
+    let mut instances = Vec::new();
+    for launcher in OtherLauncher::ALL {
+        for dir in launcher.default_locations() {
+            if !file_utils::exists(&dir).await {
+                continue;
+            }
+            match list_instances_in(*launcher, &dir).await {
+                Ok(n) => instances.extend(n),
+                Err(error) => err!("Couldn't read {launcher} instances in {dir:?}: {error}"),
+            }
+        }
+    }
+    instances
+}
+
+/// Lists instances of a launcher installed at a custom location.
+///
+/// `dir` is the root data directory of the launcher
+/// (eg: `.minecraft`, `ATLauncher`, `curseforge/minecraft`).
+///
+/// # Errors
+/// If the launcher's instance/profile list couldn't be read or parsed.
+pub async fn list_instances_in(
+    launcher: OtherLauncher,
+    dir: &Path,
+) -> Result<Vec<ForeignInstance>, InstancePackageError> {
+    match launcher {
+        OtherLauncher::Official => official::list(dir).await,
+        OtherLauncher::ATLauncher => atlauncher::list(dir).await,
+        OtherLauncher::CurseForge => curseforge::list(dir).await,
+    }
+}
+
+/// Imports an instance from another launcher (found through
+/// [`discover_instances`] or [`list_instances_in`]).
+///
+/// This creates a fresh instance of the same version,
+/// installs the same loader, copies over the game files
+/// and carries over memory, Java args and (where known)
+/// the store IDs of installed mods.
+///
+/// The other launcher's files are only read, never modified.
+///
+/// If anything fails after the instance was created,
+/// it's deleted again.
+///
+/// # Errors
+/// - The other launcher's metadata couldn't be read/parsed
+/// - Instance creation (downloading) or loader installation fails
+///   (also if the loader can't be installed automatically)
+/// - Copying the files fails
+pub async fn import_from_launcher(
+    foreign: ForeignInstance,
+    download_assets: bool,
+    sender: Option<Sender<GenericProgress>>,
+) -> Result<Instance, InstancePackageError> {
+    info!("Importing {} instance: {}", foreign.launcher, foreign.name);
+    let sender = sender.map(Arc::new);
+    let recipe = match foreign.launcher {
+        OtherLauncher::Official => official::get_recipe(&foreign).await?,
+        OtherLauncher::ATLauncher => atlauncher::get_recipe(&foreign).await?,
+        OtherLauncher::CurseForge => curseforge::get_recipe(&foreign).await?,
+    };
+    pt!("Version: {}", recipe.mc_version);
+    pt!("Loader: {:?} ({:?})", recipe.loader, recipe.loader_version);
+
+    let instance_name = get_unique_name(&recipe.name).await?;
+    let (d_send, d_recv) = std::sync::mpsc::channel();
+    if let Some(sender) = sender.clone() {
+        std::thread::spawn(move || {
+            pipe_progress(d_recv, &sender);
+        });
+    }
+    let instance_name = ql_instances::create_instance(
+        instance_name,
+        ListEntry::new(recipe.mc_version.clone()),
+        Some(d_send),
+        download_assets,
+    )
+    .await?;
+    let instance = Instance::client(&instance_name);
+
+    let result = set_up_imported(recipe, &instance, sender.as_ref()).await;
+    if result.is_err() {
+        instance.remove_unfinished().await;
+    }
+    result?;
+
+    info!("Finished importing {} instance", foreign.launcher);
+    Ok(instance)
+}
+
+/// Everything after creating the instance in [`import_from_launcher`].
+async fn set_up_imported(
+    recipe: ImportRecipe,
+    instance: &Instance,
+    sender: Option<&Arc<Sender<GenericProgress>>>,
+) -> Result<(), InstancePackageError> {
+    match ql_mod_manager::loaders::install_specified_loader(
+        instance.clone(),
+        recipe.loader,
+        sender.cloned(),
+        recipe.loader_version.clone(),
+    )
+    .await
+    .map_err(InstancePackageError::Loader)?
+    {
+        LoaderInstallResult::Ok => {}
+        LoaderInstallResult::NeedsOptifine | LoaderInstallResult::Unsupported => {
+            return Err(InstancePackageError::Loader(format!(
+                "{} can't be installed automatically for Minecraft {}",
+                recipe.loader, recipe.mc_version
+            )));
+        }
+    }
+
+    pt!("Copying files");
+    if let Some(sender) = sender {
+        _ = sender.send(GenericProgress {
+            done: 2,
+            total: OUT_OF,
+            message: Some("Copying files...".to_owned()),
+            has_finished: false,
+        });
+    }
+    copy_game_dir(&recipe, instance).await?;
+
+    if let Some(sender) = sender {
+        _ = sender.send(GenericProgress {
+            done: 3,
+            total: OUT_OF,
+            message: Some("Setting up instance...".to_owned()),
+            has_finished: false,
+        });
+    }
+    let mut config = InstanceConfigJson::read(instance).await?;
+    apply_config(&recipe, &mut config);
+    config.save(instance).await?;
+
+    if !recipe.mods.is_empty() {
+        let mut index = ModIndex::load(instance).await?;
+        add_to_index(&mut index, recipe.mods);
+        index.save(instance).await?;
+    }
+    Ok(())
+}
+
+async fn copy_game_dir(
+    recipe: &ImportRecipe,
+    instance: &Instance,
+) -> Result<(), InstancePackageError> {
+    if !file_utils::exists(&recipe.game_dir).await {
+        return Ok(());
+    }
+    let exceptions: Vec<PathBuf> = file_utils::read_filenames_from_dir(&recipe.game_dir)
+        .await?
+        .into_iter()
+        .filter(|n| (recipe.skip)(&n.name))
+        .map(|n| recipe.game_dir.join(n.name))
+        .collect();
+
+    let dot_minecraft = instance.get_dot_minecraft_path();
+    file_utils::copy_dir_recursive_ext(&recipe.game_dir, &dot_minecraft, &exceptions).await?;
+
+    // We keep disabled mods in place with a `.disabled` suffix,
+    // other launchers may move them elsewhere
+    if let Some(disabled_dir) = &recipe.disabled_mods_dir {
+        copy_disabled_mods(disabled_dir, &dot_minecraft.join("mods")).await?;
+    }
+    Ok(())
+}
+
+async fn copy_disabled_mods(src_dir: &Path, mods_dir: &Path) -> Result<(), InstancePackageError> {
+    if !file_utils::exists(src_dir).await {
+        return Ok(());
+    }
+    fs::create_dir_all(mods_dir).await.path(mods_dir)?;
+    for entry in file_utils::read_filenames_from_dir(src_dir).await? {
+        if !entry.is_file {
+            continue;
+        }
+        let src = src_dir.join(&entry.name);
+        let dst = mods_dir.join(format!("{}.disabled", entry.name));
+        fs::copy(&src, &dst).await.path(&src)?;
+    }
+    Ok(())
+}
+
+fn apply_config(recipe: &ImportRecipe, config: &mut InstanceConfigJson) {
+    if let Some(ram) = recipe.ram_in_mb {
+        config.ram_in_mb = ram;
+    }
+    if !recipe.java_args.is_empty() {
+        config
+            .java_args
+            .get_or_insert_default()
+            .extend(recipe.java_args.iter().cloned());
+    }
+    if let Some((width, height)) = recipe.window_size {
+        let settings = config.c_global_settings();
+        settings.window_width = Some(width);
+        settings.window_height = Some(height);
+    }
+}
+
+fn add_to_index(index: &mut ModIndex, mods: Vec<KnownMod>) {
+    for known in mods {
+        index.mods.insert(
+            known.id.clone(),
+            ModConfig {
+                name: Arc::from(known.name),
+                manually_installed: true,
+                installed_version: known.version,
+                // Unknown dates are treated as "now",
+                // to avoid every mod being flagged for update
+                version_release_time: known
+                    .release_time
+                    .unwrap_or_else(|| chrono::Local::now().fixed_offset()),
+                enabled: known.enabled,
+                description: String::new(),
+                icon_url: None,
+                project_source: known.id.get_backend(),
+                project_id: known.id,
+                files: vec![ModFile {
+                    url: known.url.unwrap_or_default(),
+                    filename: known.filename,
+                    primary: true,
+                }],
+                supported_versions: known.supported_versions,
+                dependencies: HashSet::new(),
+                dependents: HashSet::new(),
+                project_type_extra: if let QueryType::ResourcePacks = known.project_type {
+                    Some("resourcepacks".to_owned())
+                } else {
+                    None
+                },
+                project_type: known.project_type,
+                extra: std::collections::HashMap::new(),
+            },
+        );
+    }
+}
+
+/// Files/folders that other launchers keep in the game
+/// directory but shouldn't end up in our `.minecraft`.
+fn is_launcher_file(name: &str) -> bool {
+    matches!(
+        name,
+        "versions"
+            | "libraries"
+            | "assets"
+            | "runtime"
+            | "bin"
+            | "natives"
+            | "webcache2"
+            | "logs"
+            | "usercache.json"
+            | "usernamecache.json"
+            | "mod_index.json"
+    ) || name.starts_with("launcher_")
+}
+
+/// Splits a Java argument string, pulling out
+/// `-Xmx` (as MB) since we set that ourselves.
+fn parse_java_args(args: &str) -> (Option<usize>, Vec<String>) {
+    let mut ram = None;
+    let mut rest = Vec::new();
+    for arg in args.split_whitespace() {
+        if let Some(mem) = arg.strip_prefix("-Xmx") {
+            ram = parse_memory_mb(mem);
+        } else {
+            rest.push(arg.to_owned());
+        }
+    }
+    (ram, rest)
+}
+
+fn parse_memory_mb(mem: &str) -> Option<usize> {
+    let (num, multiplier) = match mem.chars().last()? {
+        'g' | 'G' => (&mem[..mem.len() - 1], 1024.0),
+        'm' | 'M' => (&mem[..mem.len() - 1], 1.0),
+        'k' | 'K' => (&mem[..mem.len() - 1], 1.0 / 1024.0),
+        _ => (mem, 1.0 / (1024.0 * 1024.0)),
+    };
+    let num: f64 = num.parse().ok()?;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Some((num * multiplier) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn java_args() {
+        let (ram, args) = parse_java_args("-Xmx2G -XX:+UseG1GC -Xmn128M");
+        assert_eq!(ram, Some(2048));
+        assert_eq!(args, vec!["-XX:+UseG1GC", "-Xmn128M"]);
+
+        assert_eq!(parse_memory_mb("4096m"), Some(4096));
+        assert_eq!(parse_memory_mb("1.5g"), Some(1536));
+        assert_eq!(parse_memory_mb("lots"), None);
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use ql_core::{IntoIoError, IntoJsonError, Loader, err, json::Manifest};
+use serde::Deserialize;
+use tokio::fs;
+
+use super::{ForeignInstance, ImportRecipe, OtherLauncher, is_launcher_file, parse_java_args};
+use crate::InstancePackageError;
+
+#[derive(Deserialize)]
+struct LauncherProfiles {
+    profiles: HashMap<String, Profile>,
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct Profile {
+    name: Option<String>,
+    r#type: Option<String>,
+    lastVersionId: Option<String>,
+    gameDir: Option<PathBuf>,
+    javaArgs: Option<String>,
+    resolution: Option<Resolution>,
+}
+
+#[derive(Deserialize)]
+struct Resolution {
+    width: u32,
+    height: u32,
+}
+
+/// The parts of a `versions/<id>/<id>.json` we care about.
+/// Loader profiles (Fabric, Forge, ...) inherit from
+/// the vanilla version they were installed on.
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct VersionProfile {
+    id: String,
+    inheritsFrom: Option<String>,
+    #[serde(default)]
+    libraries: Vec<VersionLibrary>,
+}
+
+#[derive(Deserialize)]
+struct VersionLibrary {
+    name: String,
+}
+
+const LATEST_RELEASE: &str = "latest-release";
+const LATEST_SNAPSHOT: &str = "latest-snapshot";
+
+impl Profile {
+    fn get_version_id(&self) -> String {
+        match self.r#type.as_deref() {
+            Some(LATEST_RELEASE) => LATEST_RELEASE.to_owned(),
+            Some(LATEST_SNAPSHOT) => LATEST_SNAPSHOT.to_owned(),
+            _ => self
+                .lastVersionId
+                .clone()
+                .unwrap_or_else(|| LATEST_RELEASE.to_owned()),
+        }
+    }
+
+    fn get_name(&self, id: &str) -> String {
+        match self.name.as_deref() {
+            Some(name) if !name.trim().is_empty() => name.to_owned(),
+            _ => match self.r#type.as_deref() {
+                Some(LATEST_RELEASE) => "Latest Release".to_owned(),
+                Some(LATEST_SNAPSHOT) => "Latest Snapshot".to_owned(),
+                _ => id.to_owned(),
+            },
+        }
+    }
+}
+
+async fn read_profiles(dir: &Path) -> Result<LauncherProfiles, InstancePackageError> {
+    let path = dir.join("launcher_profiles.json");
+    let file = fs::read_to_string(&path).await.path(path)?;
+    Ok(serde_json::from_str(&file).json(file)?)
+}
+
+pub async fn list(dir: &Path) -> Result<Vec<ForeignInstance>, InstancePackageError> {
+    let profiles = read_profiles(dir).await?;
+
+    let mut instances = Vec::new();
+    for (id, profile) in profiles.profiles {
+        let version_id = profile.get_version_id();
+        let (version, loader, _) = match read_version(dir, &version_id).await {
+            Ok(n) => n,
+            Err(error) => {
+                err!("Skipping official launcher profile {id}: {error}");
+                continue;
+            }
+        };
+        instances.push(ForeignInstance {
+            launcher: OtherLauncher::Official,
+            name: profile.get_name(&id),
+            version,
+            loader,
+            path: dir.to_owned(),
+            profile_id: Some(id),
+        });
+    }
+    Ok(instances)
+}
+
+pub async fn get_recipe(foreign: &ForeignInstance) -> Result<ImportRecipe, InstancePackageError> {
+    let profiles = read_profiles(&foreign.path).await?;
+    let profile = foreign
+        .profile_id
+        .as_ref()
+        .and_then(|id| profiles.profiles.get(id))
+        .ok_or_else(|| InstancePackageError::ProfileMissing(foreign.name.clone()))?;
+
+    let (mut mc_version, loader, loader_version) =
+        read_version(&foreign.path, &profile.get_version_id()).await?;
+    if mc_version == LATEST_RELEASE || mc_version == LATEST_SNAPSHOT {
+        let manifest = Manifest::download().await?;
+        let latest = if mc_version == LATEST_RELEASE {
+            manifest.get_latest_release()
+        } else {
+            manifest.versions.first()
+        };
+        if let Some(latest) = latest {
+            mc_version.clone_from(&latest.id);
+        }
+    }
+
+    let (ram_in_mb, java_args) = profile
+        .javaArgs
+        .as_deref()
+        .map(parse_java_args)
+        .unwrap_or_default();
+
+    Ok(ImportRecipe {
+        name: foreign.name.clone(),
+        mc_version,
+        loader,
+        loader_version,
+        ram_in_mb,
+        java_args,
+        window_size: profile.resolution.as_ref().map(|n| (n.width, n.height)),
+        game_dir: profile
+            .gameDir
+            .clone()
+            .unwrap_or_else(|| foreign.path.clone()),
+        disabled_mods_dir: None,
+        skip: is_launcher_file,
+        // The official launcher doesn't track mods
+        mods: Vec::new(),
+    })
+}
+
+/// Returns `(minecraft_version, loader, loader_version)`
+/// for a version ID in `versions/`.
+///
+/// Versions that aren't downloaded yet
+/// are assumed to be vanilla.
+async fn read_version(
+    dir: &Path,
+    version_id: &str,
+) -> Result<(String, Loader, Option<String>), InstancePackageError> {
+    let path = dir
+        .join("versions")
+        .join(version_id)
+        .join(format!("{version_id}.json"));
+    if !path.is_file() {
+        return Ok((version_id.to_owned(), Loader::Vanilla, None));
+    }
+    let file = fs::read_to_string(&path).await.path(&path)?;
+    let profile: VersionProfile = serde_json::from_str(&file).json(file)?;
+
+    let mc_version = profile.inheritsFrom.clone().unwrap_or(profile.id);
+    for lib in &profile.libraries {
+        if let Some((loader, version)) = get_loader_from_library(&lib.name, &mc_version) {
+            return Ok((mc_version, loader, Some(version)));
+        }
+    }
+    Ok((mc_version, Loader::Vanilla, None))
+}
+
+fn get_loader_from_library(name: &str, mc_version: &str) -> Option<(Loader, String)> {
+    // Forge versions look like `1.20.1-47.2.0`
+    // (older ones may have another `-1.7.10` at the end)
+    let forge_version = |n: &str| {
+        let n = n.strip_prefix(mc_version).unwrap_or(n);
+        n.trim_start_matches('-')
+            .split('-')
+            .next()
+            .unwrap_or(n)
+            .to_owned()
+    };
+
+    let mut parts = name.split(':');
+    let (group, artifact, version) = (parts.next()?, parts.next()?, parts.next()?);
+    Some(match (group, artifact) {
+        ("net.fabricmc", "fabric-loader") => (Loader::Fabric, version.to_owned()),
+        ("org.quiltmc", "quilt-loader") => (Loader::Quilt, version.to_owned()),
+        ("net.minecraftforge", "forge" | "fmlloader") => (Loader::Forge, forge_version(version)),
+        ("net.neoforged", "neoforge") => (Loader::NeoForge, version.to_owned()),
+        ("net.neoforged", "forge") => (Loader::NeoForge, forge_version(version)),
+        _ => return None,
+    })
+}
@@ -1,19 +1,31 @@
 use std::{collections::HashSet, path::PathBuf};
 
 use ql_core::{IoError, JsonError, RequestError, impl_3_errs_jri};
-use ql_mod_manager::loaders::{fabric::FabricInstallError, forge::ForgeInstallError};
+use ql_mod_manager::{
+    loaders::{fabric::FabricInstallError, forge::ForgeInstallError},
+    store::ModError,
+};
 use ql_servers::ServerError;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use ql_instances::DownloadError;
 
+mod archive;
+mod client_from_server;
 mod export;
 mod import;
+mod launchers;
 mod multimc;
 
+pub use archive::ExportCompression;
+pub use client_from_server::{ClientFromServer, create_client_from_server};
 pub use export::{EXCEPTIONS, export_instance};
 pub use import::import_instance;
+pub use launchers::{
+    ForeignInstance, OtherLauncher, discover_instances, import_from_launcher, list_instances_in,
+};
+pub use multimc::export_instance_multimc;
 
 const PKG_ERR_PREFIX: &str = "while importing/exporting instance:\n";
 #[derive(Debug, Error)]
@@ -38,6 +50,8 @@ pub enum InstancePackageError {
     Server(#[from] ServerError),
     #[error("{PKG_ERR_PREFIX}while installing packaged loader:\n{0}")]
     Loader(String),
+    #[error("{0}")]
+    Mod(#[from] ModError),
 
     #[error("{PKG_ERR_PREFIX}{0}")]
     Forge(#[from] ForgeInstallError),
@@ -46,15 +60,19 @@ pub enum InstancePackageError {
 
     #[error("{PKG_ERR_PREFIX}while dealing with zip:\n{0}")]
     Zip(#[from] zip::result::ZipError),
+    #[error("{PKG_ERR_PREFIX}file {0:?} is missing from the zip")]
+    ZipFileMissing(String),
 
-    #[error("{PKG_ERR_PREFIX}while creating temporary directory:\n{0}")]
-    TempDir(std::io::Error),
     #[error("{PKG_ERR_PREFIX}while adding to zip:\n{0}")]
     ZipIo(std::io::Error),
     #[error("{PKG_ERR_PREFIX}while parsing ini file:\n{0}")]
     Ini(#[from] ini::ParseError),
     #[error("{PKG_ERR_PREFIX}in ini file:\nentry {1:?} of section {0:?} is missing!")]
     IniFieldMissing(String, String),
+    #[error("{PKG_ERR_PREFIX}servers can't be exported in MultiMC/Prism format")]
+    MmcServerUnsupported,
+    #[error("{PKG_ERR_PREFIX}profile {0:?} not found in launcher_profiles.json")]
+    ProfileMissing(String),
 }
 
 impl_3_errs_jri!(InstancePackageError, Json, Request, Io);
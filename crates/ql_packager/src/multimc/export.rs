@@ -0,0 +1,458 @@
+use ini::Ini;
+use ql_core::{
+    GenericProgress, Instance, IntoJsonError, Loader, err, info,
+    jarmod::JarMods,
+    json::{InstanceConfigJson, VersionDetails},
+    pt,
+};
+use std::{collections::HashSet, path::PathBuf, sync::mpsc::Sender};
+
+use super::{MmcPack, MmcPackComponent};
+use crate::{
+    EXCEPTIONS, InstancePackageError,
+    archive::{ArchiveWriter, ByteProgress, ExportCompression, dir_size},
+};
+
+/// Exports a client instance in the MultiMC/Prism Launcher format,
+/// as a zip (at `output`) that can be imported by either launcher.
+///
+/// The zip contains:
+/// - `instance.cfg`: name, notes, memory, Java args, window size
+///   and wrapper command
+/// - `mmc-pack.json`: Minecraft, LWJGL and mod loader components
+/// - `.minecraft/`: the game directory (minus exceptions)
+/// - `jarmods/` and `patches/`: any enabled jar mods
+///
+/// Like [`crate::export_instance`], files are streamed straight
+/// into the zip. Always uses deflate compression, as that's
+/// all MultiMC/Prism can read.
+///
+/// # Arguments
+/// - `instance`: The instance to export (must be a client)
+/// - `exceptions`: Extra paths to exclude, on top of [`EXCEPTIONS`].
+///   Relative to the instance dir (parent dir of `.minecraft`)
+///
+/// # Errors
+/// - The instance is a server (MultiMC doesn't support these)
+/// - `config.json` or `details.json` couldn't be read
+/// - File I/O operations (reading, zipping) fail
+pub async fn export_instance_multimc(
+    instance: Instance,
+    mut exceptions: HashSet<String>,
+    output: PathBuf,
+    progress: Option<Sender<GenericProgress>>,
+) -> Result<(), InstancePackageError> {
+    if instance.is_server() {
+        return Err(InstancePackageError::MmcServerUnsupported);
+    }
+    info!("Exporting instance (MultiMC/Prism format)...");
+    exceptions.extend(EXCEPTIONS.iter().map(|n| (*n).to_owned()));
+    exceptions.insert(".minecraft/mod_index.json".to_owned());
+    pt!("Exceptions (not included in export): {exceptions:?}");
+
+    pt!("Creating metadata");
+    let config = InstanceConfigJson::read(&instance).await?;
+    let details = VersionDetails::load(&instance).await?;
+    let notes = ql_instances::notes::read(instance.clone()).await?;
+
+    let mut components = get_components(&config, &details);
+    let jarmods = get_jarmods(&instance).await?;
+    components.extend(jarmods.iter().map(|n| n.component.clone()));
+    let mmc_pack = MmcPack {
+        components,
+        formatVersion: 1,
+    };
+    let mmc_pack = serde_json::to_string_pretty(&mmc_pack).json_to()?;
+
+    let mut cfg = Vec::new();
+    create_config_ini(instance.get_name(), &config, &notes)
+        .write_to(&mut cfg)
+        .map_err(InstancePackageError::ZipIo)?;
+
+    let instance_path = instance.get_instance_path();
+    let dot_minecraft = instance.get_dot_minecraft_path();
+    let exceptions: Vec<PathBuf> = exceptions.iter().map(|n| instance_path.join(n)).collect();
+
+    pt!("Packaging the instance into zip");
+    let out = output.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let total = dir_size(&dot_minecraft, &exceptions);
+        let mut progress = ByteProgress::new(progress, "Zipping files...", total);
+
+        let mut writer = ArchiveWriter::create(&out, ExportCompression::Deflate)?;
+        writer.add_bytes("instance.cfg", &cfg)?;
+        writer.add_bytes("mmc-pack.json", mmc_pack.as_bytes())?;
+        for jarmod in &jarmods {
+            writer.add_file(
+                &jarmod.src,
+                &format!("jarmods/{}.jar", jarmod.id),
+                &mut progress,
+            )?;
+            writer.add_bytes(
+                &format!("patches/{}.json", jarmod.component.uid),
+                jarmod.patch.as_bytes(),
+            )?;
+        }
+        writer.add_dir(&dot_minecraft, ".minecraft/", &exceptions, &mut progress)?;
+        writer.finish()
+    })
+    .await
+    .map_err(|n| InstancePackageError::ZipIo(std::io::Error::other(n)))?;
+
+    if result.is_err() {
+        _ = tokio::fs::remove_file(&output).await;
+    } else {
+        pt!("Done!");
+    }
+    result
+}
+
+/// The launcher only sets the maximum memory (`-Xmx`),
+/// so the minimum (`-Xms`) is left at MultiMC/Prism's default.
+const MMC_DEFAULT_MIN_MEMORY_MB: usize = 512;
+
+fn create_config_ini(name: &str, config: &InstanceConfigJson, notes: &str) -> Ini {
+    let mut ini = Ini::new();
+    let mut general = ini.with_section(Some("General"));
+    general
+        .set("ConfigVersion", "1.2")
+        .set("InstanceType", "OneSix")
+        .set("iconKey", "default")
+        .set("name", name)
+        .set("notes", notes)
+        .set("OverrideMemory", "true")
+        // Can't be more than the maximum
+        .set(
+            "MinMemAlloc",
+            config.ram_in_mb.min(MMC_DEFAULT_MIN_MEMORY_MB).to_string(),
+        )
+        .set("MaxMemAlloc", config.ram_in_mb.to_string());
+
+    if let Some(java_args) = config.java_args.as_ref().filter(|n| !n.is_empty()) {
+        general
+            .set("OverrideJavaArgs", "true")
+            .set("JvmArgs", java_args.join(" "));
+    }
+
+    if let Some(settings) = &config.global_settings {
+        if settings.window_width.is_some() || settings.window_height.is_some() {
+            general.set("OverrideWindow", "true");
+            if let Some(width) = settings.window_width {
+                general.set("MinecraftWinWidth", width.to_string());
+            }
+            if let Some(height) = settings.window_height {
+                general.set("MinecraftWinHeight", height.to_string());
+            }
+        }
+        if let Some(prefix) = settings
+            .pre_launch_prefix
+            .as_ref()
+            .filter(|n| !n.is_empty())
+        {
+            general
+                .set("OverrideCommands", "true")
+                .set("WrapperCommand", prefix.join(" "));
+        }
+    }
+
+    ini
+}
+
+fn get_components(config: &InstanceConfigJson, details: &VersionDetails) -> Vec<MmcPackComponent> {
+    let mc_version = details.get_id().to_owned();
+    let mut components = Vec::new();
+
+    if let Some((is_lwjgl3, version)) = get_lwjgl_version(details) {
+        components.push(MmcPackComponent {
+            cachedName: if is_lwjgl3 { "LWJGL 3" } else { "LWJGL 2" }.to_owned(),
+            cachedVersion: Some(version.clone()),
+            uid: if is_lwjgl3 { "org.lwjgl3" } else { "org.lwjgl" }.to_owned(),
+            version: Some(version),
+            important: None,
+            dependencyOnly: Some(true),
+        });
+    }
+
+    components.push(MmcPackComponent {
+        cachedName: "Minecraft".to_owned(),
+        cachedVersion: Some(mc_version.clone()),
+        uid: "net.minecraft".to_owned(),
+        version: Some(mc_version.clone()),
+        important: Some(true),
+        dependencyOnly: None,
+    });
+
+    let loader_version = config
+        .mod_type_info
+        .as_ref()
+        .and_then(|n| n.version.clone());
+    let (name, uid) = match config.mod_type {
+        Loader::Vanilla => return components,
+        Loader::Fabric => ("Fabric Loader", "net.fabricmc.fabric-loader"),
+        Loader::Quilt => ("Quilt Loader", "org.quiltmc.quilt-loader"),
+        Loader::Forge => ("Forge", "net.minecraftforge"),
+        Loader::NeoForge => ("NeoForge", "net.neoforged"),
+        loader => {
+            err!("Loader {loader:?} isn't supported by MultiMC/Prism, skipping");
+            return components;
+        }
+    };
+
+    if let Loader::Fabric | Loader::Quilt = config.mod_type {
+        components.push(MmcPackComponent {
+            cachedName: "Intermediary Mappings".to_owned(),
+            cachedVersion: Some(mc_version.clone()),
+            uid: "net.fabricmc.intermediary".to_owned(),
+            version: Some(mc_version),
+            important: None,
+            dependencyOnly: Some(true),
+        });
+    }
+
+    if loader_version.is_none() {
+        err!("Unknown {name} version, Prism will pick the recommended one");
+    }
+    components.push(MmcPackComponent {
+        cachedName: name.to_owned(),
+        cachedVersion: loader_version.clone(),
+        uid: uid.to_owned(),
+        version: loader_version,
+        important: None,
+        dependencyOnly: None,
+    });
+
+    components
+}
+
+/// Returns `(is_lwjgl3, version)` based on the
+/// LWJGL library listed in `details.json`.
+fn get_lwjgl_version(details: &VersionDetails) -> Option<(bool, String)> {
+    details
+        .libraries
+        .iter()
+        .filter_map(|lib| lib.name.as_deref())
+        .find_map(|name| {
+            if let Some(version) = name.strip_prefix("org.lwjgl:lwjgl:") {
+                Some((true, version.to_owned()))
+            } else {
+                name.strip_prefix("org.lwjgl.lwjgl:lwjgl:")
+                    .map(|version| (false, version.to_owned()))
+            }
+        })
+}
+
+struct ExportedJarMod {
+    src: PathBuf,
+    /// Sanitized filename (without `.jar`)
+    id: String,
+    /// Contents of `patches/<uid>.json`
+    patch: String,
+    component: MmcPackComponent,
+}
+
+/// Gets the enabled jar mods, along with their
+/// `patches/` files and `mmc-pack.json` components.
+async fn get_jarmods(instance: &Instance) -> Result<Vec<ExportedJarMod>, InstancePackageError> {
+    let jarmods = JarMods::read(instance).await?;
+    let src_dir = instance.get_instance_path().join("jarmods");
+
+    let mut out = Vec::new();
+    for jarmod in jarmods.mods.iter().filter(|n| n.enabled) {
+        let src = src_dir.join(&jarmod.filename);
+        if !src.is_file() {
+            continue;
+        }
+
+        // Our importer takes the last `.`-separated part
+        // of the uid as the filename, so no dots allowed
+        let id = jarmod
+            .filename
+            .strip_suffix(".jar")
+            .unwrap_or(&jarmod.filename)
+            .replace('.', "_");
+        let uid = format!("custom.jarmod.{id}");
+        let name = format!("{} (jar mod)", jarmod.filename);
+
+        let patch = serde_json::json!({
+            "formatVersion": 1,
+            "uid": uid,
+            "name": name,
+            "jarMods": [{
+                "MMC-displayname": jarmod.filename,
+                "MMC-filename": format!("{id}.jar"),
+                "MMC-hint": "local",
+                "name": format!("org.multimc.jarmods:{id}:1"),
+            }],
+        });
+        let patch = serde_json::to_string_pretty(&patch).json_to()?;
+
+        out.push(ExportedJarMod {
+            src,
+            id,
+            patch,
+            component: MmcPackComponent {
+                cachedName: name,
+                cachedVersion: None,
+                uid,
+                version: None,
+                important: None,
+                dependencyOnly: None,
+            },
+        });
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use ql_core::{
+        InstanceKind,
+        json::{
+            GlobalSettings,
+            instance_config::{ModTypeInfo, VersionInfo},
+            version::Library,
+        },
+    };
+
+    use super::*;
+
+    fn details(minecraft: &str, lwjgl: &str) -> VersionDetails {
+        let mut details = VersionDetails::default();
+        details.id = minecraft.to_owned();
+        details.libraries.push(Library {
+            downloads: None,
+            extract: None,
+            name: Some(lwjgl.to_owned()),
+            rules: None,
+            natives: None,
+            url: None,
+        });
+        details
+    }
+
+    /// `(uid, version)` of each component
+    fn components(
+        loader: Loader,
+        loader_version: Option<&str>,
+        details: &VersionDetails,
+    ) -> Vec<(String, Option<String>)> {
+        let mut config =
+            InstanceConfigJson::new(InstanceKind::Client, false, VersionInfo::new(&details.id));
+        config.mod_type = loader;
+        config.mod_type_info = loader_version.map(|n| ModTypeInfo::new_regular(n.to_owned()));
+        get_components(&config, details)
+            .into_iter()
+            .map(|n| (n.uid, n.version))
+            .collect()
+    }
+
+    fn expected(components: &[(&str, Option<&str>)]) -> Vec<(String, Option<String>)> {
+        components
+            .iter()
+            .map(|(uid, version)| ((*uid).to_owned(), version.map(str::to_owned)))
+            .collect()
+    }
+
+    #[test]
+    fn pack_components() {
+        let modern = details("1.21.1", "org.lwjgl:lwjgl:3.3.3");
+        assert_eq!(
+            components(Loader::Vanilla, None, &modern),
+            expected(&[
+                ("org.lwjgl3", Some("3.3.3")),
+                ("net.minecraft", Some("1.21.1")),
+            ])
+        );
+        assert_eq!(
+            components(Loader::Fabric, Some("0.16.10"), &modern),
+            expected(&[
+                ("org.lwjgl3", Some("3.3.3")),
+                ("net.minecraft", Some("1.21.1")),
+                ("net.fabricmc.intermediary", Some("1.21.1")),
+                ("net.fabricmc.fabric-loader", Some("0.16.10")),
+            ])
+        );
+        assert_eq!(
+            components(Loader::Quilt, Some("0.28.0"), &modern),
+            expected(&[
+                ("org.lwjgl3", Some("3.3.3")),
+                ("net.minecraft", Some("1.21.1")),
+                ("net.fabricmc.intermediary", Some("1.21.1")),
+                ("org.quiltmc.quilt-loader", Some("0.28.0")),
+            ])
+        );
+        assert_eq!(
+            components(Loader::NeoForge, Some("21.1.77"), &modern),
+            expected(&[
+                ("org.lwjgl3", Some("3.3.3")),
+                ("net.minecraft", Some("1.21.1")),
+                ("net.neoforged", Some("21.1.77")),
+            ])
+        );
+        // Not supported by MultiMC/Prism
+        assert_eq!(
+            components(Loader::OptiFine, None, &modern),
+            expected(&[
+                ("org.lwjgl3", Some("3.3.3")),
+                ("net.minecraft", Some("1.21.1")),
+            ])
+        );
+
+        let old = details("1.12.2", "org.lwjgl.lwjgl:lwjgl:2.9.4-nightly-20150209");
+        assert_eq!(
+            components(Loader::Forge, Some("14.23.5.2860"), &old),
+            expected(&[
+                ("org.lwjgl", Some("2.9.4-nightly-20150209")),
+                ("net.minecraft", Some("1.12.2")),
+                ("net.minecraftforge", Some("14.23.5.2860")),
+            ])
+        );
+        // Prism picks one
+        assert_eq!(
+            components(Loader::Forge, None, &old).last(),
+            Some(&("net.minecraftforge".to_owned(), None))
+        );
+    }
+
+    #[test]
+    fn instance_cfg() {
+        let mut config =
+            InstanceConfigJson::new(InstanceKind::Client, false, VersionInfo::new("1.21.1"));
+        config.ram_in_mb = 4096;
+        config.java_args = Some(vec!["-XX:+UseG1GC".to_owned(), "-Dfoo=bar".to_owned()]);
+        let mut settings = GlobalSettings::default();
+        settings.window_width = Some(1280);
+        settings.pre_launch_prefix = Some(vec!["gamemoderun".to_owned()]);
+        config.global_settings = Some(settings);
+
+        let ini = create_config_ini("My Pack", &config, "Some notes");
+        let general = ini.section(Some("General")).unwrap();
+        for (key, value) in [
+            ("InstanceType", "OneSix"),
+            ("name", "My Pack"),
+            ("notes", "Some notes"),
+            ("OverrideMemory", "true"),
+            ("MinMemAlloc", "512"),
+            ("MaxMemAlloc", "4096"),
+            ("OverrideJavaArgs", "true"),
+            ("JvmArgs", "-XX:+UseG1GC -Dfoo=bar"),
+            ("OverrideWindow", "true"),
+            ("MinecraftWinWidth", "1280"),
+            ("OverrideCommands", "true"),
+            ("WrapperCommand", "gamemoderun"),
+        ] {
+            assert_eq!(general.get(key), Some(value), "{key}");
+        }
+        assert_eq!(general.get("MinecraftWinHeight"), None);
+
+        // Less memory than the default minimum
+        config.ram_in_mb = 256;
+        config.java_args = None;
+        config.global_settings = None;
+        let ini = create_config_ini("Tiny", &config, "");
+        let general = ini.section(Some("General")).unwrap();
+        assert_eq!(general.get("MinMemAlloc"), Some("256"));
+        assert_eq!(general.get("OverrideJavaArgs"), None);
+        assert_eq!(general.get("OverrideWindow"), None);
+    }
+}
@@ -1,14 +1,15 @@
 use chrono::DateTime;
 use ini::Ini;
-use std::{
-    path::Path,
-    sync::{Arc, Mutex, mpsc::Sender},
-};
+use std::sync::{Arc, Mutex, mpsc::Sender};
 
-use crate::{InstancePackageError, import::OUT_OF, import::pipe_progress};
+use crate::{
+    InstancePackageError,
+    archive::ArchiveReader,
+    import::{get_unique_name, pipe_progress},
+};
 use ql_core::{
-    GenericProgress, Instance, IntoIoError, IntoJsonError, LAUNCHER_DIR, ListEntry, Loader,
-    do_jobs, download, err,
+    GenericProgress, Instance, IntoIoError, IntoJsonError, ListEntry, Loader, do_jobs, download,
+    err,
     file_utils::{self, exists},
     info,
     jarmod::{JarMod, JarMods},
@@ -20,11 +21,20 @@ use ql_core::{
 };
 use ql_mod_manager::loaders::fabric::{self, get_list_of_versions_from_backend};
 use serde::{Deserialize, Serialize};
-use tokio::fs;
+
+mod export;
+pub use export::export_instance_multimc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
 pub struct MmcPack {
     components: Vec<MmcPackComponent>,
+    #[serde(default = "default_format_version")]
+    formatVersion: usize,
+}
+
+fn default_format_version() -> usize {
+    1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +43,12 @@ pub struct MmcPackComponent {
     cachedName: String,
     cachedVersion: Option<String>,
     uid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    important: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dependencyOnly: Option<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -69,14 +85,14 @@ impl InstanceRecipe {
 
 pub async fn import(
     download_assets: bool,
-    temp_dir: &Path,
+    mut reader: ArchiveReader,
     mmc_pack: &str,
     sender: Option<Arc<Sender<GenericProgress>>>,
 ) -> Result<Instance, InstancePackageError> {
     info!("Importing MultiMC instance...");
     let mmc_pack: MmcPack = serde_json::from_str(mmc_pack).json(mmc_pack.to_owned())?;
 
-    let ini = read_config_ini(temp_dir).await?;
+    let ini = read_config_ini(&mut reader)?;
     let (instance, instance_recipe) =
         tokio::try_join!(get_instance(&ini), get_instance_recipe(&mmc_pack))?;
 
@@ -90,7 +106,7 @@ pub async fn import(
 
     install_loader(sender.as_deref(), &instance, &instance_recipe).await?;
 
-    copy_files(temp_dir, sender, &instance).await?;
+    extract_files(reader, sender, &instance).await?;
 
     tokio::try_join!(
         setup_details(&instance),
@@ -148,6 +164,14 @@ fn setup_config(ini: &Ini, instance_recipe: &InstanceRecipe, config: &mut Instan
     }
     // TODO: `LaunchMaximized: bool`
 
+    if general_get(ini, "OverrideMemory").is_ok_and(|n| n == "true") {
+        if let Ok(max_mem) = general_get(ini, "MaxMemAlloc") {
+            if let Ok(ram) = max_mem.parse::<usize>() {
+                config.ram_in_mb = ram;
+            }
+        }
+    }
+
     if let Ok(win_height) = general_get(ini, "MinecraftWinHeight") {
         if let Ok(height) = win_height.parse::<u32>() {
             config.c_global_settings().window_height = Some(height);
@@ -184,29 +208,14 @@ fn general_get<'a>(ini: &'a Ini, key: &str) -> Result<&'a str, InstancePackageEr
 }
 
 async fn get_instance(ini: &Ini) -> Result<Instance, InstancePackageError> {
-    let mut instance_name = general_get(ini, "name")?.to_owned();
-
-    // If `MyInstance` exists, try `MyInstance (1)`, `(2)`...
-    let instance_dir = LAUNCHER_DIR.join("instances");
-    let mut path = instance_dir.join(&instance_name);
-
-    if fs::try_exists(&path).await.path(&path)? {
-        let mut name_i = 1;
-        let mut name = String::new();
-        while fs::try_exists(&path).await.path(&path)? {
-            name = format!("{instance_name} ({name_i})");
-            path = instance_dir.join(&name);
-            name_i += 1;
-        }
-        instance_name = name;
-    }
-
+    let instance_name = get_unique_name(general_get(ini, "name")?).await?;
     Ok(Instance::client(&instance_name))
 }
 
-async fn read_config_ini(temp_dir: &Path) -> Result<Ini, InstancePackageError> {
-    let ini_path = temp_dir.join("instance.cfg");
-    let ini = fs::read_to_string(&ini_path).await.path(ini_path)?;
+fn read_config_ini(reader: &mut ArchiveReader) -> Result<Ini, InstancePackageError> {
+    let ini = reader
+        .read_string("instance.cfg")?
+        .ok_or_else(|| InstancePackageError::ZipFileMissing("instance.cfg".to_owned()))?;
     Ok(Ini::load_from_str(&filter_bytearray(&ini))?)
 }
 
@@ -395,42 +404,32 @@ async fn install_fabric(
     Ok(())
 }
 
-async fn copy_files(
-    temp_dir: &Path,
+async fn extract_files(
+    reader: ArchiveReader,
     sender: Option<Arc<Sender<GenericProgress>>>,
     instance_selection: &Instance,
 ) -> Result<(), InstancePackageError> {
-    let src = temp_dir.join("minecraft");
-    if src.is_dir() {
-        let dst = instance_selection.get_dot_minecraft_path();
-        if let Some(sender) = sender.as_deref() {
-            _ = sender.send(GenericProgress {
-                done: 2,
-                total: OUT_OF,
-                message: Some("Copying files...".to_owned()),
-                has_finished: false,
-            });
-        }
-        file_utils::copy_dir_recursive(&src, &dst).await?;
-    }
-
-    copy_folder_over(temp_dir, instance_selection, "jarmods").await?;
-    copy_folder_over(temp_dir, instance_selection, "patches").await?;
-
-    Ok(())
-}
-
-async fn copy_folder_over(
-    temp_dir: &Path,
-    instance_selection: &Instance,
-    path: &'static str,
-) -> Result<(), InstancePackageError> {
-    let src = temp_dir.join(path);
-    if src.is_dir() {
-        let dst = instance_selection.get_instance_path().join(path);
-        file_utils::copy_dir_recursive(&src, &dst).await?;
-    }
-    Ok(())
+    let instance_dir = instance_selection.get_instance_path();
+    let dot_minecraft = instance_selection.get_dot_minecraft_path();
+
+    reader
+        .extract(
+            move |name| {
+                // MultiMC uses `minecraft/`, Prism (and our exporter) uses `.minecraft/`
+                if let Some(rest) = name
+                    .strip_prefix(".minecraft/")
+                    .or_else(|| name.strip_prefix("minecraft/"))
+                {
+                    Some(dot_minecraft.join(rest))
+                } else if name.starts_with("jarmods/") || name.starts_with("patches/") {
+                    Some(instance_dir.join(name))
+                } else {
+                    None
+                }
+            },
+            sender.as_deref().cloned(),
+        )
+        .await
 }
 
 async fn create_minecraft_instance(
@@ -0,0 +1,593 @@
+//! World backups (snapshots) for servers and client instances.
+//!
+//! Each snapshot is a plain copy of the world folders in
+//! `QuantumLauncher/backups/<servers|instances>/<NAME>/<ID>/`.
+//! Files that didn't change since the previous snapshot
+//! (usually most region files) are hardlinked to it instead
+//! of being copied, so a snapshot only costs the space of
+//! what actually changed.
+//!
+//! Deleting a snapshot is just deleting its folder,
+//! hardlinks keep the data alive for the others.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Datelike, Local, Utc};
+use ql_core::{
+    Instance, InstanceKind, IntoIoError, IntoJsonError, IoError, LAUNCHER_DIR, LaunchedProcess,
+    err, file_utils::exists, info, pt, sanitize_instance_name,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    DaemonState, ServerError, daemon::STATE_FILE, diagnostics::world_lock_holder,
+    send_console_command,
+};
+
+mod schedule;
+pub use schedule::backup_scheduler;
+
+const SNAPSHOT_FILE: &str = "snapshot.json";
+const CONFIG_FILE: &str = "config.json";
+
+/// How long to wait for the server to write
+/// the world after `save-all`, when there's no
+/// confirmation (console instead of RCON)
+const SAVE_WAIT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BackupConfig {
+    /// Take a snapshot every this many minutes while
+    /// the server is running (`None` to disable)
+    pub interval_minutes: Option<u32>,
+    /// Take a snapshot when the server stops
+    pub on_stop: bool,
+    pub retention: RetentionPolicy,
+}
+
+impl BackupConfig {
+    /// Loads the backup config of an instance (default if not set).
+    ///
+    /// # Errors
+    /// If the config exists but couldn't be read or parsed.
+    pub async fn load(instance: &Instance) -> Result<Self, ServerError> {
+        let path = backups_dir(instance).join(CONFIG_FILE);
+        if !exists(&path).await {
+            return Ok(Self::default());
+        }
+        let text = tokio::fs::read_to_string(&path).await.path(path)?;
+        Ok(serde_json::from_str(&text).json(text)?)
+    }
+
+    /// # Errors
+    /// If the config couldn't be saved.
+    pub async fn save(&self, instance: &Instance) -> Result<(), ServerError> {
+        let dir = backups_dir(instance);
+        tokio::fs::create_dir_all(&dir).await.path(&dir)?;
+        let path = dir.join(CONFIG_FILE);
+        let text = serde_json::to_string_pretty(self).json_to()?;
+        tokio::fs::write(&path, text).await.path(path)?;
+        Ok(())
+    }
+}
+
+/// Which old snapshots to keep. The rest are
+/// deleted after taking a new snapshot.
+///
+/// A snapshot is kept if any of the rules keep it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct RetentionPolicy {
+    /// Keep this many of the newest snapshots
+    pub keep_last: usize,
+    /// Keep the newest snapshot of each of the last this many days
+    pub keep_daily: usize,
+    /// Keep the newest snapshot of each of the last this many weeks
+    pub keep_weekly: usize,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: 10,
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BackupReason {
+    Manual,
+    Scheduled,
+    ServerStopped,
+    /// Automatically taken before restoring another snapshot,
+    /// so that the restore can be undone
+    BeforeRestore,
+}
+
+impl std::fmt::Display for BackupReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                BackupReason::Manual => "Manual",
+                BackupReason::Scheduled => "Scheduled",
+                BackupReason::ServerStopped => "Server stopped",
+                BackupReason::BeforeRestore => "Before restore",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: String,
+    pub created: DateTime<Utc>,
+    pub reason: BackupReason,
+    /// World folders, relative to the game directory
+    /// (eg: `world`, `world_nether` or `saves/New World`)
+    pub worlds: Vec<String>,
+    /// Path (relative to the game directory, `/`-separated)
+    /// to file info, for detecting changes next time
+    pub files: BTreeMap<String, FileInfo>,
+}
+
+impl Snapshot {
+    /// Total size of the worlds in the snapshot, in bytes
+    /// (not the space it takes on disk, due to hardlinks).
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        self.files.values().map(|n| n.size).sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileInfo {
+    pub size: u64,
+    /// Modification time of the original file, in
+    /// milliseconds since the Unix epoch
+    pub modified: u64,
+}
+
+/// Where [`restore_snapshot`] puts the worlds.
+#[derive(Debug, Clone)]
+pub enum RestoreTarget {
+    /// Replace the worlds of the instance itself.
+    /// It must not be running!
+    Live,
+    /// Create a new server with this name, copying
+    /// everything else (jar, mods, config) from the original.
+    /// Only for servers.
+    NewServer(String),
+}
+
+/// `QuantumLauncher/backups/<servers|instances>/<NAME>/`
+#[must_use]
+pub fn backups_dir(instance: &Instance) -> PathBuf {
+    LAUNCHER_DIR
+        .join("backups")
+        .join(match instance.kind {
+            InstanceKind::Client => "instances",
+            InstanceKind::Server => "servers",
+        })
+        .join(instance.get_name())
+}
+
+/// Lists all complete snapshots of an instance, newest first.
+///
+/// # Errors
+/// If the backups folder couldn't be read.
+pub async fn list_snapshots(instance: &Instance) -> Result<Vec<Snapshot>, ServerError> {
+    let dir = backups_dir(instance);
+    if !exists(&dir).await {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots = Vec::new();
+    let mut entries = tokio::fs::read_dir(&dir).await.path(&dir)?;
+    while let Some(entry) = entries.next_entry().await.path(&dir)? {
+        let path = entry.path().join(SNAPSHOT_FILE);
+        // Snapshots without it were interrupted
+        if !path.is_file() {
+            continue;
+        }
+        let text = tokio::fs::read_to_string(&path).await.path(&path)?;
+        match serde_json::from_str::<Snapshot>(&text) {
+            Ok(n) => snapshots.push(n),
+            Err(e) => err!("Invalid snapshot {path:?}: {e}"),
+        }
+    }
+    snapshots.sort_by(|a, b| b.created.cmp(&a.created));
+    Ok(snapshots)
+}
+
+/// Takes a snapshot of the worlds of an instance,
+/// then deletes old snapshots according to its
+/// [`RetentionPolicy`].
+///
+/// If `process` is the running server, it's told to write
+/// everything to disk (and stop autosaving) while copying,
+/// through its console or RCON.
+///
+/// # Errors
+/// - The worlds couldn't be read, or the snapshot couldn't be written
+/// - The backup config couldn't be read
+pub async fn create_snapshot(
+    instance: &Instance,
+    process: Option<&LaunchedProcess>,
+    reason: BackupReason,
+) -> Result<Snapshot, ServerError> {
+    let is_running = match process {
+        Some(process) => process
+            .child
+            .lock()
+            .await
+            .try_wait()
+            .ok()
+            .flatten()
+            .is_none(),
+        None => false,
+    };
+    // Clients can't be told to save, and classic
+    // servers (or proxies) don't have these commands
+    let flush = is_running
+        && instance.is_server()
+        && !process.is_some_and(|n| n.is_classic_server || n.is_proxy);
+
+    if flush {
+        flush_world(process, instance.get_name()).await;
+    }
+    let result = copy_snapshot(instance.clone(), reason).await;
+    if flush {
+        if let Err(err) = send_console_command(process, instance.get_name(), "save-on").await {
+            err!("Couldn't turn autosave back on: {err}");
+        }
+    }
+    let snapshot = result?;
+
+    let config = BackupConfig::load(instance).await?;
+    apply_retention(instance, &config.retention).await?;
+    Ok(snapshot)
+}
+
+async fn flush_world(process: Option<&LaunchedProcess>, name: &str) {
+    pt!("Saving world before backup");
+    if let Err(err) = send_console_command(process, name, "save-off").await {
+        err!("Couldn't turn off autosave: {err}");
+    }
+    match send_console_command(process, name, "save-all flush").await {
+        // RCON only responds once it's saved
+        Ok(Some(_)) => {}
+        Ok(None) => tokio::time::sleep(SAVE_WAIT).await,
+        Err(err) => err!("Couldn't save world: {err}"),
+    }
+}
+
+async fn copy_snapshot(instance: Instance, reason: BackupReason) -> Result<Snapshot, ServerError> {
+    let previous = list_snapshots(&instance).await?.into_iter().next();
+    let game_dir = instance.get_dot_minecraft_path();
+    let backups_dir = backups_dir(&instance);
+
+    let created = Utc::now();
+    let id = created
+        .with_timezone(&Local)
+        .format("%Y-%m-%d_%H-%M-%S")
+        .to_string();
+    let dir = backups_dir.join(&id);
+    if exists(&dir).await {
+        // Two snapshots in the same second
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        return Box::pin(copy_snapshot(instance, reason)).await;
+    }
+
+    info!("Backing up worlds of {} ({reason})", instance.get_name());
+    let worlds = find_worlds(&game_dir, instance.kind);
+    let prev_dir = previous.as_ref().map(|n| backups_dir.join(&n.id));
+
+    let snapshot = tokio::task::spawn_blocking(move || -> Result<Snapshot, IoError> {
+        let mut files = BTreeMap::new();
+        let (mut copied, mut linked) = (0, 0);
+
+        for world in &worlds {
+            for entry in walkdir::WalkDir::new(game_dir.join(world))
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|n| n.file_type().is_file())
+            {
+                // Held open by the running server, and meaningless in a backup
+                if entry.file_name() == "session.lock" {
+                    continue;
+                }
+                let path = entry.path();
+                let Ok(rel) = path.strip_prefix(&game_dir) else {
+                    continue;
+                };
+                let key = rel.to_string_lossy().replace('\\', "/");
+                let metadata = entry.metadata().map_err(std::io::Error::from).path(path)?;
+                let info = FileInfo {
+                    size: metadata.len(),
+                    modified: metadata
+                        .modified()
+                        .ok()
+                        .and_then(|n| n.duration_since(UNIX_EPOCH).ok())
+                        .map(|n| u64::try_from(n.as_millis()).unwrap_or(u64::MAX))
+                        .unwrap_or_default(),
+                };
+
+                let dest = dir.join(rel);
+                if let Some(parent) = dest.parent() {
+                    std::fs::create_dir_all(parent).path(parent)?;
+                }
+
+                let unchanged = previous
+                    .as_ref()
+                    .and_then(|n| n.files.get(&key))
+                    .is_some_and(|n| *n == info);
+                let linked_ok = unchanged
+                    && prev_dir
+                        .as_ref()
+                        .is_some_and(|prev| std::fs::hard_link(prev.join(rel), &dest).is_ok());
+                if linked_ok {
+                    linked += 1;
+                } else {
+                    std::fs::copy(path, &dest).path(path)?;
+                    copied += 1;
+                }
+                files.insert(key, info);
+            }
+        }
+
+        let snapshot = Snapshot {
+            id,
+            created,
+            reason,
+            worlds,
+            files,
+        };
+        // Written last, marking the snapshot as complete
+        let path = dir.join(SNAPSHOT_FILE);
+        let text = serde_json::to_string(&snapshot).map_err(std::io::Error::other);
+        std::fs::write(&path, text.path(&path)?).path(&path)?;
+
+        pt!("Copied {copied} files, reused {linked} unchanged");
+        Ok(snapshot)
+    })
+    .await
+    .map_err(|n| ServerError::BackupTask(n.to_string()))??;
+
+    Ok(snapshot)
+}
+
+/// World folders relative to the game directory:
+/// folders with a `level.dat` in the server directory
+/// (`world`, and `world_nether`/`world_the_end` on Bukkit)
+/// or in `saves/` for clients.
+fn find_worlds(game_dir: &Path, kind: InstanceKind) -> Vec<String> {
+    let (search_dir, prefix) = match kind {
+        InstanceKind::Server => (game_dir.to_owned(), ""),
+        InstanceKind::Client => (game_dir.join("saves"), "saves/"),
+    };
+    let Ok(entries) = std::fs::read_dir(&search_dir) else {
+        return Vec::new();
+    };
+    let mut worlds: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|n| n.path().join("level.dat").is_file())
+        .map(|n| format!("{prefix}{}", n.file_name().to_string_lossy()))
+        .collect();
+    worlds.sort();
+    worlds
+}
+
+/// Deletes snapshots not kept by `policy`.
+///
+/// # Errors
+/// If snapshots couldn't be listed or deleted.
+pub async fn apply_retention(
+    instance: &Instance,
+    policy: &RetentionPolicy,
+) -> Result<(), ServerError> {
+    let snapshots = list_snapshots(instance).await?;
+    let keep = snapshots_to_keep(&snapshots, policy);
+
+    let dir = backups_dir(instance);
+    for snapshot in snapshots.iter().filter(|n| !keep.contains(n.id.as_str())) {
+        pt!("Deleting old snapshot {}", snapshot.id);
+        delete_snapshot_dir(&dir.join(&snapshot.id)).await?;
+    }
+    Ok(())
+}
+
+/// `snapshots` must be newest first.
+fn snapshots_to_keep<'a>(snapshots: &'a [Snapshot], policy: &RetentionPolicy) -> HashSet<&'a str> {
+    let mut keep: HashSet<&str> = snapshots
+        .iter()
+        .take(policy.keep_last)
+        .map(|n| n.id.as_str())
+        .collect();
+
+    let mut keep_newest_per = |count: usize, period: &dyn Fn(&Snapshot) -> (i32, u32)| {
+        let mut seen = HashSet::new();
+        for snapshot in snapshots {
+            if seen.len() >= count {
+                break;
+            }
+            if seen.insert(period(snapshot)) {
+                keep.insert(snapshot.id.as_str());
+            }
+        }
+    };
+    keep_newest_per(policy.keep_daily, &|n| {
+        let date = n.created.with_timezone(&Local);
+        (date.year(), date.ordinal())
+    });
+    keep_newest_per(policy.keep_weekly, &|n| {
+        let week = n.created.with_timezone(&Local).iso_week();
+        (week.year(), week.week())
+    });
+    keep
+}
+
+/// Deletes a snapshot.
+///
+/// # Errors
+/// If it couldn't be deleted.
+pub async fn delete_snapshot(instance: &Instance, id: &str) -> Result<(), ServerError> {
+    delete_snapshot_dir(&backups_dir(instance).join(id)).await
+}
+
+async fn delete_snapshot_dir(dir: &Path) -> Result<(), ServerError> {
+    // Remove the marker first, so a partial
+    // deletion isn't seen as a valid snapshot
+    let marker = dir.join(SNAPSHOT_FILE);
+    if exists(&marker).await {
+        tokio::fs::remove_file(&marker).await.path(marker)?;
+    }
+    tokio::fs::remove_dir_all(dir).await.path(dir)?;
+    Ok(())
+}
+
+/// Restores a snapshot's worlds, either over the
+/// instance's current worlds or into a new server.
+///
+/// When restoring over the current worlds, a snapshot
+/// of them is taken first (see [`BackupReason::BeforeRestore`]).
+///
+/// Returns the name of the instance the worlds were restored into.
+///
+/// # Errors
+/// - The snapshot doesn't exist
+/// - [`RestoreTarget::Live`]: the server is running
+///   ([`ServerError::ServerInUse`])
+/// - [`RestoreTarget::NewServer`]: the instance is a client,
+///   or a server with that name already exists
+/// - Files couldn't be copied
+pub async fn restore_snapshot(
+    instance: &Instance,
+    id: &str,
+    target: RestoreTarget,
+) -> Result<String, ServerError> {
+    let snapshot_dir = backups_dir(instance).join(id);
+    let path = snapshot_dir.join(SNAPSHOT_FILE);
+    let text = tokio::fs::read_to_string(&path).await.path(&path)?;
+    let snapshot: Snapshot = serde_json::from_str(&text).json(text)?;
+
+    let (name, game_dir) = match target {
+        RestoreTarget::Live => {
+            if instance.is_server() {
+                check_stopped(instance).await?;
+            }
+            // Not `create_snapshot`, retention could
+            // delete the snapshot being restored
+            copy_snapshot(instance.clone(), BackupReason::BeforeRestore).await?;
+            let game_dir = instance.get_dot_minecraft_path();
+            // Worlds created after the snapshot are removed too,
+            // matching the state at the time of the snapshot
+            for world in find_worlds(&game_dir, instance.kind) {
+                let path = game_dir.join(world);
+                tokio::fs::remove_dir_all(&path).await.path(path)?;
+            }
+            (instance.get_name().to_owned(), game_dir)
+        }
+        RestoreTarget::NewServer(name) => {
+            if instance.kind == InstanceKind::Client {
+                return Err(ServerError::RestoreClientToServer);
+            }
+            let name = sanitize_instance_name(name);
+            if name.is_empty() {
+                return Err(ServerError::InvalidName);
+            }
+            let new_dir = LAUNCHER_DIR.join("servers").join(&name);
+            if exists(&new_dir).await {
+                return Err(ServerError::ServerAlreadyExists);
+            }
+            let old_dir = instance.get_dot_minecraft_path();
+            // The copy isn't running, and must not be
+            // mistaken for the original by the daemon
+            let exceptions: Vec<PathBuf> = find_worlds(&old_dir, instance.kind)
+                .into_iter()
+                .map(|n| old_dir.join(n))
+                .chain(std::iter::once(old_dir.join(STATE_FILE)))
+                .collect();
+            ql_core::file_utils::copy_dir_recursive_ext(&old_dir, &new_dir, &exceptions).await?;
+            (name, new_dir)
+        }
+    };
+
+    info!("Restoring snapshot {id} into {name}");
+    for world in &snapshot.worlds {
+        let src = snapshot_dir.join(world);
+        if exists(&src).await {
+            ql_core::file_utils::copy_dir_recursive(&src, &game_dir.join(world)).await?;
+        }
+    }
+    pt!("Done");
+
+    let config = BackupConfig::load(instance).await?;
+    apply_retention(instance, &config.retention).await?;
+    Ok(name)
+}
+
+/// Fails with [`ServerError::ServerInUse`] if the server is running,
+/// in the background or elsewhere (its world is locked).
+async fn check_stopped(server: &Instance) -> Result<(), ServerError> {
+    if let Some(state) = DaemonState::load(server).await? {
+        // `quantum_launcher server start`
+        return Err(ServerError::ServerInUse(
+            state.pid,
+            "quantum_launcher".to_owned(),
+        ));
+    }
+    if let Some((pid, process)) = world_lock_holder(&server.get_instance_path()).await? {
+        return Err(ServerError::ServerInUse(pid, process));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::{Duration, Utc};
+
+    use super::{BackupReason, RetentionPolicy, Snapshot, snapshots_to_keep};
+
+    #[test]
+    fn retention() {
+        let now = Utc::now();
+        // One snapshot every 6 hours, for 30 days, newest first
+        let snapshots: Vec<Snapshot> = (0..120)
+            .map(|i| Snapshot {
+                id: i.to_string(),
+                created: now - Duration::hours(i * 6),
+                reason: BackupReason::Scheduled,
+                worlds: Vec::new(),
+                files: BTreeMap::new(),
+            })
+            .collect();
+
+        let policy = RetentionPolicy {
+            keep_last: 3,
+            keep_daily: 0,
+            keep_weekly: 0,
+        };
+        let keep = snapshots_to_keep(&snapshots, &policy);
+        assert_eq!(keep.len(), 3);
+        assert!(keep.contains("0") && keep.contains("2"));
+
+        let policy = RetentionPolicy {
+            keep_last: 1,
+            keep_daily: 7,
+            keep_weekly: 4,
+        };
+        let keep = snapshots_to_keep(&snapshots, &policy);
+        // Daily and weekly snapshots overlap in the first week
+        assert!((7..=11).contains(&keep.len()), "{}", keep.len());
+        assert!(keep.contains("0"));
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::{Duration, Instant};
+
+use ql_core::{LaunchedProcess, err};
+
+use super::{BackupConfig, BackupReason, create_snapshot};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Takes snapshots of a running server according to its
+/// [`BackupConfig`]: every `interval_minutes`, and once
+/// it stops (if `on_stop` is set).
+///
+/// Runs until the server exits, so spawn this as a
+/// separate task after starting the server.
+/// The config is re-read every time, so changes apply
+/// without restarting the server.
+pub async fn backup_scheduler(process: LaunchedProcess) {
+    let instance = process.instance.clone();
+    let mut last_backup = Instant::now();
+
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let has_exited = match process.child.lock().await.try_wait() {
+            Ok(status) => status.is_some(),
+            Err(_) => true,
+        };
+
+        let config = match BackupConfig::load(&instance).await {
+            Ok(n) => n,
+            Err(error) => {
+                err!("Couldn't load backup config: {error}");
+                BackupConfig::default()
+            }
+        };
+
+        if has_exited {
+            if config.on_stop {
+                if let Err(error) =
+                    create_snapshot(&instance, None, BackupReason::ServerStopped).await
+                {
+                    err!("Couldn't back up server after stopping: {error}");
+                }
+            }
+            return;
+        }
+
+        let Some(interval) = config.interval_minutes.filter(|n| *n > 0) else {
+            continue;
+        };
+        if last_backup.elapsed() < Duration::from_secs(u64::from(interval) * 60) {
+            continue;
+        }
+        last_backup = Instant::now();
+        if let Err(error) =
+            create_snapshot(&instance, Some(&process), BackupReason::Scheduled).await
+        {
+            err!("Couldn't take scheduled backup: {error}");
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! Helpers for the server console:
+//! command history and tab completion.
+
+use std::path::Path;
+
+use ql_core::{IntoIoError, IoError};
+
+const HISTORY_FILE: &str = "command_history.txt";
+const HISTORY_LIMIT: usize = 200;
+
+/// Commands of the vanilla server (as of 1.21),
+/// for tab completion in the console.
+pub const VANILLA_COMMANDS: &[&str] = &[
+    "advancement",
+    "attribute",
+    "ban",
+    "ban-ip",
+    "banlist",
+    "bossbar",
+    "clear",
+    "clone",
+    "damage",
+    "data",
+    "datapack",
+    "debug",
+    "defaultgamemode",
+    "deop",
+    "difficulty",
+    "effect",
+    "enchant",
+    "execute",
+    "experience",
+    "fill",
+    "fillbiome",
+    "forceload",
+    "function",
+    "gamemode",
+    "gamerule",
+    "give",
+    "help",
+    "item",
+    "jfr",
+    "kick",
+    "kill",
+    "list",
+    "locate",
+    "loot",
+    "me",
+    "msg",
+    "op",
+    "pardon",
+    "pardon-ip",
+    "particle",
+    "perf",
+    "place",
+    "playsound",
+    "random",
+    "recipe",
+    "reload",
+    "return",
+    "ride",
+    "rotate",
+    "save-all",
+    "save-off",
+    "save-on",
+    "say",
+    "schedule",
+    "scoreboard",
+    "seed",
+    "setblock",
+    "setidletimeout",
+    "setworldspawn",
+    "spawnpoint",
+    "spectate",
+    "spreadplayers",
+    "stop",
+    "stopsound",
+    "summon",
+    "tag",
+    "team",
+    "teammsg",
+    "teleport",
+    "tell",
+    "tellraw",
+    "tick",
+    "time",
+    "title",
+    "tm",
+    "tp",
+    "transfer",
+    "trigger",
+    "w",
+    "weather",
+    "whitelist",
+    "worldborder",
+    "xp",
+];
+
+/// Commands starting with what's typed in `input`.
+///
+/// Only the command name (first word) is completed,
+/// so this is empty once there's a space.
+#[must_use]
+pub fn command_suggestions(input: &str) -> Vec<&'static str> {
+    let word = input.strip_prefix('/').unwrap_or(input);
+    if word.is_empty() || word.contains(' ') {
+        return Vec::new();
+    }
+    VANILLA_COMMANDS
+        .iter()
+        .filter(|n| n.starts_with(word))
+        .copied()
+        .collect()
+}
+
+/// Tab-completes the command name in `input`.
+///
+/// If only one command matches, it's completed fully
+/// (with a trailing space), otherwise the input is extended
+/// to the longest prefix all matches share.
+/// Returns `None` if there's nothing to complete.
+#[must_use]
+pub fn complete_command(input: &str) -> Option<String> {
+    let slash = if input.starts_with('/') { "/" } else { "" };
+    let word = &input[slash.len()..];
+    let suggestions = command_suggestions(input);
+
+    let completed = match suggestions.as_slice() {
+        [] => return None,
+        [only] => format!("{only} "),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.len(), |len, n| {
+                first
+                    .bytes()
+                    .zip(n.bytes())
+                    .take(len)
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            });
+            first[..common].to_owned()
+        }
+    };
+    (completed.len() > word.len()).then(|| format!("{slash}{completed}"))
+}
+
+/// Previously sent console commands of a server,
+/// with up/down navigation like a terminal.
+#[derive(Debug, Clone, Default)]
+pub struct CommandHistory {
+    entries: Vec<String>,
+    /// Position while navigating with up/down,
+    /// `None` when not navigating
+    cursor: Option<usize>,
+}
+
+impl CommandHistory {
+    /// Loads the history saved in a server directory
+    /// (empty if there's none yet).
+    #[must_use]
+    pub fn load(server_dir: &Path) -> Self {
+        let entries = std::fs::read_to_string(server_dir.join(HISTORY_FILE))
+            .map(|n| n.lines().map(str::to_owned).collect())
+            .unwrap_or_default();
+        Self {
+            entries,
+            cursor: None,
+        }
+    }
+
+    /// Saves the history to a server directory.
+    ///
+    /// # Errors
+    /// If the file couldn't be written.
+    pub async fn save(&self, server_dir: &Path) -> Result<(), IoError> {
+        let path = server_dir.join(HISTORY_FILE);
+        let mut text = self.entries.join("\n");
+        text.push('\n');
+        tokio::fs::write(&path, text).await.path(path)
+    }
+
+    /// Adds a sent command, skipping empty ones
+    /// and repeats of the last command.
+    pub fn push(&mut self, command: &str) {
+        self.cursor = None;
+        let command = command.trim();
+        if command.is_empty() || self.entries.last().is_some_and(|n| n == command) {
+            return;
+        }
+        self.entries.push(command.to_owned());
+        if self.entries.len() > HISTORY_LIMIT {
+            self.entries.remove(0);
+        }
+    }
+
+    /// Moves to the previous (older) command.
+    pub fn older(&mut self) -> Option<&str> {
+        let i = match self.cursor {
+            Some(0) => 0,
+            Some(i) => i - 1,
+            None => self.entries.len().checked_sub(1)?,
+        };
+        self.cursor = Some(i);
+        self.entries.get(i).map(String::as_str)
+    }
+
+    /// Moves to the next (newer) command.
+    ///
+    /// Returns an empty string when moving past the
+    /// newest one, to clear the input.
+    pub fn newer(&mut self) -> Option<&str> {
+        let i = self.cursor? + 1;
+        if i >= self.entries.len() {
+            self.cursor = None;
+            return Some("");
+        }
+        self.cursor = Some(i);
+        self.entries.get(i).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandHistory, complete_command};
+
+    #[test]
+    fn completion() {
+        assert_eq!(complete_command("whi").as_deref(), Some("whitelist "));
+        assert_eq!(complete_command("/pard").as_deref(), Some("/pardon"));
+        assert_eq!(complete_command("game"), None);
+        assert_eq!(complete_command("gamemode").as_deref(), Some("gamemode "));
+        assert_eq!(complete_command("ban"), None);
+        assert_eq!(complete_command("op Steve"), None);
+        assert_eq!(complete_command("zzz"), None);
+    }
+
+    #[test]
+    fn history_navigation() {
+        let mut history = CommandHistory::default();
+        assert_eq!(history.older(), None);
+        history.push("list");
+        history.push("say hi");
+        history.push("say hi");
+        assert_eq!(history.older(), Some("say hi"));
+        assert_eq!(history.older(), Some("list"));
+        assert_eq!(history.older(), Some("list"));
+        assert_eq!(history.newer(), Some("say hi"));
+        assert_eq!(history.newer(), Some(""));
+        assert_eq!(history.newer(), None);
+    }
+}
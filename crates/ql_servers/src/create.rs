@@ -1,18 +1,26 @@
 use std::sync::mpsc::Sender;
 
 use ql_core::{
-    DownloadProgress, IntoIoError, IntoJsonError, IntoStringError, LAUNCHER_DIR, ListEntry,
-    download,
+    DownloadProgress, Instance, IntoIoError, IntoJsonError, IntoStringError, LAUNCHER_DIR,
+    ListEntry, download,
     file_utils::{self, exists},
     info,
-    json::{InstanceConfigJson, Manifest, VersionDetails, instance_config::VersionInfo},
+    json::{
+        InstanceConfigJson, Manifest, VersionDetails,
+        instance_config::{EulaAcceptance, VersionInfo},
+    },
     pt, sanitize_instance_name,
 };
 
-use crate::ServerError;
+use chrono::Utc;
+
+use crate::{ServerError, assign_ports};
 
 /// Creates a minecraft server with the given name and version.
 ///
+/// The Minecraft EULA isn't accepted, the user
+/// has to agree to it first (see [`accept_eula`]).
+///
 /// # Arguments
 /// - `name` - The name of the server.
 /// - `version` - The version of the server.
@@ -26,7 +34,7 @@ use crate::ServerError;
 ///
 /// If:
 /// - server already exists
-/// - EULA and `config.json` file couldn't be saved
+/// - `config.json` file couldn't be saved
 /// ## Server Jar...
 /// - ...couldn't be downloaded from
 ///   Mojang/Omniarchive (internet/server issue)
@@ -91,12 +99,14 @@ pub async fn create_server(
     }
 
     version_json.save_to_dir(&server_dir).await?;
-    write_eula(&server_dir).await?;
     write_config(is_classic_server, &server_dir, &version_json).await?;
 
     let mods_dir = server_dir.join("mods");
     tokio::fs::create_dir(&mods_dir).await.path(mods_dir)?;
 
+    // So it can run alongside the other servers
+    assign_ports(&Instance::server(&name), false).await?;
+
     pt!("Finished");
 
     Ok(name)
@@ -142,14 +152,56 @@ fn progress_manifest(sender: Option<&Sender<DownloadProgress>>) {
     }
 }
 
-async fn write_eula(server_dir: &std::path::Path) -> Result<(), ServerError> {
+/// Accepts the Minecraft EULA (<https://aka.ms/MinecraftEULA>)
+/// for a server, by writing `eula=true` to `eula.txt`.
+///
+/// **Only call this once the user has agreed to it.**
+/// Who (the user account on this computer) and when
+/// is recorded in `config.json`.
+///
+/// # Errors
+/// If `eula.txt` or `config.json` couldn't be written.
+pub async fn accept_eula(server_dir: &std::path::Path) -> Result<(), ServerError> {
+    let acceptance = EulaAcceptance {
+        by: std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".to_owned()),
+        at: Utc::now(),
+    };
+    info!(
+        "Minecraft EULA accepted by {} for {server_dir:?}",
+        acceptance.by
+    );
+
     let eula_path = server_dir.join("eula.txt");
-    tokio::fs::write(&eula_path, "eula=true\n")
-        .await
-        .path(eula_path)?;
+    let eula = format!(
+        "#By changing the setting below to TRUE you are indicating your agreement to our EULA (https://aka.ms/MinecraftEULA).\n#Accepted by {} in QuantumLauncher at {}\neula=true\n",
+        acceptance.by,
+        acceptance.at.to_rfc3339()
+    );
+    tokio::fs::write(&eula_path, eula).await.path(eula_path)?;
+
+    if exists(server_dir.join("config.json")).await {
+        let mut config = InstanceConfigJson::read_from_dir(server_dir).await?;
+        config.eula_accepted = Some(acceptance);
+        config.save_to_dir(server_dir).await?;
+    }
     Ok(())
 }
 
+/// Whether `eula.txt` of a server says `eula=true`.
+pub async fn is_eula_accepted(server_dir: &std::path::Path) -> bool {
+    tokio::fs::read_to_string(server_dir.join("eula.txt"))
+        .await
+        .is_ok_and(|text| {
+            text.lines()
+                .filter_map(|line| line.split_once('='))
+                .any(|(key, value)| {
+                    key.trim() == "eula" && value.trim().eq_ignore_ascii_case("true")
+                })
+        })
+}
+
 fn progress_server_jar(sender: Option<&Sender<DownloadProgress>>) {
     pt!("Downloading server jar");
     if let Some(sender) = sender {
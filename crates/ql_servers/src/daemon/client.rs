@@ -0,0 +1,190 @@
+use std::{net::Ipv4Addr, time::Duration};
+
+use ql_core::Instance;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    net::{
+        TcpStream,
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+    },
+};
+
+use super::{ControlAction, ControlRequest, ControlResponse, DaemonError, DaemonState};
+use crate::ServerError;
+
+/// How long [`DaemonClient::run_command`] waits for
+/// the server to print something after a command
+const OUTPUT_QUIET_TIME: Duration = Duration::from_millis(500);
+/// ...and how long in total, for commands with a lot of output
+const OUTPUT_MAX_TIME: Duration = Duration::from_secs(5);
+/// How long [`DaemonClient::stop`] waits for the daemon
+/// to exit after the server stopped (it may take a backup)
+const DAEMON_EXIT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Talks to a server running in the background
+/// (see the [module docs](super)).
+pub struct DaemonClient {
+    instance: Instance,
+    state: DaemonState,
+}
+
+impl DaemonClient {
+    /// # Errors
+    /// - The server isn't running in the background
+    /// - Its state file couldn't be read
+    pub async fn connect(instance: &Instance) -> Result<Self, ServerError> {
+        let state = DaemonState::load(instance)
+            .await?
+            .ok_or_else(|| DaemonError::NotRunning(instance.name.to_string()))?;
+        Ok(Self {
+            instance: instance.clone(),
+            state,
+        })
+    }
+
+    #[must_use]
+    pub fn state(&self) -> &DaemonState {
+        &self.state
+    }
+
+    async fn request(
+        &self,
+        action: ControlAction,
+    ) -> Result<(Lines<BufReader<OwnedReadHalf>>, OwnedWriteHalf), DaemonError> {
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, self.state.port)).await?;
+        let (read, mut write) = stream.into_split();
+        let mut line = serde_json::to_string(&ControlRequest {
+            token: self.state.token.clone(),
+            action,
+        })?;
+        line.push('\n');
+        write.write_all(line.as_bytes()).await?;
+        Ok((BufReader::new(read).lines(), write))
+    }
+
+    /// Attaches to the server console. `backlog`
+    /// is how many of the previous lines to get.
+    ///
+    /// # Errors
+    /// If the daemon couldn't be reached.
+    pub async fn attach(
+        &self,
+        backlog: usize,
+    ) -> Result<(ConsoleReader, ConsoleWriter), ServerError> {
+        let (mut lines, write) = self.request(ControlAction::Attach { backlog }).await?;
+        let backlog = match read_response(&mut lines).await? {
+            Some(ControlResponse::Backlog(lines)) => lines,
+            Some(ControlResponse::Error(error)) => return Err(DaemonError::Rejected(error).into()),
+            _ => return Err(DaemonError::Rejected("unexpected response".to_owned()).into()),
+        };
+        Ok((ConsoleReader { lines, backlog }, ConsoleWriter { write }))
+    }
+
+    /// Sends a command to the server console, returning
+    /// what the server printed right after it.
+    ///
+    /// # Errors
+    /// If the daemon couldn't be reached, or the server isn't running.
+    pub async fn run_command(&self, command: &str) -> Result<Vec<String>, ServerError> {
+        let (mut reader, mut writer) = self.attach(0).await?;
+        writer.send_command(command).await?;
+
+        let mut output = Vec::new();
+        let start = tokio::time::Instant::now();
+        while start.elapsed() < OUTPUT_MAX_TIME {
+            match tokio::time::timeout(OUTPUT_QUIET_TIME, reader.next_line()).await {
+                Ok(Ok(Some(line))) => output.push(line),
+                Ok(Ok(None)) | Err(_) => break,
+                Ok(Err(error)) => return Err(error),
+            }
+        }
+        Ok(output)
+    }
+
+    /// Stops the server (without restarting it),
+    /// waiting until the daemon has exited.
+    ///
+    /// # Errors
+    /// If the daemon couldn't be reached or the server couldn't be stopped.
+    pub async fn stop(&self) -> Result<(), ServerError> {
+        let (mut lines, _write) = self.request(ControlAction::Stop).await?;
+        match read_response(&mut lines).await {
+            // The daemon may exit before answering
+            Ok(Some(ControlResponse::Stopped) | None)
+            | Err(ServerError::Daemon(DaemonError::Io(_))) => {}
+            Ok(Some(ControlResponse::Error(error))) => {
+                return Err(DaemonError::Rejected(error).into());
+            }
+            Ok(Some(_)) => {
+                return Err(DaemonError::Rejected("unexpected response".to_owned()).into());
+            }
+            Err(error) => return Err(error),
+        }
+
+        let start = tokio::time::Instant::now();
+        while DaemonState::load(&self.instance).await?.is_some() {
+            if start.elapsed() > DAEMON_EXIT_TIMEOUT {
+                return Err(DaemonError::AlreadyRunning(
+                    self.instance.name.to_string(),
+                    self.state.pid,
+                )
+                .into());
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+        Ok(())
+    }
+}
+
+async fn read_response(
+    lines: &mut Lines<BufReader<OwnedReadHalf>>,
+) -> Result<Option<ControlResponse>, ServerError> {
+    let Some(line) = lines.next_line().await.map_err(DaemonError::Io)? else {
+        return Ok(None);
+    };
+    Ok(Some(
+        serde_json::from_str(&line).map_err(DaemonError::Json)?,
+    ))
+}
+
+/// Output of a server console (see [`DaemonClient::attach`]).
+pub struct ConsoleReader {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    /// Lines printed before attaching
+    pub backlog: Vec<String>,
+}
+
+impl ConsoleReader {
+    /// The next line printed by the server,
+    /// or `None` once the daemon exits.
+    ///
+    /// # Errors
+    /// - The connection was lost
+    /// - A command sent with [`ConsoleWriter`] failed
+    pub async fn next_line(&mut self) -> Result<Option<String>, ServerError> {
+        match read_response(&mut self.lines).await? {
+            Some(ControlResponse::Line(line)) => Ok(Some(line)),
+            Some(ControlResponse::Error(error)) => Err(DaemonError::Rejected(error).into()),
+            Some(_) => Err(DaemonError::Rejected("unexpected response".to_owned()).into()),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Input of a server console (see [`DaemonClient::attach`]).
+pub struct ConsoleWriter {
+    write: OwnedWriteHalf,
+}
+
+impl ConsoleWriter {
+    /// # Errors
+    /// If the connection was lost.
+    pub async fn send_command(&mut self, command: &str) -> Result<(), ServerError> {
+        let command = command.trim().replace('\n', " ");
+        self.write
+            .write_all(format!("{command}\n").as_bytes())
+            .await
+            .map_err(DaemonError::Io)?;
+        Ok(())
+    }
+}
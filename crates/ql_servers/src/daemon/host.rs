@@ -0,0 +1,309 @@
+use std::{
+    collections::VecDeque,
+    io::Write,
+    net::Ipv4Addr,
+    process::ExitStatus,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::Utc;
+use ql_core::{Instance, LaunchedProcess, err, info, read_log::LogLine};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream, tcp::OwnedWriteHalf},
+    sync::broadcast,
+};
+
+use super::{
+    ControlAction, ControlRequest, ControlResponse, DaemonError, DaemonState, DaemonStatus,
+    console_log_path,
+};
+use crate::{
+    ServerError, Watchdog, backup_scheduler, diagnostics::world_lock_holder,
+    rcon::generate_password,
+};
+
+/// Lines kept in memory for clients that attach later
+const BACKLOG_LEN: usize = 1000;
+/// How long the server gets to save and stop before it's killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Runs a server in this process until it's stopped, restarting
+/// it according to its [`crate::WatchdogConfig`], and serves the
+/// control socket. See the [module docs](super).
+///
+/// This is what the detached process started by
+/// `quantum_launcher server start` runs.
+///
+/// # Errors
+/// - The server is already running in the background
+/// - The server is already running elsewhere, eg: from the
+///   launcher window (its world is locked, [`ServerError::ServerInUse`])
+/// - The server couldn't be started (see [`crate::run`])
+/// - The control socket or state file couldn't be set up
+pub async fn run_daemon(name: Arc<str>) -> Result<(), ServerError> {
+    let instance = Instance::server(&name);
+    if let Some((pid, process)) = world_lock_holder(&instance.get_instance_path()).await? {
+        return Err(ServerError::ServerInUse(pid, process));
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .await
+        .map_err(DaemonError::Io)?;
+    let port = listener.local_addr().map_err(DaemonError::Io)?.port();
+
+    let token = generate_password();
+    let mut state = DaemonState {
+        pid: std::process::id(),
+        server_pid: None,
+        port,
+        token: token.clone(),
+        started: Utc::now(),
+        status: DaemonStatus::Starting,
+    };
+    // Before anything else, so that two daemons
+    // can't start at once
+    state.claim(&instance).await?;
+
+    let log_path = console_log_path(&instance);
+    let log_file = std::fs::File::create(&log_path).map_err(DaemonError::Io)?;
+
+    let host = Arc::new(Host {
+        token,
+        process: Mutex::new(None),
+        console: Mutex::new(Console {
+            backlog: VecDeque::new(),
+            file: log_file,
+            sender: broadcast::channel(256).0,
+        }),
+        is_stopping: AtomicBool::new(false),
+    });
+    info!("Running {name} in the background (control port {port})");
+
+    let accept_task = tokio::spawn(accept_connections(listener, host.clone()));
+    let result = supervise(&instance, &host, &mut state).await;
+    accept_task.abort();
+
+    if let Err(error) = &result {
+        host.push_line(format!("[QuantumLauncher] {error}"));
+    }
+    let state_path = instance.get_instance_path().join(super::STATE_FILE);
+    if let Err(error) = tokio::fs::remove_file(&state_path).await {
+        err!("Couldn't remove {state_path:?}: {error}");
+    }
+    result
+}
+
+struct Host {
+    token: String,
+    /// `None` while (re)starting
+    process: Mutex<Option<LaunchedProcess>>,
+    console: Mutex<Console>,
+    /// A client asked to stop, don't restart
+    is_stopping: AtomicBool,
+}
+
+struct Console {
+    backlog: VecDeque<String>,
+    file: std::fs::File,
+    sender: broadcast::Sender<String>,
+}
+
+impl Host {
+    fn push_line(&self, line: String) {
+        let mut console = self.console.lock().unwrap();
+        _ = writeln!(console.file, "{line}");
+        if console.backlog.len() >= BACKLOG_LEN {
+            console.backlog.pop_front();
+        }
+        console.backlog.push_back(line.clone());
+        // Nobody may be attached
+        _ = console.sender.send(line);
+    }
+
+    /// The last `backlog` lines, and a receiver for the next ones
+    /// (taken together, so that no line is missed or repeated).
+    fn subscribe(&self, backlog: usize) -> (Vec<String>, broadcast::Receiver<String>) {
+        let console = self.console.lock().unwrap();
+        let skip = console.backlog.len().saturating_sub(backlog);
+        let lines = console.backlog.iter().skip(skip).cloned().collect();
+        (lines, console.sender.subscribe())
+    }
+
+    fn process(&self) -> Option<LaunchedProcess> {
+        self.process.lock().unwrap().clone()
+    }
+}
+
+async fn supervise(
+    instance: &Instance,
+    host: &Arc<Host>,
+    state: &mut DaemonState,
+) -> Result<(), ServerError> {
+    let watchdog = Watchdog::default();
+    let mut child = crate::run(instance.name.clone(), None).await?;
+
+    loop {
+        if host.is_stopping.load(Ordering::SeqCst) {
+            // Asked to stop while (re)starting
+            _ = child.stop(STOP_TIMEOUT).await;
+        }
+        *host.process.lock().unwrap() = Some(child.clone());
+        state.status = DaemonStatus::Running;
+        state.server_pid = child.child.lock().await.id();
+        state.save(instance).await?;
+
+        let backups = tokio::spawn(backup_scheduler(child.clone()));
+        let restarts = tokio::spawn(watchdog.clone().restart_scheduler(child.clone()));
+
+        let (sender, receiver) = std::sync::mpsc::channel::<LogLine>();
+        let pump = {
+            let host = host.clone();
+            tokio::task::spawn_blocking(move || {
+                for line in receiver {
+                    host.push_line(line.to_string().trim_end().to_owned());
+                }
+            })
+        };
+
+        let result = child.read_logs(Vec::new(), Some(sender)).await;
+        // Without logging, `read_logs` returns right away
+        let status = wait_for_exit(&child).await;
+        *host.process.lock().unwrap() = None;
+        restarts.abort();
+
+        let is_stopping = host.is_stopping.load(Ordering::SeqCst);
+        state.status = if is_stopping {
+            DaemonStatus::Stopping
+        } else {
+            DaemonStatus::Restarting
+        };
+        state.server_pid = None;
+        state.save(instance).await?;
+
+        _ = pump.await;
+        match result {
+            Some(Ok((_, _, Some(diag)))) => host.push_line(format!("[QuantumLauncher] {diag}")),
+            Some(Err(error)) => return Err(DaemonError::ReadLog(error).into()),
+            _ => {}
+        }
+        host.push_line(format!("[QuantumLauncher] Server exited ({status})"));
+        // Let it finish the on-stop backup, if any
+        _ = backups.await;
+
+        match watchdog
+            .restart_after_exit(instance, status, is_stopping)
+            .await
+        {
+            Some(restarted) => {
+                host.push_line("[QuantumLauncher] Server restarted".to_owned());
+                child = restarted?;
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+async fn wait_for_exit(child: &LaunchedProcess) -> ExitStatus {
+    loop {
+        match child.child.lock().await.try_wait() {
+            Ok(Some(status)) => return status,
+            Ok(None) => {}
+            Err(error) => {
+                err!("Couldn't check if server exited: {error}");
+                return ExitStatus::default();
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+async fn accept_connections(listener: TcpListener, host: Arc<Host>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let host = host.clone();
+                tokio::spawn(async move {
+                    if let Err(error) = handle_connection(stream, &host).await {
+                        err!("Control connection: {error}");
+                    }
+                });
+            }
+            Err(error) => err!("Couldn't accept control connection: {error}"),
+        }
+    }
+}
+
+async fn handle_connection(stream: TcpStream, host: &Host) -> Result<(), DaemonError> {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+
+    let Some(request) = lines.next_line().await? else {
+        return Ok(());
+    };
+    let request: ControlRequest = match serde_json::from_str(&request) {
+        Ok(n) => n,
+        Err(error) => {
+            let msg = format!("invalid request: {error}");
+            return respond(&mut write, &ControlResponse::Error(msg)).await;
+        }
+    };
+    if request.token != host.token {
+        let msg = "invalid token".to_owned();
+        return respond(&mut write, &ControlResponse::Error(msg)).await;
+    }
+
+    match request.action {
+        ControlAction::Stop => {
+            host.is_stopping.store(true, Ordering::SeqCst);
+            if let Some(process) = host.process() {
+                host.push_line("[QuantumLauncher] Stopping server".to_owned());
+                if let Err(error) = process.stop(STOP_TIMEOUT).await {
+                    let msg = error.to_string();
+                    return respond(&mut write, &ControlResponse::Error(msg)).await;
+                }
+            }
+            respond(&mut write, &ControlResponse::Stopped).await
+        }
+        ControlAction::Attach { backlog } => {
+            let (backlog, mut receiver) = host.subscribe(backlog);
+            respond(&mut write, &ControlResponse::Backlog(backlog)).await?;
+            loop {
+                tokio::select! {
+                    line = receiver.recv() => match line {
+                        Ok(line) => respond(&mut write, &ControlResponse::Line(line)).await?,
+                        Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                    },
+                    command = lines.next_line() => {
+                        let Some(command) = command? else {
+                            // Detached
+                            return Ok(());
+                        };
+                        let result = match host.process() {
+                            Some(process) => process.send_command(&command).await.map_err(|n| n.to_string()),
+                            None => Err("the server isn't running right now".to_owned()),
+                        };
+                        if let Err(error) = result {
+                            respond(&mut write, &ControlResponse::Error(error)).await?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn respond(
+    write: &mut OwnedWriteHalf,
+    response: &ControlResponse,
+) -> Result<(), DaemonError> {
+    let mut line = serde_json::to_string(response)?;
+    line.push('\n');
+    write.write_all(line.as_bytes()).await?;
+    Ok(())
+}
@@ -0,0 +1,210 @@
+//! Running servers in the background, detached from the launcher
+//! (`quantum_launcher server start`).
+//!
+//! The daemon process runs the server (restarting it with its
+//! [`crate::Watchdog`]) and writes `daemon.json` to the server folder,
+//! with its PID and the port of its control socket. Later invocations
+//! connect to that socket (on localhost, with a token from the same file)
+//! to attach to the console or stop the server.
+//!
+//! Everything the server prints is also written to `console.log`.
+//!
+//! # Protocol
+//! Newline-separated JSON. The client sends a [`ControlRequest`],
+//! and the daemon answers with [`ControlResponse`]s. After attaching,
+//! every line the client sends is a console command.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use ql_core::{Instance, IntoIoError, IntoJsonError, file_utils::exists, read_log::ReadError};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+
+use crate::ServerError;
+
+mod client;
+mod host;
+pub use client::{ConsoleReader, ConsoleWriter, DaemonClient};
+pub use host::run_daemon;
+
+pub(crate) const STATE_FILE: &str = "daemon.json";
+const CONSOLE_LOG_FILE: &str = "console.log";
+
+const DAEMON_ERR_PREFIX: &str = "while talking to background server:\n";
+
+#[derive(Debug, Error)]
+pub enum DaemonError {
+    #[error("{DAEMON_ERR_PREFIX}{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{DAEMON_ERR_PREFIX}invalid message: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{DAEMON_ERR_PREFIX}{0}")]
+    ReadLog(#[from] ReadError),
+    #[error("{0} isn't running in the background\n(start it with `server start {0}`)")]
+    NotRunning(String),
+    #[error("{0} is already running in the background (PID {1})")]
+    AlreadyRunning(String, u32),
+    #[error("{DAEMON_ERR_PREFIX}{0}")]
+    Rejected(String),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DaemonStatus {
+    /// Getting Java and starting the server
+    Starting,
+    Running,
+    /// Exited, waiting to be restarted by the watchdog
+    Restarting,
+    Stopping,
+}
+
+impl std::fmt::Display for DaemonStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DaemonStatus::Starting => "Starting",
+                DaemonStatus::Running => "Running",
+                DaemonStatus::Restarting => "Restarting",
+                DaemonStatus::Stopping => "Stopping",
+            }
+        )
+    }
+}
+
+/// `daemon.json`: present while a server runs in the background.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonState {
+    /// Of the daemon, not the server
+    pub pid: u32,
+    /// Of the Java (server) process, if it's running
+    pub server_pid: Option<u32>,
+    /// Control socket, on localhost
+    pub port: u16,
+    /// Secret that clients have to send first
+    pub token: String,
+    pub started: DateTime<Utc>,
+    pub status: DaemonStatus,
+}
+
+impl DaemonState {
+    /// The state of the daemon running a server,
+    /// or `None` if it isn't running in the background.
+    ///
+    /// Deletes the state file if it's left over
+    /// from a daemon that didn't exit cleanly.
+    ///
+    /// # Errors
+    /// If the state file couldn't be read or parsed.
+    pub async fn load(instance: &Instance) -> Result<Option<Self>, ServerError> {
+        let path = instance.get_instance_path().join(STATE_FILE);
+        if !exists(&path).await {
+            return Ok(None);
+        }
+        let text = tokio::fs::read_to_string(&path).await.path(&path)?;
+        let state: Self = serde_json::from_str(&text).json(text)?;
+
+        if is_process_alive(state.pid) {
+            Ok(Some(state))
+        } else {
+            tokio::fs::remove_file(&path).await.path(path)?;
+            Ok(None)
+        }
+    }
+
+    /// Writes the state file, failing if it already exists
+    /// (so only one daemon can run a server at a time).
+    /// A file left over from a crashed daemon is replaced.
+    async fn claim(&self, instance: &Instance) -> Result<(), ServerError> {
+        let path = instance.get_instance_path().join(STATE_FILE);
+        let text = serde_json::to_string_pretty(self).json_to()?;
+        loop {
+            let mut options = tokio::fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            // Only the owner may read the token
+            #[cfg(unix)]
+            options.mode(0o600);
+            match options.open(&path).await {
+                Ok(mut file) => {
+                    file.write_all(text.as_bytes()).await.path(&path)?;
+                    return Ok(());
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {
+                    // Removes it if it's left over
+                    if let Some(state) = Self::load(instance).await? {
+                        return Err(DaemonError::AlreadyRunning(
+                            instance.name.to_string(),
+                            state.pid,
+                        )
+                        .into());
+                    }
+                }
+                Err(error) => return Err(error.path(path).into()),
+            }
+        }
+    }
+
+    async fn save(&self, instance: &Instance) -> Result<(), ServerError> {
+        let path = instance.get_instance_path().join(STATE_FILE);
+        let tmp = path.with_extension("json.tmp");
+        let text = serde_json::to_string_pretty(self).json_to()?;
+        tokio::fs::write(&tmp, text).await.path(&tmp)?;
+        #[cfg(unix)]
+        {
+            // Only the owner may read the token
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600))
+                .await
+                .path(&tmp)?;
+        }
+        tokio::fs::rename(&tmp, &path).await.path(path)?;
+        Ok(())
+    }
+}
+
+/// Everything the server printed while running in the
+/// background (the last run only, cleared on start).
+#[must_use]
+pub fn console_log_path(instance: &Instance) -> PathBuf {
+    instance.get_instance_path().join(CONSOLE_LOG_FILE)
+}
+
+fn is_process_alive(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing(),
+    );
+    system.process(pid).is_some()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ControlRequest {
+    token: String,
+    action: ControlAction,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum ControlAction {
+    /// Receive the last `backlog` lines and then everything the
+    /// server prints. Lines sent after this are console commands.
+    Attach { backlog: usize },
+    /// Stop the server (without restarting) and the daemon
+    Stop,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum ControlResponse {
+    /// First answer to [`ControlAction::Attach`]
+    Backlog(Vec<String>),
+    Line(String),
+    /// [`ControlAction::Stop`] is done
+    Stopped,
+    Error(String),
+}
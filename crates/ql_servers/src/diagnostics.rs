@@ -0,0 +1,327 @@
+//! One-click fixes for common server start failures,
+//! detected from the log as a [`Diagnostic`].
+
+use std::{ffi::OsStr, path::Path};
+
+use ql_core::{
+    Instance, IntoIoError, JavaVersion, info, json::InstanceConfigJson, read_log::Diagnostic,
+};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::{
+    ServerError, ServerPorts, ServerProperties, accept_eula,
+    ports::{Protocol, ReservedPorts, bind_ip, is_free, next_free_port, ports_in_use},
+};
+
+/// A fix for a [`Diagnostic`], that can be applied
+/// with [`DiagnosticFix::apply`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticFix {
+    /// Delete leftover `session.lock` files
+    /// (after checking no process uses the server)
+    ClearSessionLock,
+    /// Change `server-port` to one that's free
+    ChangePort,
+    AcceptEula,
+    /// Set the launcher-provided Java version
+    /// (`java_override_version`) to this
+    UseJava(usize),
+    /// Double the memory allocation
+    IncreaseMemory,
+}
+
+impl DiagnosticFix {
+    /// The fix for a diagnostic of `instance`, if any.
+    #[must_use]
+    pub fn for_diagnostic(diagnostic: &Diagnostic, instance: &Instance) -> Option<Self> {
+        let is_server = instance.is_server();
+        match diagnostic {
+            Diagnostic::SessionLocked if is_server => Some(Self::ClearSessionLock),
+            Diagnostic::PortInUse(_) if is_server => Some(Self::ChangePort),
+            Diagnostic::EulaNotAccepted if is_server => Some(Self::AcceptEula),
+            Diagnostic::UnsupportedJavaVersion { required } => {
+                let version = match required {
+                    // The oldest one that's new enough
+                    Some(required) => JavaVersion::ALL
+                        .iter()
+                        .find(|n| **n as usize >= *required)?,
+                    None => JavaVersion::ALL.last()?,
+                };
+                Some(Self::UseJava(*version as usize))
+            }
+            Diagnostic::OutOfMemory => Some(Self::IncreaseMemory),
+            _ => None,
+        }
+    }
+
+    /// Button text for the fix.
+    #[must_use]
+    pub fn label(&self) -> String {
+        match self {
+            DiagnosticFix::ClearSessionLock => "Remove world lock".to_owned(),
+            DiagnosticFix::ChangePort => "Use a free port".to_owned(),
+            DiagnosticFix::AcceptEula => "Accept EULA".to_owned(),
+            DiagnosticFix::UseJava(n) => format!("Switch to Java {n}"),
+            DiagnosticFix::IncreaseMemory => "Double memory allocation".to_owned(),
+        }
+    }
+
+    /// Applies the fix, returning a description of what changed.
+    ///
+    /// # Errors
+    /// - [`DiagnosticFix::ClearSessionLock`]: a process is still using the server
+    /// - [`DiagnosticFix::ChangePort`]: no free port was found
+    /// - Files (`server.properties`, `config.json`, ...)
+    ///   couldn't be read or written
+    pub async fn apply(&self, instance: &Instance) -> Result<String, ServerError> {
+        let dir = instance.get_instance_path();
+        let msg = match self {
+            DiagnosticFix::ClearSessionLock => {
+                let count = clear_session_locks(&dir).await?;
+                format!("Removed {count} world lock(s)")
+            }
+            DiagnosticFix::ChangePort => {
+                let port = change_to_free_port(&dir).await?;
+                format!("Changed server port to {port}")
+            }
+            DiagnosticFix::AcceptEula => {
+                accept_eula(&dir).await?;
+                "Accepted the Minecraft EULA".to_owned()
+            }
+            DiagnosticFix::UseJava(version) => {
+                let mut config = InstanceConfigJson::read(instance).await?;
+                config.java_override_version = Some(*version);
+                config.save(instance).await?;
+                format!("Switched to Java {version}")
+            }
+            DiagnosticFix::IncreaseMemory => {
+                let mut config = InstanceConfigJson::read(instance).await?;
+                config.ram_in_mb *= 2;
+                config.save(instance).await?;
+                format!("Increased memory allocation to {} MB", config.ram_in_mb)
+            }
+        };
+        info!("{msg}");
+        Ok(msg)
+    }
+}
+
+/// Deletes `session.lock` from the worlds of a server,
+/// returning how many were deleted.
+///
+/// The lock is only stale if the server crashed, so this
+/// first checks that no process is running in (or was
+/// started with) the server directory.
+///
+/// # Errors
+/// - A process is using the server
+/// - The locks couldn't be deleted
+pub async fn clear_session_locks(server_dir: &Path) -> Result<usize, ServerError> {
+    if let Some((pid, name)) = find_process_using(server_dir) {
+        return Err(ServerError::ServerInUse(pid, name));
+    }
+
+    let mut count = 0;
+    let mut entries = tokio::fs::read_dir(server_dir).await.dir(server_dir)?;
+    while let Some(entry) = entries.next_entry().await.dir(server_dir)? {
+        let lock = entry.path().join("session.lock");
+        if lock.is_file() {
+            tokio::fs::remove_file(&lock).await.path(lock)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// The process running a server, if its main world
+/// is locked (`session.lock`) and a process is using the server.
+/// Without such a process, the lock is left over from a crash.
+///
+/// # Errors
+/// If `server.properties` couldn't be read.
+pub(crate) async fn world_lock_holder(
+    server_dir: &Path,
+) -> Result<Option<(u32, String)>, ServerError> {
+    let properties = ServerProperties::load_from_dir(server_dir)
+        .await?
+        .unwrap_or_default();
+    let world = properties.get("level-name").unwrap_or("world");
+    if !server_dir.join(world).join("session.lock").is_file() {
+        return Ok(None);
+    }
+    Ok(find_process_using(server_dir))
+}
+
+/// A process (other than the launcher) whose working directory
+/// is in `dir`, or whose arguments mention it, as `(pid, name)`.
+fn find_process_using(dir: &Path) -> Option<(u32, String)> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_owned());
+
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_cwd(UpdateKind::Always)
+            .with_cmd(UpdateKind::Always),
+    );
+    let current = sysinfo::get_current_pid().ok();
+
+    system
+        .processes()
+        .iter()
+        .filter(|(pid, _)| Some(**pid) != current)
+        .find(|(_, process)| {
+            process.cwd().is_some_and(|n| n.starts_with(&dir))
+                || process.cmd().iter().any(|n| mentions_dir(n, &dir))
+        })
+        .map(|(pid, process)| (pid.as_u32(), process.name().to_string_lossy().into_owned()))
+}
+
+/// Whether a command line argument is a path in `dir`,
+/// also as the value of an option (`-Dkey=<path>`).
+fn mentions_dir(arg: &OsStr, dir: &Path) -> bool {
+    let arg = arg.to_string_lossy();
+    let path = arg.rsplit_once('=').map_or(arg.as_ref(), |(_, n)| n);
+    Path::new(path).starts_with(dir)
+}
+
+/// Changes `server-port` in `server.properties` to
+/// the next free port, returning it.
+///
+/// # Errors
+/// - No free port was found
+/// - `server.properties` couldn't be read or written
+pub async fn change_to_free_port(server_dir: &Path) -> Result<u16, ServerError> {
+    let name = server_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let reserved = ReservedPorts::collect(ports_in_use().await?, &name);
+    move_server_port(server_dir, &reserved).await
+}
+
+async fn move_server_port(server_dir: &Path, reserved: &ReservedPorts) -> Result<u16, ServerError> {
+    let mut properties = ServerProperties::load_from_dir(server_dir)
+        .await?
+        .unwrap_or_default();
+    let current = ServerPorts::from_properties(&properties);
+    let bind_ip = bind_ip(&properties);
+    let old = current.server;
+
+    let port = next_free_port(old, |n| {
+        !reserved.tcp.contains_key(&n)
+            && current.rcon != Some(n)
+            && is_free(Protocol::Tcp, n, bind_ip)
+    })?;
+
+    // The query port follows the server port by default
+    if current.query == Some(old) {
+        properties.set("query.port", port.to_string());
+    }
+    properties.set("server-port", port.to_string());
+    properties.save_to_dir(server_dir).await?;
+    Ok(port)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn fixes_for_diagnostics() {
+        let server = Instance::server("test");
+        let client = Instance::client("test");
+        let fix = DiagnosticFix::for_diagnostic;
+
+        assert_eq!(
+            fix(&Diagnostic::SessionLocked, &server),
+            Some(DiagnosticFix::ClearSessionLock)
+        );
+        assert_eq!(
+            fix(&Diagnostic::PortInUse(Some(25565)), &server),
+            Some(DiagnosticFix::ChangePort)
+        );
+        assert_eq!(
+            fix(&Diagnostic::EulaNotAccepted, &server),
+            Some(DiagnosticFix::AcceptEula)
+        );
+        // Server only
+        assert_eq!(fix(&Diagnostic::SessionLocked, &client), None);
+        assert_eq!(fix(&Diagnostic::PortInUse(None), &client), None);
+
+        let java = |required| Diagnostic::UnsupportedJavaVersion { required };
+        assert_eq!(
+            fix(&java(Some(17)), &client),
+            Some(DiagnosticFix::UseJava(17))
+        );
+        assert_eq!(
+            fix(&java(Some(18)), &server),
+            Some(DiagnosticFix::UseJava(21))
+        );
+        assert_eq!(fix(&java(None), &server), Some(DiagnosticFix::UseJava(25)));
+        assert_eq!(fix(&java(Some(99)), &server), None);
+
+        assert_eq!(
+            fix(&Diagnostic::OutOfMemory, &client),
+            Some(DiagnosticFix::IncreaseMemory)
+        );
+        assert_eq!(fix(&Diagnostic::XrandrNotInstalled, &client), None);
+    }
+
+    #[test]
+    fn dir_in_arguments() {
+        let dir = Path::new("/launcher/servers/foo");
+        let mentions = |arg: &str| mentions_dir(OsStr::new(arg), dir);
+        assert!(mentions("/launcher/servers/foo"));
+        assert!(mentions("/launcher/servers/foo/server.jar"));
+        assert!(mentions(
+            "-Dlog4j.configurationFile=/launcher/servers/foo/log4j2.xml"
+        ));
+        // Another server with the same start
+        assert!(!mentions("/launcher/servers/foobar/server.jar"));
+        assert!(!mentions("-Dpath=/launcher/servers/foobar"));
+        assert!(!mentions("nogui"));
+    }
+
+    #[tokio::test]
+    async fn free_port() {
+        // Taken by "another program"
+        let listener = TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let taken = listener.local_addr().unwrap().port();
+
+        let dir = std::env::temp_dir().join("ql-diagnostics-test-port");
+        _ = tokio::fs::remove_dir_all(&dir).await;
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let rcon = taken + 1;
+        let mut reserved = ReservedPorts::default();
+        reserved.tcp.insert(taken + 2, "other".to_owned());
+        ServerProperties::parse(&format!(
+            "server-port={taken}\nenable-query=true\nquery.port={taken}\nenable-rcon=true\nrcon.port={rcon}\n"
+        ))
+        .save_to_dir(&dir)
+        .await
+        .unwrap();
+
+        let port = move_server_port(&dir, &reserved).await.unwrap();
+        assert!(
+            port > taken + 2,
+            "{port} should skip {taken}, RCON's {rcon} and another server's"
+        );
+
+        let properties = ServerProperties::load_from_dir(&dir)
+            .await
+            .unwrap()
+            .unwrap();
+        let port = port.to_string();
+        assert_eq!(properties.get("server-port"), Some(port.as_str()));
+        // Follows the server port
+        assert_eq!(properties.get("query.port"), Some(port.as_str()));
+        assert_eq!(properties.get("rcon.port"), Some(rcon.to_string().as_str()));
+
+        drop(listener);
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
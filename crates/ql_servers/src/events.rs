@@ -0,0 +1,494 @@
+//! Typed events (players joining, chat, lag, etc.)
+//! parsed from the server's log output, and a live
+//! view of the server ([`ServerMonitor`]) built from them.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::mpsc::{Receiver, Sender},
+    time::{Duration, Instant},
+};
+
+use ql_core::read_log::LogLine;
+
+/// Lag warnings older than this don't count
+/// towards [`ServerHealth::Lagging`]
+const LAG_WINDOW: Duration = Duration::from_secs(5 * 60);
+/// Below this TPS (out of 20) the server is lagging
+const LAGGING_TPS: f32 = 18.0;
+
+/// The start of death messages, after the player name
+/// (`Steve was slain by Zombie`).
+const DEATH_MESSAGES: &[&str] = &[
+    "was slain by",
+    "was shot by",
+    "was killed",
+    "was blown up by",
+    "blew up",
+    "was fireballed by",
+    "was pummeled by",
+    "was impaled",
+    "was skewered by",
+    "was stung to death",
+    "was poked to death",
+    "was pricked to death",
+    "was squashed by",
+    "was squished",
+    "was struck by lightning",
+    "was obliterated by",
+    "was frozen to death",
+    "was roasted in dragon's breath",
+    "was doomed to fall",
+    "was burned to a crisp",
+    "was burnt to a crisp",
+    "was smashed by",
+    "drowned",
+    "died",
+    "experienced kinetic energy",
+    "hit the ground too hard",
+    "fell ",
+    "went up in flames",
+    "went off with a bang",
+    "walked into",
+    "burned to death",
+    "tried to swim in lava",
+    "discovered the floor was lava",
+    "suffocated in a wall",
+    "starved to death",
+    "withered away",
+    "froze to death",
+    "left the confines of this world",
+    "didn't want to live in the same world as",
+];
+
+const ADVANCEMENTS: &[(&str, AdvancementKind)] = &[
+    (" has made the advancement [", AdvancementKind::Task),
+    (" has reached the goal [", AdvancementKind::Goal),
+    (" has completed the challenge [", AdvancementKind::Challenge),
+];
+
+/// Something that happened on the server,
+/// parsed from a line of its log.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerEvent {
+    /// Finished starting up (`Done (4.2s)!`)
+    Started {
+        elapsed: Duration,
+    },
+    Stopping,
+    /// `uuid` and `ip` are taken from the lines before
+    /// (not logged on all servers)
+    Joined {
+        name: String,
+        uuid: Option<String>,
+        ip: Option<String>,
+    },
+    Left {
+        name: String,
+    },
+    Chat {
+        name: String,
+        message: String,
+    },
+    /// `message` is the whole death message
+    Death {
+        name: String,
+        message: String,
+    },
+    Advancement {
+        name: String,
+        title: String,
+        kind: AdvancementKind,
+    },
+    /// `Can't keep up! Is the server overloaded?`
+    Lagging {
+        behind: Duration,
+        ticks: u64,
+    },
+    /// Output of `/tps` (Paper, Spigot) or
+    /// `/forge tps`, out of 20
+    Tps(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdvancementKind {
+    Task,
+    Goal,
+    Challenge,
+}
+
+/// Turns log lines into [`ServerEvent`]s.
+///
+/// Keeps some state between lines, as the UUID
+/// and IP of a joining player are logged separately
+/// from the join itself.
+#[derive(Debug, Default)]
+pub struct ServerEventParser {
+    uuids: HashMap<String, String>,
+    ips: HashMap<String, String>,
+    /// For telling deaths apart from other messages
+    online: Vec<String>,
+}
+
+impl ServerEventParser {
+    /// Parses one line of server output
+    /// (with or without the `[12:00:00] [Server thread/INFO]: ` part).
+    pub fn parse(&mut self, line: &str) -> Option<ServerEvent> {
+        let message = strip_prefix(line);
+
+        // First, as players can chat anything below
+        // (eg: `<Steve> Alex joined the game`)
+        if let Some(rest) = message
+            .strip_prefix("[Not Secure] ")
+            .unwrap_or(message)
+            .strip_prefix('<')
+        {
+            let (name, chat) = rest.split_once("> ")?;
+            return Some(ServerEvent::Chat {
+                name: name.to_owned(),
+                message: chat.to_owned(),
+            });
+        }
+        if let Some(rest) = message.strip_prefix("UUID of player ") {
+            let (name, uuid) = rest.split_once(" is ")?;
+            self.uuids.insert(name.to_owned(), uuid.trim().to_owned());
+            return None;
+        }
+        if let Some((name, ip)) = parse_login(message) {
+            self.ips.insert(name.to_owned(), ip.to_owned());
+            return None;
+        }
+        if let Some((name, _)) = message.split_once(" joined the game") {
+            if !is_player_name(name) {
+                return None;
+            }
+            self.online.push(name.to_owned());
+            return Some(ServerEvent::Joined {
+                name: name.to_owned(),
+                uuid: self.uuids.remove(name),
+                ip: self.ips.remove(name),
+            });
+        }
+        if let Some(name) = message.strip_suffix(" left the game") {
+            if !is_player_name(name) {
+                return None;
+            }
+            self.online.retain(|n| n != name);
+            return Some(ServerEvent::Left {
+                name: name.to_owned(),
+            });
+        }
+        for (pattern, kind) in ADVANCEMENTS {
+            if let Some((name, title)) = message.split_once(pattern) {
+                return Some(ServerEvent::Advancement {
+                    name: name.to_owned(),
+                    title: title.trim_end_matches(']').to_owned(),
+                    kind: *kind,
+                });
+            }
+        }
+        if let Some(rest) =
+            message.strip_prefix("Can't keep up! Is the server overloaded? Running ")
+        {
+            let (ms, ticks) = rest.split_once("ms or ")?;
+            return Some(ServerEvent::Lagging {
+                behind: Duration::from_millis(ms.parse().ok()?),
+                ticks: ticks.split(' ').next()?.parse().ok()?,
+            });
+        }
+        if let Some(rest) = message.strip_prefix("Done (") {
+            let (seconds, _) = rest.split_once("s)!")?;
+            let seconds: f64 = seconds.replace(',', ".").parse().ok()?;
+            return Some(ServerEvent::Started {
+                elapsed: Duration::from_secs_f64(seconds),
+            });
+        }
+        if message == "Stopping server" || message.starts_with("Shutting down the proxy") {
+            self.online.clear();
+            return Some(ServerEvent::Stopping);
+        }
+        if let Some(tps) = parse_tps(message) {
+            return Some(ServerEvent::Tps(tps));
+        }
+        self.parse_death(message)
+    }
+
+    fn parse_death(&self, message: &str) -> Option<ServerEvent> {
+        let (name, rest) = message.split_once(' ')?;
+        (self.online.iter().any(|n| n == name)
+            && DEATH_MESSAGES.iter().any(|n| rest.starts_with(n)))
+        .then(|| ServerEvent::Death {
+            name: name.to_owned(),
+            message: message.to_owned(),
+        })
+    }
+}
+
+/// The message, without the time, thread and level
+fn strip_prefix(line: &str) -> &str {
+    let line = line.trim_end();
+    if line.starts_with('[') {
+        if let Some((_, message)) = line.split_once("]: ") {
+            return message;
+        }
+    }
+    line
+}
+
+/// `Steve[/127.0.0.1:54321] logged in with entity id 42 at (...)`
+fn parse_login(message: &str) -> Option<(&str, &str)> {
+    let (player, _) = message.split_once(" logged in with entity id ")?;
+    let (name, address) = player.split_once("[/")?;
+    let address = address.strip_suffix(']')?;
+    let ip = address.rsplit_once(':').map_or(address, |(ip, _port)| ip);
+    Some((name, ip))
+}
+
+/// Paper: `TPS from last 1m, 5m, 15m: 20.0, 19.97, 20.0`
+/// (the last minute is used)
+///
+/// Forge: `Overall: Mean tick time: 2.134 ms. Mean TPS: 20.000`
+fn parse_tps(message: &str) -> Option<f32> {
+    let value = if let Some(rest) = message.strip_prefix("TPS from last 1m, 5m, 15m: ") {
+        rest.split(',').next()?
+    } else if message.starts_with("Overall:") {
+        message.split_once("Mean TPS: ")?.1
+    } else {
+        return None;
+    };
+    // Paper marks values above 20 with `*`
+    value.trim().trim_start_matches('*').parse().ok()
+}
+
+/// 1 to 16 letters, digits or underscores
+fn is_player_name(name: &str) -> bool {
+    // Bedrock players (through Geyser/Floodgate) get a `.` prefix
+    let name = name.strip_prefix('.').unwrap_or(name);
+    (1..=16).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parses the server's log lines into [`ServerEvent`]s
+/// on the side, while passing them on to `sender`.
+///
+/// Returns the sender to give to
+/// [`ql_core::LaunchedProcess::read_logs`] instead,
+/// and the receiver for the events.
+#[must_use]
+pub fn log_events(sender: Sender<LogLine>) -> (Sender<LogLine>, Receiver<ServerEvent>) {
+    let (log_sender, log_receiver) = std::sync::mpsc::channel::<LogLine>();
+    let (event_sender, event_receiver) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || {
+        let mut parser = ServerEventParser::default();
+        for line in log_receiver {
+            if let Some(event) = parser.parse(&line.to_string()) {
+                // The events may not be needed
+                _ = event_sender.send(event);
+            }
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    (log_sender, event_receiver)
+}
+
+#[derive(Debug, Clone)]
+pub struct OnlinePlayer {
+    pub uuid: Option<String>,
+    pub ip: Option<String>,
+    pub joined: Instant,
+}
+
+/// How the server is doing, see [`ServerMonitor::health`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServerHealth {
+    Starting,
+    Healthy,
+    /// Lag warnings in the last 5 minutes
+    /// or low TPS
+    Lagging {
+        warnings: usize,
+        worst: Duration,
+    },
+    Stopping,
+}
+
+impl std::fmt::Display for ServerHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerHealth::Starting => write!(f, "Starting"),
+            ServerHealth::Healthy => write!(f, "Healthy"),
+            ServerHealth::Lagging { warnings: 0, .. } => write!(f, "Lagging (low TPS)"),
+            ServerHealth::Lagging { warnings, worst } => write!(
+                f,
+                "Lagging ({warnings} warnings, up to {} ms behind)",
+                worst.as_millis()
+            ),
+            ServerHealth::Stopping => write!(f, "Stopping"),
+        }
+    }
+}
+
+/// A live view of a running server,
+/// from its [`ServerEvent`]s.
+#[derive(Debug, Clone, Default)]
+pub struct ServerMonitor {
+    /// By name
+    pub players: BTreeMap<String, OnlinePlayer>,
+    /// How long the server took to start
+    pub startup_time: Option<Duration>,
+    /// Last reported TPS, if `/tps` was used
+    pub tps: Option<f32>,
+    pub deaths: usize,
+    pub chat_messages: usize,
+    is_stopping: bool,
+    lag_warnings: VecDeque<(Instant, Duration)>,
+}
+
+impl ServerMonitor {
+    pub fn apply(&mut self, event: &ServerEvent) {
+        match event {
+            ServerEvent::Started { elapsed } => self.startup_time = Some(*elapsed),
+            ServerEvent::Stopping => {
+                self.is_stopping = true;
+                self.players.clear();
+            }
+            ServerEvent::Joined { name, uuid, ip } => {
+                self.players.insert(
+                    name.clone(),
+                    OnlinePlayer {
+                        uuid: uuid.clone(),
+                        ip: ip.clone(),
+                        joined: Instant::now(),
+                    },
+                );
+            }
+            ServerEvent::Left { name } => {
+                self.players.remove(name);
+            }
+            ServerEvent::Chat { .. } => self.chat_messages += 1,
+            ServerEvent::Death { .. } => self.deaths += 1,
+            ServerEvent::Advancement { .. } => {}
+            ServerEvent::Lagging { behind, .. } => {
+                self.lag_warnings.push_back((Instant::now(), *behind));
+            }
+            ServerEvent::Tps(tps) => self.tps = Some(*tps),
+        }
+        while self
+            .lag_warnings
+            .front()
+            .is_some_and(|(time, _)| time.elapsed() > LAG_WINDOW)
+        {
+            self.lag_warnings.pop_front();
+        }
+    }
+
+    #[must_use]
+    pub fn health(&self) -> ServerHealth {
+        if self.is_stopping {
+            return ServerHealth::Stopping;
+        }
+        if self.startup_time.is_none() {
+            return ServerHealth::Starting;
+        }
+        let recent: Vec<Duration> = self
+            .lag_warnings
+            .iter()
+            .filter(|(time, _)| time.elapsed() <= LAG_WINDOW)
+            .map(|(_, behind)| *behind)
+            .collect();
+        if !recent.is_empty() || self.tps.is_some_and(|n| n < LAGGING_TPS) {
+            ServerHealth::Lagging {
+                warnings: recent.len(),
+                worst: recent.into_iter().max().unwrap_or_default(),
+            }
+        } else {
+            ServerHealth::Healthy
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_events() {
+        let mut parser = ServerEventParser::default();
+        let lines = [
+            "[12:00:03] [Server thread/INFO]: Done (4.521s)! For help, type \"help\"",
+            "[12:00:10] [User Authenticator #1/INFO]: UUID of player Steve is 8667ba71-b85a-4004-af54-457a9734eed7",
+            "[12:00:10] [Server thread/INFO]: Steve[/127.0.0.1:51234] logged in with entity id 42 at (0.5, 64.0, 0.5)",
+            "[12:00:10] [Server thread/INFO]: Steve joined the game",
+            "[12:00:20 INFO]: [Not Secure] <Steve> hi all",
+            "[12:00:25 INFO]: <Steve> Notch joined the game",
+            "[12:00:26 INFO]: [Server] Notch joined the game",
+            "[12:00:30] [Server thread/INFO]: Steve has made the advancement [Stone Age]",
+            "[12:00:40] [Server thread/INFO]: Steve was slain by Zombie",
+            "[12:00:45] [Server thread/INFO]: Alex fell from a high place",
+            "[12:00:50] [Server thread/WARN]: Can't keep up! Is the server overloaded? Running 2034ms or 40 ticks behind",
+            "[12:00:55 INFO]: TPS from last 1m, 5m, 15m: *20.0, 19.5, 19.9",
+            "[12:01:00] [Server thread/INFO]: Steve left the game",
+        ];
+        let events: Vec<ServerEvent> = lines.iter().filter_map(|n| parser.parse(n)).collect();
+        assert_eq!(
+            events,
+            [
+                ServerEvent::Started {
+                    elapsed: Duration::from_secs_f64(4.521)
+                },
+                ServerEvent::Joined {
+                    name: "Steve".to_owned(),
+                    uuid: Some("8667ba71-b85a-4004-af54-457a9734eed7".to_owned()),
+                    ip: Some("127.0.0.1".to_owned()),
+                },
+                ServerEvent::Chat {
+                    name: "Steve".to_owned(),
+                    message: "hi all".to_owned()
+                },
+                ServerEvent::Chat {
+                    name: "Steve".to_owned(),
+                    message: "Notch joined the game".to_owned()
+                },
+                ServerEvent::Advancement {
+                    name: "Steve".to_owned(),
+                    title: "Stone Age".to_owned(),
+                    kind: AdvancementKind::Task
+                },
+                ServerEvent::Death {
+                    name: "Steve".to_owned(),
+                    message: "Steve was slain by Zombie".to_owned()
+                },
+                // Alex isn't online
+                ServerEvent::Lagging {
+                    behind: Duration::from_millis(2034),
+                    ticks: 40
+                },
+                ServerEvent::Tps(20.0),
+                ServerEvent::Left {
+                    name: "Steve".to_owned()
+                },
+            ]
+        );
+
+        let mut monitor = ServerMonitor::default();
+        assert_eq!(monitor.health(), ServerHealth::Starting);
+        for event in &events[..2] {
+            monitor.apply(event);
+        }
+        assert!(monitor.players.contains_key("Steve"));
+        assert_eq!(monitor.health(), ServerHealth::Healthy);
+        for event in &events[2..] {
+            monitor.apply(event);
+        }
+        assert!(monitor.players.is_empty());
+        assert!(is_player_name(".Bedrock_Player"));
+        assert!(!is_player_name("[Server] Notch"));
+        assert!(!is_player_name("ThisNameIsWayTooLong"));
+        assert!(matches!(
+            monitor.health(),
+            ServerHealth::Lagging { warnings: 1, .. }
+        ));
+    }
+}
@@ -0,0 +1,177 @@
+//! Creating servers to play a client instance or modpack on,
+//! with the same game version, loader and mods
+//! (leaving out the client-only ones).
+
+use std::{collections::HashSet, sync::mpsc::Sender};
+
+use ql_core::{
+    DownloadProgress, Instance, ListEntry, Loader, file_utils, info,
+    json::{InstanceConfigJson, VersionDetails},
+    pt,
+};
+use ql_mod_manager::{
+    loaders::{self, LoaderInstallResult},
+    store::{CurseforgeNotAllowed, ModSide},
+};
+
+use crate::{ServerError, create_server};
+
+/// Folders copied from the client, besides `mods`
+const CONFIG_DIRS: &[&str] = &["config", "defaultconfigs"];
+
+/// What [`create_server_from_instance`] and
+/// [`create_server_from_modpack`] did.
+#[derive(Debug, Clone)]
+pub struct CreatedServer {
+    /// Name of the new server (sanitized)
+    pub name: String,
+    /// Client-only mods that were left out
+    /// (or disabled, for modpacks)
+    pub skipped_mods: Vec<(String, ModSide)>,
+    /// Modpack files blocked by CurseForge,
+    /// that have to be downloaded manually
+    pub not_allowed: HashSet<CurseforgeNotAllowed>,
+}
+
+/// Creates a server with the same Minecraft version and
+/// mod loader as the `client` instance, and copies over
+/// its mods (except client-only ones) and configs.
+///
+/// See [`ql_mod_manager::store::mod_sides`] for how
+/// client-only mods are found.
+///
+/// If anything fails after the server was created,
+/// it's deleted again.
+///
+/// # Errors
+/// - The client instance couldn't be read
+/// - The server couldn't be created (see [`create_server`])
+/// - The loader couldn't be installed on the server
+/// - The mods or configs couldn't be copied
+pub async fn create_server_from_instance(
+    client: &Instance,
+    name: String,
+    sender: Option<&Sender<DownloadProgress>>,
+) -> Result<CreatedServer, ServerError> {
+    let config = InstanceConfigJson::read(client).await?;
+    let details = VersionDetails::load(client).await?;
+    // Client-only, there's nothing to install on the server
+    let loader = if config.mod_type == Loader::OptiFine {
+        Loader::Vanilla
+    } else {
+        config.mod_type
+    };
+    let loader_version = config.mod_type_info.and_then(|n| n.version);
+
+    let name = create_server(name, ListEntry::new(details.get_id().to_owned()), sender).await?;
+    let server = Instance::server(&name);
+    info!("Setting up {name} like {}", client.name);
+    let result = set_up_like_client(client, &server, loader, loader_version).await;
+    if result.is_err() {
+        server.remove_unfinished().await;
+    }
+    let skipped_mods = result?;
+
+    pt!("Finished");
+    Ok(CreatedServer {
+        name,
+        skipped_mods,
+        not_allowed: HashSet::new(),
+    })
+}
+
+/// Returns the mods that were left out.
+async fn set_up_like_client(
+    client: &Instance,
+    server: &Instance,
+    loader: Loader,
+    loader_version: Option<String>,
+) -> Result<Vec<(String, ModSide)>, ServerError> {
+    install_loader(server, loader, loader_version).await?;
+
+    let report = ql_mod_manager::store::copy_mods(client, server).await?;
+
+    let client_dir = client.get_dot_minecraft_path();
+    let server_dir = server.get_dot_minecraft_path();
+    for dir in CONFIG_DIRS {
+        let path = client_dir.join(dir);
+        if file_utils::exists(&path).await {
+            pt!("Copying {dir}");
+            file_utils::copy_dir_recursive(&path, &server_dir.join(dir)).await?;
+        }
+    }
+    Ok(report.skipped)
+}
+
+/// Creates a server for a Curseforge or Modrinth modpack,
+/// with the Minecraft version and loader it needs,
+/// and installs it there.
+///
+/// Files that Modrinth packs mark as client-only aren't
+/// downloaded, and its `server-overrides` are used. Any other
+/// client-only mods (see [`ql_mod_manager::store::mod_sides`])
+/// are disabled afterwards.
+///
+/// If anything fails after the server was created,
+/// it's deleted again.
+///
+/// # Errors
+/// - The modpack is invalid
+/// - The server couldn't be created (see [`create_server`])
+/// - The loader or modpack couldn't be installed
+pub async fn create_server_from_modpack(
+    name: String,
+    file: Vec<u8>,
+    sender: Option<&Sender<DownloadProgress>>,
+) -> Result<CreatedServer, ServerError> {
+    let pack = ql_mod_manager::store::read_pack_info(&file)?;
+    info!(
+        "Creating server for modpack {} ({} {}, Minecraft {})",
+        pack.name,
+        pack.loader,
+        pack.loader_version.as_deref().unwrap_or("latest"),
+        pack.minecraft
+    );
+
+    let name = create_server(name, ListEntry::new(pack.minecraft), sender).await?;
+    let server = Instance::server(&name);
+    let result = async {
+        install_loader(&server, pack.loader, pack.loader_version).await?;
+        let not_allowed = ql_mod_manager::store::install_modpack(file, None, server.clone(), None)
+            .await?
+            .unwrap_or_default();
+        let skipped_mods = ql_mod_manager::store::disable_unneeded_mods(&server).await?;
+        Ok::<_, ServerError>((not_allowed, skipped_mods))
+    }
+    .await;
+    if result.is_err() {
+        server.remove_unfinished().await;
+    }
+    let (not_allowed, skipped_mods) = result?;
+
+    pt!("Finished");
+    Ok(CreatedServer {
+        name,
+        skipped_mods,
+        not_allowed,
+    })
+}
+
+async fn install_loader(
+    server: &Instance,
+    loader: Loader,
+    version: Option<String>,
+) -> Result<(), ServerError> {
+    if loader.is_vanilla() {
+        return Ok(());
+    }
+    match loaders::install_specified_loader(server.clone(), loader, None, version)
+        .await
+        .map_err(ServerError::LoaderInstall)?
+    {
+        LoaderInstallResult::Ok => Ok(()),
+        LoaderInstallResult::NeedsOptifine | LoaderInstallResult::Unsupported => {
+            Err(ServerError::UnsupportedLoader(loader))
+        }
+    }
+}
@@ -0,0 +1,563 @@
+//! Importing servers that weren't made by the launcher
+//! (eg: set up by hand), by working out what they run.
+
+use std::{
+    cmp::Ordering,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use ql_core::{
+    InstanceKind, IntoIoError, LAUNCHER_DIR, Loader, file_utils, find_forge_shim_file, info,
+    json::{
+        InstanceConfigJson, Manifest, VersionDetails,
+        instance_config::{ModTypeInfo, VersionInfo},
+    },
+    pt, sanitize_instance_name,
+};
+use serde::Deserialize;
+
+use crate::{ServerError, daemon::STATE_FILE};
+
+/// Plugin servers and proxies, by the start of their jar name.
+/// The launcher names them `<name>_server.jar`,
+/// but downloads are usually like `paper-1.21.1-123.jar`.
+const JAR_PREFIXES: &[(&str, Loader)] = &[
+    ("paper", Loader::Paper),
+    ("purpur", Loader::Purpur),
+    ("folia", Loader::Folia),
+    ("spigot", Loader::Spigot),
+    ("craftbukkit", Loader::Bukkit),
+    ("velocity", Loader::Velocity),
+    ("bungeecord", Loader::BungeeCord),
+    ("waterfall", Loader::BungeeCord),
+];
+
+/// What runs in a server folder, see [`detect_server`].
+#[derive(Debug, Clone)]
+pub struct DetectedServer {
+    pub minecraft: String,
+    pub loader: Loader,
+    pub loader_version: Option<String>,
+    /// Server jar to run, if it isn't
+    /// where the launcher would put it
+    pub jar: Option<String>,
+}
+
+/// How [`import_server`] brings a server folder into the launcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Copy it into the launcher's `servers` folder
+    Copy,
+    /// Leave it where it is, and link to it from
+    /// the `servers` folder. Deleting the server in
+    /// the launcher only removes the link.
+    Adopt,
+}
+
+/// Works out the Minecraft version and server software
+/// (vanilla, Fabric, Quilt, Forge, NeoForge, Paper and the like)
+/// of a server folder, along with the loader version
+/// if it can be found.
+///
+/// Proxies (Velocity, BungeeCord) work with any Minecraft version,
+/// so if none is found, they get the latest release
+/// (only used to pick Java).
+///
+/// # Errors
+/// - The folder couldn't be read
+/// - There's no server jar in it
+/// - The Minecraft version couldn't be found
+/// - The version list couldn't be downloaded (for proxies)
+pub async fn detect_server(dir: &Path) -> Result<DetectedServer, ServerError> {
+    let mut jars = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await.path(dir)?;
+    while let Some(entry) = entries.next_entry().await.path(dir)? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.ends_with(".jar") && entry.path().is_file() {
+            jars.push(name);
+        }
+    }
+    jars.sort();
+    if jars.is_empty() && !dir.join("libraries").is_dir() {
+        return Err(ServerError::NotAServerDir(dir.to_owned()));
+    }
+
+    let (loader, loader_version, jar) = detect_software(dir, &jars).await;
+    let minecraft = match detect_minecraft_version(dir, &jars).await {
+        Some(version) => version,
+        None if loader.is_proxy() => Manifest::download()
+            .await?
+            .get_latest_release()
+            .map(|n| n.id.clone())
+            .ok_or_else(|| ServerError::UnknownServerVersion(dir.to_owned()))?,
+        None => return Err(ServerError::UnknownServerVersion(dir.to_owned())),
+    };
+
+    Ok(DetectedServer {
+        minecraft,
+        loader,
+        loader_version,
+        jar,
+    })
+}
+
+async fn detect_software(dir: &Path, jars: &[String]) -> (Loader, Option<String>, Option<String>) {
+    let has = |name: &str| jars.iter().any(|n| n == name);
+
+    // Through `unix_args.txt`/`win_args.txt`
+    if let Some(version) = newest_subdir(&dir.join("libraries/net/neoforged/neoforge")).await {
+        return (Loader::NeoForge, Some(version), None);
+    }
+
+    if let Some(shim) = find_forge_shim_file(dir).await {
+        let name = shim.file_name().unwrap_or_default().to_string_lossy();
+        let version = name
+            .strip_suffix("-shim.jar")
+            .and_then(|n| n.split_once('-'))
+            .and_then(|(_, n)| n.split_once('-'))
+            .map(|(_, forge)| forge.to_owned());
+        return (Loader::Forge, version, None);
+    }
+    if let Some(dir_name) = newest_subdir(&dir.join("libraries/net/minecraftforge/forge")).await {
+        // `<MC>-<FORGE>`
+        let version = dir_name.split_once('-').map(|(_, n)| n.to_owned());
+        // Older Forge servers run a jar in the main folder,
+        // newer ones an args file in the libraries
+        let jar = jars
+            .iter()
+            .find(|n| n.starts_with("forge-") && !n.contains("installer"))
+            .cloned();
+        return (Loader::Forge, version, jar);
+    }
+
+    for (launch_jar, loader_dir, loader) in [
+        (
+            "fabric-server-launch.jar",
+            "libraries/net/fabricmc/fabric-loader",
+            Loader::Fabric,
+        ),
+        (
+            "quilt-server-launch.jar",
+            "libraries/org/quiltmc/quilt-loader",
+            Loader::Quilt,
+        ),
+    ] {
+        if has(launch_jar) {
+            return (loader, newest_subdir(&dir.join(loader_dir)).await, None);
+        }
+    }
+    // Fabric's own server launcher:
+    // `fabric-server-mc.1.21.1-loader.0.16.5-launcher.1.0.1.jar`
+    if let Some(jar) = jars.iter().find(|n| n.starts_with("fabric-server-mc.")) {
+        let version = jar
+            .split_once("-loader.")
+            .and_then(|(_, n)| n.split_once("-launcher."))
+            .map(|(n, _)| n.to_owned());
+        return (Loader::Fabric, version, Some(jar.clone()));
+    }
+
+    for (prefix, loader) in JAR_PREFIXES {
+        let Some(jar) = jars.iter().find(|n| n.to_lowercase().starts_with(prefix)) else {
+            continue;
+        };
+        let version = read_version_history(dir).await.map(|(_, build)| build);
+        let is_launcher_name = jar.ends_with("_server.jar");
+        return (*loader, version, (!is_launcher_name).then(|| jar.clone()));
+    }
+
+    let jar = if has("server.jar") {
+        None
+    } else {
+        // eg: `minecraft_server.1.12.2.jar`
+        jars.iter()
+            .find(|n| n.starts_with("minecraft_server"))
+            .or(jars.first())
+            .cloned()
+    };
+    (Loader::Vanilla, None, jar)
+}
+
+async fn detect_minecraft_version(dir: &Path, jars: &[String]) -> Option<String> {
+    // 1.14+ server jars have it inside
+    let mut jars_to_check: Vec<&String> = jars.iter().filter(|n| *n == "server.jar").collect();
+    jars_to_check.extend(jars.iter().filter(|n| *n != "server.jar"));
+    for jar in jars_to_check {
+        if let Ok(bytes) = tokio::fs::read(dir.join(jar)).await {
+            if let Some(version) = version_from_jar(&bytes) {
+                return Some(version);
+            }
+        }
+    }
+
+    // Paper and the like, after running once
+    if let Some((minecraft, _)) = read_version_history(dir).await {
+        return Some(minecraft);
+    }
+    // `versions/<MC>/` (1.18+ bundler) and
+    // `libraries/net/minecraft/server/<MC>-<MCP>/` (modern Forge)
+    for versions_dir in ["versions", "libraries/net/minecraft/server"] {
+        if let Some(version) = newest_subdir(&dir.join(versions_dir)).await {
+            return Some(version.split('-').next().unwrap_or(&version).to_owned());
+        }
+    }
+    if let Some(version) = newest_subdir(&dir.join("libraries/net/minecraftforge/forge")).await {
+        return version.split_once('-').map(|(n, _)| n.to_owned());
+    }
+    // From the jar names
+    jars.iter().find_map(|jar| {
+        let name = jar.strip_suffix(".jar")?;
+        if let Some(rest) = name.strip_prefix("fabric-server-mc.") {
+            rest.split_once("-loader.").map(|(n, _)| n.to_owned())
+        } else if let Some(version) = name.strip_prefix("minecraft_server.") {
+            Some(version.to_owned())
+        } else {
+            let (_, rest) = name.split_once('-')?;
+            let version = rest.split('-').next()?;
+            version.starts_with("1.").then(|| version.to_owned())
+        }
+    })
+}
+
+/// `id` in `version.json` inside the server jar
+fn version_from_jar(jar: &[u8]) -> Option<String> {
+    #[derive(Deserialize)]
+    struct VersionJson {
+        id: String,
+    }
+
+    let mut zip = zip::ZipArchive::new(Cursor::new(jar)).ok()?;
+    let mut file = zip.by_name("version.json").ok()?;
+    let mut text = String::new();
+    file.read_to_string(&mut text).ok()?;
+    let json: VersionJson = serde_json::from_str(&text).ok()?;
+    Some(json.id)
+}
+
+/// Minecraft version and build from `version_history.json`
+/// of Paper-like servers (`git-Paper-123 (MC: 1.21.1)`)
+async fn read_version_history(dir: &Path) -> Option<(String, String)> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct VersionHistory {
+        current_version: String,
+    }
+
+    let text = tokio::fs::read_to_string(dir.join("version_history.json"))
+        .await
+        .ok()?;
+    let history: VersionHistory = serde_json::from_str(&text).ok()?;
+    parse_version_history(&history.current_version)
+}
+
+fn parse_version_history(current: &str) -> Option<(String, String)> {
+    let (build, minecraft) = current.split_once(" (MC: ")?;
+    let minecraft = minecraft.strip_suffix(')')?;
+    let build = build.rsplit('-').next()?;
+    Some((minecraft.to_owned(), build.to_owned()))
+}
+
+/// Name of the subfolder with the newest version
+/// (see [`compare_versions`])
+async fn newest_subdir(dir: &Path) -> Option<String> {
+    let mut entries = tokio::fs::read_dir(dir).await.ok()?;
+    let mut newest: Option<String> = None;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if !entry.path().is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if newest
+            .as_ref()
+            .is_none_or(|n| compare_versions(n, &name) == Ordering::Less)
+        {
+            newest = Some(name);
+        }
+    }
+    newest
+}
+
+/// Compares numbers in versions by value, the rest as text,
+/// so `0.16.10` is newer than `0.16.9`.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn parts(version: &str) -> Vec<Result<u64, &str>> {
+        let mut parts = Vec::new();
+        let mut rest = version;
+        while let Some(first) = rest.chars().next() {
+            let is_digit = first.is_ascii_digit();
+            let end = rest
+                .find(|c: char| c.is_ascii_digit() != is_digit)
+                .unwrap_or(rest.len());
+            let (part, next) = rest.split_at(end);
+            parts.push(if is_digit {
+                part.parse().map_err(|_| part)
+            } else {
+                Err(part)
+            });
+            rest = next;
+        }
+        parts
+    }
+    // Numbers before text
+    parts(a).cmp(&parts(b))
+}
+
+/// Imports an existing server folder (eg: one set up by hand)
+/// as a launcher server named `name`, detecting what it runs
+/// with [`detect_server`] and writing the launcher's
+/// `config.json` and `details.json` for it.
+///
+/// Folders that are already launcher servers are imported as-is.
+///
+/// `eula.txt` is left alone: if it doesn't accept the EULA yet,
+/// starting the server fails until [`crate::accept_eula`] is called.
+///
+/// Returns the (sanitized) name of the server
+/// and what was detected.
+///
+/// # Errors
+/// - The name is invalid or taken
+/// - The server couldn't be detected (see [`detect_server`])
+/// - The version details couldn't be downloaded
+/// - The folder couldn't be copied, or linked
+///   (on Windows, this needs Developer Mode or admin rights)
+/// - The folder has a `config.json` that isn't the launcher's
+pub async fn import_server(
+    dir: &Path,
+    name: String,
+    mode: ImportMode,
+) -> Result<(String, DetectedServer), ServerError> {
+    let name = sanitize_instance_name(name);
+    if name.is_empty() {
+        return Err(ServerError::InvalidName);
+    }
+    let server_dir = LAUNCHER_DIR.join("servers").join(&name);
+    if file_utils::exists(&server_dir).await {
+        return Err(ServerError::ServerAlreadyExists);
+    }
+    let dir = tokio::fs::canonicalize(dir).await.path(dir)?;
+    info!("Importing server from {dir:?} as {name}");
+
+    let existing = InstanceConfigJson::read_from_dir(&dir).await.ok();
+    let details = VersionDetails::load_from_path(&dir).await.ok();
+    let (detected, details) = if let (Some(config), Some(details)) = (existing, details) {
+        pt!("Already set up for the launcher");
+        let detected = DetectedServer {
+            minecraft: details.get_id().to_owned(),
+            loader: config.mod_type,
+            loader_version: config.mod_type_info.and_then(|n| n.version),
+            jar: config.server_jar,
+        };
+        (detected, None)
+    } else {
+        if file_utils::exists(dir.join("config.json")).await {
+            return Err(ServerError::ImportConfigExists(dir));
+        }
+        let detected = detect_server(&dir).await?;
+        pt!(
+            "Found {} {} (Minecraft {})",
+            detected.loader,
+            detected.loader_version.as_deref().unwrap_or_default(),
+            detected.minecraft
+        );
+        let details = download_details(&detected.minecraft).await?;
+        (detected, Some(details))
+    };
+
+    let servers_dir = LAUNCHER_DIR.join("servers");
+    tokio::fs::create_dir_all(&servers_dir)
+        .await
+        .path(&servers_dir)?;
+    match mode {
+        ImportMode::Copy => {
+            pt!("Copying files");
+            // If it's a launcher server that's running,
+            // the copy must not be mistaken for it
+            let exceptions = [dir.join(STATE_FILE)];
+            file_utils::copy_dir_recursive_ext(&dir, &server_dir, &exceptions).await?;
+        }
+        ImportMode::Adopt => {
+            pt!("Linking {server_dir:?} to {dir:?}");
+            file_utils::create_symlink(&dir, &server_dir)?;
+        }
+    }
+    // Into the copy, the original stays untouched
+    if let Some(details) = details {
+        write_launcher_files(&server_dir, &detected, &details).await?;
+    }
+
+    pt!("Finished");
+    Ok((name, detected))
+}
+
+async fn download_details(minecraft: &str) -> Result<VersionDetails, ServerError> {
+    pt!("Downloading version details");
+    let manifest = Manifest::download().await?;
+    let version = manifest
+        .find_name(minecraft)
+        .ok_or_else(|| ServerError::VersionNotFoundInManifest(minecraft.to_owned()))?;
+    Ok(file_utils::download_file_to_json(&version.url, false).await?)
+}
+
+async fn write_launcher_files(
+    dir: &Path,
+    detected: &DetectedServer,
+    details: &VersionDetails,
+) -> Result<(), ServerError> {
+    details.save_to_dir(dir).await?;
+
+    let mut config =
+        InstanceConfigJson::new(InstanceKind::Server, false, VersionInfo::new(&details.id));
+    config.mod_type = detected.loader;
+    config.mod_type_info = detected
+        .loader_version
+        .clone()
+        .map(ModTypeInfo::new_regular);
+    config.server_jar.clone_from(&detected.jar);
+    config.save_to_dir(dir).await?;
+
+    let mods_dir = dir.join("mods");
+    let is_modded = matches!(
+        detected.loader,
+        Loader::Fabric | Loader::Quilt | Loader::Forge | Loader::NeoForge
+    );
+    if is_modded && !mods_dir.is_dir() {
+        tokio::fs::create_dir(&mods_dir).await.path(mods_dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// A server folder with these (empty) files and folders
+    async fn server_dir(name: &str, files: &[&str], dirs: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ql-import-test-{name}"));
+        _ = tokio::fs::remove_dir_all(&dir).await;
+        for sub in dirs {
+            tokio::fs::create_dir_all(dir.join(sub)).await.unwrap();
+        }
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        for file in files {
+            tokio::fs::write(dir.join(file), "").await.unwrap();
+        }
+        dir
+    }
+
+    async fn detect(
+        name: &str,
+        files: &[&str],
+        dirs: &[&str],
+    ) -> (Loader, Option<String>, Option<String>) {
+        let dir = server_dir(name, files, dirs).await;
+        let files: Vec<String> = files.iter().map(|n| (*n).to_owned()).collect();
+        let detected = detect_software(&dir, &files).await;
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+        detected
+    }
+
+    #[tokio::test]
+    async fn software() {
+        assert_eq!(
+            detect("vanilla", &["server.jar"], &[]).await,
+            (Loader::Vanilla, None, None)
+        );
+        assert_eq!(
+            detect("old-vanilla", &["minecraft_server.1.12.2.jar"], &[]).await,
+            (
+                Loader::Vanilla,
+                None,
+                Some("minecraft_server.1.12.2.jar".to_owned())
+            )
+        );
+        assert_eq!(
+            detect(
+                "fabric",
+                &["fabric-server-launch.jar", "server.jar"],
+                &[
+                    "libraries/net/fabricmc/fabric-loader/0.16.9",
+                    "libraries/net/fabricmc/fabric-loader/0.16.10"
+                ]
+            )
+            .await,
+            (Loader::Fabric, Some("0.16.10".to_owned()), None)
+        );
+        assert_eq!(
+            detect(
+                "neoforge",
+                &[],
+                &["libraries/net/neoforged/neoforge/21.1.77"]
+            )
+            .await,
+            (Loader::NeoForge, Some("21.1.77".to_owned()), None)
+        );
+        assert_eq!(
+            detect("paper", &["paper-1.21.1-123.jar"], &[]).await,
+            (Loader::Paper, None, Some("paper-1.21.1-123.jar".to_owned()))
+        );
+        assert_eq!(
+            detect("velocity", &["velocity_server.jar"], &[]).await,
+            (Loader::Velocity, None, None)
+        );
+    }
+
+    #[tokio::test]
+    async fn imported_jar_survives_config_roundtrip() {
+        let dir = server_dir("jar-roundtrip", &["paper-1.21.1-123.jar"], &[]).await;
+        let detected = DetectedServer {
+            minecraft: "1.21.1".to_owned(),
+            loader: Loader::Paper,
+            loader_version: None,
+            jar: Some("paper-1.21.1-123.jar".to_owned()),
+        };
+        let mut details = VersionDetails::default();
+        details.id = "1.21.1".to_owned();
+        write_launcher_files(&dir, &detected, &details)
+            .await
+            .unwrap();
+
+        // Like the Edit tab saving it
+        let config = InstanceConfigJson::read_from_dir(&dir).await.unwrap();
+        // Custom jars live in the launcher's `custom_jars` folder,
+        // the Edit tab would clear this
+        assert!(config.custom_jar.is_none());
+        config.save_to_dir(&dir).await.unwrap();
+
+        let jar = crate::run::ServerLauncher::from_dir(dir.clone())
+            .await
+            .unwrap()
+            .get_server_jar()
+            .await
+            .unwrap();
+        assert_eq!(jar, dir.join("paper-1.21.1-123.jar"));
+        assert!(jar.is_file());
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(compare_versions("0.16.10", "0.16.9"), Ordering::Greater);
+        assert_eq!(
+            compare_versions("1.20.1-47.3.0", "1.20.1-47.10.0"),
+            Ordering::Less
+        );
+        assert_eq!(compare_versions("1.21", "1.21.1"), Ordering::Less);
+        assert_eq!(compare_versions("21.1.77", "21.1.77"), Ordering::Equal);
+    }
+
+    #[test]
+    fn version_history() {
+        assert_eq!(
+            parse_version_history("git-Paper-123 (MC: 1.21.1)"),
+            Some(("1.21.1".to_owned(), "123".to_owned()))
+        );
+        assert_eq!(
+            parse_version_history("git-Purpur-2300 (MC: 1.21.3)"),
+            Some(("1.21.3".to_owned(), "2300".to_owned()))
+        );
+        assert_eq!(parse_version_history("1.21.1"), None);
+    }
+}
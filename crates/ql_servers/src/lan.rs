@@ -0,0 +1,80 @@
+//! Announcing servers on the local network, the same way
+//! singleplayer worlds opened to LAN are, so they show up
+//! at the bottom of the multiplayer screen of nearby players.
+
+use std::{
+    net::Ipv4Addr,
+    sync::{Arc, Weak},
+    time::Duration,
+};
+
+use ql_core::{err, info};
+use tokio::{net::UdpSocket, process::Child, sync::Mutex};
+
+/// Where Minecraft clients listen for LAN worlds
+const LAN_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 2, 60);
+const LAN_PORT: u16 = 4445;
+/// Same as singleplayer
+const INTERVAL: Duration = Duration::from_millis(1500);
+
+/// Announces a server on the LAN in the background,
+/// until its process exits.
+pub(crate) fn broadcast_lan(child: &Arc<Mutex<Child>>, motd: String, port: u16) {
+    let child = Arc::downgrade(child);
+    tokio::spawn(async move {
+        let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await {
+            Ok(n) => n,
+            Err(error) => {
+                err!("Couldn't announce server on LAN: {error}");
+                return;
+            }
+        };
+        info!("Announcing server on LAN (port {port})");
+        let message = lan_message(&motd, port);
+        while !has_exited(&child) {
+            if let Err(error) = socket
+                .send_to(message.as_bytes(), (LAN_GROUP, LAN_PORT))
+                .await
+            {
+                err!("Couldn't announce server on LAN: {error}");
+                return;
+            }
+            tokio::time::sleep(INTERVAL).await;
+        }
+    });
+}
+
+fn has_exited(child: &Weak<Mutex<Child>>) -> bool {
+    let Some(child) = child.upgrade() else {
+        return true;
+    };
+    // If it's locked, it's being used (so still running)
+    child
+        .try_lock()
+        .is_ok_and(|mut n| n.try_wait().map_or(true, |status| status.is_some()))
+}
+
+/// Clients show the MOTD, and connect to
+/// the port at the address it came from.
+fn lan_message(motd: &str, port: u16) -> String {
+    // Brackets would end the MOTD early
+    let motd = motd.replace(['[', ']'], "");
+    format!("[MOTD]{motd}[/MOTD][AD]{port}[/AD]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message() {
+        assert_eq!(
+            lan_message("A Minecraft Server", 25565),
+            "[MOTD]A Minecraft Server[/MOTD][AD]25565[/AD]"
+        );
+        assert_eq!(
+            lan_message("[Survival] world", 25570),
+            "[MOTD]Survival world[/MOTD][AD]25570[/AD]"
+        );
+    }
+}
@@ -8,16 +8,58 @@
 
 use std::path::PathBuf;
 
-use ql_core::{IoError, JsonError, RequestError, impl_3_errs_jri};
+use ql_core::{IoError, JsonError, Loader, RequestError, impl_3_errs_jri};
 use ql_java_handler::JavaInstallError;
 
+mod backup;
+mod console;
 mod create;
+mod daemon;
+mod diagnostics;
+mod events;
+mod from_client;
+mod import;
+mod lan;
+#[cfg(target_os = "linux")]
+mod limits;
+mod network;
+mod ping;
+mod players;
+mod ports;
+mod rcon;
 mod run;
 mod server_properties;
+mod watchdog;
 // mod ssh;
-pub use create::{create_server, delete_server};
+pub use backup::{
+    BackupConfig, BackupReason, FileInfo, RestoreTarget, RetentionPolicy, Snapshot,
+    apply_retention, backup_scheduler, backups_dir, create_snapshot, delete_snapshot,
+    list_snapshots, restore_snapshot,
+};
+pub use console::{CommandHistory, VANILLA_COMMANDS, command_suggestions, complete_command};
+pub use create::{accept_eula, create_server, delete_server, is_eula_accepted};
+pub use daemon::{
+    ConsoleReader, ConsoleWriter, DaemonClient, DaemonError, DaemonState, DaemonStatus,
+    console_log_path, run_daemon,
+};
+pub use diagnostics::{DiagnosticFix, change_to_free_port, clear_session_locks};
+pub use events::{
+    AdvancementKind, OnlinePlayer, ServerEvent, ServerEventParser, ServerHealth, ServerMonitor,
+    log_events,
+};
+pub use from_client::{CreatedServer, create_server_from_instance, create_server_from_modpack};
+pub use import::{DetectedServer, ImportMode, detect_server, import_server};
+pub use network::{ForwardingMode, NetworkConfig, apply_network, stop_network};
+pub use ping::{PingError, ServerStatus, ping, ping_local};
+pub use players::{
+    BanEntry, IpBanEntry, ListChange, OpEntry, PlayerLists, WhitelistEntry, apply_list_change,
+    offline_uuid, resolve_player, set_op_level,
+};
+pub use ports::{PortChange, PortConflict, ServerPorts, assign_ports, ports_in_use};
+pub use rcon::{RconClient, RconError, connect_rcon, send_console_command, setup_rcon};
 pub use run::run;
-pub use server_properties::ServerProperties;
+pub use server_properties::{PropertyInfo, PropertyKind, ServerProperties, known_properties};
+pub use watchdog::{RestartEvent, RestartKind, Watchdog, WatchdogConfig, restart_history};
 // pub use ssh::run_tunnel;
 
 use thiserror::Error;
@@ -50,6 +92,60 @@ pub enum ServerError {
     NoForgeShimFound,
     #[error("{SERVER_ERR_PREFIX}couldn't convert PathBuf to str: {0:?}")]
     PathBufToStr(PathBuf),
+    #[error("{0}")]
+    Rcon(#[from] RconError),
+    #[error("{0}")]
+    Ping(#[from] PingError),
+    #[error("{0}")]
+    Daemon(#[from] DaemonError),
+    #[error("{SERVER_ERR_PREFIX}{0}")]
+    Console(#[from] ql_core::ConsoleError),
+    #[error("{SERVER_ERR_PREFIX}backup task failed: {0}")]
+    BackupTask(String),
+    #[error("a client world backup can't be restored as a server")]
+    RestoreClientToServer,
+    #[error("the server is still in use by process {1} (PID {0})\nclose it first")]
+    ServerInUse(u32, String),
+    #[error("no free port found after {0}")]
+    NoFreePort(u16),
+    #[error(
+        "port {0} is already in use\n(is the server already running, or is another program using it?)"
+    )]
+    PortInUse(u16),
+    #[error("{0} isn't a proxy\ninstall Velocity or BungeeCord on it first")]
+    NotAProxy(String),
+    #[error("{0} is a proxy, so it can't be a backend server of a network")]
+    BackendIsProxy(String),
+    #[error("{SERVER_ERR_PREFIX}couldn't parse proxy config:\n{0}")]
+    ProxyConfig(String),
+    #[error("invalid player name: {0:?}\n(1-16 letters, numbers or underscores)")]
+    InvalidPlayerName(String),
+    #[error("invalid IP address: {0:?}")]
+    InvalidIp(String),
+    #[error("player {0:?} doesn't exist (checked with Mojang, as the server is in online mode)")]
+    UnknownPlayer(String),
+    #[error("{SERVER_ERR_PREFIX}couldn't install loader:\n{0}")]
+    LoaderInstall(String),
+    #[error("{0} can't be installed on servers")]
+    UnsupportedLoader(Loader),
+    #[error("{0}")]
+    Mod(#[from] ql_mod_manager::store::ModError),
+    #[error("{0}")]
+    Pack(#[from] ql_mod_manager::store::PackError),
+    #[error(
+        "the Minecraft EULA hasn't been accepted for {0}\nread it at https://aka.ms/MinecraftEULA and accept it to run the server"
+    )]
+    EulaNotAccepted(String),
+    #[error("no server jar found in {0:?}")]
+    NotAServerDir(PathBuf),
+    #[error("couldn't find the Minecraft version of the server in {0:?}")]
+    UnknownServerVersion(PathBuf),
+    #[error("{0:?} has a config.json that isn't from this launcher\nmove or rename it first")]
+    ImportConfigExists(PathBuf),
+    #[error(
+        "backends {0} and {1} have the same name in the proxy config ({2})\nrename one of them"
+    )]
+    DuplicateBackendName(String, String, String),
 }
 
 impl_3_errs_jri!(ServerError, Json, Request, Io);
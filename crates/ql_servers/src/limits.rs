@@ -0,0 +1,358 @@
+//! Per-server resource limits on Linux
+//! (see [`ResourceLimits`]), for hosting
+//! several servers on one machine.
+//!
+//! They're applied by running the server through a chain of commands:
+//! - `sh`, moving itself into the server's cgroup v2 group
+//!   (CPU quota, memory ceiling)
+//! - `nice` and `ionice` (CPU and disk priority)
+//! - `bwrap` (bubblewrap), leaving only the server folder writable
+//!
+//! These `exec` the next one (keeping the PID), except for `bwrap`:
+//! it stays around as the parent of the server, so with it the launcher
+//! only sees `bwrap`'s PID. The server is killed along with it.
+//!
+//! Whatever isn't available is skipped with a warning.
+
+use std::path::{Path, PathBuf};
+
+use ql_core::{
+    IntoIoError, IoError, err,
+    file_utils::exists,
+    info,
+    json::instance_config::{IoClass, ResourceLimits},
+};
+use thiserror::Error;
+
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+/// `cpu.max` period, in microseconds (the kernel default)
+const CPU_PERIOD: u64 = 100_000;
+
+/// Commands to run `java` through, so the server stays
+/// within `limits`. Empty if there's nothing to apply.
+pub async fn limits_prefix(
+    name: &str,
+    dir: &Path,
+    limits: &ResourceLimits,
+    ram_in_mb: usize,
+) -> Vec<String> {
+    let mut prefix = Vec::new();
+
+    if let Some(memory_mb) = limits.memory_mb {
+        if memory_mb <= ram_in_mb {
+            err!(
+                "Memory limit of {name} ({memory_mb} MB) isn't above its allocated RAM ({ram_in_mb} MB),\nthe server will likely get killed when it fills up"
+            );
+        }
+    }
+
+    if limits.has_cgroup_limits() {
+        match setup_cgroup(name, limits).await {
+            Ok(group) => {
+                info!("Limiting {name} with cgroup {group:?}");
+                prefix.extend(cgroup_args(&group));
+            }
+            Err(error) => err!(
+                "Couldn't limit CPU/memory of {name}, running without those limits: {error}\n(cgroups might not be delegated to your user, see `systemd-run --user`)"
+            ),
+        }
+    }
+
+    if let Some(nice) = limits.nice {
+        if let Some(program) = find_program("nice") {
+            prefix.extend(nice_args(program, nice));
+        } else {
+            err!("`nice` isn't installed, running {name} with normal priority");
+        }
+    }
+
+    if limits.io_class.is_some() || limits.io_priority.is_some() {
+        if let Some(program) = find_program("ionice") {
+            prefix.extend(ionice_args(program, limits.io_class, limits.io_priority));
+        } else {
+            err!("`ionice` isn't installed, running {name} with normal disk priority");
+        }
+    }
+
+    if limits.restrict_dir {
+        match find_program("bwrap") {
+            Some(program) if bwrap_works(&program).await => {
+                prefix.push(program);
+                prefix.extend(sandbox_args(dir).await);
+            }
+            Some(_) => err!(
+                "bubblewrap can't run here (are user namespaces disabled?),\nnot restricting {name} to its folder"
+            ),
+            None => {
+                err!("`bwrap` (bubblewrap) isn't installed, not restricting {name} to its folder")
+            }
+        }
+    }
+
+    prefix
+}
+
+/// Moves itself into `group`, then runs the rest
+fn cgroup_args(group: &Path) -> Vec<String> {
+    vec![
+        "sh".to_owned(),
+        "-c".to_owned(),
+        r#"echo $$ > "$0" && exec "$@""#.to_owned(),
+        group.join("cgroup.procs").to_string_lossy().into_owned(),
+    ]
+}
+
+fn nice_args(program: String, nice: i8) -> Vec<String> {
+    vec![program, "-n".to_owned(), nice.clamp(-20, 19).to_string()]
+}
+
+fn ionice_args(program: String, class: Option<IoClass>, priority: Option<u8>) -> Vec<String> {
+    let class = class.unwrap_or(IoClass::BestEffort);
+    // -t: still run the server if the priority can't be set
+    let mut args = vec![program, "-t".to_owned(), "-c".to_owned()];
+    args.push(
+        match class {
+            IoClass::Realtime => "1",
+            IoClass::BestEffort => "2",
+            IoClass::Idle => "3",
+        }
+        .to_owned(),
+    );
+    if let (Some(priority), false) = (priority, class == IoClass::Idle) {
+        args.extend(["-n".to_owned(), priority.min(7).to_string()]);
+    }
+    args
+}
+
+#[derive(Debug, Error)]
+enum CgroupError {
+    #[error("cgroup v2 isn't mounted at {CGROUP_ROOT}")]
+    NotV2,
+    #[error("couldn't find the launcher's cgroup in /proc/self/cgroup")]
+    NoOwnGroup,
+    #[error("the {0} controller isn't available to the launcher's cgroup")]
+    NoController(&'static str),
+    #[error("other processes share the launcher's cgroup")]
+    Busy,
+    #[error("{0}")]
+    Io(#[from] IoError),
+}
+
+/// Sets up `<launcher's cgroup>/ql-server-<NAME>`
+/// with the CPU and memory limits, returning its path.
+///
+/// cgroup v2 doesn't allow processes in a group that
+/// hands out controllers to its children, so if needed
+/// the launcher first moves itself into a `launcher` child.
+async fn setup_cgroup(name: &str, limits: &ResourceLimits) -> Result<PathBuf, CgroupError> {
+    if !exists(Path::new(CGROUP_ROOT).join("cgroup.controllers")).await {
+        return Err(CgroupError::NotV2);
+    }
+
+    let own = read("/proc/self/cgroup").await?;
+    let base = cgroup_base(&own).ok_or(CgroupError::NoOwnGroup)?;
+
+    let available = read(base.join("cgroup.controllers")).await?;
+    let mut needed = Vec::new();
+    if limits.cpu_percent.is_some() {
+        needed.push("cpu");
+    }
+    if limits.memory_mb.is_some() {
+        needed.push("memory");
+    }
+    if let Some(missing) = needed
+        .iter()
+        .find(|n| !available.split_whitespace().any(|c| c == **n))
+    {
+        return Err(CgroupError::NoController(missing));
+    }
+
+    let subtree_control = base.join("cgroup.subtree_control");
+    let enable = needed
+        .iter()
+        .map(|n| format!("+{n}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if tokio::fs::write(&subtree_control, &enable).await.is_err() {
+        let leaf = base.join("launcher");
+        create_dir(&leaf).await?;
+        write(leaf.join("cgroup.procs"), std::process::id().to_string()).await?;
+        tokio::fs::write(&subtree_control, &enable)
+            .await
+            .map_err(|_| CgroupError::Busy)?;
+    }
+
+    let group_name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let group = base.join(format!("ql-server-{group_name}"));
+    create_dir(&group).await?;
+
+    // Limits removed since the last run are reset
+    let cpu_max = group.join("cpu.max");
+    if let Some(percent) = limits.cpu_percent {
+        let quota = u64::from(percent.max(1)) * CPU_PERIOD / 100;
+        write(cpu_max, format!("{quota} {CPU_PERIOD}")).await?;
+    } else if exists(&cpu_max).await {
+        write(cpu_max, format!("max {CPU_PERIOD}")).await?;
+    }
+    let memory_max = group.join("memory.max");
+    if let Some(memory_mb) = limits.memory_mb {
+        write(memory_max, (memory_mb * 1024 * 1024).to_string()).await?;
+    } else if exists(&memory_max).await {
+        write(memory_max, "max".to_owned()).await?;
+    }
+
+    Ok(group)
+}
+
+/// The group to create server groups in, from `/proc/self/cgroup`.
+///
+/// That's the launcher's own group, or its parent if the
+/// launcher already moved into its `launcher` child before
+/// (otherwise every launch would nest one level deeper).
+fn cgroup_base(proc_self_cgroup: &str) -> Option<PathBuf> {
+    let own = proc_self_cgroup
+        .lines()
+        .find_map(|n| n.strip_prefix("0::"))?
+        .trim()
+        .trim_start_matches('/');
+    let own = own
+        .strip_suffix("/launcher")
+        .or_else(|| own.strip_prefix("launcher").filter(|n| n.is_empty()))
+        .unwrap_or(own);
+    Some(Path::new(CGROUP_ROOT).join(own))
+}
+
+/// `bwrap` arguments to only leave `dir` (and a
+/// private `/tmp`) writable, ending with `--`.
+async fn sandbox_args(dir: &Path) -> Vec<String> {
+    // Adopted servers are symlinks to the real folder
+    let real_dir = tokio::fs::canonicalize(dir)
+        .await
+        .unwrap_or_else(|_| dir.to_owned());
+    let real_dir = real_dir.to_string_lossy().into_owned();
+
+    [
+        "--ro-bind",
+        "/",
+        "/",
+        "--dev",
+        "/dev",
+        "--proc",
+        "/proc",
+        "--tmpfs",
+        "/tmp",
+        "--bind",
+    ]
+    .into_iter()
+    .map(str::to_owned)
+    .chain([real_dir.clone(), real_dir])
+    .chain(["--chdir".to_owned(), dir.to_string_lossy().into_owned()])
+    .chain(["--die-with-parent", "--"].map(str::to_owned))
+    .collect()
+}
+
+/// bubblewrap needs unprivileged user namespaces,
+/// which some distros turn off.
+async fn bwrap_works(program: &str) -> bool {
+    tokio::process::Command::new(program)
+        .args(["--ro-bind", "/", "/", "--", "true"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await
+        .is_ok_and(|n| n.success())
+}
+
+fn find_program(name: &str) -> Option<String> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|n| n.join(name))
+        .find(|n| n.is_file())
+        .map(|n| n.to_string_lossy().into_owned())
+}
+
+async fn read(path: impl AsRef<Path>) -> Result<String, IoError> {
+    let path = path.as_ref();
+    tokio::fs::read_to_string(path).await.path(path)
+}
+
+async fn write(path: PathBuf, contents: String) -> Result<(), IoError> {
+    tokio::fs::write(&path, contents).await.path(path)
+}
+
+async fn create_dir(path: &Path) -> Result<(), IoError> {
+    if exists(path).await {
+        return Ok(());
+    }
+    tokio::fs::create_dir(path).await.path(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cgroup_base_skips_launcher_leaf() {
+        let root = Path::new(CGROUP_ROOT);
+        let base = root.join("user.slice/user-1000.slice/app.slice/ql.scope");
+        assert_eq!(
+            cgroup_base("0::/user.slice/user-1000.slice/app.slice/ql.scope\n"),
+            Some(base.clone())
+        );
+        assert_eq!(
+            cgroup_base("0::/user.slice/user-1000.slice/app.slice/ql.scope/launcher\n"),
+            Some(base)
+        );
+        assert_eq!(cgroup_base("0::/launcher\n"), Some(root.to_owned()));
+        // cgroup v1 only
+        assert_eq!(cgroup_base("1:name=systemd:/user.slice\n"), None);
+    }
+
+    #[test]
+    fn priority_args() {
+        assert_eq!(nice_args("nice".to_owned(), -128), ["nice", "-n", "-20"]);
+        assert_eq!(
+            ionice_args("ionice".to_owned(), None, Some(9)),
+            ["ionice", "-t", "-c", "2", "-n", "7"]
+        );
+        // Idle has no priorities
+        assert_eq!(
+            ionice_args("ionice".to_owned(), Some(IoClass::Idle), Some(3)),
+            ["ionice", "-t", "-c", "3"]
+        );
+    }
+
+    #[tokio::test]
+    async fn prefix() {
+        let dir = Path::new("/nonexistent/servers/test");
+        assert!(
+            limits_prefix("test", dir, &ResourceLimits::default(), 2048)
+                .await
+                .is_empty()
+        );
+
+        let sandbox = sandbox_args(dir).await;
+        let bind = sandbox.iter().position(|n| n == "--bind").unwrap();
+        assert_eq!(sandbox[bind + 1..bind + 3], [dir.to_str().unwrap(); 2]);
+        assert_eq!(
+            sandbox[sandbox.len() - 4..],
+            ["--chdir", dir.to_str().unwrap(), "--die-with-parent", "--"]
+        );
+
+        // Every part runs the next one, with `java` last
+        let group = Path::new(CGROUP_ROOT).join("ql-server-test");
+        let prefix: Vec<String> = cgroup_args(&group)
+            .into_iter()
+            .chain(nice_args("nice".to_owned(), 5))
+            .chain(std::iter::once("bwrap".to_owned()))
+            .chain(sandbox)
+            .collect();
+        assert_eq!(prefix[3], group.join("cgroup.procs").to_str().unwrap());
+        assert_eq!(prefix[4..7], ["nice", "-n", "5"]);
+        assert_eq!(prefix[7], "bwrap");
+        assert_eq!(prefix.last().unwrap(), "--");
+    }
+}
@@ -0,0 +1,598 @@
+//! Proxy networks: a Velocity or BungeeCord proxy
+//! connecting players to several backend servers.
+//!
+//! A network belongs to its proxy server, and is stored in
+//! `QuantumLauncher/servers/<PROXY>/network.json`.
+//! [`apply_network`] writes the server list and player info
+//! forwarding settings into the configs of the proxy and
+//! every backend, so they can be started together.
+
+use std::{collections::HashSet, fmt::Display, path::Path, time::Duration};
+
+use ql_core::{
+    Instance, IntoIoError, IntoJsonError, LAUNCHER_DIR, LaunchedProcess, Loader, err,
+    file_utils::exists, info, json::InstanceConfigJson, pt,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{ServerError, ServerProperties, ports::next_free_port, rcon::generate_password};
+
+mod yaml;
+
+const NETWORK_FILE: &str = "network.json";
+const VELOCITY_CONFIG: &str = "velocity.toml";
+const VELOCITY_SECRET_FILE: &str = "forwarding.secret";
+const BUNGEECORD_CONFIG: &str = "config.yml";
+/// Velocity and BungeeCord both listen on this by default
+const DEFAULT_PROXY_PORT: u16 = 25577;
+pub(crate) const DEFAULT_SERVER_PORT: u16 = 25565;
+
+/// Written if BungeeCord hasn't generated its config yet
+/// (it fills in the rest when it starts).
+const BUNGEECORD_DEFAULT_CONFIG: &str = "listeners:
+- query_port: 25577
+  motd: '&1Another Bungee server'
+  tab_list: GLOBAL_PING
+  query_enabled: false
+  proxy_protocol: false
+  forced_hosts: {}
+  ping_passthrough: false
+  priorities: []
+  bind_local_address: true
+  host: 0.0.0.0:25577
+  max_players: 1
+  tab_size: 60
+  force_default_server: false
+";
+
+/// How the proxy tells backends who a player is
+/// (name, UUID, IP, skin), since backends run in offline mode.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ForwardingMode {
+    /// Velocity's secure forwarding, checked with a shared secret.
+    /// Needs Paper (or a fork) or FabricProxy-Lite on the backends.
+    #[default]
+    Modern,
+    /// BungeeCord-style forwarding (`bungeecord: true`
+    /// in `spigot.yml`). Not secured: backends must not
+    /// be reachable except through the proxy.
+    Legacy,
+}
+
+impl ForwardingMode {
+    pub const ALL: &[Self] = &[Self::Modern, Self::Legacy];
+}
+
+impl Display for ForwardingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForwardingMode::Modern => write!(f, "Modern (Velocity)"),
+            ForwardingMode::Legacy => write!(f, "Legacy (BungeeCord)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct NetworkConfig {
+    /// Names of the backend servers. Players join
+    /// the first one, or the next if it's down.
+    pub backends: Vec<String>,
+    /// Ignored for BungeeCord, which only supports
+    /// [`ForwardingMode::Legacy`]
+    pub forwarding: ForwardingMode,
+}
+
+impl NetworkConfig {
+    /// Loads the network of a proxy server (empty if not set up).
+    ///
+    /// # Errors
+    /// If the config exists but couldn't be read or parsed.
+    pub async fn load(proxy: &Instance) -> Result<Self, ServerError> {
+        let path = proxy.get_instance_path().join(NETWORK_FILE);
+        if !exists(&path).await {
+            return Ok(Self::default());
+        }
+        let text = tokio::fs::read_to_string(&path).await.path(path)?;
+        Ok(serde_json::from_str(&text).json(text)?)
+    }
+
+    /// # Errors
+    /// If the config couldn't be saved.
+    pub async fn save(&self, proxy: &Instance) -> Result<(), ServerError> {
+        let path = proxy.get_instance_path().join(NETWORK_FILE);
+        let text = serde_json::to_string_pretty(self).json_to()?;
+        tokio::fs::write(&path, text).await.path(path)?;
+        Ok(())
+    }
+
+    /// All servers of the network, in the order
+    /// they should be started (backends, then the proxy).
+    #[must_use]
+    pub fn start_order(&self, proxy: &Instance) -> Vec<Instance> {
+        self.backends
+            .iter()
+            .map(|n| Instance::server(n))
+            .chain(std::iter::once(proxy.clone()))
+            .collect()
+    }
+}
+
+/// The proxy whose network `backend` is part of, if any.
+///
+/// # Errors
+/// If a network config couldn't be read or parsed.
+pub(crate) async fn find_network(backend: &str) -> Result<Option<String>, ServerError> {
+    let Ok(mut entries) = tokio::fs::read_dir(LAUNCHER_DIR.join("servers")).await else {
+        return Ok(None);
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if !exists(&entry.path().join(NETWORK_FILE)).await {
+            continue;
+        }
+        let proxy = Instance::server(&entry.file_name().to_string_lossy());
+        if NetworkConfig::load(&proxy)
+            .await?
+            .backends
+            .iter()
+            .any(|n| n == backend)
+        {
+            return Ok(Some(proxy.get_name().to_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Configures the proxy and all backends of a network:
+///
+/// - Gives every backend its own port (if they clash
+///   with each other or with other servers),
+///   only listening on `127.0.0.1` so players
+///   can't skip the proxy
+/// - Sets `online-mode=false` on backends
+///   (the proxy authenticates players instead)
+/// - Turns on player info forwarding on backends
+///   (`spigot.yml`, `paper-global.yml`, FabricProxy-Lite)
+/// - Writes the server list and forwarding settings
+///   to `velocity.toml` or BungeeCord's `config.yml`
+///
+/// Returns warnings about backends that can't be
+/// fully set up (eg: vanilla servers can't receive
+/// forwarded player info).
+///
+/// # Errors
+/// - `proxy` isn't a proxy, or a backend is one
+/// - Two backends have the same name in the proxy config
+/// - Configs couldn't be read, parsed or written
+pub async fn apply_network(
+    proxy: &Instance,
+    config: &NetworkConfig,
+) -> Result<Vec<String>, ServerError> {
+    let proxy_dir = proxy.get_instance_path();
+    let proxy_loader = InstanceConfigJson::read_from_dir(&proxy_dir)
+        .await?
+        .mod_type;
+    let forwarding = match proxy_loader {
+        Loader::Velocity => config.forwarding,
+        Loader::BungeeCord => ForwardingMode::Legacy,
+        _ => return Err(ServerError::NotAProxy(proxy.get_name().to_owned())),
+    };
+    info!(
+        "Setting up network {} ({} backends)",
+        proxy.get_name(),
+        config.backends.len()
+    );
+
+    let names = proxy_server_names(&config.backends)?;
+
+    let secret = if let Loader::Velocity = proxy_loader {
+        Some(get_or_create_secret(&proxy_dir).await?)
+    } else {
+        None
+    };
+
+    let proxy_port = match proxy_loader {
+        Loader::Velocity => read_velocity_port(&proxy_dir).await?,
+        _ => read_bungeecord_port(&proxy_dir).await?,
+    };
+
+    let mut warnings = Vec::new();
+    // Other servers keep their ports too
+    let mut used_ports: HashSet<u16> = crate::ports_in_use()
+        .await?
+        .into_iter()
+        .filter(|(name, _)| name != proxy.get_name() && !config.backends.contains(name))
+        .flat_map(|(_, ports)| std::iter::once(ports.server).chain(ports.rcon))
+        .collect();
+    used_ports.insert(proxy_port);
+    let mut servers = Vec::new();
+    for (backend, name) in config.backends.iter().zip(names) {
+        let dir = Instance::server(backend).get_instance_path();
+        let loader = InstanceConfigJson::read_from_dir(&dir).await?.mod_type;
+        if loader.is_proxy() {
+            return Err(ServerError::BackendIsProxy(backend.clone()));
+        }
+        pt!("Backend {backend} ({loader})");
+
+        let port = configure_backend_properties(&dir, &mut used_ports).await?;
+        configure_backend_forwarding(
+            backend,
+            &dir,
+            loader,
+            forwarding,
+            secret.as_deref(),
+            &mut warnings,
+        )
+        .await?;
+        servers.push((name, port));
+    }
+
+    match proxy_loader {
+        Loader::Velocity => write_velocity_config(&proxy_dir, &servers, forwarding).await?,
+        _ => write_bungeecord_config(&proxy_dir, &servers).await?,
+    }
+
+    for warning in &warnings {
+        err!("{warning}");
+    }
+    Ok(warnings)
+}
+
+/// Gracefully stops a network: the proxy first
+/// (so players aren't moved around), then the backends.
+///
+/// # Errors
+/// If a server couldn't be killed after not stopping in time.
+pub async fn stop_network(
+    proxy: Option<LaunchedProcess>,
+    backends: Vec<LaunchedProcess>,
+    timeout: Duration,
+) -> Result<(), ServerError> {
+    if let Some(proxy) = proxy {
+        proxy.stop(timeout).await?;
+    }
+    // Backends can save their worlds at the same time
+    let handles: Vec<_> = backends
+        .into_iter()
+        .map(|backend| tokio::spawn(async move { backend.stop(timeout).await }))
+        .collect();
+    for handle in handles {
+        if let Ok(result) = handle.await {
+            result?;
+        }
+    }
+    Ok(())
+}
+
+/// Server names in proxy configs can only have
+/// some characters, unlike launcher server names.
+fn proxy_server_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Names of the backends in the proxy config.
+///
+/// Checked before anything is changed, since backends
+/// like `My Server` and `my-server` would overwrite
+/// each other in the server list.
+fn proxy_server_names(backends: &[String]) -> Result<Vec<String>, ServerError> {
+    let mut names: Vec<String> = Vec::with_capacity(backends.len());
+    for backend in backends {
+        let name = proxy_server_name(backend);
+        if let Some(i) = names.iter().position(|n| *n == name) {
+            return Err(ServerError::DuplicateBackendName(
+                backends[i].clone(),
+                backend.clone(),
+                name,
+            ));
+        }
+        names.push(name);
+    }
+    Ok(names)
+}
+
+/// Sets up `server.properties` of a backend, returning its port.
+async fn configure_backend_properties(
+    dir: &Path,
+    used_ports: &mut HashSet<u16>,
+) -> Result<u16, ServerError> {
+    let mut properties = ServerProperties::load_from_dir(dir)
+        .await?
+        .unwrap_or_default();
+
+    let old_port = properties
+        .get("server-port")
+        .and_then(|n| n.trim().parse().ok())
+        .unwrap_or(DEFAULT_SERVER_PORT);
+    let port = if used_ports.contains(&old_port) {
+        next_free_port(old_port, |n| !used_ports.contains(&n))?
+    } else {
+        old_port
+    };
+    used_ports.insert(port);
+
+    properties.set("server-port", port.to_string());
+    properties.set("server-ip", "127.0.0.1");
+    properties.set("online-mode", "false");
+    properties.save_to_dir(dir).await?;
+    Ok(port)
+}
+
+async fn configure_backend_forwarding(
+    name: &str,
+    dir: &Path,
+    loader: Loader,
+    forwarding: ForwardingMode,
+    secret: Option<&str>,
+    warnings: &mut Vec<String>,
+) -> Result<(), ServerError> {
+    let is_modern = forwarding == ForwardingMode::Modern;
+    match loader {
+        Loader::Paper | Loader::Folia | Loader::Purpur => {
+            edit_yaml(dir, "spigot.yml", &[(
+                &["settings", "bungeecord"],
+                yaml::Value::Scalar(bool_str(!is_modern)),
+            )])
+            .await?;
+
+            // Older Paper versions have all settings in `paper.yml`
+            let (file, path): (&str, &[&str]) = if exists(&dir.join("paper.yml")).await {
+                ("paper.yml", &["settings", "velocity-support"])
+            } else {
+                ("config/paper-global.yml", &["proxies", "velocity"])
+            };
+            let enabled = [path, &["enabled"]].concat();
+            let online_mode = [path, &["online-mode"]].concat();
+            let secret_key = [path, &["secret"]].concat();
+            let secret = format!("'{}'", secret.unwrap_or_default());
+
+            let mut edits = vec![(
+                enabled.as_slice(),
+                yaml::Value::Scalar(bool_str(is_modern)),
+            )];
+            if is_modern {
+                edits.push((online_mode.as_slice(), yaml::Value::Scalar("true")));
+                edits.push((secret_key.as_slice(), yaml::Value::Scalar(&secret)));
+            }
+            edit_yaml(dir, file, &edits).await?;
+        }
+        Loader::Spigot => {
+            edit_yaml(dir, "spigot.yml", &[(
+                &["settings", "bungeecord"],
+                yaml::Value::Scalar("true"),
+            )])
+            .await?;
+            if is_modern {
+                warnings.push(format!(
+                    "{name}: Spigot doesn't support modern forwarding, use Paper or legacy forwarding"
+                ));
+            }
+        }
+        Loader::Fabric | Loader::Quilt if is_modern => {
+            // https://github.com/OKTW-Network/FabricProxy-Lite
+            let config_dir = dir.join("config");
+            tokio::fs::create_dir_all(&config_dir)
+                .await
+                .path(&config_dir)?;
+            let path = config_dir.join("FabricProxy-Lite.toml");
+            let text = format!(
+                "hackOnlineMode = true\nhackEarlySend = false\nhackMessageChain = true\nsecret = \"{}\"\n",
+                secret.unwrap_or_default()
+            );
+            tokio::fs::write(&path, text).await.path(path)?;
+            if !has_mod(dir, "fabricproxy-lite").await {
+                warnings.push(format!(
+                    "{name}: install the FabricProxy-Lite mod for players to keep their skins and UUIDs"
+                ));
+            }
+        }
+        _ => warnings.push(format!(
+            "{name}: {loader} servers can't receive forwarded player info (players will have offline UUIDs and no skins)"
+        )),
+    }
+    Ok(())
+}
+
+fn bool_str(b: bool) -> &'static str {
+    if b { "true" } else { "false" }
+}
+
+async fn has_mod(dir: &Path, name: &str) -> bool {
+    let mods_dir = dir.join("mods");
+    let Ok(mut entries) = tokio::fs::read_dir(&mods_dir).await else {
+        return false;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if entry
+            .file_name()
+            .to_string_lossy()
+            .to_lowercase()
+            .contains(name)
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Applies `edits` to a YAML config (creating it if it doesn't exist).
+async fn edit_yaml(
+    dir: &Path,
+    file: &str,
+    edits: &[(&[&str], yaml::Value<'_>)],
+) -> Result<(), ServerError> {
+    let path = dir.join(file);
+    let mut text = if exists(&path).await {
+        tokio::fs::read_to_string(&path).await.path(&path)?
+    } else {
+        String::new()
+    };
+    for (key, value) in edits {
+        text = yaml::set(&text, key, value);
+    }
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.path(parent)?;
+    }
+    tokio::fs::write(&path, text).await.path(path)?;
+    Ok(())
+}
+
+/// The secret shared by Velocity and backends for modern forwarding.
+async fn get_or_create_secret(proxy_dir: &Path) -> Result<String, ServerError> {
+    let path = proxy_dir.join(VELOCITY_SECRET_FILE);
+    if exists(&path).await {
+        let secret = tokio::fs::read_to_string(&path).await.path(&path)?;
+        let secret = secret.trim();
+        if !secret.is_empty() {
+            return Ok(secret.to_owned());
+        }
+    }
+    let secret = generate_password();
+    tokio::fs::write(&path, &secret).await.path(path)?;
+    Ok(secret)
+}
+
+async fn read_velocity_config(proxy_dir: &Path) -> Result<toml_edit::DocumentMut, ServerError> {
+    let path = proxy_dir.join(VELOCITY_CONFIG);
+    if !exists(&path).await {
+        // Velocity uses defaults for the rest
+        let mut doc = toml_edit::DocumentMut::new();
+        doc["config-version"] = toml_edit::value("2.7");
+        doc["bind"] = toml_edit::value(format!("0.0.0.0:{DEFAULT_PROXY_PORT}"));
+        return Ok(doc);
+    }
+    let text = tokio::fs::read_to_string(&path).await.path(&path)?;
+    text.parse()
+        .map_err(|err: toml_edit::TomlError| ServerError::ProxyConfig(err.to_string()))
+}
+
+pub(crate) async fn read_velocity_port(proxy_dir: &Path) -> Result<u16, ServerError> {
+    let doc = read_velocity_config(proxy_dir).await?;
+    Ok(doc
+        .get("bind")
+        .and_then(|n| n.as_str())
+        .and_then(|n| n.rsplit_once(':'))
+        .and_then(|(_, port)| port.parse().ok())
+        .unwrap_or(DEFAULT_PROXY_PORT))
+}
+
+async fn write_velocity_config(
+    proxy_dir: &Path,
+    servers: &[(String, u16)],
+    forwarding: ForwardingMode,
+) -> Result<(), ServerError> {
+    let mut doc = read_velocity_config(proxy_dir).await?;
+    doc["online-mode"] = toml_edit::value(true);
+    doc["player-info-forwarding-mode"] = toml_edit::value(match forwarding {
+        ForwardingMode::Modern => "modern",
+        ForwardingMode::Legacy => "legacy",
+    });
+    doc["forwarding-secret-file"] = toml_edit::value(VELOCITY_SECRET_FILE);
+
+    let mut table = toml_edit::Table::new();
+    for (name, port) in servers {
+        table[name.as_str()] = toml_edit::value(format!("127.0.0.1:{port}"));
+    }
+    let try_order: toml_edit::Array = servers.iter().map(|(name, _)| name.as_str()).collect();
+    table["try"] = toml_edit::value(try_order);
+    doc["servers"] = toml_edit::Item::Table(table);
+    // These point to servers by name, so old
+    // entries would stop Velocity from starting
+    doc["forced-hosts"] = toml_edit::Item::Table(toml_edit::Table::new());
+
+    let path = proxy_dir.join(VELOCITY_CONFIG);
+    tokio::fs::write(&path, doc.to_string()).await.path(path)?;
+    Ok(())
+}
+
+pub(crate) async fn read_bungeecord_port(proxy_dir: &Path) -> Result<u16, ServerError> {
+    let path = proxy_dir.join(BUNGEECORD_CONFIG);
+    if !exists(&path).await {
+        return Ok(DEFAULT_PROXY_PORT);
+    }
+    let text = tokio::fs::read_to_string(&path).await.path(&path)?;
+    Ok(text
+        .lines()
+        .filter_map(|n| n.trim().strip_prefix("host:"))
+        .find_map(|n| n.trim().rsplit_once(':')?.1.parse().ok())
+        .unwrap_or(DEFAULT_PROXY_PORT))
+}
+
+async fn write_bungeecord_config(
+    proxy_dir: &Path,
+    servers: &[(String, u16)],
+) -> Result<(), ServerError> {
+    let path = proxy_dir.join(BUNGEECORD_CONFIG);
+    let mut text = if exists(&path).await {
+        tokio::fs::read_to_string(&path).await.path(&path)?
+    } else {
+        BUNGEECORD_DEFAULT_CONFIG.to_owned()
+    };
+
+    let server_lines: Vec<String> = servers
+        .iter()
+        .flat_map(|(name, port)| {
+            [
+                format!("{name}:"),
+                format!("  motd: '{name}'"),
+                format!("  address: 127.0.0.1:{port}"),
+                "  restricted: false".to_owned(),
+            ]
+        })
+        .collect();
+    let priorities: Vec<String> = servers
+        .iter()
+        .map(|(name, _)| format!("- {name}"))
+        .collect();
+
+    text = yaml::set(&text, &["online_mode"], &yaml::Value::Scalar("true"));
+    text = yaml::set(&text, &["ip_forward"], &yaml::Value::Scalar("true"));
+    text = yaml::set(&text, &["servers"], &yaml::Value::Block(&server_lines));
+    text = yaml::set(
+        &text,
+        &["listeners", "priorities"],
+        &yaml::Value::Block(&priorities),
+    );
+    // These point to servers by name
+    text = yaml::set(
+        &text,
+        &["listeners", "forced_hosts"],
+        &yaml::Value::Scalar("{}"),
+    );
+
+    tokio::fs::write(&path, text).await.path(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_backend_names() {
+        let backends = ["Lobby".to_owned(), "My Server!".to_owned()];
+        assert_eq!(
+            proxy_server_names(&backends).unwrap(),
+            ["lobby", "my-server-"]
+        );
+
+        let backends = [
+            "lobby".to_owned(),
+            "My Server".to_owned(),
+            "my-server".to_owned(),
+        ];
+        let err = proxy_server_names(&backends).unwrap_err();
+        assert!(matches!(
+            err,
+            ServerError::DuplicateBackendName(a, b, name)
+                if a == "My Server" && b == "my-server" && name == "my-server"
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+//! Minimal editing of YAML config files (`spigot.yml`,
+//! `paper-global.yml`, BungeeCord's `config.yml`) that
+//! keeps the rest of the file (and its comments) as-is.
+//!
+//! Only handles block-style mappings as written by
+//! the servers themselves, which is all that's needed.
+//! A list item (`- key: value`) is treated like a mapping,
+//! so `["listeners", "priorities"]` finds the first listener's
+//! priorities.
+
+/// A value to set a key to.
+#[derive(Clone, Copy)]
+pub enum Value<'a> {
+    /// Written as-is after the key (`key: value`)
+    Scalar(&'a str),
+    /// Nested lines (unindented), written below the key
+    Block(&'a [String]),
+}
+
+/// Sets the key at `path` to `value`, replacing any existing
+/// value (including nested ones) and creating missing parents.
+pub fn set(text: &str, path: &[&str], value: &Value) -> String {
+    let mut lines: Vec<String> = text.lines().map(str::to_owned).collect();
+
+    // The range of lines (and their indentation)
+    // of the current level, starting with the whole file
+    let mut start = 0;
+    let mut end = lines.len();
+    let mut indent = 0;
+    for (depth, key) in path.iter().enumerate() {
+        let Some(i) = (start..end).find(|&i| key_at(&lines[i], indent) == Some(key)) else {
+            // Create the rest of the path at the end of this level
+            let new_lines: Vec<String> = path[depth..]
+                .iter()
+                .enumerate()
+                .flat_map(|(n, key)| {
+                    let pad = " ".repeat(indent + n * 2);
+                    if depth + n + 1 == path.len() {
+                        format_value(&pad, key, value)
+                    } else {
+                        vec![format!("{pad}{key}:")]
+                    }
+                })
+                .collect();
+            lines.splice(end..end, new_lines);
+            return join(&lines);
+        };
+
+        let block_end = block_end(&lines, i, indent);
+        if depth + 1 == path.len() {
+            lines.splice(i..block_end, format_value(&" ".repeat(indent), key, value));
+            return join(&lines);
+        }
+        // The first child decides the indentation of the next level
+        indent = (i + 1..block_end)
+            .find_map(|i| content_indent(&lines[i]))
+            .unwrap_or(indent + 2);
+        start = i + 1;
+        end = block_end;
+    }
+    join(&lines)
+}
+
+fn format_value(pad: &str, key: &str, value: &Value) -> Vec<String> {
+    match value {
+        Value::Scalar(v) => vec![format!("{pad}{key}: {v}")],
+        Value::Block(block) => std::iter::once(format!("{pad}{key}:"))
+            .chain(block.iter().map(|line| format!("{pad}  {line}")))
+            .collect(),
+    }
+}
+
+fn join(lines: &[String]) -> String {
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// The key defined on this line, if it's at `indent`
+/// (ignoring a list item's `- `).
+fn key_at(line: &str, indent: usize) -> Option<&str> {
+    if content_indent(line)? != indent {
+        return None;
+    }
+    let content = line.trim_start().trim_start_matches("- ");
+    let (key, _) = content.split_once(':')?;
+    Some(key.trim().trim_matches(['"', '\'']))
+}
+
+/// Indentation of the line's content, treating `- ` (list item)
+/// as indentation. `None` for blank lines and comments.
+fn content_indent(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return None;
+    }
+    let mut indent = line.len() - trimmed.len();
+    let mut rest = trimmed;
+    while let Some(n) = rest.strip_prefix("- ") {
+        indent += 2;
+        rest = n.trim_start();
+    }
+    Some(indent)
+}
+
+/// The end (exclusive) of the value of the key at line `i`:
+/// all following lines that are indented further, or list
+/// items at the same indentation (`key:\n- item`).
+///
+/// Trailing blank lines and comments are left out.
+fn block_end(lines: &[String], i: usize, indent: usize) -> usize {
+    let mut end = i + 1;
+    for (n, line) in lines.iter().enumerate().skip(i + 1) {
+        let trimmed = line.trim_start();
+        let line_indent = line.len() - trimmed.len();
+        match content_indent(line) {
+            None => {}
+            Some(_) if line_indent > indent => end = n + 1,
+            Some(_) if line_indent == indent && trimmed.starts_with("- ") => end = n + 1,
+            Some(_) => break,
+        }
+    }
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Value, set};
+
+    #[test]
+    fn set_nested_values() {
+        let text = "# comment\nsettings:\n  debug: false\n  bungeecord: false\nworld-settings:\n  default:\n    verbose: false\n";
+        let out = set(text, &["settings", "bungeecord"], &Value::Scalar("true"));
+        assert_eq!(
+            out,
+            "# comment\nsettings:\n  debug: false\n  bungeecord: true\nworld-settings:\n  default:\n    verbose: false\n"
+        );
+
+        // Missing keys are created under the deepest existing parent
+        let out = set(
+            text,
+            &["settings", "velocity", "enabled"],
+            &Value::Scalar("true"),
+        );
+        assert_eq!(
+            out,
+            "# comment\nsettings:\n  debug: false\n  bungeecord: false\n  velocity:\n    enabled: true\nworld-settings:\n  default:\n    verbose: false\n"
+        );
+
+        assert_eq!(set("", &["a", "b"], &Value::Scalar("1")), "a:\n  b: 1\n");
+    }
+
+    #[test]
+    fn replace_blocks_and_lists() {
+        let text = "listeners:\n- query_port: 25577\n  priorities:\n  - lobby\n  host: 0.0.0.0:25577\nservers:\n  lobby:\n    address: localhost:25565\nip_forward: false\n";
+        let priorities = ["- survival".to_owned(), "- creative".to_owned()];
+        let out = set(
+            text,
+            &["listeners", "priorities"],
+            &Value::Block(&priorities),
+        );
+        assert_eq!(
+            out,
+            "listeners:\n- query_port: 25577\n  priorities:\n    - survival\n    - creative\n  host: 0.0.0.0:25577\nservers:\n  lobby:\n    address: localhost:25565\nip_forward: false\n"
+        );
+
+        let servers = [
+            "survival:".to_owned(),
+            "  address: 127.0.0.1:25566".to_owned(),
+        ];
+        let out = set(text, &["servers"], &Value::Block(&servers));
+        assert!(
+            out.contains(
+                "servers:\n  survival:\n    address: 127.0.0.1:25566\nip_forward: false\n"
+            )
+        );
+        assert!(!out.contains("lobby:\n    address"));
+    }
+}
@@ -0,0 +1,452 @@
+//! A client for the Server List Ping protocol, used by
+//! the multiplayer menu to show a server's MOTD,
+//! player count and version without joining.
+//!
+//! Supports the modern protocol (1.7+) and the legacy
+//! `0xFE` ping (Beta 1.8 to 1.6). See
+//! <https://minecraft.wiki/w/Java_Edition_protocol/Server_List_Ping>.
+
+use std::time::{Duration, Instant};
+
+use ql_core::{Instance, Loader, json::InstanceConfigJson};
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{
+    ServerError, ServerProperties,
+    network::{DEFAULT_SERVER_PORT, read_bungeecord_port, read_velocity_port},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// Status responses are JSON with an optional
+/// base64 favicon, never anywhere near this big
+const MAX_PACKET_LEN: usize = 1 << 21;
+/// "Any version", the server answers with its own
+const PROTOCOL_ANY: i32 = -1;
+/// The protocol 1.6 clients send in the legacy ping
+const LEGACY_PROTOCOL: u8 = 74;
+
+const PING_ERR_PREFIX: &str = "while pinging server:\n";
+
+#[derive(Debug, Error)]
+pub enum PingError {
+    #[error("{PING_ERR_PREFIX}{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{PING_ERR_PREFIX}connection timed out")]
+    Timeout,
+    #[error("{PING_ERR_PREFIX}invalid response from server")]
+    InvalidPacket,
+    #[error("{PING_ERR_PREFIX}invalid status JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{PING_ERR_PREFIX}invalid address: {0}")]
+    InvalidAddress(String),
+}
+
+/// What a server shows in the multiplayer menu.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerStatus {
+    /// Plain text, with formatting codes removed
+    pub motd: String,
+    /// Version name as reported by the server
+    /// (may be custom text on proxies/modded servers).
+    /// `None` for servers older than 1.4
+    pub version: Option<String>,
+    pub protocol: Option<i32>,
+    pub players_online: i64,
+    pub players_max: i64,
+    /// Some of the online players' names
+    /// (the server chooses which, often none)
+    pub player_sample: Vec<String>,
+    pub latency: Duration,
+    /// Base64-encoded PNG
+    pub favicon: Option<String>,
+}
+
+/// Pings a server, trying the modern protocol first
+/// and falling back to the legacy one for old servers.
+///
+/// `address` is `host` or `host:port` (`[ipv6]:port` for IPv6).
+/// SRV records aren't looked up, so addresses relying on
+/// them need the port written out.
+///
+/// # Errors
+/// If the server couldn't be reached or sent an invalid response.
+pub async fn ping(address: &str) -> Result<ServerStatus, PingError> {
+    let (host, port) = split_address(address)?;
+    match timeout(ping_modern(&host, port)).await {
+        Ok(status) => Ok(status),
+        // Either down, or not going to answer the legacy ping either
+        Err(err @ PingError::Timeout) => Err(err),
+        Err(PingError::Io(err)) if err.kind() == std::io::ErrorKind::ConnectionRefused => {
+            Err(PingError::Io(err))
+        }
+        Err(err) => timeout(ping_legacy(&host, port)).await.map_err(|_| err),
+    }
+}
+
+/// Pings a server run by the launcher, on its local port.
+///
+/// # Errors
+/// - The server isn't running
+/// - Its config couldn't be read
+pub async fn ping_local(instance: &Instance) -> Result<ServerStatus, ServerError> {
+    let port = local_port(instance).await?;
+    Ok(ping(&format!("127.0.0.1:{port}")).await?)
+}
+
+/// The port a server of the launcher listens on.
+async fn local_port(instance: &Instance) -> Result<u16, ServerError> {
+    let dir = instance.get_instance_path();
+    Ok(match InstanceConfigJson::read(instance).await?.mod_type {
+        Loader::Velocity => read_velocity_port(&dir).await?,
+        Loader::BungeeCord => read_bungeecord_port(&dir).await?,
+        _ => ServerProperties::load_from_dir(&dir)
+            .await?
+            .and_then(|n| n.get("server-port")?.parse().ok())
+            .unwrap_or(DEFAULT_SERVER_PORT),
+    })
+}
+
+async fn timeout<T>(f: impl Future<Output = Result<T, PingError>>) -> Result<T, PingError> {
+    tokio::time::timeout(TIMEOUT, f)
+        .await
+        .map_err(|_| PingError::Timeout)?
+}
+
+fn split_address(address: &str) -> Result<(String, u16), PingError> {
+    let address = address.trim();
+    let invalid = || PingError::InvalidAddress(address.to_owned());
+    if address.is_empty() {
+        return Err(invalid());
+    }
+    // [::1]:25565
+    if let Some(rest) = address.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']').ok_or_else(invalid)?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => port.parse().map_err(|_| invalid())?,
+            None if rest.is_empty() => DEFAULT_SERVER_PORT,
+            None => return Err(invalid()),
+        };
+        return Ok((host.to_owned(), port));
+    }
+    match address.rsplit_once(':') {
+        // Bare IPv6 address
+        Some((host, _)) if host.contains(':') => Ok((address.to_owned(), DEFAULT_SERVER_PORT)),
+        Some((host, port)) => Ok((host.to_owned(), port.parse().map_err(|_| invalid())?)),
+        None => Ok((address.to_owned(), DEFAULT_SERVER_PORT)),
+    }
+}
+
+async fn connect(host: &str, port: u16) -> Result<TcpStream, PingError> {
+    let stream = TcpStream::connect((host, port)).await?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+#[derive(Deserialize)]
+struct StatusJson {
+    version: Option<VersionJson>,
+    players: Option<PlayersJson>,
+    #[serde(default)]
+    description: serde_json::Value,
+    favicon: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct VersionJson {
+    name: String,
+    protocol: i32,
+}
+
+#[derive(Deserialize)]
+struct PlayersJson {
+    max: i64,
+    online: i64,
+    #[serde(default)]
+    sample: Vec<PlayerJson>,
+}
+
+#[derive(Deserialize)]
+struct PlayerJson {
+    name: String,
+}
+
+async fn ping_modern(host: &str, port: u16) -> Result<ServerStatus, PingError> {
+    let mut stream = connect(host, port).await?;
+    let start = Instant::now();
+
+    let mut handshake = vec![0x00];
+    write_varint(&mut handshake, PROTOCOL_ANY);
+    write_string(&mut handshake, host);
+    handshake.extend_from_slice(&port.to_be_bytes());
+    write_varint(&mut handshake, 1); // Next state: status
+    send_packet(&mut stream, &handshake).await?;
+    send_packet(&mut stream, &[0x00]).await?; // Status request
+
+    let response = read_packet(&mut stream).await?;
+    let mut reader = response.as_slice();
+    if read_varint_from(&mut reader)? != 0x00 {
+        return Err(PingError::InvalidPacket);
+    }
+    let len =
+        usize::try_from(read_varint_from(&mut reader)?).map_err(|_| PingError::InvalidPacket)?;
+    let json = reader.get(..len).ok_or(PingError::InvalidPacket)?;
+    let status: StatusJson = serde_json::from_slice(json)?;
+    let mut latency = start.elapsed();
+
+    // Ping for a more accurate latency (the status may take
+    // a while to generate). Some proxies close the connection
+    // instead of answering, so the status is still used then.
+    let payload = i64::try_from(start.elapsed().as_millis()).unwrap_or_default();
+    let mut ping = vec![0x01];
+    ping.extend_from_slice(&payload.to_be_bytes());
+    let ping_start = Instant::now();
+    if send_packet(&mut stream, &ping).await.is_ok() {
+        if let Ok(pong) = read_packet(&mut stream).await {
+            if pong.first() == Some(&0x01) {
+                latency = ping_start.elapsed();
+            }
+        }
+    }
+
+    let (players_online, players_max, player_sample) = status.players.map_or_else(
+        || (0, 0, Vec::new()),
+        |n| {
+            (
+                n.online,
+                n.max,
+                n.sample.into_iter().map(|n| n.name).collect(),
+            )
+        },
+    );
+    Ok(ServerStatus {
+        motd: strip_formatting(&chat_to_plain(&status.description)),
+        version: status.version.as_ref().map(|n| n.name.clone()),
+        protocol: status.version.map(|n| n.protocol),
+        players_online,
+        players_max,
+        player_sample,
+        latency,
+        favicon: status.favicon.map(|n| {
+            n.strip_prefix("data:image/png;base64,")
+                .map_or(n.clone(), str::to_owned)
+        }),
+    })
+}
+
+/// The `0xFE 0x01` ping, with the `MC|PingHost` plugin message
+/// 1.6 expects. Older servers ignore the extra data.
+async fn ping_legacy(host: &str, port: u16) -> Result<ServerStatus, PingError> {
+    let mut stream = connect(host, port).await?;
+    let start = Instant::now();
+
+    let host_utf16 = utf16_be(host);
+    let mut packet = vec![0xFE, 0x01, 0xFA];
+    let channel = "MC|PingHost";
+    packet.extend_from_slice(
+        &u16::try_from(channel.len())
+            .unwrap_or_default()
+            .to_be_bytes(),
+    );
+    packet.extend_from_slice(&utf16_be(channel));
+    let rest_len = 7 + host_utf16.len();
+    packet.extend_from_slice(&u16::try_from(rest_len).unwrap_or_default().to_be_bytes());
+    packet.push(LEGACY_PROTOCOL);
+    let host_len = u16::try_from(host.encode_utf16().count()).unwrap_or_default();
+    packet.extend_from_slice(&host_len.to_be_bytes());
+    packet.extend_from_slice(&host_utf16);
+    packet.extend_from_slice(&i32::from(port).to_be_bytes());
+    stream.write_all(&packet).await?;
+
+    // Kick packet with the status as its reason
+    if stream.read_u8().await? != 0xFF {
+        return Err(PingError::InvalidPacket);
+    }
+    let len = stream.read_u16().await?;
+    let mut data = vec![0; usize::from(len) * 2];
+    stream.read_exact(&mut data).await?;
+    let latency = start.elapsed();
+
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|n| u16::from_be_bytes([n[0], n[1]]))
+        .collect();
+    let text = String::from_utf16(&units).map_err(|_| PingError::InvalidPacket)?;
+    parse_legacy(&text, latency).ok_or(PingError::InvalidPacket)
+}
+
+fn parse_legacy(text: &str, latency: Duration) -> Option<ServerStatus> {
+    // 1.4+: §1\0protocol\0version\0motd\0online\0max
+    if let Some(rest) = text.strip_prefix("§1\0") {
+        let mut fields = rest.split('\0');
+        let protocol = fields.next()?.parse().ok();
+        let version = fields.next()?.to_owned();
+        let motd = fields.next()?;
+        return Some(ServerStatus {
+            motd: strip_formatting(motd),
+            version: Some(version),
+            protocol,
+            players_online: fields.next()?.parse().ok()?,
+            players_max: fields.next()?.parse().ok()?,
+            player_sample: Vec::new(),
+            latency,
+            favicon: None,
+        });
+    }
+    // Beta 1.8 - 1.3: motd§online§max
+    let mut fields = text.rsplitn(3, '§');
+    let players_max = fields.next()?.parse().ok()?;
+    let players_online = fields.next()?.parse().ok()?;
+    Some(ServerStatus {
+        motd: strip_formatting(fields.next()?),
+        version: None,
+        protocol: None,
+        players_online,
+        players_max,
+        player_sample: Vec::new(),
+        latency,
+        favicon: None,
+    })
+}
+
+/// Flattens a chat component (string, object or array) to text.
+fn chat_to_plain(value: &serde_json::Value) -> String {
+    use serde_json::Value;
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts.iter().map(chat_to_plain).collect(),
+        Value::Object(obj) => {
+            let mut text = obj
+                .get("text")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_owned();
+            if let Some(Value::Array(extra)) = obj.get("extra") {
+                text.extend(extra.iter().map(chat_to_plain));
+            }
+            text
+        }
+        _ => String::new(),
+    }
+}
+
+/// Removes `§` formatting codes (colors, bold, ...).
+fn strip_formatting(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn utf16_be(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(u16::to_be_bytes).collect()
+}
+
+fn write_varint(out: &mut Vec<u8>, value: i32) {
+    let mut value = u32::from_ne_bytes(value.to_ne_bytes());
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    write_varint(out, i32::try_from(s.len()).unwrap_or(i32::MAX));
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn read_varint_from(reader: &mut &[u8]) -> Result<i32, PingError> {
+    let mut value: u32 = 0;
+    for i in 0..5 {
+        let (byte, rest) = reader.split_first().ok_or(PingError::InvalidPacket)?;
+        *reader = rest;
+        value |= u32::from(byte & 0x7F) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(i32::from_ne_bytes(value.to_ne_bytes()));
+        }
+    }
+    Err(PingError::InvalidPacket)
+}
+
+async fn send_packet(stream: &mut TcpStream, data: &[u8]) -> Result<(), PingError> {
+    let mut packet = Vec::with_capacity(data.len() + 5);
+    write_varint(
+        &mut packet,
+        i32::try_from(data.len()).map_err(|_| PingError::InvalidPacket)?,
+    );
+    packet.extend_from_slice(data);
+    stream.write_all(&packet).await?;
+    Ok(())
+}
+
+async fn read_packet(stream: &mut TcpStream) -> Result<Vec<u8>, PingError> {
+    let mut len: u32 = 0;
+    for i in 0..5 {
+        let byte = stream.read_u8().await?;
+        len |= u32::from(byte & 0x7F) << (7 * i);
+        if byte & 0x80 == 0 {
+            let len = len as usize;
+            if len == 0 || len > MAX_PACKET_LEN {
+                return Err(PingError::InvalidPacket);
+            }
+            let mut data = vec![0; len];
+            stream.read_exact(&mut data).await?;
+            return Ok(data);
+        }
+    }
+    Err(PingError::InvalidPacket)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{chat_to_plain, parse_legacy, read_varint_from, split_address, write_varint};
+
+    #[test]
+    fn varint_roundtrip() {
+        for n in [0, 1, 127, 128, 25565, i32::MAX, -1, i32::MIN] {
+            let mut out = Vec::new();
+            write_varint(&mut out, n);
+            assert_eq!(read_varint_from(&mut out.as_slice()).unwrap(), n);
+        }
+    }
+
+    #[test]
+    fn addresses_and_motds() {
+        assert_eq!(
+            split_address("example.com").unwrap(),
+            ("example.com".to_owned(), 25565)
+        );
+        assert_eq!(
+            split_address("[::1]:25566").unwrap(),
+            ("::1".to_owned(), 25566)
+        );
+        assert!(split_address("host:port").is_err());
+
+        let motd = serde_json::json!({"text": "§aHello", "extra": [" ", {"text": "world"}]});
+        assert_eq!(chat_to_plain(&motd), "§aHello world");
+
+        let status =
+            parse_legacy("§1\x0061\x001.5.2\x00§6A server\x003\x0020", Duration::ZERO).unwrap();
+        assert_eq!(status.motd, "A server");
+        assert_eq!((status.players_online, status.players_max), (3, 20));
+        let status = parse_legacy("An old server§1§10", Duration::ZERO).unwrap();
+        assert_eq!(status.motd, "An old server");
+    }
+}
@@ -0,0 +1,444 @@
+//! The whitelist, ops and ban lists of a server
+//! (`whitelist.json`, `ops.json`, `banned-players.json`,
+//! `banned-ips.json`).
+//!
+//! While a server is running it keeps these lists in memory
+//! and overwrites the files with them, so changes are sent as
+//! commands then (see [`apply_list_change`]). Otherwise the
+//! files are edited directly.
+
+use std::{path::Path, time::Duration};
+
+use ql_core::{
+    Instance, IntoIoError, IntoJsonError, JsonDownloadError, LAUNCHER_DIR, LaunchedProcess,
+    RequestError, download, file_utils::exists, hash, to_hex,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{NetworkConfig, ServerError, ServerProperties, send_console_command};
+
+const WHITELIST_FILE: &str = "whitelist.json";
+const OPS_FILE: &str = "ops.json";
+const BANNED_PLAYERS_FILE: &str = "banned-players.json";
+const BANNED_IPS_FILE: &str = "banned-ips.json";
+
+const DEFAULT_OP_LEVEL: u8 = 4;
+const COMMAND_SAVE_DELAY: Duration = Duration::from_millis(500);
+const BAN_SOURCE: &str = "QuantumLauncher";
+const DEFAULT_BAN_REASON: &str = "Banned by an operator.";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WhitelistEntry {
+    pub uuid: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpEntry {
+    pub uuid: String,
+    pub name: String,
+    /// Permission level, 1-4
+    pub level: u8,
+    /// Can join even when the server is full
+    pub bypasses_player_limit: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BanEntry {
+    pub uuid: String,
+    pub name: String,
+    /// `yyyy-MM-dd HH:mm:ss Z`
+    pub created: String,
+    pub source: String,
+    /// `forever`, or a date like `created`
+    pub expires: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpBanEntry {
+    pub ip: String,
+    pub created: String,
+    pub source: String,
+    pub expires: String,
+    pub reason: String,
+}
+
+/// All player lists of a server.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerLists {
+    pub whitelist: Vec<WhitelistEntry>,
+    pub ops: Vec<OpEntry>,
+    pub banned_players: Vec<BanEntry>,
+    pub banned_ips: Vec<IpBanEntry>,
+}
+
+impl PlayerLists {
+    /// Reads the lists of a server. Missing files are empty lists.
+    ///
+    /// # Errors
+    /// If a file couldn't be read or is invalid.
+    pub async fn load(instance: &Instance) -> Result<Self, ServerError> {
+        let dir = instance.get_instance_path();
+        Ok(Self {
+            whitelist: read_list(&dir, WHITELIST_FILE).await?,
+            ops: read_list(&dir, OPS_FILE).await?,
+            banned_players: read_list(&dir, BANNED_PLAYERS_FILE).await?,
+            banned_ips: read_list(&dir, BANNED_IPS_FILE).await?,
+        })
+    }
+
+    /// Writes all the lists. Only do this while the server
+    /// is stopped, otherwise it will overwrite them.
+    ///
+    /// # Errors
+    /// If a file couldn't be written.
+    pub async fn save(&self, instance: &Instance) -> Result<(), ServerError> {
+        let dir = instance.get_instance_path();
+        write_list(&dir, WHITELIST_FILE, &self.whitelist).await?;
+        write_list(&dir, OPS_FILE, &self.ops).await?;
+        write_list(&dir, BANNED_PLAYERS_FILE, &self.banned_players).await?;
+        write_list(&dir, BANNED_IPS_FILE, &self.banned_ips).await?;
+        Ok(())
+    }
+}
+
+async fn read_list<T: DeserializeOwned>(dir: &Path, file: &str) -> Result<Vec<T>, ServerError> {
+    let path = dir.join(file);
+    if !exists(&path).await {
+        return Ok(Vec::new());
+    }
+    let text = tokio::fs::read_to_string(&path).await.path(&path)?;
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_str(&text).json(text)?)
+}
+
+async fn write_list<T: Serialize>(dir: &Path, file: &str, list: &[T]) -> Result<(), ServerError> {
+    let path = dir.join(file);
+    let text = serde_json::to_string_pretty(list).json_to()?;
+    tokio::fs::write(&path, text).await.path(path)?;
+    Ok(())
+}
+
+/// A change to one of the lists, by player name (or IP).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListChange {
+    WhitelistAdd(String),
+    WhitelistRemove(String),
+    Op(String),
+    Deop(String),
+    Ban {
+        name: String,
+        reason: Option<String>,
+    },
+    Pardon(String),
+    BanIp {
+        ip: String,
+        reason: Option<String>,
+    },
+    PardonIp(String),
+}
+
+impl ListChange {
+    fn command(&self) -> String {
+        let with_reason = |cmd: String, reason: &Option<String>| {
+            // A newline would end the command and start another one
+            let reason: String = reason
+                .as_deref()
+                .unwrap_or_default()
+                .chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .collect();
+            let reason = reason.trim();
+            if reason.is_empty() {
+                cmd
+            } else {
+                format!("{cmd} {reason}")
+            }
+        };
+        match self {
+            ListChange::WhitelistAdd(name) => format!("whitelist add {name}"),
+            ListChange::WhitelistRemove(name) => format!("whitelist remove {name}"),
+            ListChange::Op(name) => format!("op {name}"),
+            ListChange::Deop(name) => format!("deop {name}"),
+            ListChange::Ban { name, reason } => with_reason(format!("ban {name}"), reason),
+            ListChange::Pardon(name) => format!("pardon {name}"),
+            ListChange::BanIp { ip, reason } => with_reason(format!("ban-ip {ip}"), reason),
+            ListChange::PardonIp(ip) => format!("pardon-ip {ip}"),
+        }
+    }
+
+    fn validate(&self) -> Result<(), ServerError> {
+        match self {
+            ListChange::BanIp { ip, .. } | ListChange::PardonIp(ip) => {
+                if ip.parse::<std::net::IpAddr>().is_err() {
+                    return Err(ServerError::InvalidIp(ip.clone()));
+                }
+            }
+            ListChange::WhitelistAdd(name)
+            | ListChange::WhitelistRemove(name)
+            | ListChange::Op(name)
+            | ListChange::Deop(name)
+            | ListChange::Ban { name, .. }
+            | ListChange::Pardon(name) => {
+                let is_valid = (1..=16).contains(&name.len())
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if !is_valid {
+                    return Err(ServerError::InvalidPlayerName(name.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Applies a change to the lists of a server.
+///
+/// - If `process` is given (the server is running), the matching
+///   command is sent, so the server updates its own lists
+///   (falling back to RCON if the console isn't available).
+///   `op` uses the server's default `op-permission-level`.
+/// - Otherwise the files are edited. Players are looked up
+///   by name to get their UUID (see [`resolve_player`]).
+///
+/// # Errors
+/// - The name/IP is invalid
+/// - The player doesn't exist (online mode)
+/// - The command couldn't be sent, or the files couldn't be edited
+pub async fn apply_list_change(
+    instance: &Instance,
+    change: &ListChange,
+    process: Option<&LaunchedProcess>,
+) -> Result<(), ServerError> {
+    change.validate()?;
+    if let Some(process) = process {
+        send_console_command(Some(process), instance.get_name(), &change.command()).await?;
+        // Give the server time to save the list,
+        // so it's up-to-date if reloaded right after
+        tokio::time::sleep(COMMAND_SAVE_DELAY).await;
+        return Ok(());
+    }
+
+    let mut lists = PlayerLists::load(instance).await?;
+    let same_name = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+    match change {
+        ListChange::WhitelistAdd(name) => {
+            if !lists.whitelist.iter().any(|n| same_name(&n.name, name)) {
+                let (name, uuid) = resolve_player(instance, name).await?;
+                lists.whitelist.push(WhitelistEntry { uuid, name });
+            }
+        }
+        ListChange::WhitelistRemove(name) => lists.whitelist.retain(|n| !same_name(&n.name, name)),
+        ListChange::Op(name) => {
+            if !lists.ops.iter().any(|n| same_name(&n.name, name)) {
+                let level = ServerProperties::load_from_dir(&instance.get_instance_path())
+                    .await?
+                    .and_then(|n| n.get("op-permission-level")?.parse().ok())
+                    .unwrap_or(DEFAULT_OP_LEVEL);
+                let (name, uuid) = resolve_player(instance, name).await?;
+                lists.ops.push(OpEntry {
+                    uuid,
+                    name,
+                    level,
+                    bypasses_player_limit: false,
+                });
+            }
+        }
+        ListChange::Deop(name) => lists.ops.retain(|n| !same_name(&n.name, name)),
+        ListChange::Ban { name, reason } => {
+            if !lists
+                .banned_players
+                .iter()
+                .any(|n| same_name(&n.name, name))
+            {
+                let (name, uuid) = resolve_player(instance, name).await?;
+                lists.banned_players.push(BanEntry {
+                    uuid,
+                    name,
+                    created: now(),
+                    source: BAN_SOURCE.to_owned(),
+                    expires: "forever".to_owned(),
+                    reason: ban_reason(reason.as_deref()),
+                });
+            }
+        }
+        ListChange::Pardon(name) => lists.banned_players.retain(|n| !same_name(&n.name, name)),
+        ListChange::BanIp { ip, reason } => {
+            if !lists.banned_ips.iter().any(|n| n.ip == *ip) {
+                lists.banned_ips.push(IpBanEntry {
+                    ip: ip.clone(),
+                    created: now(),
+                    source: BAN_SOURCE.to_owned(),
+                    expires: "forever".to_owned(),
+                    reason: ban_reason(reason.as_deref()),
+                });
+            }
+        }
+        ListChange::PardonIp(ip) => lists.banned_ips.retain(|n| n.ip != *ip),
+    }
+    lists.save(instance).await
+}
+
+/// Sets the permission level of an op, by editing `ops.json`.
+/// There's no command for this, so the server must be stopped.
+///
+/// # Errors
+/// If `ops.json` couldn't be read/written.
+pub async fn set_op_level(instance: &Instance, name: &str, level: u8) -> Result<(), ServerError> {
+    let mut lists = PlayerLists::load(instance).await?;
+    if let Some(op) = lists
+        .ops
+        .iter_mut()
+        .find(|n| n.name.eq_ignore_ascii_case(name))
+    {
+        op.level = level.clamp(1, 4);
+    }
+    lists.save(instance).await
+}
+
+fn now() -> String {
+    chrono::Local::now()
+        .format("%Y-%m-%d %H:%M:%S %z")
+        .to_string()
+}
+
+fn ban_reason(reason: Option<&str>) -> String {
+    reason
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .unwrap_or(DEFAULT_BAN_REASON)
+        .to_owned()
+}
+
+/// Looks up a player's UUID the way the server would,
+/// returning `(name, uuid)`.
+///
+/// - Online mode: from Mojang (with the correct capitalization of the name)
+/// - Offline mode: derived from the name
+///
+/// Servers behind a proxy network run in offline mode, but
+/// get players' real UUIDs from the proxy, so online UUIDs
+/// are used for them too.
+///
+/// # Errors
+/// - The player doesn't exist (online mode)
+/// - The Mojang API couldn't be reached
+pub async fn resolve_player(
+    instance: &Instance,
+    name: &str,
+) -> Result<(String, String), ServerError> {
+    if uses_online_uuids(instance).await? {
+        fetch_online_uuid(name).await
+    } else {
+        Ok((name.to_owned(), offline_uuid(name)))
+    }
+}
+
+async fn uses_online_uuids(instance: &Instance) -> Result<bool, ServerError> {
+    let is_online = ServerProperties::load_from_dir(&instance.get_instance_path())
+        .await?
+        .is_none_or(|n| n.get("online-mode") != Some("false"));
+    Ok(is_online || is_network_backend(instance.get_name()).await)
+}
+
+async fn is_network_backend(name: &str) -> bool {
+    let Ok(mut entries) = tokio::fs::read_dir(LAUNCHER_DIR.join("servers")).await else {
+        return false;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let proxy = Instance::server(&entry.file_name().to_string_lossy());
+        if !exists(proxy.get_instance_path().join("network.json")).await {
+            continue;
+        }
+        if let Ok(config) = NetworkConfig::load(&proxy).await {
+            if config.backends.iter().any(|n| n == name) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[derive(Deserialize)]
+struct MojangProfile {
+    id: String,
+    name: String,
+}
+
+async fn fetch_online_uuid(name: &str) -> Result<(String, String), ServerError> {
+    let url = format!("https://api.mojang.com/users/profiles/minecraft/{name}");
+    match download(&url).user_agent_ql().json::<MojangProfile>().await {
+        Ok(profile) => Ok((profile.name, hyphenate(&profile.id))),
+        // Older API versions returned 204 with an empty body
+        Err(JsonDownloadError::EmptyResponse(_)) => {
+            Err(ServerError::UnknownPlayer(name.to_owned()))
+        }
+        Err(JsonDownloadError::RequestError(RequestError::DownloadError { code, .. }))
+            if code.as_u16() == 404 || code.as_u16() == 204 =>
+        {
+            Err(ServerError::UnknownPlayer(name.to_owned()))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// The UUID offline-mode servers give a player:
+/// a version 3 UUID of `OfflinePlayer:<name>`.
+#[must_use]
+pub fn offline_uuid(name: &str) -> String {
+    let mut bytes = hash::md5(format!("OfflinePlayer:{name}").as_bytes());
+    bytes[6] = (bytes[6] & 0x0f) | 0x30;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    hyphenate(&to_hex(&bytes))
+}
+
+/// `0123...` to `01234567-89ab-cdef-0123-456789abcdef`
+fn hyphenate(hex: &str) -> String {
+    if hex.len() != 32 || !hex.is_ascii() {
+        return hex.to_owned();
+    }
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ListChange, offline_uuid};
+
+    #[test]
+    fn offline_uuid_matches_server() {
+        assert_eq!(
+            offline_uuid("Notch"),
+            "b50ad385-829d-3141-a216-7e7d7539ba7f"
+        );
+    }
+
+    #[test]
+    fn commands() {
+        let ban = ListChange::Ban {
+            name: "Steve".to_owned(),
+            reason: Some(" griefing ".to_owned()),
+        };
+        assert_eq!(ban.command(), "ban Steve griefing");
+        let ban = ListChange::BanIp {
+            ip: "127.0.0.1".to_owned(),
+            reason: Some("spam\nop Steve\r".to_owned()),
+        };
+        assert_eq!(ban.command(), "ban-ip 127.0.0.1 spam op Steve");
+        assert!(ListChange::Op("not a name".to_owned()).validate().is_err());
+        assert!(
+            ListChange::PardonIp("127.0.0.1".to_owned())
+                .validate()
+                .is_ok()
+        );
+    }
+}
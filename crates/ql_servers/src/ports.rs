@@ -0,0 +1,409 @@
+//! Keeping the ports of servers apart, so several can run at once.
+//!
+//! Every server gets its own `server-port` (and `query.port`
+//! and `rcon.port` if those are on), picked when it's created
+//! and checked again right before it starts, also against
+//! other programs on this computer.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    net::{Ipv4Addr, TcpListener, UdpSocket},
+    path::Path,
+};
+
+use ql_core::{Instance, LAUNCHER_DIR, Loader, info, json::InstanceConfigJson};
+
+use crate::{
+    ServerError, ServerProperties,
+    network::{DEFAULT_SERVER_PORT, find_network, read_bungeecord_port, read_velocity_port},
+    rcon::DEFAULT_PORT as DEFAULT_RCON_PORT,
+};
+
+/// How many ports after a taken one to try
+const PORT_SEARCH_RANGE: u16 = 100;
+
+/// The ports a server listens on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerPorts {
+    /// For players (TCP)
+    pub server: u16,
+    /// For server list tools (UDP), `None` if turned off
+    pub query: Option<u16>,
+    /// For remote consoles (TCP), `None` if turned off
+    pub rcon: Option<u16>,
+}
+
+impl ServerPorts {
+    pub(crate) fn from_properties(properties: &ServerProperties) -> Self {
+        let get_port = |key| {
+            properties
+                .get(key)
+                .and_then(|n: &str| n.trim().parse::<u16>().ok())
+        };
+        let is_on = |key| properties.get(key) == Some("true");
+
+        let server = get_port("server-port").unwrap_or(DEFAULT_SERVER_PORT);
+        Self {
+            server,
+            // Query is UDP, so it can share the number
+            query: is_on("enable-query").then(|| get_port("query.port").unwrap_or(server)),
+            rcon: is_on("enable-rcon").then(|| get_port("rcon.port").unwrap_or(DEFAULT_RCON_PORT)),
+        }
+    }
+
+    pub(crate) fn tcp(&self) -> impl Iterator<Item = u16> {
+        std::iter::once(self.server).chain(self.rcon)
+    }
+}
+
+/// A port changed by [`assign_ports`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortChange {
+    /// The `server.properties` key
+    pub key: &'static str,
+    pub old: u16,
+    pub new: u16,
+    pub conflict: PortConflict,
+}
+
+/// Why a port had to be changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortConflict {
+    /// Another server of the launcher is set to use it
+    Server(String),
+    /// Some other program is listening on it right now
+    /// (never for `server-port`, see [`assign_ports`])
+    OtherProgram,
+}
+
+impl Display for PortChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {} (", self.key, self.old, self.new)?;
+        match &self.conflict {
+            PortConflict::Server(name) => write!(f, "used by server {name})"),
+            PortConflict::OtherProgram => write!(f, "used by another program)"),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Protocol {
+    Tcp,
+    Udp,
+}
+
+/// The ports every server of the launcher is set to use, by name.
+///
+/// Classic servers are left out (their ports
+/// aren't managed), and so are broken servers.
+///
+/// # Errors
+/// If the servers folder couldn't be read.
+pub async fn ports_in_use() -> Result<BTreeMap<String, ServerPorts>, ServerError> {
+    let mut ports = BTreeMap::new();
+    let Ok(mut entries) = tokio::fs::read_dir(LAUNCHER_DIR.join("servers")).await else {
+        return Ok(ports);
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if let Ok(Some(server_ports)) = read_ports(&entry.path()).await {
+            ports.insert(name, server_ports);
+        }
+    }
+    Ok(ports)
+}
+
+async fn read_ports(dir: &Path) -> Result<Option<ServerPorts>, ServerError> {
+    let config = InstanceConfigJson::read_from_dir(dir).await?;
+    if config.is_classic_server.unwrap_or_default() {
+        return Ok(None);
+    }
+    let proxy_port = match config.mod_type {
+        Loader::Velocity => read_velocity_port(dir).await?,
+        Loader::BungeeCord => read_bungeecord_port(dir).await?,
+        _ => {
+            let properties = ServerProperties::load_from_dir(dir)
+                .await?
+                .unwrap_or_default();
+            return Ok(Some(ServerPorts::from_properties(&properties)));
+        }
+    };
+    Ok(Some(ServerPorts {
+        server: proxy_port,
+        query: None,
+        rcon: None,
+    }))
+}
+
+/// Makes sure a server doesn't share ports with the other
+/// servers of the launcher, changing them in `server.properties`
+/// to free ones if needed (missing ones are written too).
+///
+/// With `check_system`, the ports are also checked against
+/// other programs on this computer (right before starting).
+/// `rcon.port` and `query.port` are moved off those, but
+/// `server-port` isn't: if it's taken, the server is
+/// most likely already running.
+///
+/// Proxies and backends of a network are only checked,
+/// as their ports are set up by the proxy network instead
+/// (see [`crate::apply_network`]).
+///
+/// Returns what was changed.
+///
+/// # Errors
+/// - With `check_system`, `server-port` is taken by another
+///   program or the server itself ([`ServerError::PortInUse`])
+/// - No free port was found nearby
+/// - The configs couldn't be read or written
+pub async fn assign_ports(
+    server: &Instance,
+    check_system: bool,
+) -> Result<Vec<PortChange>, ServerError> {
+    let dir = server.get_instance_path();
+    let config = InstanceConfigJson::read_from_dir(&dir).await?;
+    if config.is_classic_server.unwrap_or_default() {
+        return Ok(Vec::new());
+    }
+    if config.mod_type.is_proxy() || find_network(server.get_name()).await?.is_some() {
+        if let (true, Some(ports)) = (check_system, read_ports(&dir).await?) {
+            if !is_free(Protocol::Tcp, ports.server, None) {
+                return Err(ServerError::PortInUse(ports.server));
+            }
+        }
+        return Ok(Vec::new());
+    }
+
+    let reserved = ReservedPorts::collect(ports_in_use().await?, server.get_name());
+    let mut properties = ServerProperties::load_from_dir(&dir)
+        .await?
+        .unwrap_or_default();
+    let (changes, is_changed) =
+        assign_in_properties(&mut properties, server.get_name(), &reserved, check_system)?;
+
+    if is_changed {
+        properties.save_to_dir(&dir).await?;
+    }
+    for change in &changes {
+        info!("Changed port of {}, {change}", server.get_name());
+    }
+    Ok(changes)
+}
+
+/// The ports other servers of the launcher
+/// are set to use, with the name of the server.
+#[derive(Default)]
+pub(crate) struct ReservedPorts {
+    pub(crate) tcp: HashMap<u16, String>,
+    pub(crate) udp: HashMap<u16, String>,
+}
+
+impl ReservedPorts {
+    /// From [`ports_in_use`], leaving out server `except`.
+    pub(crate) fn collect(ports: BTreeMap<String, ServerPorts>, except: &str) -> Self {
+        let mut reserved = Self::default();
+        for (name, ports) in ports {
+            if name == except {
+                continue;
+            }
+            if let Some(query) = ports.query {
+                reserved.udp.insert(query, name.clone());
+            }
+            for port in ports.tcp() {
+                reserved.tcp.insert(port, name.clone());
+            }
+        }
+        reserved
+    }
+}
+
+/// The part of [`assign_ports`] that works on `server.properties`,
+/// returning the changes and whether `properties` has to be saved.
+fn assign_in_properties(
+    properties: &mut ServerProperties,
+    name: &str,
+    reserved: &ReservedPorts,
+    check_system: bool,
+) -> Result<(Vec<PortChange>, bool), ServerError> {
+    let current = ServerPorts::from_properties(properties);
+    let bind_ip = bind_ip(properties);
+
+    let mut changes = Vec::new();
+    let mut is_changed = false;
+    let mut taken_tcp = HashSet::new();
+    for (key, port, protocol) in [
+        ("server-port", Some(current.server), Protocol::Tcp),
+        ("rcon.port", current.rcon, Protocol::Tcp),
+        ("query.port", current.query, Protocol::Udp),
+    ] {
+        let Some(port) = port else {
+            continue;
+        };
+        let (reserved, taken) = match protocol {
+            Protocol::Tcp => (&reserved.tcp, Some(&taken_tcp)),
+            Protocol::Udp => (&reserved.udp, None),
+        };
+        let is_available = |n: u16| {
+            !reserved.contains_key(&n)
+                && !taken.is_some_and(|t| t.contains(&n))
+                && (!check_system || is_free(protocol, n, bind_ip))
+        };
+
+        let conflict = if let Some(name) = reserved.get(&port) {
+            Some(PortConflict::Server(name.clone()))
+        } else if taken.is_some_and(|t| t.contains(&port)) {
+            // Own server port, for RCON
+            Some(PortConflict::Server(name.to_owned()))
+        } else if check_system && !is_free(protocol, port, bind_ip) {
+            if key == "server-port" {
+                return Err(ServerError::PortInUse(port));
+            }
+            Some(PortConflict::OtherProgram)
+        } else {
+            None
+        };
+
+        let new = if let Some(conflict) = conflict {
+            let new = next_free_port(port, is_available)?;
+            changes.push(PortChange {
+                key,
+                old: port,
+                new,
+                conflict,
+            });
+            new
+        } else {
+            port
+        };
+        if properties.get(key).is_none() || new != port {
+            properties.set(key, new.to_string());
+            is_changed = true;
+        }
+        if let Protocol::Tcp = protocol {
+            taken_tcp.insert(new);
+        }
+    }
+    Ok((changes, is_changed))
+}
+
+/// The first port after `port` that `is_available`,
+/// at most [`PORT_SEARCH_RANGE`] ports away.
+///
+/// # Errors
+/// [`ServerError::NoFreePort`] if there's none.
+pub(crate) fn next_free_port(
+    port: u16,
+    is_available: impl Fn(u16) -> bool,
+) -> Result<u16, ServerError> {
+    (1..=PORT_SEARCH_RANGE)
+        .filter_map(|n| port.checked_add(n))
+        .find(|n| is_available(*n))
+        .ok_or(ServerError::NoFreePort(port))
+}
+
+/// The `server-ip` a server binds to, if set.
+pub(crate) fn bind_ip(properties: &ServerProperties) -> Option<Ipv4Addr> {
+    properties
+        .get("server-ip")
+        .and_then(|n| n.trim().parse().ok())
+}
+
+pub(crate) fn is_free(protocol: Protocol, port: u16, ip: Option<Ipv4Addr>) -> bool {
+    let address = (ip.unwrap_or(Ipv4Addr::UNSPECIFIED), port);
+    match protocol {
+        Protocol::Tcp => TcpListener::bind(address).is_ok(),
+        Protocol::Udp => UdpSocket::bind(address).is_ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ports_from_properties() {
+        let ports = ServerPorts::from_properties(&ServerProperties::parse(
+            "server-port=25570\nenable-query=true\nenable-rcon=false\nrcon.port=25580\n",
+        ));
+        assert_eq!(
+            ports,
+            ServerPorts {
+                server: 25570,
+                query: Some(25570),
+                rcon: None,
+            }
+        );
+
+        let ports = ServerPorts::from_properties(&ServerProperties::parse("enable-rcon=true\n"));
+        assert_eq!(ports.server, DEFAULT_SERVER_PORT);
+        assert_eq!(ports.rcon, Some(DEFAULT_RCON_PORT));
+        assert_eq!(ports.tcp().collect::<Vec<_>>(), [25565, 25575]);
+    }
+
+    #[test]
+    fn assign_moves_off_other_servers() {
+        let mut reserved = ReservedPorts::default();
+        reserved.tcp.insert(25565, "other".to_owned());
+        reserved.tcp.insert(25566, "other".to_owned());
+        reserved.udp.insert(25567, "third".to_owned());
+
+        let mut properties =
+            ServerProperties::parse("enable-query=true\nquery.port=25567\nenable-rcon=true\n");
+        let (changes, is_changed) =
+            assign_in_properties(&mut properties, "mine", &reserved, false).unwrap();
+        assert!(is_changed);
+        assert_eq!(
+            changes,
+            [
+                PortChange {
+                    key: "server-port",
+                    old: 25565,
+                    new: 25567,
+                    conflict: PortConflict::Server("other".to_owned()),
+                },
+                PortChange {
+                    key: "query.port",
+                    old: 25567,
+                    new: 25568,
+                    conflict: PortConflict::Server("third".to_owned()),
+                },
+            ]
+        );
+        assert_eq!(properties.get("server-port"), Some("25567"));
+        assert_eq!(properties.get("query.port"), Some("25568"));
+        // Missing ports are written too
+        assert_eq!(properties.get("rcon.port"), Some("25575"));
+    }
+
+    #[test]
+    fn assign_keeps_rcon_off_own_port() {
+        let mut properties =
+            ServerProperties::parse("server-port=25575\nenable-rcon=true\nrcon.port=25575\n");
+        let (changes, is_changed) =
+            assign_in_properties(&mut properties, "mine", &ReservedPorts::default(), false)
+                .unwrap();
+        assert!(is_changed);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].key, "rcon.port");
+        assert_eq!(changes[0].conflict, PortConflict::Server("mine".to_owned()));
+        assert_eq!(properties.get("rcon.port"), Some("25576"));
+
+        // Nothing to do the second time
+        let (changes, is_changed) =
+            assign_in_properties(&mut properties, "mine", &ReservedPorts::default(), false)
+                .unwrap();
+        assert!(changes.is_empty());
+        assert!(!is_changed);
+    }
+
+    #[test]
+    fn free_port_search() {
+        assert_eq!(next_free_port(25565, |n| n > 25570).unwrap(), 25571);
+        assert!(matches!(
+            next_free_port(25565, |_| false),
+            Err(ServerError::NoFreePort(25565))
+        ));
+        assert!(next_free_port(u16::MAX, |_| true).is_err());
+    }
+}
@@ -0,0 +1,404 @@
+//! A client for the Source RCON protocol, used by Minecraft
+//! servers for remote console access.
+//!
+//! This lets the launcher control servers it didn't start
+//! (or started without a console), as long as `enable-rcon`
+//! is on. See <https://minecraft.wiki/w/RCON>.
+
+use std::{path::Path, time::Duration};
+
+use ql_core::{LAUNCHER_DIR, LaunchedProcess, err, info};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{ServerError, ServerProperties};
+
+const KEYRING_SERVICE: &str = "QuantumLauncher";
+pub(crate) const DEFAULT_PORT: u16 = 25575;
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+const TYPE_RESPONSE: i32 = 0;
+const TYPE_COMMAND: i32 = 2;
+const TYPE_AUTH: i32 = 3;
+
+/// Minecraft rejects longer requests
+const MAX_REQUEST_BODY: usize = 1446;
+/// Minecraft never sends more than 4096 bytes of body per packet
+const MAX_RESPONSE_BODY: usize = 4096;
+
+const RCON_ERR_PREFIX: &str = "while using RCON:\n";
+
+#[derive(Debug, Error)]
+pub enum RconError {
+    #[error("{RCON_ERR_PREFIX}{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{RCON_ERR_PREFIX}connection timed out")]
+    Timeout,
+    #[error("{RCON_ERR_PREFIX}wrong password")]
+    AuthFailed,
+    #[error("{RCON_ERR_PREFIX}command is too long ({0} bytes, max {MAX_REQUEST_BODY})")]
+    CommandTooLong(usize),
+    #[error("{RCON_ERR_PREFIX}invalid packet from server")]
+    InvalidPacket,
+    #[error("{RCON_ERR_PREFIX}it isn't enabled for this server (enable-rcon=false)")]
+    NotEnabled,
+}
+
+/// A connection to a server's RCON port.
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl RconClient {
+    /// Connects and logs in.
+    ///
+    /// # Errors
+    /// - The server isn't reachable (not running, wrong port)
+    /// - The password is wrong
+    pub async fn connect(address: &str, password: &str) -> Result<Self, RconError> {
+        let stream = tokio::time::timeout(TIMEOUT, TcpStream::connect(address))
+            .await
+            .map_err(|_| RconError::Timeout)??;
+        let mut client = Self { stream, next_id: 1 };
+
+        let id = client.send(TYPE_AUTH, password).await?;
+        // Some servers send an empty response before the auth response
+        loop {
+            let (resp_id, kind, _) = client.receive().await?;
+            if resp_id == -1 {
+                return Err(RconError::AuthFailed);
+            }
+            if resp_id == id && kind == TYPE_COMMAND {
+                return Ok(client);
+            }
+        }
+    }
+
+    /// Runs a command, returning its output.
+    ///
+    /// # Errors
+    /// - The command is too long
+    /// - The connection was lost
+    pub async fn command(&mut self, command: &str) -> Result<String, RconError> {
+        let command = command.strip_prefix('/').unwrap_or(command);
+        if command.len() > MAX_REQUEST_BODY {
+            return Err(RconError::CommandTooLong(command.len()));
+        }
+        let id = self.send(TYPE_COMMAND, command).await?;
+        // Long responses are split into multiple packets,
+        // with no marker for the last one. So send an invalid
+        // packet after: the server answers it only after it's done
+        // with the command, marking the end of the output.
+        let end_id = self.send(TYPE_RESPONSE, "").await?;
+
+        let mut output = String::new();
+        loop {
+            let (resp_id, _, body) = self.receive().await?;
+            if resp_id == end_id {
+                return Ok(output);
+            }
+            if resp_id == id {
+                output.push_str(&body);
+            }
+        }
+    }
+
+    async fn send(&mut self, kind: i32, body: &str) -> Result<i32, RconError> {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+
+        let len = i32::try_from(body.len() + 10).map_err(|_| RconError::InvalidPacket)?;
+        let mut packet = Vec::with_capacity(body.len() + 14);
+        packet.extend_from_slice(&len.to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+
+        self.stream.write_all(&packet).await?;
+        Ok(id)
+    }
+
+    /// Reads a packet as `(id, type, body)`.
+    async fn receive(&mut self) -> Result<(i32, i32, String), RconError> {
+        tokio::time::timeout(TIMEOUT, async {
+            let len = self.stream.read_i32_le().await?;
+            let len = usize::try_from(len).map_err(|_| RconError::InvalidPacket)?;
+            if !(10..=MAX_RESPONSE_BODY + 10).contains(&len) {
+                return Err(RconError::InvalidPacket);
+            }
+            let mut data = vec![0; len];
+            self.stream.read_exact(&mut data).await?;
+
+            let id = i32::from_le_bytes(data[0..4].try_into().unwrap());
+            let kind = i32::from_le_bytes(data[4..8].try_into().unwrap());
+            let body = String::from_utf8_lossy(&data[8..len - 2]).into_owned();
+            Ok((id, kind, body))
+        })
+        .await
+        .map_err(|_| RconError::Timeout)?
+    }
+}
+
+/// Turns on RCON for a server with a generated password
+/// (also saved in the system keyring), if it isn't configured yet.
+///
+/// Servers where `enable-rcon` was explicitly turned off
+/// are left alone. If it's on but has no password,
+/// a password is generated.
+///
+/// # Errors
+/// If `server.properties` couldn't be read or written.
+pub async fn setup_rcon(server_name: &str, server_dir: &Path) -> Result<(), ServerError> {
+    let mut properties = ServerProperties::load_from_dir(server_dir)
+        .await?
+        .unwrap_or_default();
+
+    let has_password = properties
+        .get("rcon.password")
+        .is_some_and(|n| !n.is_empty());
+    match properties.get("enable-rcon") {
+        Some("true") if has_password => return Ok(()),
+        Some("true") | None => {}
+        Some(_) => return Ok(()),
+    }
+
+    info!("Setting up RCON for server {server_name}");
+    let password = match properties.get("rcon.password") {
+        Some(n) if has_password => n.to_owned(),
+        _ => get_rcon_password(server_name)
+            .await
+            .flatten()
+            .unwrap_or_else(generate_password),
+    };
+    if let Err(err) = set_rcon_password(server_name, password.clone()).await {
+        // Not fatal, it's in `server.properties` too
+        err!("Couldn't save RCON password to keyring: {err}");
+    }
+
+    properties.set("enable-rcon", "true");
+    properties.set("rcon.password", password);
+    if properties.get("rcon.port").is_none() {
+        properties.set("rcon.port", DEFAULT_PORT.to_string());
+    }
+    properties.save_to_dir(server_dir).await?;
+    Ok(())
+}
+
+/// Connects to the RCON port of a (running) server,
+/// using its `server.properties` and saved password.
+///
+/// # Errors
+/// - RCON isn't enabled for the server
+/// - The server isn't running/reachable
+/// - The password is wrong
+pub async fn connect_rcon(server_name: &str) -> Result<RconClient, ServerError> {
+    let server_dir = LAUNCHER_DIR.join("servers").join(server_name);
+    let properties = ServerProperties::load_from_dir(&server_dir)
+        .await?
+        .unwrap_or_default();
+    if properties.get("enable-rcon") != Some("true") {
+        return Err(RconError::NotEnabled.into());
+    }
+
+    let port = properties
+        .get("rcon.port")
+        .and_then(|n| n.trim().parse().ok())
+        .unwrap_or(DEFAULT_PORT);
+    let host = match properties.get("server-ip") {
+        Some(ip) if !ip.trim().is_empty() => ip.trim(),
+        _ => "127.0.0.1",
+    };
+    // The file is what the server actually uses, but
+    // fall back to the keyring in case it was cleared
+    let password = match properties.get("rcon.password") {
+        Some(n) if !n.is_empty() => n.to_owned(),
+        _ => get_rcon_password(server_name)
+            .await
+            .flatten()
+            .unwrap_or_default(),
+    };
+
+    Ok(RconClient::connect(&format!("{host}:{port}"), &password).await?)
+}
+
+/// Sends a command to a server's console.
+///
+/// Writes to the console (stdin) of `process` if possible,
+/// falling back to RCON otherwise (eg: the server was started
+/// in another session, or with logging disabled).
+///
+/// Returns the command output for RCON. With stdin,
+/// the output shows up in the server log instead, so it's `None`.
+///
+/// # Errors
+/// If neither stdin nor RCON worked.
+pub async fn send_console_command(
+    process: Option<&LaunchedProcess>,
+    server_name: &str,
+    command: &str,
+) -> Result<Option<String>, ServerError> {
+    if let Some(process) = process {
+        match process.send_command(command).await {
+            Ok(()) => return Ok(None),
+            Err(ql_core::ConsoleError::NoStdin) => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    let mut client = connect_rcon(server_name).await?;
+    Ok(Some(client.command(command).await?))
+}
+
+/// Returns `None` if the keyring couldn't be accessed.
+async fn get_rcon_password(server_name: &str) -> Option<Option<String>> {
+    let user = format!("#rcon-{server_name}");
+    // The keyring can block (eg: on D-Bus)
+    tokio::task::spawn_blocking(move || {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, &user).ok()?;
+        match entry.get_password() {
+            Ok(n) => Some(Some(n)),
+            Err(keyring::Error::NoEntry) => Some(None),
+            Err(err) => {
+                err!("Couldn't read RCON password from keyring: {err}");
+                None
+            }
+        }
+    })
+    .await
+    .ok()?
+}
+
+async fn set_rcon_password(server_name: &str, password: String) -> Result<(), keyring::Error> {
+    let user = format!("#rcon-{server_name}");
+    tokio::task::spawn_blocking(move || {
+        keyring::Entry::new(KEYRING_SERVICE, &user)?.set_password(&password)
+    })
+    .await
+    .map_err(|err| keyring::Error::PlatformFailure(Box::new(err)))?
+}
+
+pub(crate) fn generate_password() -> String {
+    use rand::distr::{Alphanumeric, SampleString};
+    Alphanumeric.sample_string(&mut rand::rng(), 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Reads a packet as `(id, type, body)`,
+    /// checking its framing.
+    async fn read_packet(stream: &mut TcpStream) -> (i32, i32, String) {
+        let len = usize::try_from(stream.read_i32_le().await.unwrap()).unwrap();
+        let mut data = vec![0; len];
+        stream.read_exact(&mut data).await.unwrap();
+        assert_eq!(&data[len - 2..], [0, 0], "body and padding end with nulls");
+        let id = i32::from_le_bytes(data[0..4].try_into().unwrap());
+        let kind = i32::from_le_bytes(data[4..8].try_into().unwrap());
+        let body = String::from_utf8(data[8..len - 2].to_vec()).unwrap();
+        (id, kind, body)
+    }
+
+    async fn write_packet(stream: &mut TcpStream, id: i32, kind: i32, body: &str) {
+        let len = i32::try_from(body.len() + 10).unwrap();
+        let mut packet = Vec::new();
+        packet.extend_from_slice(&len.to_le_bytes());
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+        stream.write_all(&packet).await.unwrap();
+    }
+
+    /// A fake server, running `handle` for the first connection.
+    async fn fake_server<F, Fut>(handle: F) -> String
+    where
+        F: FnOnce(TcpStream) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            handle(stream).await;
+        });
+        address
+    }
+
+    /// Accepts the password `hunter2`, after an empty response
+    /// (like some servers send).
+    async fn log_in(stream: &mut TcpStream) {
+        let (id, kind, body) = read_packet(stream).await;
+        assert_eq!((kind, body.as_str()), (TYPE_AUTH, "hunter2"));
+        write_packet(stream, id, TYPE_RESPONSE, "").await;
+        write_packet(stream, id, TYPE_COMMAND, "").await;
+    }
+
+    #[tokio::test]
+    async fn command_output_until_end_marker() {
+        let address = fake_server(|mut stream| async move {
+            log_in(&mut stream).await;
+
+            let (id, kind, body) = read_packet(&mut stream).await;
+            assert_eq!((kind, body.as_str()), (TYPE_COMMAND, "list"));
+            let (end_id, kind, body) = read_packet(&mut stream).await;
+            assert_eq!((kind, body.as_str()), (TYPE_RESPONSE, ""));
+            assert_ne!(id, end_id);
+
+            // Split over several packets
+            write_packet(&mut stream, id, TYPE_RESPONSE, "There are 0 of ").await;
+            write_packet(&mut stream, id, TYPE_RESPONSE, "20 players online").await;
+            write_packet(&mut stream, end_id, TYPE_RESPONSE, "Unknown request 0").await;
+        })
+        .await;
+
+        let mut client = RconClient::connect(&address, "hunter2").await.unwrap();
+        assert_eq!(
+            client.command("/list").await.unwrap(),
+            "There are 0 of 20 players online"
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_password() {
+        let address = fake_server(|mut stream| async move {
+            let (_, kind, _) = read_packet(&mut stream).await;
+            assert_eq!(kind, TYPE_AUTH);
+            write_packet(&mut stream, -1, TYPE_COMMAND, "").await;
+        })
+        .await;
+
+        assert!(matches!(
+            RconClient::connect(&address, "wrong").await,
+            Err(RconError::AuthFailed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn invalid_requests_and_responses() {
+        let address = fake_server(|mut stream| async move {
+            log_in(&mut stream).await;
+            read_packet(&mut stream).await;
+            read_packet(&mut stream).await;
+            // Shorter than the id and type
+            stream.write_all(&4_i32.to_le_bytes()).await.unwrap();
+        })
+        .await;
+
+        let mut client = RconClient::connect(&address, "hunter2").await.unwrap();
+        assert!(matches!(
+            client.command(&"a".repeat(MAX_REQUEST_BODY + 1)).await,
+            Err(RconError::CommandTooLong(_))
+        ));
+        assert!(matches!(
+            client.command("list").await,
+            Err(RconError::InvalidPacket)
+        ));
+    }
+}
@@ -5,15 +5,26 @@ use std::{
 };
 
 use ql_core::{
-    GenericProgress, Instance, IntoIoError, LAUNCHER_DIR, LaunchedProcess, Loader,
+    GenericProgress, Instance, IntoIoError, LAUNCHER_DIR, LaunchedProcess, Loader, err,
     find_forge_shim_file, info,
     json::{InstanceConfigJson, VersionDetails},
     no_window, pt,
 };
 use ql_java_handler::{JavaVersion, get_java_binary};
-use tokio::{process::Command, sync::Mutex};
+use ql_mod_manager::loaders::{buildtools, bungeecord, paper::PaperProject};
+use tokio::{
+    process::{Child, Command},
+    sync::Mutex,
+};
+
+use crate::{
+    ServerError, ServerProperties, assign_ports, is_eula_accepted, network::DEFAULT_SERVER_PORT,
+    setup_rcon,
+};
 
-use crate::ServerError;
+/// The EULA was added in 1.7.10
+const V_1_7_10: &str = "2014-05-14T17:29:23+00:00";
+const DEFAULT_MOTD: &str = "A Minecraft Server";
 
 /// Runs a server.
 ///
@@ -29,6 +40,11 @@ use crate::ServerError;
 /// # Errors
 /// - Instance `config.json` couldn't be read or parsed
 /// - Instance `details.json` couldn't be read or parsed
+/// - The Minecraft EULA wasn't accepted in `eula.txt`
+///   ([`ServerError::EulaNotAccepted`], see [`crate::accept_eula`])
+/// - The server's port is taken, eg: it's already running
+///   ([`ServerError::PortInUse`])
+/// - No free port was found (see [`crate::assign_ports`])
 /// - Java binary path could not be obtained
 /// - Java could not be installed (if not found)
 /// - `Command` couldn't be spawned (IO Error)
@@ -39,6 +55,9 @@ pub async fn run(
     java_install_progress: Option<Sender<GenericProgress>>,
 ) -> Result<LaunchedProcess, ServerError> {
     let launcher = ServerLauncher::new(&name).await?;
+    if launcher.needs_eula() && !is_eula_accepted(&launcher.dir).await {
+        return Err(ServerError::EulaNotAccepted(name.to_string()));
+    }
 
     let server_jar_path = launcher.get_server_jar().await?;
 
@@ -46,13 +65,33 @@ pub async fn run(
 
     let java_args = launcher.get_java_args(&server_jar_path).await?;
     let mut game_args = launcher.config.game_args.clone().unwrap_or_default();
-    game_args.push("nogui".to_owned());
+    if !launcher.is_proxy() {
+        game_args.push("nogui".to_owned());
+    }
 
     info!("Java: {java_path:?}\n");
     info!("Java args: {java_args:?}\n");
     info!("Server args: {game_args:?}\n");
 
-    let mut command = Command::new(java_path);
+    if !launcher.is_classic_server() && !launcher.is_proxy() {
+        // So the server can be controlled even without
+        // its console (eg: from another launcher session)
+        if let Err(err) = setup_rcon(&name, &launcher.dir).await {
+            err!("Couldn't set up RCON: {err}");
+        }
+    }
+    // Running several servers at once
+    assign_ports(&Instance::server(&name), true).await?;
+
+    let prefix = launcher.get_limits_prefix(&name).await;
+    let mut command = if prefix.is_empty() {
+        Command::new(java_path)
+    } else {
+        info!("Prefix: {prefix:?}\n");
+        let mut command = Command::new(&prefix[0]);
+        command.args(&prefix[1..]).arg(java_path);
+        command
+    };
     command
         .args(java_args.iter().chain(game_args.iter()))
         .current_dir(&launcher.dir)
@@ -74,14 +113,19 @@ pub async fn run(
     } else {
         pt!("No ID found!");
     }
+    let child = Arc::new(Mutex::new(child));
+    if launcher.config.lan_broadcast.unwrap_or_default() {
+        launcher.broadcast_lan(&child).await;
+    }
     Ok(LaunchedProcess {
-        child: Arc::new(Mutex::new(child)),
+        child,
         instance: Instance::server(&name),
         is_classic_server: launcher.is_classic_server(),
+        is_proxy: launcher.is_proxy(),
     })
 }
 
-struct ServerLauncher {
+pub(crate) struct ServerLauncher {
     dir: PathBuf,
     version_json: VersionDetails,
     config: InstanceConfigJson,
@@ -89,7 +133,10 @@ struct ServerLauncher {
 
 impl ServerLauncher {
     async fn new(name: &str) -> Result<Self, ServerError> {
-        let dir = LAUNCHER_DIR.join("servers").join(name);
+        Self::from_dir(LAUNCHER_DIR.join("servers").join(name)).await
+    }
+
+    pub(crate) async fn from_dir(dir: PathBuf) -> Result<Self, ServerError> {
         Ok(Self {
             version_json: VersionDetails::load_from_path(&dir).await?,
             config: InstanceConfigJson::read_from_dir(&dir).await?,
@@ -105,11 +152,60 @@ impl ServerLauncher {
         self.config.is_classic_server.unwrap_or_default()
     }
 
+    fn is_proxy(&self) -> bool {
+        self.config.mod_type.is_proxy()
+    }
+
+    fn needs_eula(&self) -> bool {
+        !self.is_proxy() && !self.is_classic_server() && self.version_json.is_after_or_eq(V_1_7_10)
+    }
+
+    /// Commands to run Java through, for the
+    /// server's [`ql_core::json::instance_config::ResourceLimits`]
+    async fn get_limits_prefix(&self, name: &str) -> Vec<String> {
+        let Some(limits) = &self.config.resource_limits else {
+            return Vec::new();
+        };
+        #[cfg(target_os = "linux")]
+        {
+            crate::limits::limits_prefix(name, &self.dir, limits, self.config.ram_in_mb).await
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            _ = limits;
+            err!("Resource limits are only supported on Linux, running {name} without them");
+            Vec::new()
+        }
+    }
+
+    async fn broadcast_lan(&self, child: &Arc<Mutex<Child>>) {
+        if self.is_proxy() || self.is_classic_server() {
+            return;
+        }
+        let properties = ServerProperties::load_from_dir(&self.dir)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let motd = properties.get("motd").unwrap_or(DEFAULT_MOTD).to_owned();
+        let port = properties
+            .get("server-port")
+            .and_then(|n| n.trim().parse().ok())
+            .unwrap_or(DEFAULT_SERVER_PORT);
+        crate::lan::broadcast_lan(child, motd, port);
+    }
+
     async fn get_java(
         &self,
         java_install_progress: Option<&Sender<GenericProgress>>,
     ) -> Result<PathBuf, ServerError> {
-        let version = if let Some(version) = self.version_json.javaVersion.clone() {
+        let version = if let Some(version) = self.config.java_override_version {
+            version.into()
+        } else if self.is_proxy() {
+            // Proxies don't depend on the game version,
+            // and need a recent Java
+            JavaVersion::Java21
+        } else if let Some(version) = self.version_json.javaVersion.clone() {
             version.into()
         } else {
             JavaVersion::Java8
@@ -122,23 +218,39 @@ impl ServerLauncher {
         Ok(path)
     }
 
-    async fn get_server_jar(&self) -> Result<PathBuf, ServerError> {
+    pub(crate) async fn get_server_jar(&self) -> Result<PathBuf, ServerError> {
         Ok(if let Some(custom_jar) = &self.config.custom_jar {
             // Should I prioritize Fabric/Forge/Paper over a custom JAR?
             PathBuf::from(&custom_jar.name)
+        } else if let Some(jar) = &self.config.server_jar {
+            self.dir.join(jar)
+        } else if let Some(project) = PaperProject::from_loader(self.config.mod_type) {
+            self.dir.join(project.jar_name())
+        } else if let Some(jar) = buildtools::server_jar_name(self.config.mod_type) {
+            self.dir.join(jar)
         } else {
             let regular = self.dir.join("server.jar");
             match self.config.mod_type {
                 Loader::Fabric | Loader::Quilt => self.dir.join("fabric-server-launch.jar"),
-                Loader::Forge => find_forge_shim_file(&self.dir)
-                    .await
-                    .ok_or(ServerError::NoForgeShimFound)?,
-                Loader::Paper => self.dir.join("paper_server.jar"),
+                Loader::Forge => match find_forge_shim_file(&self.dir).await {
+                    Some(shim) => shim,
+                    // Run through the args file instead
+                    None if self.args_file().await.is_some() => regular,
+                    None => return Err(ServerError::NoForgeShimFound),
+                },
+                Loader::BungeeCord => self.dir.join(bungeecord::JAR_NAME),
                 Loader::OptiFine => {
                     debug_assert!(false, "Optifine can't run on servers");
                     regular
                 }
-                Loader::NeoForge
+                // Paper-like and BuildTools servers are handled above
+                Loader::Paper
+                | Loader::Folia
+                | Loader::Purpur
+                | Loader::Velocity
+                | Loader::Spigot
+                | Loader::Bukkit
+                | Loader::NeoForge
                 | Loader::Vanilla
                 | Loader::Liteloader
                 | Loader::Modloader
@@ -147,6 +259,44 @@ impl ServerLauncher {
         })
     }
 
+    /// The `unix_args.txt`/`win_args.txt` file that NeoForge,
+    /// and Forge 1.17 to 1.20.2 (without a shim jar) are run with
+    async fn args_file(&self) -> Option<PathBuf> {
+        #[cfg(target_family = "unix")]
+        const FILENAME: &str = "unix_args.txt";
+        #[cfg(target_os = "windows")]
+        const FILENAME: &str = "win_args.txt";
+        #[cfg(not(any(target_family = "unix", target_os = "windows")))]
+        const FILENAME: &str = "YOUR_OS_IS_UNSUPPORTED";
+
+        let version = self
+            .config
+            .mod_type_info
+            .as_ref()
+            .and_then(|n| n.version.as_deref());
+
+        if self.is_neoforge() {
+            let mut args_path = self.dir.join("libraries/net/neoforged/neoforge");
+            if let Some(ver) = version {
+                args_path = args_path.join(ver);
+            }
+            Some(args_path.join(FILENAME))
+        } else if self.config.mod_type == Loader::Forge
+            && self.config.custom_jar.is_none()
+            && self.config.server_jar.is_none()
+        {
+            let args_path = self
+                .dir
+                .join("libraries/net/minecraftforge/forge")
+                .join(format!("{}-{}", self.version_json.get_id(), version?))
+                .join(FILENAME);
+            (find_forge_shim_file(&self.dir).await.is_none() && args_path.is_file())
+                .then_some(args_path)
+        } else {
+            None
+        }
+    }
+
     async fn get_java_args(&self, jar: &Path) -> Result<Vec<String>, ServerError> {
         let mut java_args: Vec<String> = self.config.get_java_args(&[]);
         java_args.push(self.config.get_ram_argument());
@@ -171,28 +321,11 @@ impl ServerLauncher {
                     ));
                 }
             }
-        } else if self.is_neoforge() {
-            #[cfg(target_family = "unix")]
-            const FILENAME: &str = "unix_args.txt";
-            #[cfg(target_os = "windows")]
-            const FILENAME: &str = "win_args.txt";
-            #[cfg(not(any(target_family = "unix", target_os = "windows")))]
-            const FILENAME: &str = "YOUR_OS_IS_UNSUPPORTED";
-
-            let mut args_path = self.dir.join("libraries/net/neoforged/neoforge");
-            if let Some(ver) = self
-                .config
-                .mod_type_info
-                .as_ref()
-                .and_then(|n| n.version.as_deref())
-            {
-                args_path = args_path.join(ver);
-            }
-            let args_path = args_path.join(FILENAME);
+        }
 
-            let args = tokio::fs::read_to_string(&args_path)
-                .await
-                .path(args_path)?;
+        let args_file = self.args_file().await;
+        if let Some(args_path) = &args_file {
+            let args = tokio::fs::read_to_string(args_path).await.path(args_path)?;
             java_args.extend(
                 args.lines()
                     .flat_map(str::split_whitespace)
@@ -202,7 +335,7 @@ impl ServerLauncher {
         }
 
         let is_cl_sr = self.is_classic_server();
-        if !self.is_neoforge() {
+        if args_file.is_none() {
             java_args.push(if is_cl_sr { "-cp" } else { "-jar" }.to_owned());
             java_args.push(
                 jar.to_str()
@@ -0,0 +1,338 @@
+//! Reading and editing `server.properties`.
+//!
+//! The file is a Java `.properties` file. This keeps everything
+//! that isn't touched exactly as-is (comments, order, blank lines,
+//! formatting), so saving doesn't scramble the user's file.
+
+use std::{fmt::Display, path::Path};
+
+use ql_core::{IntoIoError, IoError, LAUNCHER_DIR, json::VersionDetails};
+
+mod schema;
+pub use schema::{PropertyInfo, PropertyKind, known_properties};
+
+#[derive(Debug, Clone)]
+enum Line {
+    /// Comments and blank lines, kept verbatim
+    Other(String),
+    Entry {
+        key: String,
+        value: String,
+        /// The original text of the key and separator (eg: `motd=`),
+        /// reused when the value is changed
+        prefix: String,
+        /// The original text (including line continuations),
+        /// `None` if the value was changed
+        raw: Option<String>,
+    },
+}
+
+/// An order- and comment-preserving `server.properties` file.
+#[derive(Debug, Clone, Default)]
+pub struct ServerProperties {
+    lines: Vec<Line>,
+}
+
+impl ServerProperties {
+    /// Loads `server.properties` of the server named `server_name`.
+    ///
+    /// Returns `None` if it doesn't exist yet
+    /// (it's created when the server is first run).
+    #[must_use]
+    pub async fn load(server_name: &str) -> Option<Self> {
+        let server_dir = LAUNCHER_DIR.join("servers").join(server_name);
+        Self::load_from_dir(&server_dir).await.ok().flatten()
+    }
+
+    /// Loads `server.properties` from a server directory.
+    ///
+    /// # Errors
+    /// If the file exists but couldn't be read.
+    pub async fn load_from_dir(server_dir: &Path) -> Result<Option<Self>, IoError> {
+        let path = server_dir.join("server.properties");
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let text = tokio::fs::read_to_string(&path).await.path(path)?;
+        Ok(Some(Self::parse(&text)))
+    }
+
+    /// Saves the configuration to a server with name `server_name`,
+    /// as a `server.properties` file.
+    ///
+    /// # Errors
+    /// If server doesn't exist or user doesn't have permission to access server
+    pub async fn save(&self, server_name: &str) -> Result<(), IoError> {
+        let server_dir = LAUNCHER_DIR.join("servers").join(server_name);
+        self.save_to_dir(&server_dir).await
+    }
+
+    /// Saves as `server.properties` inside a server directory.
+    ///
+    /// # Errors
+    /// If the directory doesn't exist or user doesn't have permission to access it
+    pub async fn save_to_dir(&self, server_dir: &Path) -> Result<(), IoError> {
+        let path = server_dir.join("server.properties");
+        tokio::fs::write(&path, self.to_string()).await.path(path)
+    }
+
+    #[must_use]
+    pub fn parse(text: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut natural = text.split_inclusive('\n').peekable();
+
+        while let Some(line) = natural.next() {
+            let trimmed = line.trim_start_matches([' ', '\t', '\x0c']);
+            if trimmed.trim_end_matches(['\r', '\n']).is_empty() || trimmed.starts_with(['#', '!'])
+            {
+                lines.push(Line::Other(line.to_owned()));
+                continue;
+            }
+
+            // Join line continuations (odd number of trailing backslashes)
+            let mut raw = line.to_owned();
+            let mut logical = strip_newline(trimmed).to_owned();
+            while ends_with_continuation(&logical) {
+                logical.pop();
+                let Some(next) = natural.next() else {
+                    break;
+                };
+                raw.push_str(next);
+                logical.push_str(strip_newline(next).trim_start_matches([' ', '\t', '\x0c']));
+            }
+
+            let (key, value, prefix_len) = split_entry(&logical);
+            let indent = line.len() - trimmed.len();
+            lines.push(Line::Entry {
+                key: unescape(key),
+                value: unescape(value),
+                prefix: raw[..indent].to_owned() + &logical[..prefix_len],
+                raw: Some(raw),
+            });
+        }
+        Self { lines }
+    }
+
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    /// Sets a property, keeping its position (and formatting)
+    /// if it exists, or adding it at the end otherwise.
+    pub fn set(&mut self, key: &str, value: impl Into<String>) {
+        let value = value.into();
+        for line in &mut self.lines {
+            if let Line::Entry {
+                key: k,
+                value: v,
+                raw,
+                ..
+            } = line
+            {
+                if k == key {
+                    if *v != value {
+                        *v = value;
+                        *raw = None;
+                    }
+                    return;
+                }
+            }
+        }
+
+        if let Some(
+            Line::Other(last)
+            | Line::Entry {
+                raw: Some(last), ..
+            },
+        ) = self.lines.last_mut()
+        {
+            if !last.ends_with('\n') {
+                last.push('\n');
+            }
+        }
+        self.lines.push(Line::Entry {
+            key: key.to_owned(),
+            value,
+            prefix: format!("{}=", escape(key, true)),
+            raw: None,
+        });
+    }
+
+    /// Removes a property, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let i = self
+            .lines
+            .iter()
+            .position(|n| matches!(n, Line::Entry { key: k, .. } if k == key))?;
+        match self.lines.remove(i) {
+            Line::Entry { value, .. } => Some(value),
+            Line::Other(_) => None,
+        }
+    }
+
+    /// All properties, in file order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.lines.iter().filter_map(|n| match n {
+            Line::Entry { key, value, .. } => Some((key.as_str(), value.as_str())),
+            Line::Other(_) => None,
+        })
+    }
+
+    /// Checks all known properties against the schema for `version`.
+    ///
+    /// Returns `(key, problem)` pairs for invalid values.
+    #[must_use]
+    pub fn validate(&self, version: &VersionDetails) -> Vec<(String, String)> {
+        known_properties(version)
+            .filter_map(|info| {
+                let value = self.get(info.key)?;
+                let err = info.kind.validate(value).err()?;
+                Some((info.key.to_owned(), err))
+            })
+            .collect()
+    }
+}
+
+impl Display for ServerProperties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            match line {
+                Line::Other(raw) | Line::Entry { raw: Some(raw), .. } => f.write_str(raw)?,
+                Line::Entry {
+                    value,
+                    prefix,
+                    raw: None,
+                    ..
+                } => writeln!(f, "{prefix}{}", escape(value, false))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn strip_newline(line: &str) -> &str {
+    line.trim_end_matches('\n').trim_end_matches('\r')
+}
+
+fn ends_with_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+/// Splits a logical line into `(key, value, prefix_len)`,
+/// where `prefix_len` is the length of the key and separator.
+fn split_entry(line: &str) -> (&str, &str, usize) {
+    let mut escaped = false;
+    let mut key_end = line.len();
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if matches!(c, '=' | ':' | ' ' | '\t' | '\x0c') {
+            key_end = i;
+            break;
+        }
+    }
+
+    let rest = &line[key_end..];
+    let mut value = rest.trim_start_matches([' ', '\t', '\x0c']);
+    if let Some(n) = value.strip_prefix(['=', ':']) {
+        value = n.trim_start_matches([' ', '\t', '\x0c']);
+    }
+    let prefix_len = line.len() - value.len();
+    (&line[..key_end], value, prefix_len)
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('f') => out.push('\x0c'),
+            Some('u') => {
+                let hex: String = chars.clone().take(4).collect();
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(n) if hex.len() == 4 => {
+                        out.push(n);
+                        chars.nth(3);
+                    }
+                    // Malformed, keep as-is
+                    _ => out.push_str("\\u"),
+                }
+            }
+            Some(n) => out.push(n),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Escapes like Java's `Properties.store` (with a `Writer`,
+/// so non-ASCII characters are written as-is).
+fn escape(text: &str, is_key: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            ' ' if is_key || i == 0 => out.push_str("\\ "),
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\x0c' => out.push_str("\\f"),
+            '=' | ':' | '#' | '!' => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ServerProperties;
+
+    const FILE: &str = "#Minecraft server properties\n#Mon Jan 01 00:00:00 UTC 2024\nmotd=A Minecraft Server\n\n! other comment\nresource-pack=https\\://example.com/pack.zip\nlevel-name : my \\\n    world\nserver-port=25565\n";
+
+    #[test]
+    fn roundtrip_unchanged() {
+        let props = ServerProperties::parse(FILE);
+        assert_eq!(props.to_string(), FILE);
+        assert_eq!(props.get("motd"), Some("A Minecraft Server"));
+        assert_eq!(
+            props.get("resource-pack"),
+            Some("https://example.com/pack.zip")
+        );
+        assert_eq!(props.get("level-name"), Some("my world"));
+    }
+
+    #[test]
+    fn edit_keeps_order_and_comments() {
+        let mut props = ServerProperties::parse(FILE);
+        props.set("level-name", "other");
+        props.set("motd", "Hi: \u{e9}");
+        props.set("pvp", "false");
+        assert_eq!(
+            props.to_string(),
+            "#Minecraft server properties\n#Mon Jan 01 00:00:00 UTC 2024\nmotd=Hi\\: \u{e9}\n\n! other comment\nresource-pack=https\\://example.com/pack.zip\nlevel-name : other\nserver-port=25565\npvp=false\n"
+        );
+        assert_eq!(props.remove("server-port").as_deref(), Some("25565"));
+        assert_eq!(props.get("server-port"), None);
+    }
+
+    #[test]
+    fn unicode_escapes() {
+        let props = ServerProperties::parse("motd=\\u00a7aGreen\nkey\\ with\\ space=1");
+        assert_eq!(props.get("motd"), Some("\u{a7}aGreen"));
+        assert_eq!(props.get("key with space"), Some("1"));
+    }
+}
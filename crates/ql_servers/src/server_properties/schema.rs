@@ -0,0 +1,201 @@
+//! Known `server.properties` keys, their types and defaults,
+//! used for validating and editing them in a user-friendly way.
+
+use PropertyKind::{Bool, Enum, String as Str};
+use ql_core::json::VersionDetails;
+
+// Release times, for `since`/`until` below
+const V_1_8: &str = "2014-09-02T08:24:35+00:00";
+const V_1_11: &str = "2016-11-14T14:34:40+00:00";
+const V_1_13: &str = "2018-07-18T15:11:46+00:00";
+const V_1_14: &str = "2019-04-23T14:52:44+00:00";
+const V_1_16: &str = "2020-06-23T16:20:52+00:00";
+const V_1_17: &str = "2021-06-08T11:00:40+00:00";
+const V_1_18: &str = "2021-11-30T09:16:29+00:00";
+const V_1_19: &str = "2022-06-07T09:42:18+00:00";
+const V_1_19_3: &str = "2022-12-07T08:17:18+00:00";
+const V_1_20_2: &str = ql_core::json::V_1_20_2;
+const V_1_20_3: &str = "2023-12-05T12:10:32+00:00";
+const V_1_20_5: &str = "2024-04-23T12:15:02+00:00";
+const V_1_21: &str = "2024-06-13T08:24:03+00:00";
+const V_1_21_2: &str = "2024-10-22T12:26:12+00:00";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyKind {
+    Bool,
+    Int {
+        min: i64,
+        max: i64,
+    },
+    String,
+    /// One of a fixed set of values
+    Enum(&'static [&'static str]),
+}
+
+impl PropertyKind {
+    /// Checks whether `value` is valid for this kind.
+    ///
+    /// # Errors
+    /// A user-facing description of what's wrong.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            PropertyKind::Bool => match value {
+                "true" | "false" => Ok(()),
+                _ => Err("should be true or false".to_owned()),
+            },
+            PropertyKind::Int { min, max } => match value.trim().parse::<i64>() {
+                Ok(n) if (*min..=*max).contains(&n) => Ok(()),
+                Ok(_) => Err(format!("should be between {min} and {max}")),
+                Err(_) => Err("should be a whole number".to_owned()),
+            },
+            PropertyKind::String => Ok(()),
+            PropertyKind::Enum(values) => {
+                if values.contains(&value) {
+                    Ok(())
+                } else {
+                    Err(format!("should be one of: {}", values.join(", ")))
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PropertyInfo {
+    pub key: &'static str,
+    pub kind: PropertyKind,
+    pub default: &'static str,
+    pub description: &'static str,
+    /// Release time of the first version with this property
+    pub since: Option<&'static str>,
+    /// Release time of the first version *without* this property
+    pub until: Option<&'static str>,
+}
+
+impl PropertyInfo {
+    /// Whether this property exists in `version`.
+    #[must_use]
+    pub fn is_supported(&self, version: &VersionDetails) -> bool {
+        self.since.is_none_or(|n| version.is_after_or_eq(n))
+            && self.until.is_none_or(|n| !version.is_after_or_eq(n))
+    }
+
+    /// Looks up a known property for `version`.
+    #[must_use]
+    pub fn get(key: &str, version: &VersionDetails) -> Option<&'static Self> {
+        known_properties(version).find(|n| n.key == key)
+    }
+}
+
+/// All known properties that exist in `version`.
+pub fn known_properties(version: &VersionDetails) -> impl Iterator<Item = &'static PropertyInfo> {
+    PROPERTIES.iter().filter(|n| n.is_supported(version))
+}
+
+const fn prop(
+    key: &'static str,
+    kind: PropertyKind,
+    default: &'static str,
+    description: &'static str,
+) -> PropertyInfo {
+    PropertyInfo {
+        key,
+        kind,
+        default,
+        description,
+        since: None,
+        until: None,
+    }
+}
+
+impl PropertyInfo {
+    const fn since(mut self, release_time: &'static str) -> Self {
+        self.since = Some(release_time);
+        self
+    }
+
+    const fn until(mut self, release_time: &'static str) -> Self {
+        self.until = Some(release_time);
+        self
+    }
+}
+
+const fn int(min: i64, max: i64) -> PropertyKind {
+    PropertyKind::Int { min, max }
+}
+
+const INT_MAX: i64 = i32::MAX as i64;
+const PORT: PropertyKind = int(1, 65535);
+const DIFFICULTIES: &[&str] = &["peaceful", "easy", "normal", "hard"];
+const GAMEMODES: &[&str] = &["survival", "creative", "adventure", "spectator"];
+
+#[rustfmt::skip]
+const PROPERTIES: &[PropertyInfo] = &[
+    prop("accepts-transfers", Bool, "false", "Allow players to be transferred here from other servers").since(V_1_20_5),
+    prop("allow-flight", Bool, "false", "Don't kick players for flying (needed by some mods)"),
+    prop("allow-nether", Bool, "true", "Allow players to travel to the Nether"),
+    prop("broadcast-console-to-ops", Bool, "true", "Send console command output to online operators"),
+    prop("broadcast-rcon-to-ops", Bool, "true", "Send RCON command output to online operators"),
+    prop("bug-report-link", Str, "", "Link shown in the disconnect screen's \"Report Server Bug\" button").since(V_1_21),
+    prop("difficulty", Enum(DIFFICULTIES), "easy", "Difficulty of the world").since(V_1_14),
+    prop("difficulty", int(0, 3), "1", "Difficulty: 0 (peaceful), 1 (easy), 2 (normal), 3 (hard)").until(V_1_14),
+    prop("enable-command-block", Bool, "false", "Allow command blocks to run"),
+    prop("enable-jmx-monitoring", Bool, "false", "Expose server metrics over JMX").since(V_1_16),
+    prop("enable-query", Bool, "false", "Enable the GameSpy4 query protocol"),
+    prop("enable-rcon", Bool, "false", "Allow remote access to the server console"),
+    prop("enable-status", Bool, "true", "Show the server as online in the server list").since(V_1_16),
+    prop("enforce-secure-profile", Bool, "true", "Only allow players with Mojang-signed chat keys").since(V_1_19),
+    prop("enforce-whitelist", Bool, "false", "Kick players not on the whitelist when it's reloaded").since(V_1_13),
+    prop("entity-broadcast-range-percentage", int(10, 1000), "100", "How far away entities are sent to players, in percent").since(V_1_16),
+    prop("force-gamemode", Bool, "false", "Put players in the default game mode every time they join"),
+    prop("function-permission-level", int(1, 4), "2", "Permission level of functions").since(V_1_14),
+    prop("gamemode", Enum(GAMEMODES), "survival", "Default game mode").since(V_1_14),
+    prop("gamemode", int(0, 3), "0", "Default game mode: 0 (survival), 1 (creative), 2 (adventure), 3 (spectator)").until(V_1_14),
+    prop("generate-structures", Bool, "true", "Generate structures (such as villages)"),
+    prop("generator-settings", Str, "{}", "Settings for customized world generation"),
+    prop("hardcore", Bool, "false", "Players are banned on death"),
+    prop("hide-online-players", Bool, "false", "Don't send the player list in status requests").since(V_1_18),
+    prop("initial-disabled-packs", Str, "", "Comma-separated datapacks not enabled when creating the world").since(V_1_19_3),
+    prop("initial-enabled-packs", Str, "vanilla", "Comma-separated datapacks enabled when creating the world").since(V_1_19_3),
+    prop("level-name", Str, "world", "Name of the world folder"),
+    prop("level-seed", Str, "", "Seed of the world (random if empty)"),
+    prop("level-type", Str, "minecraft:normal", "World preset (eg: minecraft:flat, minecraft:large_biomes)"),
+    prop("log-ips", Bool, "true", "Show player IP addresses in the log").since(V_1_20_2),
+    prop("max-build-height", int(64, 256), "256", "Maximum height players can build at").until(V_1_17),
+    prop("max-chained-neighbor-updates", int(-1, INT_MAX), "1000000", "Limit on chained block updates (negative means no limit)").since(V_1_19),
+    prop("max-players", int(0, INT_MAX), "20", "Maximum number of players online at once"),
+    prop("max-tick-time", int(-1, i64::MAX), "60000", "Milliseconds a tick may take before the watchdog stops the server (-1 to disable)").since(V_1_8),
+    prop("max-world-size", int(1, 29_999_984), "29999984", "Maximum radius of the world border, in blocks").since(V_1_8),
+    prop("motd", Str, "A Minecraft Server", "Message shown in the server list"),
+    prop("network-compression-threshold", int(-1, INT_MAX), "256", "Packets larger than this (in bytes) are compressed (-1 to disable)").since(V_1_8),
+    prop("online-mode", Bool, "true", "Check that players are logged in with a Microsoft account"),
+    prop("op-permission-level", int(1, 4), "4", "Default permission level of operators"),
+    prop("pause-when-empty-seconds", int(0, INT_MAX), "60", "Pause the server after this many seconds without players").since(V_1_21_2),
+    prop("player-idle-timeout", int(0, INT_MAX), "0", "Kick players idle for this many minutes (0 to disable)"),
+    prop("prevent-proxy-connections", Bool, "false", "Kick players whose ISP differs from the one Mojang sees").since(V_1_11),
+    prop("previews-chat", Bool, "false", "Enable chat previews").since(V_1_19).until(V_1_19_3),
+    prop("pvp", Bool, "true", "Allow players to hurt each other"),
+    prop("query.port", PORT, "25565", "Port of the query protocol"),
+    prop("rate-limit", int(0, INT_MAX), "0", "Kick players sending more packets per second than this (0 to disable)").since(V_1_16),
+    prop("rcon.password", Str, "", "Password for RCON"),
+    prop("rcon.port", PORT, "25575", "Port of RCON"),
+    prop("region-file-compression", Enum(&["deflate", "lz4", "none"]), "deflate", "Compression of region files").since(V_1_20_5),
+    prop("require-resource-pack", Bool, "false", "Kick players who decline the resource pack").since(V_1_17),
+    prop("resource-pack", Str, "", "Download link of the server resource pack"),
+    prop("resource-pack-id", Str, "", "UUID of the resource pack").since(V_1_20_3),
+    prop("resource-pack-prompt", Str, "", "Message shown when asking to use the resource pack").since(V_1_17),
+    prop("resource-pack-sha1", Str, "", "SHA-1 of the resource pack, to check it").since(V_1_8),
+    prop("server-ip", Str, "", "Address to listen on (empty for all)"),
+    prop("server-port", PORT, "25565", "Port the server listens on"),
+    prop("simulation-distance", int(3, 32), "10", "Distance (in chunks) around players where entities are updated").since(V_1_18),
+    prop("snooper-enabled", Bool, "true", "Send usage statistics to Mojang").until(V_1_18),
+    prop("spawn-animals", Bool, "true", "Spawn animals"),
+    prop("spawn-monsters", Bool, "true", "Spawn monsters"),
+    prop("spawn-npcs", Bool, "true", "Spawn villagers"),
+    prop("spawn-protection", int(0, INT_MAX), "16", "Radius around spawn only operators can build in (0 to disable)"),
+    prop("sync-chunk-writes", Bool, "true", "Write chunks synchronously (safer, but slower)").since(V_1_16),
+    prop("text-filtering-config", Str, "", "Text filtering configuration").since(V_1_16),
+    prop("use-native-transport", Bool, "true", "Use optimized packet handling on Linux").since(V_1_8),
+    prop("view-distance", int(2, 32), "10", "Distance (in chunks) sent to players"),
+    prop("white-list", Bool, "false", "Only allow players on the whitelist"),
+];
@@ -0,0 +1,444 @@
+//! Keeps servers running: restarts them after a crash
+//! (with backoff, giving up on crash loops) and once a day
+//! at a scheduled time, warning players beforehand.
+//!
+//! Settings are in `watchdog.json` and the restart history
+//! in `restarts.json`, both in the server folder.
+
+use std::{
+    process::ExitStatus,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local, NaiveTime, TimeZone, Utc};
+use ql_core::{
+    Instance, IntoIoError, IntoJsonError, LaunchedProcess, err, file_utils::exists, info,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{ServerError, send_console_command};
+
+const CONFIG_FILE: &str = "watchdog.json";
+const HISTORY_FILE: &str = "restarts.json";
+/// Older restarts are dropped from the history
+const HISTORY_LIMIT: usize = 100;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const CONFIG_RELOAD_INTERVAL: Duration = Duration::from_secs(30);
+/// How long the server gets to save and stop
+/// for a scheduled restart before it's killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(60);
+/// Waiting a bit after a scheduled stop,
+/// so that the port is free again
+const SCHEDULED_RESTART_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    /// Start the server again if it exits abnormally
+    pub restart_on_crash: bool,
+    /// Give up restarting after this many crashes
+    /// within `crash_window_minutes` (crash loop)
+    pub max_crashes: u32,
+    pub crash_window_minutes: u32,
+    /// Wait before restarting after a crash,
+    /// doubled for every other recent crash
+    pub backoff_seconds: u32,
+    pub max_backoff_seconds: u32,
+    /// Restart every day at this (local) time
+    pub daily_restart: Option<NaiveTime>,
+    /// Tell players in-game this many seconds
+    /// before a scheduled restart
+    pub warnings: Vec<u32>,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            restart_on_crash: false,
+            max_crashes: 5,
+            crash_window_minutes: 10,
+            backoff_seconds: 5,
+            max_backoff_seconds: 300,
+            daily_restart: None,
+            warnings: vec![600, 300, 60, 30, 10, 5, 4, 3, 2, 1],
+        }
+    }
+}
+
+impl WatchdogConfig {
+    /// Loads the watchdog config of a server (default if not set).
+    ///
+    /// # Errors
+    /// If the config exists but couldn't be read or parsed.
+    pub async fn load(instance: &Instance) -> Result<Self, ServerError> {
+        let path = instance.get_instance_path().join(CONFIG_FILE);
+        if !exists(&path).await {
+            return Ok(Self::default());
+        }
+        let text = tokio::fs::read_to_string(&path).await.path(path)?;
+        Ok(serde_json::from_str(&text).json(text)?)
+    }
+
+    /// # Errors
+    /// If the config couldn't be saved.
+    pub async fn save(&self, instance: &Instance) -> Result<(), ServerError> {
+        let path = instance.get_instance_path().join(CONFIG_FILE);
+        let text = serde_json::to_string_pretty(self).json_to()?;
+        tokio::fs::write(&path, text).await.path(path)?;
+        Ok(())
+    }
+
+    /// How long to wait before restarting after a crash,
+    /// given the times of recent crashes (including this one).
+    ///
+    /// Forgets crashes older than the crash window, and returns
+    /// `None` if there were too many (a crash loop).
+    fn crash_delay(&self, crashes: &mut Vec<Instant>, now: Instant) -> Option<Duration> {
+        let window = Duration::from_secs(u64::from(self.crash_window_minutes) * 60);
+        crashes.retain(|n| now.duration_since(*n) < window);
+        crashes.push(now);
+
+        let count = u32::try_from(crashes.len()).unwrap_or(u32::MAX);
+        if count > self.max_crashes {
+            return None;
+        }
+        let backoff = u64::from(self.backoff_seconds)
+            .saturating_mul(1 << (count - 1).min(16))
+            .min(u64::from(self.max_backoff_seconds));
+        Some(Duration::from_secs(backoff))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RestartKind {
+    /// Exited abnormally and was restarted
+    Crash {
+        exit_code: Option<i32>,
+        delay_seconds: u64,
+    },
+    /// Crashed too often, wasn't restarted
+    CrashLoop { crashes: u32 },
+    /// The daily restart
+    Scheduled,
+}
+
+impl std::fmt::Display for RestartKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RestartKind::Crash {
+                exit_code: Some(code),
+                delay_seconds,
+            } => write!(
+                f,
+                "Crashed (exit code {code}), restarted after {delay_seconds}s"
+            ),
+            RestartKind::Crash {
+                exit_code: None,
+                delay_seconds,
+            } => write!(f, "Crashed, restarted after {delay_seconds}s"),
+            RestartKind::CrashLoop { crashes } => {
+                write!(f, "Crashed {crashes} times in a row, gave up restarting")
+            }
+            RestartKind::Scheduled => write!(f, "Scheduled restart"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartEvent {
+    pub time: DateTime<Utc>,
+    pub kind: RestartKind,
+}
+
+/// Restarts of a server by the watchdog, newest first.
+///
+/// # Errors
+/// If the history exists but couldn't be read or parsed.
+pub async fn restart_history(instance: &Instance) -> Result<Vec<RestartEvent>, ServerError> {
+    let path = instance.get_instance_path().join(HISTORY_FILE);
+    if !exists(&path).await {
+        return Ok(Vec::new());
+    }
+    let text = tokio::fs::read_to_string(&path).await.path(path)?;
+    Ok(serde_json::from_str(&text).json(text)?)
+}
+
+async fn record_restart(instance: &Instance, kind: RestartKind) {
+    async fn inner(instance: &Instance, kind: RestartKind) -> Result<(), ServerError> {
+        let mut history = restart_history(instance).await?;
+        history.insert(
+            0,
+            RestartEvent {
+                time: Utc::now(),
+                kind,
+            },
+        );
+        history.truncate(HISTORY_LIMIT);
+
+        let path = instance.get_instance_path().join(HISTORY_FILE);
+        let text = serde_json::to_string_pretty(&history).json_to()?;
+        tokio::fs::write(&path, text).await.path(path)?;
+        Ok(())
+    }
+
+    if let Err(error) = inner(instance, kind).await {
+        err!("Couldn't save restart history: {error}");
+    }
+}
+
+/// Supervises one server across restarts.
+///
+/// Keep one per server (cloning shares it) for as long as
+/// it should be watched, since it remembers recent crashes.
+///
+/// - Spawn [`Watchdog::restart_scheduler`] every time
+///   the server starts, for the daily restart.
+/// - Call [`Watchdog::restart_after_exit`] every time
+///   it exits, to start it again if needed.
+#[derive(Debug, Clone, Default)]
+pub struct Watchdog {
+    crashes: Arc<Mutex<Vec<Instant>>>,
+    /// Set when the scheduler stopped the server,
+    /// so that the exit isn't taken for a crash or a manual stop
+    is_scheduled_stop: Arc<AtomicBool>,
+}
+
+impl Watchdog {
+    /// Stops the server at its daily restart time (if set in the
+    /// [`WatchdogConfig`]), warning players in-game beforehand.
+    /// [`Watchdog::restart_after_exit`] then starts it again.
+    ///
+    /// Runs until the server exits, so spawn this as a
+    /// separate task after starting the server. Changes to
+    /// the config are picked up within half a minute.
+    ///
+    /// Proxies aren't warned, as they don't have a `say` command.
+    pub async fn restart_scheduler(self, process: LaunchedProcess) {
+        let instance = process.instance.clone();
+        let mut config = WatchdogConfig::default();
+        let mut last_load: Option<Instant> = None;
+        // (restart time, seconds left on the previous check)
+        let mut next: Option<(DateTime<Local>, i64)> = None;
+
+        loop {
+            let has_exited = match process.child.lock().await.try_wait() {
+                Ok(status) => status.is_some(),
+                Err(_) => true,
+            };
+            if has_exited {
+                return;
+            }
+
+            if last_load.is_none_or(|n| n.elapsed() >= CONFIG_RELOAD_INTERVAL) {
+                last_load = Some(Instant::now());
+                match WatchdogConfig::load(&instance).await {
+                    Ok(new) => {
+                        if new.daily_restart != config.daily_restart {
+                            next = None;
+                        }
+                        config = new;
+                    }
+                    Err(error) => err!("Couldn't load watchdog config: {error}"),
+                }
+            }
+
+            if let Some(time) = config.daily_restart {
+                let now = Local::now();
+                let (at, previous) = *next.get_or_insert_with(|| {
+                    let at = next_restart(time, &now);
+                    (at, (at - now).num_seconds())
+                });
+                let left = (at - now).num_seconds();
+
+                if !process.is_proxy {
+                    if let Some(warning) = config
+                        .warnings
+                        .iter()
+                        .map(|n| i64::from(*n))
+                        .filter(|n| *n > 0 && left <= *n && *n < previous)
+                        .min()
+                    {
+                        let command = format!("say Server restarting in {}", countdown(warning));
+                        if let Err(error) =
+                            send_console_command(Some(&process), &instance.name, &command).await
+                        {
+                            err!("Couldn't warn players of restart: {error}");
+                        }
+                    }
+                }
+                next = Some((at, left));
+
+                if left <= 0 {
+                    info!("Scheduled restart of {}", instance.name);
+                    self.is_scheduled_stop.store(true, Ordering::SeqCst);
+                    if let Err(error) = process.stop(STOP_TIMEOUT).await {
+                        err!("Couldn't stop server for scheduled restart: {error}");
+                    }
+                    return;
+                }
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Call this after the server exits. Starts it again if
+    /// it was a scheduled restart, or if it crashed and
+    /// [`WatchdogConfig::restart_on_crash`] is on (waiting
+    /// for the backoff first), and records the restart.
+    ///
+    /// Returns `None` if it shouldn't be restarted:
+    /// - It exited normally (eg: `stop` command).
+    /// - `stopped_by_user`: it was stopped/killed from the launcher.
+    /// - It's in a crash loop.
+    pub async fn restart_after_exit(
+        &self,
+        instance: &Instance,
+        status: ExitStatus,
+        stopped_by_user: bool,
+    ) -> Option<Result<LaunchedProcess, ServerError>> {
+        let delay = if self.is_scheduled_stop.swap(false, Ordering::SeqCst) {
+            record_restart(instance, RestartKind::Scheduled).await;
+            SCHEDULED_RESTART_DELAY
+        } else {
+            if stopped_by_user || status.success() {
+                return None;
+            }
+            let config = match WatchdogConfig::load(instance).await {
+                Ok(n) => n,
+                Err(error) => {
+                    err!("Couldn't load watchdog config: {error}");
+                    return None;
+                }
+            };
+            if !config.restart_on_crash {
+                return None;
+            }
+
+            let (delay, crashes) = {
+                let mut crashes = self.crashes.lock().unwrap();
+                let delay = config.crash_delay(&mut crashes, Instant::now());
+                let count = u32::try_from(crashes.len()).unwrap_or(u32::MAX);
+                if delay.is_none() {
+                    crashes.clear();
+                }
+                (delay, count)
+            };
+            let Some(delay) = delay else {
+                err!(
+                    "{} crashed {crashes} times in {} minutes, not restarting it",
+                    instance.name,
+                    config.crash_window_minutes
+                );
+                record_restart(instance, RestartKind::CrashLoop { crashes }).await;
+                return None;
+            };
+
+            info!(
+                "{} crashed ({status}), restarting in {}s",
+                instance.name,
+                delay.as_secs()
+            );
+            record_restart(
+                instance,
+                RestartKind::Crash {
+                    exit_code: status.code(),
+                    delay_seconds: delay.as_secs(),
+                },
+            )
+            .await;
+            delay
+        };
+
+        tokio::time::sleep(delay).await;
+        Some(crate::run(instance.name.clone(), None).await)
+    }
+}
+
+/// The first time after `now` that the clock shows `time`
+/// (skipping it on days it doesn't exist, due to DST).
+fn next_restart<Tz: TimeZone>(time: NaiveTime, now: &DateTime<Tz>) -> DateTime<Tz> {
+    let today = now.date_naive();
+    today
+        .iter_days()
+        .take(3)
+        .filter_map(|day| {
+            day.and_time(time)
+                .and_local_timezone(now.timezone())
+                .earliest()
+        })
+        .find(|n| n > now)
+        .unwrap_or_else(|| now.clone() + chrono::Duration::days(1))
+}
+
+fn countdown(seconds: i64) -> String {
+    let (n, unit) = if seconds >= 60 && seconds % 60 == 0 {
+        (seconds / 60, "minute")
+    } else {
+        (seconds, "second")
+    };
+    format!("{n} {unit}{}", if n == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crash_backoff() {
+        let config = WatchdogConfig {
+            max_crashes: 3,
+            crash_window_minutes: 10,
+            backoff_seconds: 5,
+            max_backoff_seconds: 15,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let mut crashes = Vec::new();
+
+        let delays: Vec<_> = (0..4)
+            .map(|i| config.crash_delay(&mut crashes, start + Duration::from_secs(i * 60)))
+            .collect();
+        assert_eq!(
+            delays,
+            [
+                Some(Duration::from_secs(5)),
+                Some(Duration::from_secs(10)),
+                Some(Duration::from_secs(15)),
+                None,
+            ]
+        );
+
+        // Old crashes are forgotten
+        let later = start + Duration::from_secs(60 * 60);
+        assert_eq!(
+            config.crash_delay(&mut crashes, later),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn next_restart_time() {
+        let time = NaiveTime::from_hms_opt(4, 0, 0).unwrap();
+        let before = Utc.with_ymd_and_hms(2024, 5, 1, 3, 0, 0).unwrap();
+        assert_eq!(
+            next_restart(time, &before),
+            Utc.with_ymd_and_hms(2024, 5, 1, 4, 0, 0).unwrap()
+        );
+        let at = Utc.with_ymd_and_hms(2024, 5, 1, 4, 0, 0).unwrap();
+        assert_eq!(
+            next_restart(time, &at),
+            Utc.with_ymd_and_hms(2024, 5, 2, 4, 0, 0).unwrap()
+        );
+
+        assert_eq!(countdown(600), "10 minutes");
+        assert_eq!(countdown(60), "1 minute");
+        assert_eq!(countdown(90), "90 seconds");
+        assert_eq!(countdown(1), "1 second");
+    }
+}
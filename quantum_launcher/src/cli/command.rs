@@ -1,11 +1,15 @@
 use owo_colors::{OwoColorize, Style};
 use ql_core::{
-    Instance, InstanceKind, IntoStringError, LAUNCHER_DIR, ListEntry, Loader,
+    Instance, InstanceKind, IntoStringError, LAUNCHER_DIR, LaunchedProcess, ListEntry, Loader,
     OptifineUniqueVersion, clean, eeprintln, err, info,
     json::{InstanceConfigJson, VersionDetails},
 };
 use ql_mod_manager::loaders::LoaderInstallResult;
-use std::{path::PathBuf, process::exit, sync::Arc};
+use std::{
+    path::PathBuf,
+    process::exit,
+    sync::{Arc, Mutex},
+};
 
 use crate::{
     cli::{CleanType, QLoader, account::refresh_account, helpers::render_row},
@@ -144,6 +148,12 @@ pub fn list_instances(
                         Loader::NeoForge => writeln!(cmds_loader, "{}", m.yellow()),
                         Loader::OptiFine => writeln!(cmds_loader, "{}", m.red().bold()),
                         Loader::Paper => writeln!(cmds_loader, "{}", m.blue()),
+                        Loader::Folia => writeln!(cmds_loader, "{}", m.bright_cyan()),
+                        Loader::Purpur => writeln!(cmds_loader, "{}", m.purple()),
+                        Loader::Velocity => writeln!(cmds_loader, "{}", m.bright_blue().bold()),
+                        Loader::BungeeCord => writeln!(cmds_loader, "{}", m.yellow().bold()),
+                        Loader::Spigot => writeln!(cmds_loader, "{}", m.bright_red()),
+                        Loader::Bukkit => writeln!(cmds_loader, "{}", m.cyan()),
                         Loader::Liteloader => writeln!(cmds_loader, "{}", m.bright_blue()),
                         Loader::Modloader => writeln!(cmds_loader, "{m}"),
                         Loader::Rift => writeln!(cmds_loader, "{}", m.bold().underline()),
@@ -176,6 +186,7 @@ pub async fn create_instance(
     instance_name: String,
     version: String,
     skip_assets: bool,
+    accept_eula: bool,
     kind: InstanceKind,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let entry = ListEntry::new(version);
@@ -185,13 +196,25 @@ pub async fn create_instance(
             ql_instances::create_instance(instance_name, entry, None, !skip_assets).await?;
         }
         InstanceKind::Server => {
-            ql_servers::create_server(instance_name, entry, None).await?;
+            require_eula(accept_eula)?;
+            let name = ql_servers::create_server(instance_name, entry, None).await?;
+            ql_servers::accept_eula(&Instance::server(&name).get_instance_path()).await?;
         }
     }
 
     Ok(())
 }
 
+/// Servers only run once the user agrees to the Minecraft EULA,
+/// so creating one asks for `--accept-eula` upfront.
+pub fn require_eula(accept_eula: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if accept_eula {
+        Ok(())
+    } else {
+        Err("servers need the Minecraft EULA (https://aka.ms/MinecraftEULA) to be accepted,\npass --accept-eula if you agree to it".into())
+    }
+}
+
 pub fn delete_instance(
     instance_name: &str,
     force: bool,
@@ -258,28 +281,26 @@ pub async fn launch_instance(
 
     let instance_name = Arc::from(instance_name);
 
-    let child = match kind {
-        InstanceKind::Client => {
-            ql_instances::launch(
-                instance_name,
-                username,
-                None,
-                account.clone(),
-                None, // No global defaults in CLI mode
-                Vec::new(),
-            )
-            .await?
-        }
-        // TODO: stdin input
-        InstanceKind::Server => ql_servers::run(instance_name, None).await?,
-    };
+    if let InstanceKind::Server = kind {
+        return run_server(instance_name).await;
+    }
+    let child = ql_instances::launch(
+        instance_name,
+        username,
+        None,
+        account.clone(),
+        None, // No global defaults in CLI mode
+        Vec::new(),
+    )
+    .await?;
 
     let mut censors = Vec::new();
     if let Some(token) = account.as_ref().and_then(|n| n.access_token.as_ref()) {
         censors.push(token.clone());
     }
 
-    match child.read_logs(censors, None).await {
+    let result = child.read_logs(censors, None).await;
+    match result {
         Some(Ok((s, _, diag))) => {
             info!("Game exited with code {s}");
             if let Some(diag) = diag {
@@ -293,6 +314,69 @@ pub async fn launch_instance(
     Ok(())
 }
 
+/// Runs a server until it stops, restarting it
+/// whenever its [`ql_servers::Watchdog`] says so.
+async fn run_server(name: Arc<str>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut child = ql_servers::run(name.clone(), None).await?;
+    let current = Arc::new(Mutex::new(child.clone()));
+    forward_stdin_to_server(current.clone(), name);
+    let watchdog = ql_servers::Watchdog::default();
+
+    loop {
+        let backups = tokio::spawn(ql_servers::backup_scheduler(child.clone()));
+        let restarts = tokio::spawn(watchdog.clone().restart_scheduler(child.clone()));
+
+        let result = child.read_logs(Vec::new(), None).await;
+        // Let it finish the on-stop backup, if any
+        _ = backups.await;
+        restarts.abort();
+
+        let status = match result {
+            Some(Ok((s, _, diag))) => {
+                info!("Server exited with code {s}");
+                if let Some(diag) = diag {
+                    err!("{diag}");
+                }
+                s
+            }
+            Some(Err(err)) => Err(err)?,
+            None => return Ok(()),
+        };
+
+        match watchdog
+            .restart_after_exit(&child.instance, status, false)
+            .await
+        {
+            Some(restarted) => {
+                child = restarted?;
+                *current.lock().unwrap() = child.clone();
+            }
+            None => exit(status.code().unwrap_or_default()),
+        }
+    }
+}
+
+/// Passes commands typed in the terminal to the server console
+/// (or RCON, if the console isn't attached).
+///
+/// `child` is swapped out when the server restarts.
+fn forward_stdin_to_server(child: Arc<Mutex<LaunchedProcess>>, name: Arc<str>) {
+    let runtime = tokio::runtime::Handle::current();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let child = child.lock().unwrap().clone();
+            match runtime.block_on(ql_servers::send_console_command(Some(&child), &name, &line)) {
+                Ok(Some(output)) => println!("{output}"),
+                Ok(None) => {}
+                Err(err) => err!("{err}"),
+            }
+        }
+    });
+}
+
 pub async fn loader(cmd: QLoader, kind: InstanceKind) -> Result<(), Box<dyn std::error::Error>> {
     match cmd {
         QLoader::Info { instance } => {
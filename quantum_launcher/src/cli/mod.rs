@@ -17,6 +17,7 @@ use crate::{
 mod account;
 mod command;
 mod helpers;
+mod server;
 
 #[derive(Parser)]
 #[cfg_attr(target_os = "windows", command(name = ".\\quantum_launcher.exe"))]
@@ -59,6 +60,11 @@ enum QSubCommand {
         #[arg(short, long)]
         #[arg(help = "Skips downloading game assets (sound/music) to speed up downloads")]
         skip_assets: bool,
+        #[arg(long)]
+        #[arg(
+            help = "Accepts the Minecraft EULA (https://aka.ms/MinecraftEULA), needed for servers"
+        )]
+        accept_eula: bool,
     },
     #[command(about = "Launches an instance")]
     Launch {
@@ -98,6 +104,8 @@ enum QSubCommand {
     Loader(QLoader),
     #[command(about = "Lists downloadable versions", short_flag = 'a')]
     ListAvailableVersions,
+    #[clap(subcommand)]
+    Server(QServer),
 }
 
 #[derive(ValueEnum, Clone, Debug)]
@@ -113,15 +121,22 @@ enum CleanType {
     about = "Manages mod loaders",
     long_about = r"Install, uninstall and look up mod loaders.
 
-Supported loaders: Fabric, Forge, Quilt, NeoForge, Paper, OptiFine
+Supported loaders: Fabric, Forge, Quilt, NeoForge, OptiFine,
+and for servers: Paper, Folia, Purpur, Spigot, Bukkit,
+and proxies: Velocity, BungeeCord
 (case-insensitive)"
 )]
 enum QLoader {
     #[command(about = "Installs the specified loader")]
     #[command(long_about = r"Installs the specified loader
 
-Supported loaders: Fabric, Forge, Quilt, NeoForge, Paper, OptiFine
-(case-insensitive)")]
+Supported loaders: Fabric, Forge, Quilt, NeoForge, OptiFine,
+and for servers: Paper, Folia, Purpur, Spigot, Bukkit,
+and proxies: Velocity, BungeeCord
+(case-insensitive)
+
+For Paper, Folia, Purpur and Velocity, --version can be a build number,
+or a channel (stable/experimental) to install the latest build from")]
     Install {
         loader: String,
         instance: String,
@@ -138,6 +153,118 @@ Supported loaders: Fabric, Forge, Quilt, NeoForge, Paper, OptiFine
     },
 }
 
+#[derive(Subcommand)]
+#[command(
+    about = "Runs servers in the background (see `server --help`)",
+    long_about = r"Runs servers in the background, without the GUI.
+
+A started server keeps running after this command exits
+(and restarts as set in its watchdog config), until stopped
+with `server stop`. Use `attach` or `console` to talk to it."
+)]
+enum QServer {
+    #[command(about = "Creates a new server")]
+    Create {
+        #[arg(help = "Version of Minecraft to download")]
+        version: String,
+        server_name: String,
+        #[arg(long)]
+        #[arg(
+            help = "Accepts the Minecraft EULA (https://aka.ms/MinecraftEULA), needed to run it"
+        )]
+        accept_eula: bool,
+    },
+    #[command(
+        about = "Creates a server for a client instance or modpack, without client-only mods"
+    )]
+    #[command(group = clap::ArgGroup::new("source").required(true))]
+    CreateFrom {
+        server_name: String,
+        #[arg(long, group = "source")]
+        #[arg(help = "Client instance to copy the version, loader, mods and configs of")]
+        instance: Option<String>,
+        #[arg(long, group = "source")]
+        #[arg(help = "Modrinth (.mrpack) or CurseForge modpack file to install")]
+        modpack: Option<PathBuf>,
+        #[arg(long)]
+        #[arg(
+            help = "Accepts the Minecraft EULA (https://aka.ms/MinecraftEULA), needed to run it"
+        )]
+        accept_eula: bool,
+    },
+    #[command(about = "Imports an existing server folder, detecting its version and software")]
+    Import {
+        #[arg(help = "The server folder")]
+        path: PathBuf,
+        #[arg(long)]
+        #[arg(help = "Name in the launcher (default: folder name)")]
+        name: Option<String>,
+        #[arg(long)]
+        #[arg(help = "Leave the folder where it is and link to it, instead of copying it")]
+        adopt: bool,
+        #[arg(long)]
+        #[arg(
+            help = "Accepts the Minecraft EULA (https://aka.ms/MinecraftEULA), needed to run it"
+        )]
+        accept_eula: bool,
+    },
+    #[command(
+        about = "Creates a matching client for players of a server, as an instance or modpack"
+    )]
+    #[command(group = clap::ArgGroup::new("output").required(true))]
+    Client {
+        server_name: String,
+        #[arg(long, group = "output")]
+        #[arg(help = "Name of the client instance to create")]
+        instance: Option<String>,
+        #[arg(long, group = "output")]
+        #[arg(help = "Where to save a Modrinth modpack (.mrpack) to hand out")]
+        mrpack: Option<PathBuf>,
+        #[arg(short, long = "companion")]
+        #[arg(help = "Extra client-only mod to add (Modrinth ID or slug), can be repeated")]
+        companions: Vec<String>,
+    },
+    #[command(about = "Starts a server in the background")]
+    Start {
+        server_name: String,
+        #[arg(short, long)]
+        #[arg(help = "Runs in this terminal instead (for service managers like systemd)")]
+        foreground: bool,
+        #[arg(long)]
+        #[arg(
+            help = "Accepts the Minecraft EULA (https://aka.ms/MinecraftEULA), needed to run it"
+        )]
+        accept_eula: bool,
+    },
+    #[command(about = "Stops a background server, saving the world")]
+    Stop { server_name: String },
+    #[command(about = "Shows which servers are running in the background")]
+    Status { server_name: Option<String> },
+    #[command(about = "Shows the ports every server is set to use")]
+    Ports,
+    #[command(about = "Opens the console of a background server (Ctrl+C to detach)")]
+    Attach { server_name: String },
+    #[command(about = "Runs a console command on a server, printing the output")]
+    Console {
+        server_name: String,
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        command: Vec<String>,
+    },
+    #[command(about = "Prints the console output of a background server")]
+    Logs {
+        server_name: String,
+        #[arg(short = 'n', long, default_value_t = 50)]
+        #[arg(help = "How many of the last lines to print")]
+        lines: usize,
+        #[arg(short, long)]
+        #[arg(help = "Keeps printing new lines while the server runs")]
+        follow: bool,
+    },
+    /// Runs the server in this process, started by `start`
+    #[command(hide = true)]
+    Daemon { server_name: String },
+}
+
 pub static EXPERIMENTAL_SERVERS: LazyLock<RwLock<bool>> = LazyLock::new(|| RwLock::new(false));
 pub static EXPERIMENTAL_MMC_IMPORT: LazyLock<RwLock<bool>> = LazyLock::new(|| RwLock::new(false));
 
@@ -253,11 +380,13 @@ pub fn start_cli(is_dir_err: bool, launcher_dir: &mut Option<PathBuf>) {
                 instance_name,
                 version,
                 skip_assets,
+                accept_eula,
             } => {
                 quit(runtime.block_on(command::create_instance(
                     instance_name,
                     version,
                     skip_assets,
+                    accept_eula,
                     kind,
                 )));
             }
@@ -306,6 +435,9 @@ pub fn start_cli(is_dir_err: bool, launcher_dir: &mut Option<PathBuf>) {
             QSubCommand::Loader(cmd) => {
                 quit(runtime.block_on(command::loader(cmd, kind)));
             }
+            QSubCommand::Server(cmd) => {
+                quit(runtime.block_on(server::server_command(cmd)));
+            }
         }
     } else {
         print_intro();
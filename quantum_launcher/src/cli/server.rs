@@ -0,0 +1,429 @@
+//! `quantum_launcher server ...`: running servers
+//! in the background, without the GUI.
+
+use std::{
+    collections::HashSet,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::Utc;
+use owo_colors::OwoColorize;
+use ql_core::{Instance, InstanceKind, err, info};
+use ql_mod_manager::store::CurseforgeNotAllowed;
+use ql_servers::{DaemonClient, DaemonError, DaemonState, DaemonStatus, ImportMode};
+
+use crate::{
+    cli::{QServer, command},
+    state::get_entries,
+};
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+pub async fn server_command(cmd: QServer) -> Result {
+    match cmd {
+        QServer::Create {
+            server_name,
+            version,
+            accept_eula,
+        } => {
+            command::create_instance(
+                server_name,
+                version,
+                false,
+                accept_eula,
+                InstanceKind::Server,
+            )
+            .await
+        }
+        QServer::CreateFrom {
+            server_name,
+            instance,
+            modpack,
+            accept_eula,
+        } => {
+            command::require_eula(accept_eula)?;
+            create_from(server_name, instance, modpack).await
+        }
+        QServer::Import {
+            path,
+            name,
+            adopt,
+            accept_eula,
+        } => import(&path, name, adopt, accept_eula).await,
+        QServer::Client {
+            server_name,
+            instance,
+            mrpack,
+            companions,
+        } => client_for(&server_name, instance, mrpack, companions).await,
+        QServer::Start {
+            server_name,
+            foreground,
+            accept_eula,
+        } => {
+            check_exists(&server_name)?;
+            if accept_eula {
+                ql_servers::accept_eula(&Instance::server(&server_name).get_instance_path())
+                    .await?;
+            }
+            if foreground {
+                Ok(ql_servers::run_daemon(Arc::from(server_name)).await?)
+            } else {
+                start_detached(&server_name).await
+            }
+        }
+        QServer::Daemon { server_name } => {
+            Ok(ql_servers::run_daemon(Arc::from(server_name)).await?)
+        }
+        QServer::Stop { server_name } => {
+            let client = DaemonClient::connect(&Instance::server(&server_name)).await?;
+            info!("Stopping {server_name}...");
+            client.stop().await?;
+            info!("Stopped {server_name}");
+            Ok(())
+        }
+        QServer::Status { server_name } => status(server_name).await,
+        QServer::Ports => ports().await,
+        QServer::Attach { server_name } => attach(&server_name).await,
+        QServer::Console {
+            server_name,
+            command,
+        } => {
+            let command = command.join(" ");
+            let instance = Instance::server(&server_name);
+            if DaemonState::load(&instance).await?.is_none() {
+                // Maybe started from the GUI, try RCON
+                if let Some(output) =
+                    ql_servers::send_console_command(None, &server_name, &command).await?
+                {
+                    println!("{output}");
+                }
+                return Ok(());
+            }
+            let client = DaemonClient::connect(&instance).await?;
+            for line in client.run_command(&command).await? {
+                println!("{line}");
+            }
+            Ok(())
+        }
+        QServer::Logs {
+            server_name,
+            lines,
+            follow,
+        } => logs(&server_name, lines, follow).await,
+    }
+}
+
+fn check_exists(server_name: &str) -> Result {
+    if Instance::server(server_name).get_instance_path().is_dir() {
+        Ok(())
+    } else {
+        Err(format!("no server named {server_name:?}").into())
+    }
+}
+
+async fn create_from(
+    server_name: String,
+    instance: Option<String>,
+    modpack: Option<PathBuf>,
+) -> Result {
+    let created = match (instance, modpack) {
+        (Some(instance), _) => {
+            let client = Instance::client(&instance);
+            if !client.get_instance_path().is_dir() {
+                return Err(format!("no instance named {instance:?}").into());
+            }
+            ql_servers::create_server_from_instance(&client, server_name, None).await?
+        }
+        (None, Some(path)) => {
+            let file = tokio::fs::read(&path).await?;
+            ql_servers::create_server_from_modpack(server_name, file, None).await?
+        }
+        (None, None) => return Err("pass --instance or --modpack".into()),
+    };
+
+    if !created.skipped_mods.is_empty() {
+        println!("Left out (not needed on servers):");
+        for (filename, side) in &created.skipped_mods {
+            println!("- {filename} {}", format!("({side})").bright_black());
+        }
+    }
+    ql_servers::accept_eula(&Instance::server(&created.name).get_instance_path()).await?;
+    print_not_allowed(&created.not_allowed, &format!("servers/{}", created.name));
+    info!("Created server {}", created.name);
+    Ok(())
+}
+
+async fn import(path: &Path, name: Option<String>, adopt: bool, accept_eula: bool) -> Result {
+    let name = match name {
+        Some(name) => name,
+        None => path
+            .canonicalize()?
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or("pass --name")?,
+    };
+    let mode = if adopt {
+        ImportMode::Adopt
+    } else {
+        ImportMode::Copy
+    };
+    let (name, detected) = ql_servers::import_server(path, name, mode).await?;
+    if accept_eula {
+        ql_servers::accept_eula(&Instance::server(&name).get_instance_path()).await?;
+    }
+    info!(
+        "Imported {name}: {} {}(Minecraft {})",
+        detected.loader,
+        detected
+            .loader_version
+            .map(|n| format!("{n} "))
+            .unwrap_or_default(),
+        detected.minecraft
+    );
+    if let Some(jar) = detected.jar {
+        println!("Runs {}", jar.yellow());
+    }
+    Ok(())
+}
+
+async fn client_for(
+    server_name: &str,
+    instance: Option<String>,
+    mrpack: Option<PathBuf>,
+    companions: Vec<String>,
+) -> Result {
+    check_exists(server_name)?;
+    let server = Instance::server(server_name);
+
+    if let Some(path) = mrpack {
+        let file = ql_mod_manager::store::export_modrinth_pack(&server, &companions).await?;
+        tokio::fs::write(&path, file).await?;
+        info!("Saved modpack to {}", path.display());
+        return Ok(());
+    }
+    let Some(name) = instance else {
+        return Err("pass --instance or --mrpack".into());
+    };
+
+    let created = ql_packager::create_client_from_server(&server, name, companions, None).await?;
+    if !created.skipped_mods.is_empty() {
+        println!("Left out (not needed on clients):");
+        for (filename, side) in &created.skipped_mods {
+            println!("- {filename} {}", format!("({side})").bright_black());
+        }
+    }
+    print_not_allowed(&created.not_allowed, &format!("instances/{}", created.name));
+    info!("Created instance {}", created.name);
+    Ok(())
+}
+
+fn print_not_allowed(not_allowed: &HashSet<CurseforgeNotAllowed>, dir: &str) {
+    if not_allowed.is_empty() {
+        return;
+    }
+    err!(
+        "Some Curseforge mods have blocked this launcher!\nDownload them manually into {dir}/mods:"
+    );
+    for entry in not_allowed {
+        println!(
+            "- {}: https://www.curseforge.com/minecraft/{}/{}/download/{}",
+            entry.name,
+            entry.project_type.to_curseforge_str(),
+            entry.slug,
+            entry.file_id
+        );
+    }
+}
+
+/// Starts `quantum_launcher server daemon <NAME>` as a separate
+/// process that outlives this one, waiting until the server runs.
+async fn start_detached(server_name: &str) -> Result {
+    let instance = Instance::server(server_name);
+    if let Some(state) = DaemonState::load(&instance).await? {
+        return Err(DaemonError::AlreadyRunning(server_name.to_owned(), state.pid).into());
+    }
+
+    let mut command = std::process::Command::new(std::env::current_exe()?);
+    command
+        .args(["server", "daemon", server_name])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Not killed by Ctrl+C in this terminal
+        command.process_group(0);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+    let mut daemon = command.spawn()?;
+    info!("Starting {server_name}...");
+
+    loop {
+        if daemon.try_wait()?.is_some() {
+            // Exited before the server could start
+            print_log_tail(&instance, 20).await;
+            return Err(format!("{server_name} couldn't start (see above)").into());
+        }
+        if let Some(state) = DaemonState::load(&instance).await? {
+            if state.status == DaemonStatus::Running {
+                info!(
+                    "Started {server_name} in the background (PID {})",
+                    state.pid
+                );
+                println!(
+                    "Use {} to open the console",
+                    format!("server attach {server_name}").yellow()
+                );
+                return Ok(());
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+}
+
+async fn status(server_name: Option<String>) -> Result {
+    let names = match server_name {
+        Some(name) => {
+            check_exists(&name)?;
+            vec![name]
+        }
+        None => get_entries(InstanceKind::Server).await?.0,
+    };
+
+    for name in names {
+        let instance = Instance::server(&name);
+        let Some(state) = DaemonState::load(&instance).await? else {
+            println!("{}  {}", name.bold(), "Stopped".bright_black());
+            continue;
+        };
+
+        let uptime = (Utc::now() - state.started).num_minutes();
+        let mut line = format!(
+            "{}  {}  PID {}  up {}h {}m",
+            name.bold(),
+            state.status.green(),
+            state.pid,
+            uptime / 60,
+            uptime % 60
+        );
+        if state.status == DaemonStatus::Running {
+            if let Ok(status) = ql_servers::ping_local(&instance).await {
+                line.push_str(&format!(
+                    "  {}/{} players",
+                    status.players_online, status.players_max
+                ));
+            }
+        }
+        println!("{line}");
+    }
+    Ok(())
+}
+
+async fn ports() -> Result {
+    for (name, ports) in ql_servers::ports_in_use().await? {
+        let mut line = format!("{}  {}", name.bold(), ports.server);
+        if let Some(query) = ports.query {
+            line.push_str(&format!("  query {query}"));
+        }
+        if let Some(rcon) = ports.rcon {
+            line.push_str(&format!("  rcon {rcon}"));
+        }
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Prints the console and sends typed lines as commands,
+/// until Ctrl+C (which only detaches) or the server stops.
+async fn attach(server_name: &str) -> Result {
+    let client = DaemonClient::connect(&Instance::server(server_name)).await?;
+    let (mut reader, mut writer) = client.attach(50).await?;
+    for line in &reader.backlog {
+        println!("{line}");
+    }
+    info!("Attached to {server_name}, press Ctrl+C to detach");
+
+    let runtime = tokio::runtime::Handle::current();
+    // Closing stdin (eg: piped input ended) detaches too
+    let has_detached = Arc::new(AtomicBool::new(false));
+    let has_detached_t = has_detached.clone();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Err(error) = runtime.block_on(writer.send_command(&line)) {
+                err!("{error}");
+                break;
+            }
+        }
+        has_detached_t.store(true, Ordering::SeqCst);
+        drop(writer);
+    });
+
+    loop {
+        match reader.next_line().await {
+            Ok(Some(line)) => println!("{line}"),
+            Ok(None) => break,
+            // A command failed, keep going
+            Err(ql_servers::ServerError::Daemon(DaemonError::Rejected(error))) => err!("{error}"),
+            Err(error) => Err(error)?,
+        }
+    }
+    if has_detached.load(Ordering::SeqCst) {
+        info!("Detached from {server_name}");
+    } else {
+        info!("{server_name} stopped");
+    }
+    Ok(())
+}
+
+async fn logs(server_name: &str, lines: usize, follow: bool) -> Result {
+    check_exists(server_name)?;
+    let instance = Instance::server(server_name);
+
+    if follow {
+        if let Ok(client) = DaemonClient::connect(&instance).await {
+            let (mut reader, _writer) = client.attach(lines).await?;
+            for line in &reader.backlog {
+                println!("{line}");
+            }
+            while let Some(line) = reader.next_line().await? {
+                println!("{line}");
+            }
+            return Ok(());
+        }
+    }
+    print_log_tail(&instance, lines).await;
+    Ok(())
+}
+
+/// The last lines of `console.log`, from the
+/// last time the server ran in the background.
+async fn print_log_tail(instance: &Instance, lines: usize) {
+    let path = ql_servers::console_log_path(instance);
+    let Ok(text) = tokio::fs::read_to_string(&path).await else {
+        err!("No background logs for {} yet", instance.name);
+        return;
+    };
+    let all: Vec<&str> = text.lines().collect();
+    let mut stdout = std::io::stdout().lock();
+    for line in &all[all.len().saturating_sub(lines)..] {
+        _ = writeln!(stdout, "{line}");
+    }
+}
@@ -11,7 +11,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::{
     collections::{HashMap, HashSet},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 pub mod discord_rpc;
@@ -544,6 +544,12 @@ pub struct PersistentSettings {
     /// Remembers version filters (eg: snapshot, release, etc) in Create Instance
     pub create_instance_filters: Option<HashSet<ListEntryKind>>,
 
+    /// Folder watched for manually downloaded CurseForge files
+    /// (ones blocked for third-party launchers).
+    /// If `None`, the system downloads folder is used.
+    // Since: v0.5.2
+    pub downloads_dir: Option<PathBuf>,
+
     #[serde(flatten)]
     _extra: HashMap<String, serde_json::Value>,
 }
@@ -556,6 +562,7 @@ impl Default for PersistentSettings {
             selected_remembered: true,
             write_mod_update_changelog: true,
             create_instance_filters: None,
+            downloads_dir: None,
             _extra: HashMap::new(),
         }
     }
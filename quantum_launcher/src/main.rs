@@ -89,6 +89,8 @@ mod stylesheet;
 mod tick;
 
 const LAUNCHER_ICON: &[u8] = include_bytes!("../../assets/icon/ql_logo.ico");
+/// How often running servers (and the open server list) are pinged
+const SERVER_STATUS_INTERVAL: Duration = Duration::from_secs(15);
 
 impl Launcher {
     fn new(
@@ -155,10 +157,13 @@ impl Launcher {
         let tick = iced::time::every(Duration::from_millis(1000 / self.tick_interval()))
             .map(|_| Message::CoreTick);
         let events = iced::event::listen_with(|a, b, _| Some(Message::CoreEvent(a, b)));
+        let server_status = iced::time::every(SERVER_STATUS_INTERVAL)
+            .map(|_| Message::ServerStatus(state::ServerStatusMessage::Refresh));
 
         iced::Subscription::batch(vec![
             tick,
             events,
+            server_status,
             // Useful for performance profiling
             // iced::window::frames().map(|_| Message::Nothing),
         ])
@@ -0,0 +1,179 @@
+use chrono::Local;
+use iced::{
+    Alignment, Length,
+    widget::{self, column, row},
+};
+
+use crate::{
+    menu_renderer::{Element, back_button, back_to_launch_screen, checkered_list, tsubtitle},
+    message_update::format_memory_bytes,
+    state::{BackupsMessage, MenuBackups},
+    stylesheet::styles::LauncherTheme,
+};
+
+impl MenuBackups {
+    pub fn view(&'_ self, is_server: bool, is_running: bool) -> Element<'_> {
+        let header = column![
+            row![
+                back_button()
+                    .on_press_maybe((!self.is_working).then_some(back_to_launch_screen(None))),
+                widget::button(if self.is_working {
+                    "Working..."
+                } else {
+                    "Back up now"
+                })
+                .on_press_maybe((!self.is_working).then_some(BackupsMessage::Create.into())),
+            ]
+            .spacing(5),
+            widget::text("World Backups").size(20),
+            self.view_settings(is_server),
+        ]
+        .push_maybe(is_running.then(|| {
+            widget::text("Stop the game to restore over the current worlds")
+                .size(12)
+                .style(tsubtitle)
+        }))
+        .padding(10)
+        .spacing(10);
+
+        let snapshots = self
+            .snapshots
+            .iter()
+            .map(|snapshot| self.view_snapshot(snapshot, is_server, is_running));
+
+        let list: Element = if self.snapshots.is_empty() {
+            widget::text("No backups yet")
+                .size(14)
+                .style(tsubtitle)
+                .into()
+        } else {
+            checkered_list(snapshots).into()
+        };
+
+        column![
+            header,
+            widget::scrollable(list)
+                .style(LauncherTheme::style_scrollable_flat_extra_dark)
+                .height(Length::Fill)
+                .spacing(1),
+        ]
+        .into()
+    }
+
+    fn view_settings(&self, is_server: bool) -> Element<'_> {
+        let config = &self.config;
+        let number = |label: &'static str, value: String, f: fn(String) -> BackupsMessage| {
+            row![
+                widget::text(label).size(14).width(200),
+                widget::text_input("", &value)
+                    .size(14)
+                    .width(80)
+                    .on_input(move |n| f(n).into()),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+        };
+
+        column![]
+            .push_maybe(is_server.then(|| {
+                column![
+                    number(
+                        "Back up every (minutes)",
+                        config
+                            .interval_minutes
+                            .map(|n| n.to_string())
+                            .unwrap_or_default(),
+                        BackupsMessage::IntervalChanged,
+                    ),
+                    widget::checkbox("Back up when the server stops", config.on_stop)
+                        .size(14)
+                        .text_size(14)
+                        .on_toggle(|t| BackupsMessage::OnStopToggled(t).into()),
+                ]
+                .spacing(5)
+            }))
+            .push(number(
+                "Keep last",
+                config.retention.keep_last.to_string(),
+                BackupsMessage::KeepLastChanged,
+            ))
+            .push(number(
+                "Keep one per day, for (days)",
+                config.retention.keep_daily.to_string(),
+                BackupsMessage::KeepDailyChanged,
+            ))
+            .push(number(
+                "Keep one per week, for (weeks)",
+                config.retention.keep_weekly.to_string(),
+                BackupsMessage::KeepWeeklyChanged,
+            ))
+            .push(
+                widget::text(
+                    "Unchanged files are shared between backups, so frequent backups take little space.",
+                )
+                .size(12)
+                .style(tsubtitle),
+            )
+            .spacing(5)
+            .into()
+    }
+
+    fn view_snapshot(
+        &self,
+        snapshot: &ql_servers::Snapshot,
+        is_server: bool,
+        is_running: bool,
+    ) -> Element<'_> {
+        let id = &snapshot.id;
+        let can_restore = !self.is_working && !is_running;
+        let can_restore_new = !self.is_working && !self.new_server_name.trim().is_empty();
+
+        column![
+            row![
+                widget::text(
+                    snapshot
+                        .created
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                )
+                .size(16),
+                widget::text(format!(
+                    "{} - {} - {}",
+                    snapshot.reason,
+                    snapshot.worlds.join(", "),
+                    format_memory_bytes(snapshot.size())
+                ))
+                .size(12)
+                .style(tsubtitle),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            row![
+                widget::button(widget::text("Restore").size(14)).on_press_maybe(
+                    can_restore.then(|| BackupsMessage::Restore(id.clone()).into())
+                ),
+                widget::button(widget::text("Delete").size(14)).on_press_maybe(
+                    (!self.is_working).then(|| BackupsMessage::Delete(id.clone()).into())
+                ),
+            ]
+            .push_maybe(is_server.then(|| {
+                row![
+                    widget::text_input("New server name", &self.new_server_name)
+                        .size(14)
+                        .width(180)
+                        .on_input(|n| BackupsMessage::NewServerNameChanged(n).into()),
+                    widget::button(widget::text("Restore as new server").size(14)).on_press_maybe(
+                        can_restore_new.then(|| BackupsMessage::RestoreAsNew(id.clone()).into())
+                    ),
+                ]
+                .spacing(5)
+            }))
+            .align_y(Alignment::Center)
+            .spacing(5),
+        ]
+        .padding(10)
+        .spacing(5)
+        .into()
+    }
+}
@@ -0,0 +1,59 @@
+use iced::{
+    Alignment,
+    widget::{self, column, row},
+};
+
+use crate::{
+    menu_renderer::{Element, back_button, back_to_launch_screen, tsubtitle},
+    state::{ClientFromServerMessage, MenuClientFromServer},
+};
+
+impl MenuClientFromServer {
+    pub fn view(&'_ self) -> Element<'_> {
+        column![
+            row![back_button().on_press_maybe(
+                (!self.is_working).then(|| back_to_launch_screen(None))
+            )],
+            widget::text("Client for Players").size(20),
+            widget::text!(
+                "Same version, loader, mods and configs as {}",
+                self.server.name
+            )
+            .size(14),
+            row![
+                widget::text("Extra mods:").size(18),
+                widget::text_input("eg: xaeros-minimap, sodium", &self.companions)
+                    .on_input(|n| ClientFromServerMessage::CompanionsInput(n).into()),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            widget::text(
+                "Client-only mods to add (Modrinth IDs or slugs, comma-separated).\nServer-only mods are left out."
+            )
+            .size(12)
+            .style(tsubtitle),
+            widget::horizontal_rule(1),
+            widget::text("Create an instance for yourself").size(16),
+            row![
+                widget::text("Name:").size(18),
+                widget::text_input("Instance name", &self.name)
+                    .on_input(|n| ClientFromServerMessage::NameInput(n).into()),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            widget::button("Create Instance").on_press_maybe(
+                (!self.is_working && !self.name.trim().is_empty())
+                    .then_some(ClientFromServerMessage::CreateInstance.into())
+            ),
+            widget::horizontal_rule(1),
+            widget::text("Or a modpack to hand out to players").size(16),
+            widget::button("Export .mrpack").on_press_maybe(
+                (!self.is_working).then_some(ClientFromServerMessage::ExportPack.into())
+            ),
+        ]
+        .push_maybe(self.is_working.then(|| widget::text("Working...").size(14)))
+        .padding(10)
+        .spacing(10)
+        .into()
+    }
+}
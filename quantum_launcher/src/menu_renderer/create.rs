@@ -10,10 +10,13 @@ use crate::{
     cli::{EXPERIMENTAL_MMC_IMPORT, EXPERIMENTAL_SERVERS},
     icons,
     menu_renderer::{
-        Column, Element, back_to_launch_screen, button_with_icon, dots, launch::import_description,
-        shortcut_ctrl, sidebar_button, tooltip, tsubtitle,
+        Column, Element, back_to_launch_screen, button_with_icon, dots, eula_checkbox,
+        launch::import_description, shortcut_ctrl, sidebar_button, tooltip, tsubtitle,
+    },
+    state::{
+        CreateInstanceMessage, CreateServerFromMessage, MenuCreateInstance,
+        MenuCreateInstanceChoosing, Message,
     },
-    state::{CreateInstanceMessage, MenuCreateInstance, MenuCreateInstanceChoosing, Message},
     stylesheet::{color::Color, styles::LauncherTheme, widgets::StyleButton},
 };
 
@@ -200,6 +203,20 @@ impl MenuCreateInstanceChoosing {
                 })
                 .size(24).width(Length::Fill),
             ]
+            .push_maybe(matches!(self.kind, InstanceKind::Server).then(|| tooltip(
+                widget::button(widget::text("From modpack...").size(14))
+                    .padding([4, 8])
+                    .on_press(CreateServerFromMessage::OpenFromModpack.into()),
+                widget::text("Create a server for a Modrinth/CurseForge modpack").size(14),
+                Position::Top
+            )))
+            .push_maybe(matches!(self.kind, InstanceKind::Server).then(|| tooltip(
+                widget::button(widget::text("From folder...").size(14))
+                    .padding([4, 8])
+                    .on_press(CreateServerFromMessage::OpenFromFolder.into()),
+                widget::text("Import a server you already have").size(14),
+                Position::Top
+            )))
             .push_maybe(mmc_import.then_some(tooltip(
                 widget::button(import_description())
                     .padding([4, 8])
@@ -225,6 +242,10 @@ impl MenuCreateInstanceChoosing {
             widget::text("If disabled, creating instance will be MUCH faster\nbut no sound or music will play").size(12),
             Position::FollowCursor
         )))
+        .push_maybe(matches!(self.kind, InstanceKind::Server).then(|| eula_checkbox(
+            self.accept_eula,
+            |t| CreateInstanceMessage::EulaToggle(t).into(),
+        )))
         .push(
             widget::text("To sideload your own custom JARs, create an instance with a similar version, then go to \"Edit->Custom Jar File\"")
                 .size(12)
@@ -241,7 +262,10 @@ impl MenuCreateInstanceChoosing {
         .push(widget::vertical_space())
         .push(row![
             widget::horizontal_space(),
-            get_create_button(already_exists),
+            get_create_button(
+                already_exists,
+                matches!(self.kind, InstanceKind::Server) && !self.accept_eula
+            ),
         ])
         .spacing(12).padding(16);
 
@@ -296,9 +320,13 @@ impl MenuCreateInstanceChoosing {
     }
 }
 
-fn get_create_button(already_exists: bool) -> widget::Tooltip<'static, Message, LauncherTheme> {
-    let create_button = button_with_icon(icons::new(), "Create", 16)
-        .on_press_maybe((!already_exists).then_some(CreateInstanceMessage::Start.into()));
+fn get_create_button(
+    already_exists: bool,
+    needs_eula: bool,
+) -> widget::Tooltip<'static, Message, LauncherTheme> {
+    let create_button = button_with_icon(icons::new(), "Create", 16).on_press_maybe(
+        (!already_exists && !needs_eula).then_some(CreateInstanceMessage::Start.into()),
+    );
 
     if already_exists {
         tooltip(
@@ -306,6 +334,12 @@ fn get_create_button(already_exists: bool) -> widget::Tooltip<'static, Message,
             "An instance with that name already exists!",
             Position::FollowCursor,
         )
+    } else if needs_eula {
+        tooltip(
+            create_button,
+            "Accept the Minecraft EULA first",
+            Position::FollowCursor,
+        )
     } else {
         tooltip(create_button, shortcut_ctrl("Enter"), Position::Bottom)
     }
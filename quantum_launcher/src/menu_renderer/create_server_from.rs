@@ -0,0 +1,74 @@
+use iced::{
+    Alignment,
+    widget::{self, column, row},
+};
+
+use crate::{
+    menu_renderer::{Element, back_button, back_to_launch_screen, eula_checkbox, tsubtitle},
+    state::{CreateServerFromMessage, MenuCreateServerFrom, ServerSource},
+};
+
+impl MenuCreateServerFrom {
+    pub fn view(&'_ self) -> Element<'_> {
+        let source = match &self.source {
+            ServerSource::Client(client) => {
+                format!("Same version, loader, mods and configs as {}", client.name)
+            }
+            ServerSource::Modpack(path) => format!(
+                "Modpack: {}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            ServerSource::Folder { path, .. } => format!("Folder: {}", path.display()),
+        };
+        let hint = match &self.source {
+            ServerSource::Folder { .. } => {
+                "The Minecraft version and server software (vanilla, Paper, Fabric,\nForge, NeoForge, etc.) are detected from the files."
+            }
+            _ => {
+                "Client-only mods (like minimaps or shaders) are left out,\nbased on Modrinth and the info inside the mods."
+            }
+        };
+
+        column![
+            row![
+                back_button()
+                    .on_press_maybe((!self.is_working).then(|| back_to_launch_screen(None)))
+            ],
+            widget::text("Create Server").size(20),
+            widget::text(source).size(14),
+            row![
+                widget::text("Name:").size(18),
+                widget::text_input("Server name", &self.name)
+                    .on_input(|n| CreateServerFromMessage::NameInput(n).into()),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            widget::text(hint).size(12).style(tsubtitle),
+        ]
+        .push_maybe(match &self.source {
+            ServerSource::Folder { adopt, .. } => Some(
+                widget::checkbox("Leave the files where they are (don't copy)", *adopt)
+                    .text_size(14)
+                    .size(14)
+                    .on_toggle(|t| CreateServerFromMessage::AdoptToggled(t).into()),
+            ),
+            _ => None,
+        })
+        .push(eula_checkbox(self.accept_eula, |t| {
+            CreateServerFromMessage::EulaToggle(t).into()
+        }))
+        .push(if self.is_working {
+            Element::from(widget::text("Creating server...").size(14))
+        } else {
+            widget::button("Create")
+                .on_press_maybe(
+                    (!self.name.trim().is_empty() && !self.needs_eula())
+                        .then_some(CreateServerFromMessage::Start.into()),
+                )
+                .into()
+        })
+        .padding(10)
+        .spacing(10)
+        .into()
+    }
+}
@@ -5,7 +5,9 @@ use crate::{
         tsubtitle,
     },
     state::{
-        CustomJarState, EditInstanceMessage, ListMessage, MenuEditInstance, Message, NONE_JAR_NAME,
+        BackupsMessage, ClientFromServerMessage, CreateServerFromMessage, CustomJarState,
+        EditInstanceMessage, ListMessage, MenuEditInstance, Message, NONE_JAR_NAME, NetworkMessage,
+        PlayersMessage, ServerListMessage, ServerPropertiesMessage, WatchdogMessage, WorldsMessage,
     },
     stylesheet::{color::Color, styles::LauncherTheme, widgets::StyleButton},
 };
@@ -50,6 +52,14 @@ impl MenuEditInstance {
                         |n| EditInstanceMessage::WindowWidthChanged(n).into(),
                         |n| EditInstanceMessage::WindowHeightChanged(n).into(),
                     ),
+                    row![
+                        widget::button("Worlds").on_press(WorldsMessage::Open.into()),
+                        widget::button("World backups").on_press(BackupsMessage::Open.into()),
+                        widget::button("Server list").on_press(ServerListMessage::Open.into()),
+                        widget::button("Create server")
+                            .on_press(CreateServerFromMessage::OpenFromInstance.into()),
+                    ]
+                    .spacing(5),
                     column![
                         widget::Space::with_height(5),
                         widget::checkbox(
@@ -63,8 +73,37 @@ impl MenuEditInstance {
                     .spacing(5),
                 ]
                 .spacing(20),
-                // TODO: Add option to edit server.properties in user-friendly way
-                InstanceKind::Server => column![widget::button("Edit server.properties")],
+                InstanceKind::Server => column![if self.config.mod_type.is_proxy() {
+                    row![
+                        widget::button("Proxy network").on_press(NetworkMessage::Open.into()),
+                        widget::button("Restarts").on_press(WatchdogMessage::Open.into()),
+                    ]
+                    .spacing(5)
+                } else {
+                    row![
+                        widget::button("Edit server.properties")
+                            .on_press(ServerPropertiesMessage::Open.into()),
+                        widget::button("Players").on_press(PlayersMessage::Open.into()),
+                        widget::button("Worlds").on_press(WorldsMessage::Open.into()),
+                        widget::button("World backups").on_press(BackupsMessage::Open.into()),
+                        widget::button("Restarts").on_press(WatchdogMessage::Open.into()),
+                        widget::button("Client for players")
+                            .on_press(ClientFromServerMessage::Open.into()),
+                    ]
+                    .spacing(5)
+                }]
+                .push_maybe((!self.config.mod_type.is_proxy()).then(|| column![
+                    widget::checkbox(
+                        "Show on LAN",
+                        self.config.lan_broadcast.unwrap_or_default()
+                    )
+                    .on_toggle(|t| EditInstanceMessage::LanBroadcastToggle(t).into()),
+                    widget::text("Lists the server in the multiplayer screen of players on your network, like \"Open to LAN\" in singleplayer")
+                        .size(12)
+                        .style(tsubtitle),
+                ]
+                .spacing(5)))
+                .spacing(20),
             },
             self.item_args(),
             self.item_java_override(),
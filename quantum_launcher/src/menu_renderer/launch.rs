@@ -6,13 +6,15 @@ use ql_core::{Instance, InstanceKind, LAUNCHER_VERSION_NAME};
 
 use crate::cli::EXPERIMENTAL_MMC_IMPORT;
 use crate::menu_renderer::onboarding::x86_warning;
+use crate::menu_renderer::server_list::{view_server_monitor, view_server_status};
 use crate::menu_renderer::{
     CTXI_SIZE, Column, FONT_MONO, barthin, ctx_button_icon, ctxbox, sidebar, tsubtitle,
     view_info_message,
 };
 use crate::state::{
     GameLogMessage, InstanceNotes, LaunchMessage, LaunchModal, LauncherSettingsTab,
-    MainMenuMessage, NotesMessage, ShortcutMessage, SidebarMessage, SidebarScroll, WindowMessage,
+    MainMenuMessage, NotesMessage, ShortcutMessage, SidebarMessage, SidebarScroll, StatusTarget,
+    WindowMessage,
 };
 use crate::{
     icons,
@@ -219,8 +221,22 @@ impl Launcher {
                 )
                 .spacing(16)
                 .align_y(Alignment::Center),
-            main_buttons,
-            notes,
+        ]
+        .push_maybe((is_running && selected.is_server()).then(|| {
+            view_server_status(
+                self.server_status
+                    .get(&StatusTarget::Local(selected.clone())),
+            )
+        }))
+        .push_maybe(
+            self.processes
+                .get(selected)
+                .filter(|_| selected.is_server())
+                .map(|process| view_server_monitor(&process.monitor)),
+        )
+        .push(main_buttons)
+        .push(notes)
+        .push(
             row![
                 row![
                     widget::button(icons::lines_s(10)).padding([5, 8]).on_press(
@@ -240,8 +256,8 @@ impl Launcher {
                 .spacing(5),
                 get_footer_text(),
             ]
-            .align_y(Alignment::End)
-        ]
+            .align_y(Alignment::End),
+        )
         .padding(16)
         .spacing(10)
         .into()
@@ -272,6 +288,7 @@ impl Launcher {
             log: log_data,
             has_crashed,
             command,
+            ..
         }) = self
             .selected_instance
             .as_ref()
@@ -326,14 +343,25 @@ impl Launcher {
                 .size(18),
             ),
         )
-        .push_maybe(
-            matches!(kind, InstanceKind::Server).then_some(
+        .push_maybe(matches!(kind, InstanceKind::Server).then(|| {
+            let suggestions = ql_servers::command_suggestions(command);
+            row![
                 widget::text_input("Enter command...", command)
+                    .id(widget::text_input::Id::new("MenuLaunch:server_command"))
                     .on_input(Message::ServerCommandEdit)
                     .on_submit(Message::ServerCommandSubmit)
                     .width(190),
-            ),
-        )
+                widget::text(match suggestions.len() {
+                    0 => "Up/Down: history, Tab: complete".to_owned(),
+                    1..=8 => suggestions.join(" "),
+                    _ => format!("{} ...", suggestions[..8].join(" ")),
+                })
+                .size(12)
+                .style(tsubtitle),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+        }))
         .push(log)
         .padding(10)
         .spacing(5)
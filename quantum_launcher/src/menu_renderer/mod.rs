@@ -9,9 +9,9 @@ use crate::{
     config::LauncherConfig,
     icons,
     state::{
-        AccountMessage, InfoMessageKind, InstallModsMessage, LauncherSettingsMessage,
-        LauncherSettingsTab, LicenseTab, ManageModsMessage, MenuCurseforgeManualDownload,
-        MenuLicense, Message, ProgressBar,
+        AccountMessage, InfoMessageKind, InstallModsMessage, LaunchMessage,
+        LauncherSettingsMessage, LauncherSettingsTab, LicenseTab, ManageModsMessage,
+        MenuCurseforgeManualDownload, MenuLicense, Message, ProgressBar,
     },
     stylesheet::{color::Color, styles::LauncherTheme, widgets::StyleButton},
 };
@@ -20,16 +20,25 @@ use crate::{
     stylesheet::styles::{BORDER_RADIUS, BORDER_WIDTH, LauncherThemeLightness},
 };
 
+mod backups;
+mod client_from_server;
 mod create;
+mod create_server_from;
 mod edit_instance;
 mod launch;
 mod log;
 mod login;
 mod mods;
+mod network;
 mod onboarding;
+mod players;
+mod server_list;
+mod server_properties;
 mod settings;
 mod shortcuts;
 mod sidebar;
+mod watchdog;
+mod worlds;
 
 pub use onboarding::changelog;
 
@@ -96,7 +105,7 @@ fn view_info_message(
         InfoMessageKind::Success | InfoMessageKind::AtPath(_) => {
             (icons::checkmark(), Color::SecondLight)
         }
-        InfoMessageKind::Error => (icons::qm(), Color::Mid),
+        InfoMessageKind::Error | InfoMessageKind::ErrorWithFix(..) => (icons::qm(), Color::Mid),
     };
 
     widget::container(
@@ -115,6 +124,17 @@ fn view_info_message(
         } else {
             None
         })
+        .push_maybe(
+            if let InfoMessageKind::ErrorWithFix(instance, fix) = &message.kind {
+                Some(
+                    widget::button(widget::text(fix.label()).size(12))
+                        .padding([2, 8])
+                        .on_press_with(|| LaunchMessage::ApplyFix(instance.clone(), *fix).into()),
+                )
+            } else {
+                None
+            },
+        )
         .push(
             widget::button(
                 icons::close()
@@ -213,6 +233,25 @@ pub fn center_x<'a>(e: impl Into<Element<'a>>) -> widget::Row<'a, Message, Launc
     ]
 }
 
+pub const EULA_URL: &str = "https://aka.ms/MinecraftEULA";
+
+/// "I accept the Minecraft EULA" with a link to it,
+/// needed before creating (and running) a server.
+pub fn eula_checkbox<'a>(
+    accepted: bool,
+    on_toggle: impl Fn(bool) -> Message + 'a,
+) -> widget::Row<'a, Message, LauncherTheme> {
+    row![
+        widget::checkbox("I accept the", accepted)
+            .text_size(14)
+            .size(14)
+            .on_toggle(on_toggle),
+        link(widget::text("Minecraft EULA").size(14), EULA_URL.to_owned()),
+    ]
+    .spacing(4)
+    .align_y(Alignment::Center)
+}
+
 pub fn tooltip<'a>(
     e: impl Into<Element<'a>>,
     tooltip: impl Into<Element<'a>>,
@@ -446,6 +485,21 @@ impl MenuCurseforgeManualDownload {
             .height(Length::Fill)
             .style(LauncherTheme::style_scrollable_flat_extra_dark),
 
+            row![
+                widget::text(if let Some(dir) = &self.downloads_dir {
+                    format!(
+                        "Watching {} - downloaded files will be added automatically{}",
+                        dir.to_string_lossy(),
+                        if self.is_scanning { "..." } else { "" }
+                    )
+                } else {
+                    "Couldn't find your downloads folder, add the files manually".to_owned()
+                })
+                .size(12),
+                widget::button(widget::text("Change").size(12))
+                    .on_press(ManageModsMessage::CurseforgeManualPickDir.into()),
+            ].spacing(10).align_y(Alignment::Center).wrap(),
+
             "Warning: Ignoring this may lead to crashes!",
             row![
                 widget::button(widget::text("+ Select above downloaded files").size(14)).on_press(ManageModsMessage::AddFile(self.delete_mods, ql_mod_manager::store::QueryType::ModPacks).into()),
@@ -0,0 +1,85 @@
+use iced::{
+    Length,
+    widget::{self, column, row},
+};
+
+use crate::{
+    menu_renderer::{Element, back_button, tsubtitle},
+    state::{ImportCollectionMessage, ManageModsMessage, MenuImportCollection},
+};
+
+impl MenuImportCollection {
+    pub fn view(&'_ self) -> Element<'_> {
+        if let Some(progress) = &self.progress {
+            return column![
+                widget::text("Importing mods").size(20),
+                progress.view(),
+                widget::text("Check debug log (at the bottom) for more info").size(12),
+            ]
+            .padding(10)
+            .spacing(10)
+            .into();
+        }
+
+        if let Some(result) = &self.result {
+            return column![
+                widget::text!("Imported {}", result.name).size(20),
+                widget::text!("{} installed", result.installed.len()),
+                "These aren't compatible with this instance's version/loader, so they were skipped:",
+                widget::scrollable(
+                    widget::column(result.incompatible.iter().map(|n| {
+                        widget::text!("- {n}")
+                            .shaping(widget::text::Shaping::Advanced)
+                            .into()
+                    }))
+                    .spacing(5)
+                )
+                .width(Length::Fill)
+                .height(Length::Fill),
+                widget::button("Continue").on_press(ManageModsMessage::Open.into()),
+            ]
+            .padding(10)
+            .spacing(10)
+            .into();
+        }
+
+        column![
+            back_button().on_press(ManageModsMessage::Open.into()),
+            widget::text("Import Modrinth collection").size(20),
+            row![
+                widget::text_input("Collection link or ID", &self.input)
+                    .on_input(|n| ImportCollectionMessage::InputChanged(n).into())
+                    .on_submit(ImportCollectionMessage::ImportCollection.into()),
+                widget::button("Import")
+                    .on_press(ImportCollectionMessage::ImportCollection.into()),
+            ]
+            .spacing(5),
+            widget::horizontal_rule(1),
+            widget::text("Followed projects").size(20),
+            widget::text(
+                "Needs a Modrinth personal access token with the \"Read user data\" scope.\nIt's stored in your system keyring."
+            )
+            .size(12)
+            .style(tsubtitle),
+            if self.has_token {
+                row![
+                    widget::button("Import followed projects")
+                        .on_press(ImportCollectionMessage::ImportFollowed.into()),
+                    widget::button("Remove token").on_press(ImportCollectionMessage::TokenClear.into()),
+                ]
+            } else {
+                row![
+                    widget::text_input("Personal access token", &self.token_input)
+                        .secure(true)
+                        .on_input(|n| ImportCollectionMessage::TokenInputChanged(n).into())
+                        .on_submit(ImportCollectionMessage::TokenSave.into()),
+                    widget::button("Save").on_press(ImportCollectionMessage::TokenSave.into()),
+                ]
+            }
+            .spacing(5),
+        ]
+        .padding(10)
+        .spacing(10)
+        .into()
+    }
+}
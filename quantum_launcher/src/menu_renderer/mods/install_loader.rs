@@ -3,15 +3,18 @@ use iced::{
     widget::{self, column, row},
 };
 use ql_core::Instance;
-use ql_mod_manager::loaders::fabric::{self, FabricVersionList, FabricVersionListItem};
+use ql_mod_manager::loaders::{
+    fabric::{self, FabricVersionList, FabricVersionListItem},
+    paper::{BuildChannel, PaperProject},
+};
 
 use crate::state::{InstallPaperMessage, MenuInstallPaper};
 use crate::{
     icons,
-    menu_renderer::{Element, back_button, button_with_icon},
+    menu_renderer::{Element, back_button, button_with_icon, tsubtitle},
     state::{
-        InstallFabricMessage, InstallOptifineMessage, ManageModsMessage, MenuInstallFabric,
-        MenuInstallForge, MenuInstallOptifine, Message,
+        InstallFabricMessage, InstallOptifineMessage, ManageModsMessage, MenuInstallBuildTools,
+        MenuInstallFabric, MenuInstallForge, MenuInstallOptifine, Message,
     },
     stylesheet::styles::LauncherTheme,
 };
@@ -275,38 +278,101 @@ impl MenuInstallForge {
     }
 }
 
+impl MenuInstallBuildTools {
+    pub fn view(&'_ self) -> Element<'_> {
+        let main_block = column![
+            widget::text!("Installing {}...", self.loader).size(20),
+            widget::text("The server is built from source, this takes a few minutes")
+                .size(12)
+                .style(tsubtitle),
+            self.progress.view()
+        ]
+        .spacing(10);
+
+        if self.is_java_getting_installed {
+            column![main_block, self.java_progress.view()]
+        } else {
+            main_block
+        }
+        .padding(20)
+        .spacing(10)
+        .into()
+    }
+}
+
 impl MenuInstallPaper {
     pub fn view(&'_ self, tick_timer: usize) -> Element<'_> {
         let dots = ".".repeat((tick_timer % 3) + 1);
+        let channel_picker = |channel: &BuildChannel| {
+            row![
+                "Channel:",
+                widget::pick_list(BuildChannel::ALL, Some(*channel), |c| {
+                    Message::InstallPaper(InstallPaperMessage::ChannelSelected(c))
+                }),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(5)
+        };
+
         match self {
-            MenuInstallPaper::Loading { .. } => column![
+            MenuInstallPaper::Loading { project, .. } => column![
                 back_button().on_press(ManageModsMessage::Open.into()),
-                widget::text!("Loading{dots}").size(20),
+                widget::text!("Loading {project} builds{dots}").size(20),
             ]
             .padding(10)
             .spacing(10)
             .into(),
-            MenuInstallPaper::Loaded { version, versions } => column![
+            MenuInstallPaper::Loaded {
+                project,
+                channel,
+                version,
+                versions,
+                ..
+            } => column![
                 back_button().on_press(ManageModsMessage::Open.into()),
-                widget::text!("Select Version").size(20),
-                row![widget::pick_list(versions.clone(), Some(version), |v| {
-                    Message::InstallPaper(InstallPaperMessage::VersionSelected(v))
-                })]
-                .push_maybe(
-                    versions
-                        .first()
-                        .is_some_and(|n| n == version)
-                        .then_some("(latest, recommended)"),
-                )
-                .align_y(Alignment::Center),
-                button_with_icon(icons::download(), "Install", 16)
-                    .on_press(Message::InstallPaper(InstallPaperMessage::ButtonClicked)),
+                widget::text!("Install {project}").size(20),
+                channel_picker(channel),
             ]
+            .push(if let Some(version) = version {
+                column![
+                    row![widget::pick_list(versions.clone(), Some(version), |v| {
+                        Message::InstallPaper(InstallPaperMessage::VersionSelected(v))
+                    })]
+                    .push_maybe(
+                        versions
+                            .first()
+                            .is_some_and(|n| n == version)
+                            .then_some("(latest, recommended)"),
+                    )
+                    .align_y(Alignment::Center)
+                    .spacing(5),
+                    button_with_icon(icons::download(), "Install", 16)
+                        .on_press(Message::InstallPaper(InstallPaperMessage::ButtonClicked)),
+                ]
+                .spacing(10)
+            } else {
+                column![
+                    widget::text!("No {channel} builds of {project} for this version").size(14),
+                ]
+                .push_maybe((*channel == BuildChannel::Stable).then(|| {
+                    widget::text("Try the experimental channel")
+                        .size(12)
+                        .style(tsubtitle)
+                }))
+                .spacing(5)
+            })
+            .push_maybe((*project == PaperProject::Velocity).then(|| {
+                widget::text(
+                    "Velocity is a proxy: it doesn't run a world, it connects players to your other servers.",
+                )
+                .size(12)
+                .style(tsubtitle)
+            }))
             .padding(10)
             .spacing(10)
             .into(),
-            MenuInstallPaper::Installing => {
-                column![widget::text!("Installing Paper{dots}").size(20)]
+            MenuInstallPaper::Installing(project) => {
+                column![widget::text!("Installing {project}{dots}").size(20)]
                     .padding(10)
                     .into()
             }
@@ -3,10 +3,12 @@ use iced::{
     widget::{self, column, row},
 };
 
+use ql_packager::ExportCompression;
+
 use crate::{
     icons,
     menu_renderer::{Element, back_button, back_to_launch_screen, button_with_icon},
-    state::{MenuExportInstance, Message},
+    state::{ExportFormat, MenuExportInstance, Message},
 };
 
 impl MenuExportInstance {
@@ -32,20 +34,36 @@ impl MenuExportInstance {
             })
             .width(Length::Fill)
             .height(Length::Fill),
-            column![
-                widget::text("Format:").size(12),
-                row![
-                    widget::pick_list(["QuantumLauncher"], Some("QuantumLauncher"), |_| {
-                        Message::Nothing
-                    })
-                    .text_line_height(1.68),
-                    button_with_icon(icons::floppydisk(), "Export", 16)
-                        .on_press(Message::ExportInstanceStart),
+            if let Some(progress) = &self.progress {
+                column![progress.view()]
+            } else {
+                column![
+                    widget::text("Format:").size(12),
+                    row![
+                        widget::pick_list(
+                            ExportFormat::ALL,
+                            Some(self.format),
+                            Message::ExportInstanceFormat
+                        )
+                        .text_line_height(1.68),
+                    ]
+                    .push_maybe((self.format == ExportFormat::QuantumLauncher).then(|| {
+                        widget::pick_list(
+                            ExportCompression::ALL,
+                            Some(self.compression),
+                            Message::ExportInstanceCompression,
+                        )
+                        .text_line_height(1.68)
+                    }))
+                    .push(
+                        button_with_icon(icons::floppydisk(), "Export", 16)
+                            .on_press(Message::ExportInstanceStart)
+                    )
+                    .spacing(5)
+                    .wrap()
                 ]
-                .spacing(5)
-                .wrap()
-            ]
-            .spacing(2),
+                .spacing(2)
+            },
         ]
         .padding(10)
         .spacing(10)
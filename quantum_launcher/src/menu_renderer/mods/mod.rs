@@ -1,5 +1,6 @@
 mod description;
 mod export_text;
+mod import_collection;
 mod install_loader;
 mod instance_package;
 mod jarmods;
@@ -19,7 +19,7 @@ use iced::{
     widget::{self, column, row},
 };
 use ql_core::{Instance, InstanceKind, Loader, json::InstanceConfigJson};
-use ql_mod_manager::store::QueryType;
+use ql_mod_manager::{loaders::paper::PaperProject, store::QueryType};
 
 pub const MODS_SIDEBAR_WIDTH: u16 = 190;
 
@@ -88,6 +88,8 @@ impl MenuEditMods {
                     ctx_button_icon(icons::download_s(CTXI_SIZE), "See recommended mods").on_press(
                         Message::RecommendedMods(crate::state::RecommendedModMessage::Open)
                     ),
+                    ctx_button_icon(icons::download_s(CTXI_SIZE), "Import Modrinth collection")
+                        .on_press(crate::state::ImportCollectionMessage::Open.into()),
                 ]
                 .spacing(4);
 
@@ -320,12 +322,41 @@ impl MenuEditMods {
                     ]
                     .spacing(5),
                     row![
-                        install_ldr("Bukkit").width(97),
-                        install_ldr("Spigot").width(97)
+                        install_ldr("Bukkit")
+                            .width(97)
+                            .on_press(Message::InstallBuildTools(Loader::Bukkit)),
+                        install_ldr("Spigot")
+                            .width(97)
+                            .on_press(Message::InstallBuildTools(Loader::Spigot)),
                     ]
                     .spacing(5),
-                    install_ldr("Paper")
-                        .on_press(Message::InstallPaper(InstallPaperMessage::ScreenOpen)),
+                    row![
+                        install_ldr("Paper")
+                            .width(97)
+                            .on_press(Message::InstallPaper(InstallPaperMessage::ScreenOpen(
+                                PaperProject::Paper
+                            ))),
+                        install_ldr("Purpur")
+                            .width(97)
+                            .on_press(Message::InstallPaper(InstallPaperMessage::ScreenOpen(
+                                PaperProject::Purpur
+                            ))),
+                    ]
+                    .spacing(5),
+                    row![
+                        install_ldr("Folia")
+                            .width(97)
+                            .on_press(Message::InstallPaper(InstallPaperMessage::ScreenOpen(
+                                PaperProject::Folia
+                            ))),
+                        install_ldr("Velocity")
+                            .width(97)
+                            .on_press(Message::InstallPaper(InstallPaperMessage::ScreenOpen(
+                                PaperProject::Velocity
+                            ))),
+                    ]
+                    .spacing(5),
+                    install_ldr("BungeeCord").on_press(Message::InstallBungeeCord),
                 ]
                 .spacing(5)
                 .into(),
@@ -347,9 +378,16 @@ impl MenuEditMods {
             .spacing(5)
             .into(),
 
-            Loader::NeoForge | Loader::Fabric | Loader::Quilt | Loader::Paper => {
-                Self::get_uninstall_panel(self.file_data.config.mod_type).into()
-            }
+            Loader::NeoForge
+            | Loader::Fabric
+            | Loader::Quilt
+            | Loader::Paper
+            | Loader::Folia
+            | Loader::Purpur
+            | Loader::Velocity
+            | Loader::BungeeCord
+            | Loader::Spigot
+            | Loader::Bukkit => Self::get_uninstall_panel(self.file_data.config.mod_type).into(),
 
             _ => widget::text!("Unknown mod type: {}", self.file_data.config.mod_type).into(),
         }
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use iced::{
+    Alignment, Length,
+    widget::{self, column, row},
+};
+use ql_core::Instance;
+use ql_servers::ForwardingMode;
+
+use crate::{
+    menu_renderer::{Element, back_button, back_to_launch_screen, checkered_list, tsubtitle},
+    state::{GameProcess, MenuNetwork, NetworkMessage},
+    stylesheet::styles::LauncherTheme,
+};
+
+impl MenuNetwork {
+    pub fn view<'a>(
+        &'a self,
+        proxy: &Instance,
+        processes: &HashMap<Instance, GameProcess>,
+    ) -> Element<'a> {
+        let is_running = |name: &str| processes.contains_key(&Instance::server(name));
+        let any_running =
+            is_running(proxy.get_name()) || self.config.backends.iter().any(|n| is_running(n));
+        let can_act = !self.is_working && !self.config.backends.is_empty();
+
+        let header = column![
+            row![
+                back_button()
+                    .on_press_maybe((!self.is_working).then_some(back_to_launch_screen(None))),
+                widget::button(if self.is_working {
+                    "Working..."
+                } else {
+                    "Save & configure servers"
+                })
+                .on_press_maybe(can_act.then_some(NetworkMessage::Apply(false).into())),
+                widget::button("Start network")
+                    .on_press_maybe(can_act.then_some(NetworkMessage::Apply(true).into())),
+                widget::button("Stop network")
+                    .on_press_maybe(any_running.then_some(NetworkMessage::Stop.into())),
+            ]
+            .spacing(5),
+            widget::text("Proxy Network").size(20),
+            widget::text(
+                "Configuring sets up the server list and player forwarding of the proxy and every server. Servers are only reachable through the proxy afterwards.",
+            )
+            .size(12)
+            .style(tsubtitle),
+            self.view_forwarding(),
+        ]
+        .push(
+            column(self.warnings.iter().map(|n| widget::text(n).size(12).into())).spacing(2),
+        )
+        .padding(10)
+        .spacing(10);
+
+        let backends = self
+            .config
+            .backends
+            .iter()
+            .enumerate()
+            .map(|(i, name)| -> Element<'a> {
+                row![
+                    widget::text(name).size(14).width(Length::Fill),
+                    widget::text(if is_running(name) { "Running" } else { "" })
+                        .size(12)
+                        .style(tsubtitle),
+                    widget::button(widget::text("Move up").size(12)).on_press_maybe(
+                        (i > 0 && !self.is_working)
+                            .then_some(NetworkMessage::MoveBackendUp(i).into())
+                    ),
+                    widget::button(widget::text("Remove").size(12)).on_press_maybe(
+                        (!self.is_working).then_some(NetworkMessage::RemoveBackend(i).into())
+                    ),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(5)
+                .padding(10)
+                .into()
+            });
+
+        let others = self.other_servers.iter().map(|name| -> Element<'a> {
+            row![
+                widget::text(name).size(14).width(Length::Fill),
+                widget::button(widget::text("Add").size(12)).on_press_maybe(
+                    (!self.is_working).then(|| NetworkMessage::AddBackend(name.clone()).into())
+                ),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(5)
+            .padding(10)
+            .into()
+        });
+
+        let list = column![
+            widget::text("Servers (players join the first one that's up)")
+                .size(14)
+                .style(tsubtitle),
+        ]
+        .push(if self.config.backends.is_empty() {
+            Element::from(widget::text("None yet, add some below").size(14))
+        } else {
+            checkered_list(backends).into()
+        })
+        .push(widget::text("Other servers").size(14).style(tsubtitle))
+        .push(checkered_list(others))
+        .padding(10)
+        .spacing(5);
+
+        column![
+            header,
+            widget::scrollable(list)
+                .style(LauncherTheme::style_scrollable_flat_extra_dark)
+                .height(Length::Fill)
+                .spacing(1),
+        ]
+        .into()
+    }
+
+    fn view_forwarding(&self) -> Element<'_> {
+        if self.is_bungeecord {
+            return widget::text("Player forwarding: Legacy (BungeeCord)")
+                .size(14)
+                .into();
+        }
+        column![
+            row![
+                widget::text("Player forwarding:").size(14),
+                widget::pick_list(ForwardingMode::ALL, Some(self.config.forwarding), |n| {
+                    NetworkMessage::ForwardingChanged(n).into()
+                })
+                .text_size(14),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            widget::text(match self.config.forwarding {
+                ForwardingMode::Modern => {
+                    "Secure. Needs Paper (or a fork), or Fabric with FabricProxy-Lite on servers"
+                }
+                ForwardingMode::Legacy => "Works with Spigot too, but isn't secured",
+            })
+            .size(12)
+            .style(tsubtitle),
+        ]
+        .spacing(5)
+        .into()
+    }
+}
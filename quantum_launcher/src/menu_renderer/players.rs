@@ -0,0 +1,165 @@
+use iced::{
+    Alignment, Length,
+    widget::{self, column, row},
+};
+
+use crate::{
+    menu_renderer::{Element, back_button, back_to_launch_screen, checkered_list, tsubtitle},
+    state::{MenuPlayers, PlayerListTab, PlayersMessage},
+    stylesheet::styles::LauncherTheme,
+};
+
+const OP_LEVELS: &[u8] = &[1, 2, 3, 4];
+
+impl MenuPlayers {
+    pub fn view(&'_ self, is_running: bool) -> Element<'_> {
+        let is_ban = matches!(
+            self.tab,
+            PlayerListTab::BannedPlayers | PlayerListTab::BannedIps
+        );
+        let can_add = !self.is_working && !self.input.trim().is_empty();
+
+        let tabs = row(PlayerListTab::ALL.iter().map(|tab| {
+            widget::button(widget::text(tab.to_string()).size(14))
+                .on_press_maybe(
+                    (*tab != self.tab).then_some(PlayersMessage::TabChanged(*tab).into()),
+                )
+                .into()
+        }))
+        .spacing(5);
+
+        let header = column![
+            row![
+                back_button()
+                    .on_press_maybe((!self.is_working).then_some(back_to_launch_screen(None))),
+            ],
+            widget::text("Players").size(20),
+            tabs,
+            row![
+                widget::text_input(
+                    if self.tab == PlayerListTab::BannedIps {
+                        "IP address"
+                    } else {
+                        "Player name"
+                    },
+                    &self.input
+                )
+                .size(14)
+                .on_input(|n| PlayersMessage::InputChanged(n).into())
+                .on_submit_maybe(can_add.then_some(PlayersMessage::Add.into())),
+            ]
+            .push_maybe(is_ban.then(|| {
+                widget::text_input("Reason (optional)", &self.reason)
+                    .size(14)
+                    .on_input(|n| PlayersMessage::ReasonChanged(n).into())
+            }))
+            .push(
+                widget::button(widget::text(if is_ban { "Ban" } else { "Add" }).size(14))
+                    .on_press_maybe(can_add.then_some(PlayersMessage::Add.into())),
+            )
+            .align_y(Alignment::Center)
+            .spacing(5),
+            widget::text(if is_running {
+                "The server is running, changes are sent as commands"
+            } else {
+                "Players are looked up by name to get their UUID"
+            })
+            .size(12)
+            .style(tsubtitle),
+        ]
+        .padding(10)
+        .spacing(10);
+
+        let entries: Vec<Element> = match self.tab {
+            PlayerListTab::Whitelist => self
+                .lists
+                .whitelist
+                .iter()
+                .map(|n| self.view_entry(&n.name, &n.uuid, None))
+                .collect(),
+            PlayerListTab::Ops => self
+                .lists
+                .ops
+                .iter()
+                .map(|n| {
+                    let level: Element = if is_running {
+                        widget::text!("Level {}", n.level).size(12).into()
+                    } else {
+                        row![
+                            widget::text("Level").size(12),
+                            widget::pick_list(OP_LEVELS, Some(n.level), |level| {
+                                PlayersMessage::OpLevelChanged(n.name.clone(), level).into()
+                            })
+                            .text_size(12),
+                        ]
+                        .align_y(Alignment::Center)
+                        .spacing(5)
+                        .into()
+                    };
+                    self.view_entry(&n.name, &n.uuid, Some(level))
+                })
+                .collect(),
+            PlayerListTab::BannedPlayers => self
+                .lists
+                .banned_players
+                .iter()
+                .map(|n| {
+                    let reason = widget::text(&n.reason).size(12).style(tsubtitle).into();
+                    self.view_entry(&n.name, &n.uuid, Some(reason))
+                })
+                .collect(),
+            PlayerListTab::BannedIps => self
+                .lists
+                .banned_ips
+                .iter()
+                .map(|n| {
+                    let reason = widget::text(&n.reason).size(12).style(tsubtitle).into();
+                    self.view_entry(&n.ip, "", Some(reason))
+                })
+                .collect(),
+        };
+
+        let list: Element = if entries.is_empty() {
+            widget::text("Nobody here yet")
+                .size(14)
+                .style(tsubtitle)
+                .into()
+        } else {
+            checkered_list(entries).into()
+        };
+
+        column![
+            header,
+            widget::scrollable(list)
+                .style(LauncherTheme::style_scrollable_flat_extra_dark)
+                .height(Length::Fill)
+                .spacing(1),
+        ]
+        .into()
+    }
+
+    fn view_entry<'a>(
+        &'a self,
+        name: &'a str,
+        uuid: &'a str,
+        extra: Option<Element<'a>>,
+    ) -> Element<'a> {
+        row![
+            column![
+                widget::text(name).size(14),
+                widget::text(uuid).size(10).style(tsubtitle),
+            ]
+            .width(Length::Fill),
+        ]
+        .push_maybe(extra)
+        .push(
+            widget::button(widget::text("Remove").size(12)).on_press_maybe(
+                (!self.is_working).then(|| PlayersMessage::Remove(name.to_owned()).into()),
+            ),
+        )
+        .align_y(Alignment::Center)
+        .spacing(10)
+        .padding(10)
+        .into()
+    }
+}
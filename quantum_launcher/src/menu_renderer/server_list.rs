@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use iced::{
+    Alignment, Length,
+    widget::{self, column, row},
+};
+
+use crate::{
+    menu_renderer::{
+        Element, back_button, back_to_launch_screen, checkered_list, tooltip, tsubtitle,
+    },
+    state::{MenuServerList, ServerListMessage, ServerStatusMessage, StatusTarget},
+    stylesheet::styles::LauncherTheme,
+};
+
+impl MenuServerList {
+    pub fn view<'a>(
+        &'a self,
+        status: &'a HashMap<StatusTarget, Result<ql_servers::ServerStatus, String>>,
+    ) -> Element<'a> {
+        let header = column![
+            row![
+                back_button()
+                    .on_press_maybe((!self.is_working).then_some(back_to_launch_screen(None))),
+                widget::button("Add server").on_press(ServerListMessage::Add.into()),
+                widget::button("Refresh").on_press(ServerStatusMessage::Refresh.into()),
+                widget::button(if self.is_working { "Saving..." } else { "Save" }).on_press_maybe(
+                    (self.is_changed && !self.is_working).then_some(ServerListMessage::Save.into())
+                ),
+            ]
+            .spacing(5),
+            widget::text("Multiplayer Servers").size(20),
+            widget::text("Changes made in-game while the launcher has unsaved changes are lost.")
+                .size(12)
+                .style(tsubtitle),
+        ]
+        .padding(10)
+        .spacing(10);
+
+        let servers = self
+            .servers
+            .iter()
+            .enumerate()
+            .map(|(i, server)| -> Element<'_> {
+                column![
+                    row![
+                        widget::text_input("Name", &server.name)
+                            .size(14)
+                            .width(Length::FillPortion(2))
+                            .on_input(move |n| ServerListMessage::NameChanged(i, n).into()),
+                        widget::text_input("Address (example.com:25565)", &server.ip)
+                            .size(14)
+                            .width(Length::FillPortion(3))
+                            .on_input(move |n| ServerListMessage::AddressChanged(i, n).into()),
+                        widget::button(widget::text("Move up").size(12))
+                            .on_press_maybe((i > 0).then_some(ServerListMessage::MoveUp(i).into())),
+                        widget::button(widget::text("Remove").size(12))
+                            .on_press(ServerListMessage::Remove(i).into()),
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(5),
+                ]
+                .push_maybe((!server.ip.is_empty()).then(|| {
+                    view_server_status(status.get(&StatusTarget::Address(server.ip.clone())))
+                }))
+                .spacing(5)
+                .padding(10)
+                .into()
+            });
+
+        let list: Element = if self.servers.is_empty() {
+            widget::text("No servers yet")
+                .size(14)
+                .style(tsubtitle)
+                .into()
+        } else {
+            checkered_list(servers).into()
+        };
+
+        column![
+            header,
+            widget::scrollable(list)
+                .style(LauncherTheme::style_scrollable_flat_extra_dark)
+                .height(Length::Fill)
+                .spacing(1),
+        ]
+        .into()
+    }
+}
+
+/// A server's MOTD, players, version and latency,
+/// from its last ping (`None`: not pinged yet).
+pub fn view_server_status<'a>(
+    status: Option<&'a Result<ql_servers::ServerStatus, String>>,
+) -> Element<'a> {
+    let status = match status {
+        None => return widget::text("Pinging...").size(12).style(tsubtitle).into(),
+        Some(Err(_)) => {
+            return widget::text("Can't reach server")
+                .size(12)
+                .style(tsubtitle)
+                .into();
+        }
+        Some(Ok(status)) => status,
+    };
+
+    let mut details = vec![format!(
+        "{}/{} players",
+        status.players_online, status.players_max
+    )];
+    if let Some(version) = &status.version {
+        details.push(version.clone());
+    }
+    details.push(format!("{} ms", status.latency.as_millis()));
+
+    column![
+        widget::text(&status.motd).size(13),
+        widget::text(details.join(" - ")).size(12).style(tsubtitle),
+    ]
+    .push_maybe((!status.player_sample.is_empty()).then(|| {
+        widget::text(format!("Online: {}", status.player_sample.join(", ")))
+            .size(12)
+            .style(tsubtitle)
+    }))
+    .spacing(2)
+    .into()
+}
+
+/// Health and online players of a running server,
+/// from its logs (see [`ql_servers::ServerEvent`])
+pub fn view_server_monitor(monitor: &ql_servers::ServerMonitor) -> Element<'_> {
+    let mut details = vec![monitor.health().to_string()];
+    if let Some(startup) = monitor.startup_time {
+        details.push(format!("started in {:.1}s", startup.as_secs_f32()));
+    }
+    if let Some(tps) = monitor.tps {
+        details.push(format!("{tps:.1} TPS"));
+    }
+    if monitor.deaths > 0 {
+        details.push(format!("{} deaths", monitor.deaths));
+    }
+
+    let players = monitor.players.iter().map(|(name, player)| {
+        let minutes = player.joined.elapsed().as_secs() / 60;
+        let player_row = row![
+            widget::text(name).size(12),
+            widget::text(format!("{}h {}m", minutes / 60, minutes % 60))
+                .size(12)
+                .style(tsubtitle),
+        ]
+        .push_maybe(
+            player
+                .ip
+                .as_deref()
+                .map(|ip| widget::text(ip).size(12).style(tsubtitle)),
+        )
+        .spacing(10);
+        match &player.uuid {
+            Some(uuid) => tooltip(
+                player_row,
+                widget::text(uuid).size(12),
+                widget::tooltip::Position::Bottom,
+            )
+            .into(),
+            None => Element::from(player_row),
+        }
+    });
+
+    column![
+        widget::text(details.join(" - ")).size(12).style(tsubtitle),
+        widget::text(format!("{} players online", monitor.players.len())).size(12),
+    ]
+    .extend(players)
+    .spacing(2)
+    .into()
+}
@@ -0,0 +1,133 @@
+use iced::{
+    Alignment, Length,
+    widget::{self, column, row},
+};
+use ql_servers::{PropertyInfo, PropertyKind};
+
+use crate::{
+    menu_renderer::{Element, back_button, back_to_launch_screen, checkered_list, tsubtitle},
+    state::{MenuServerProperties, ServerPropertiesMessage},
+    stylesheet::styles::LauncherTheme,
+};
+
+const ERROR_COLOR: iced::Color = iced::Color::from_rgb(0.88, 0.35, 0.35);
+
+impl MenuServerProperties {
+    pub fn view(&'_ self) -> Element<'_> {
+        let errors = self.errors();
+        let unknown: Vec<(&str, &str)> = self
+            .properties
+            .entries()
+            .filter(|(k, _)| !self.known.iter().any(|n| n.key == *k))
+            .collect();
+
+        let header = column![
+            row![
+                back_button().on_press(back_to_launch_screen(None)),
+                widget::button("Save").on_press_maybe(
+                    errors
+                        .is_empty()
+                        .then_some(ServerPropertiesMessage::Save.into())
+                ),
+            ]
+            .spacing(5),
+            widget::text("Edit server.properties").size(20),
+        ]
+        .push_maybe(self.is_new.then_some(
+            widget::text(
+                "The server hasn't been run yet, so only changed properties will be saved.\nThe rest are filled in with defaults on first run.",
+            )
+            .size(12)
+            .style(tsubtitle),
+        ))
+        .padding(10)
+        .spacing(10);
+
+        let known = self.known.iter().map(|info| {
+            let error = errors.iter().find(|(k, _)| *k == info.key);
+            self.view_property(info, error.map(|(_, e)| e.clone()))
+        });
+        let unknown = (!unknown.is_empty()).then(|| {
+            column![widget::text("Other (from mods/plugins)").size(16)]
+                .extend(unknown.into_iter().map(|(key, value)| {
+                    row![
+                        widget::text(key).size(14).width(Length::FillPortion(2)),
+                        widget::text_input("", value)
+                            .size(14)
+                            .width(Length::FillPortion(3))
+                            .on_input(move |n| {
+                                ServerPropertiesMessage::Edit(key.to_owned(), n).into()
+                            }),
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(10)
+                    .into()
+                }))
+                .padding(10)
+                .spacing(5)
+        });
+
+        column![
+            header,
+            widget::scrollable(checkered_list(known.chain(unknown.map(Into::into))))
+                .style(LauncherTheme::style_scrollable_flat_extra_dark)
+                .height(Length::Fill)
+                .spacing(1),
+        ]
+        .into()
+    }
+
+    fn view_property(&self, info: &'static PropertyInfo, error: Option<String>) -> Element<'_> {
+        let value = self.properties.get(info.key);
+        let current = value.unwrap_or(info.default);
+        let key = info.key;
+
+        let input: Element = match info.kind {
+            PropertyKind::Bool => widget::checkbox("", current == "true")
+                .on_toggle(move |t| {
+                    ServerPropertiesMessage::Edit(key.to_owned(), t.to_string()).into()
+                })
+                .into(),
+            PropertyKind::Enum(values) => widget::pick_list(
+                values,
+                values.iter().find(|n| **n == current),
+                move |n: &str| ServerPropertiesMessage::Edit(key.to_owned(), n.to_owned()).into(),
+            )
+            .text_size(14)
+            .into(),
+            PropertyKind::Int { .. } | PropertyKind::String => {
+                widget::text_input(info.default, value.unwrap_or_default())
+                    .size(14)
+                    .on_input(move |n| ServerPropertiesMessage::Edit(key.to_owned(), n).into())
+                    .into()
+            }
+        };
+
+        column![
+            row![
+                widget::text(key).size(14).width(Length::FillPortion(2)),
+                row![input]
+                    .push_maybe(
+                        value.is_some().then_some(
+                            widget::button(widget::text("Reset").size(12))
+                                .on_press(ServerPropertiesMessage::Reset(key.to_owned()).into())
+                        )
+                    )
+                    .align_y(Alignment::Center)
+                    .spacing(5)
+                    .width(Length::FillPortion(3)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            widget::text(info.description).size(12).style(tsubtitle),
+        ]
+        .push_maybe(error.map(|e| {
+            widget::text!("Invalid value: {e}")
+                .size(12)
+                .color(ERROR_COLOR)
+        }))
+        .padding(10)
+        .spacing(5)
+        .into()
+    }
+}
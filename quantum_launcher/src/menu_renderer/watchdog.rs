@@ -0,0 +1,137 @@
+use chrono::{Local, NaiveTime};
+use iced::{
+    Alignment, Length,
+    widget::{self, column, row},
+};
+
+use crate::{
+    menu_renderer::{Element, back_button, back_to_launch_screen, checkered_list, tsubtitle},
+    state::{MenuWatchdog, WatchdogMessage},
+    stylesheet::styles::LauncherTheme,
+};
+
+impl MenuWatchdog {
+    pub fn view(&'_ self) -> Element<'_> {
+        let header = column![
+            row![back_button().on_press(back_to_launch_screen(None))],
+            widget::text("Restarts").size(20),
+            self.view_settings(),
+            widget::text("Restart history").size(16),
+        ]
+        .padding(10)
+        .spacing(10);
+
+        let events = self.history.iter().map(|event| -> Element<'_> {
+            row![
+                widget::text(
+                    event
+                        .time
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                )
+                .size(14),
+                widget::text(event.kind.to_string())
+                    .size(12)
+                    .style(tsubtitle),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+            .padding(10)
+            .into()
+        });
+
+        let list: Element = if self.history.is_empty() {
+            widget::text("No restarts yet")
+                .size(14)
+                .style(tsubtitle)
+                .into()
+        } else {
+            checkered_list(events).into()
+        };
+
+        column![
+            header,
+            widget::scrollable(list)
+                .style(LauncherTheme::style_scrollable_flat_extra_dark)
+                .height(Length::Fill)
+                .spacing(1),
+        ]
+        .into()
+    }
+
+    fn view_settings(&self) -> Element<'_> {
+        let config = &self.config;
+        let number = |label: &'static str, value: u32, f: fn(String) -> WatchdogMessage| {
+            row![
+                widget::text(label).size(14).width(250),
+                widget::text_input("", &value.to_string())
+                    .size(14)
+                    .width(80)
+                    .on_input(move |n| f(n).into()),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+        };
+
+        let is_time_invalid = !self.daily_restart.trim().is_empty()
+            && NaiveTime::parse_from_str(self.daily_restart.trim(), "%H:%M").is_err();
+
+        column![
+            widget::checkbox("Restart when the server crashes", config.restart_on_crash)
+                .size(14)
+                .text_size(14)
+                .on_toggle(|t| WatchdogMessage::RestartOnCrashToggled(t).into()),
+            number(
+                "Give up after this many crashes",
+                config.max_crashes,
+                WatchdogMessage::MaxCrashesChanged,
+            ),
+            number(
+                "...within (minutes)",
+                config.crash_window_minutes,
+                WatchdogMessage::CrashWindowChanged,
+            ),
+            number(
+                "Wait before restarting (seconds)",
+                config.backoff_seconds,
+                WatchdogMessage::BackoffChanged,
+            ),
+            number(
+                "Longest wait (seconds)",
+                config.max_backoff_seconds,
+                WatchdogMessage::MaxBackoffChanged,
+            ),
+            widget::text("The wait doubles with every crash in a row.")
+                .size(12)
+                .style(tsubtitle),
+            row![
+                widget::text("Restart every day at").size(14).width(250),
+                widget::text_input("HH:MM", &self.daily_restart)
+                    .size(14)
+                    .width(80)
+                    .on_input(|n| WatchdogMessage::DailyRestartChanged(n).into()),
+            ]
+            .push_maybe(
+                is_time_invalid.then(|| widget::text("Invalid time").size(12).style(tsubtitle))
+            )
+            .align_y(Alignment::Center)
+            .spacing(10),
+            row![
+                widget::text("Warn players before (seconds)")
+                    .size(14)
+                    .width(250),
+                widget::text_input("600, 60, 10", &self.warnings)
+                    .size(14)
+                    .on_input(|n| WatchdogMessage::WarningsChanged(n).into()),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            widget::text("Leave the time empty to disable daily restarts.")
+                .size(12)
+                .style(tsubtitle),
+        ]
+        .spacing(5)
+        .into()
+    }
+}
@@ -0,0 +1,158 @@
+use chrono::Local;
+use iced::{
+    Alignment, Length,
+    widget::{self, column, row},
+};
+use ql_core::nbt::level::{Difficulty, GameMode, WorldInfo};
+
+use crate::{
+    menu_renderer::{Element, back_button, back_to_launch_screen, checkered_list, tsubtitle},
+    state::{MenuWorlds, Message, WorldsMessage},
+    stylesheet::styles::LauncherTheme,
+};
+
+impl MenuWorlds {
+    pub fn view(&'_ self, is_running: bool) -> Element<'_> {
+        let header = column![
+            row![
+                back_button()
+                    .on_press_maybe((!self.is_working).then_some(back_to_launch_screen(None))),
+            ],
+            widget::text("Worlds").size(20),
+        ]
+        .push_maybe(is_running.then(|| {
+            widget::text("Stop the game to edit worlds (it would overwrite the changes)")
+                .size(12)
+                .style(tsubtitle)
+        }))
+        .padding(10)
+        .spacing(10);
+
+        let worlds = self
+            .worlds
+            .iter()
+            .enumerate()
+            .map(|(i, world)| match &self.editing {
+                Some((j, edited)) if *j == i => self.view_edit(edited, is_running),
+                _ => self.view_world(i, world, is_running),
+            });
+
+        let list: Element = if self.worlds.is_empty() {
+            widget::text("No worlds yet")
+                .size(14)
+                .style(tsubtitle)
+                .into()
+        } else {
+            checkered_list(worlds).into()
+        };
+
+        column![
+            header,
+            widget::scrollable(list)
+                .style(LauncherTheme::style_scrollable_flat_extra_dark)
+                .height(Length::Fill)
+                .spacing(1),
+        ]
+        .into()
+    }
+
+    fn view_world<'a>(&'a self, i: usize, world: &'a WorldInfo, is_running: bool) -> Element<'a> {
+        let mut details = vec![format!(
+            "{}{}",
+            world.game_mode,
+            if world.hardcore { " (Hardcore)" } else { "" }
+        )];
+        if let Some(version) = &world.version {
+            details.push(version.clone());
+        }
+        if let Some(last_played) = world.last_played {
+            details.push(format!(
+                "Last played {}",
+                last_played.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            ));
+        }
+
+        column![
+            row![
+                widget::text(&world.name).size(16),
+                widget::text(details.join(" - ")).size(12).style(tsubtitle),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            row![
+                widget::text(
+                    world
+                        .seed
+                        .map_or_else(|| "Seed: unknown".to_owned(), |n| format!("Seed: {n}"))
+                )
+                .size(12)
+                .style(tsubtitle),
+                widget::button(widget::text("Copy seed").size(12))
+                    .on_press_maybe(world.seed.map(|n| Message::CoreCopyText(n.to_string()))),
+                widget::button(widget::text("Edit").size(12)).on_press_maybe(
+                    (!is_running && !self.is_working && self.editing.is_none())
+                        .then_some(WorldsMessage::Edit(i).into())
+                ),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+        ]
+        .padding(10)
+        .spacing(5)
+        .into()
+    }
+
+    fn view_edit<'a>(&'a self, world: &'a WorldInfo, is_running: bool) -> Element<'a> {
+        let label = |n: &'static str| widget::text(n).size(14).width(100);
+        column![
+            row![
+                label("Name"),
+                widget::text_input("", &world.name)
+                    .size(14)
+                    .on_input(|n| WorldsMessage::NameChanged(n).into()),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            row![
+                label("Game mode"),
+                widget::pick_list(GameMode::ALL, Some(world.game_mode), |n| {
+                    WorldsMessage::GameModeChanged(n).into()
+                })
+                .text_size(14),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            row![
+                label("Difficulty"),
+                widget::pick_list(Difficulty::ALL, Some(world.difficulty), |n| {
+                    WorldsMessage::DifficultyChanged(n).into()
+                })
+                .text_size(14),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            widget::checkbox("Hardcore", world.hardcore)
+                .size(14)
+                .text_size(14)
+                .on_toggle(|t| WorldsMessage::HardcoreToggled(t).into()),
+            widget::checkbox("Allow cheats", world.allow_commands)
+                .size(14)
+                .text_size(14)
+                .on_toggle(|t| WorldsMessage::CommandsToggled(t).into()),
+            row![
+                widget::button(widget::text("Save").size(14)).on_press_maybe(
+                    (!is_running && !self.is_working).then_some(WorldsMessage::Save.into())
+                ),
+                widget::button(widget::text("Cancel").size(14))
+                    .on_press_maybe((!self.is_working).then_some(WorldsMessage::EditCancel.into())),
+            ]
+            .spacing(5),
+            widget::text("The previous level.dat is kept as level.dat_old")
+                .size(12)
+                .style(tsubtitle),
+        ]
+        .padding(10)
+        .spacing(8)
+        .into()
+    }
+}
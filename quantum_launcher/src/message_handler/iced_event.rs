@@ -3,9 +3,11 @@ use crate::message_update::MSG_RESIZE;
 use crate::state::{
     AutoSaveKind, CreateInstanceMessage, EditModsUpdates, InfoMessage, LaunchMessage, LaunchTab,
     Launcher, LauncherSettingsMessage, LauncherSettingsTab, MainMenuMessage, ManageModsMessage,
-    MenuCreateInstance, MenuCreateInstanceChoosing, MenuEditMods, MenuEditPresets,
-    MenuExportInstance, MenuInstallFabric, MenuInstallOptifine, MenuInstallPaper,
-    MenuLoginAlternate, MenuLoginMS, MenuRecommendedMods, MenuWelcome, Message, State,
+    MenuBackups, MenuClientFromServer, MenuCreateInstance, MenuCreateInstanceChoosing,
+    MenuCreateServerFrom, MenuEditMods, MenuEditPresets, MenuExportInstance, MenuImportCollection,
+    MenuInstallFabric, MenuInstallOptifine, MenuInstallPaper, MenuLoginAlternate, MenuLoginMS,
+    MenuNetwork, MenuPlayers, MenuRecommendedMods, MenuServerList, MenuWelcome, MenuWorlds,
+    Message, State,
 };
 use iced::{
     Task,
@@ -189,7 +191,25 @@ impl Launcher {
             } else if let Key::Named(Named::ArrowDown) = key {
                 return Task::done(Message::LicenseChangeTab(menu.selected_tab.next()));
             }
-        } else if let (State::Launch(_), true) = (&self.state, ignored) {
+        } else if let (State::Launch(menu), true) = (&self.state, ignored) {
+            let in_server_console = matches!(menu.tab, LaunchTab::Log)
+                && self
+                    .selected_instance
+                    .as_ref()
+                    .is_some_and(|n| n.is_server() && self.processes.contains_key(n));
+            if in_server_console {
+                match key {
+                    Key::Named(Named::ArrowUp) => {
+                        return Task::done(Message::ServerCommandHistory(true));
+                    }
+                    Key::Named(Named::ArrowDown) => {
+                        return Task::done(Message::ServerCommandHistory(false));
+                    }
+                    Key::Named(Named::Tab) => return Task::done(Message::ServerCommandComplete),
+                    _ => {}
+                }
+            }
+
             if let Key::Named(Named::ArrowUp) = key {
                 return self.select_instance_recursive(InstSelectOperation::Up);
             } else if let Key::Named(Named::ArrowDown) = key {
@@ -323,6 +343,29 @@ impl Launcher {
                 is_loading: false, ..
             })
             | State::CreateShortcut(_)
+            | State::ServerProperties(_)
+            | State::Backups(MenuBackups {
+                is_working: false, ..
+            })
+            | State::Network(MenuNetwork {
+                is_working: false, ..
+            })
+            | State::Worlds(MenuWorlds {
+                is_working: false, ..
+            })
+            | State::ServerList(MenuServerList {
+                is_working: false, ..
+            })
+            | State::Players(MenuPlayers {
+                is_working: false, ..
+            })
+            | State::Watchdog(_)
+            | State::CreateServerFrom(MenuCreateServerFrom {
+                is_working: false, ..
+            })
+            | State::ClientFromServer(MenuClientFromServer {
+                is_working: false, ..
+            })
             | State::Welcome(_) => {
                 ret_to_main_screen = true;
             }
@@ -362,6 +405,7 @@ impl Launcher {
             | State::InstallPaper(
                 MenuInstallPaper::Loading { .. } | MenuInstallPaper::Loaded { .. },
             )
+            | State::ImportCollection(MenuImportCollection { progress: None, .. })
             | State::ModDescription(_) => {
                 ret_to_mods = true;
             }
@@ -376,6 +420,7 @@ impl Launcher {
             State::InstallPaper(_)
             | State::ExportInstance(_)
             | State::InstallForge(_)
+            | State::InstallBuildTools(_)
             | State::InstallJava
             | State::InstallOptifine(_)
             | State::InstallFabric(_)
@@ -387,6 +432,14 @@ impl Launcher {
             | State::AccountLoginProgress(_)
             | State::ImportModpack(_)
             | State::CurseforgeManualDownload(_)
+            | State::ImportCollection(_)
+            | State::Backups(_)
+            | State::Network(_)
+            | State::Worlds(_)
+            | State::ServerList(_)
+            | State::Players(_)
+            | State::CreateServerFrom(_)
+            | State::ClientFromServer(_)
             | State::LoginAlternate(_)
             | State::LogUploadResult { .. }
             | State::RecommendedMods(MenuRecommendedMods::Loading { .. })
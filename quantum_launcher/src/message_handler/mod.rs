@@ -4,19 +4,19 @@ use crate::{
     state::{
         AutoSaveKind, ContentWatcher, EditModsFileData, EditModsSelection, EditModsUiState,
         EditModsUpdates, EditPresetsMessage, FsWatcher, InfoMessage, LaunchTab, LogState,
-        ManageModsMessage, MenuEditMods, MenuInstallForge, MenuInstallOptifine, ProgressBar,
-        SelectedState, State,
+        ManageModsMessage, MenuCurseforgeManualDownload, MenuEditMods, MenuInstallBuildTools,
+        MenuInstallForge, MenuInstallOptifine, ProgressBar, SelectedState, State,
     },
 };
 use iced::{Task, futures::executor::block_on, widget::scrollable::AbsoluteOffset};
 use ql_core::{
-    GenericProgress, Instance, IntoIoError, IntoStringError, err,
+    GenericProgress, Instance, IntoIoError, IntoStringError, Loader, err,
     file_utils::exists,
     json::{VersionDetails, instance_config::InstanceConfigJson},
 };
 use ql_mod_manager::{
     loaders,
-    store::{LocalMod, ModIndex, QueryType},
+    store::{CurseforgeNotAllowed, LocalMod, ModIndex, QueryType},
 };
 use std::{
     collections::HashSet,
@@ -105,6 +105,24 @@ impl Launcher {
         self.autosave.remove(&AutoSaveKind::LauncherConfig);
     }
 
+    /// Asks the user to manually download files blocked by CurseForge,
+    /// picking them up from their downloads folder automatically.
+    pub fn go_to_curseforge_manual_download(
+        &mut self,
+        not_allowed: HashSet<CurseforgeNotAllowed>,
+    ) -> Task<Message> {
+        let downloads_dir = self
+            .config
+            .persistent
+            .as_ref()
+            .and_then(|n| n.downloads_dir.clone())
+            .or_else(dirs::download_dir);
+        let mut menu = MenuCurseforgeManualDownload::new(not_allowed, downloads_dir);
+        let task = menu.scan(self.instance().clone());
+        self.state = State::CurseforgeManualDownload(menu);
+        task
+    }
+
     pub fn go_to_edit_mods_menu(&mut self, msg: Option<InfoMessage>) -> Task<Message> {
         async fn inner(
             this: &mut Launcher,
@@ -177,6 +195,29 @@ impl Launcher {
         }
     }
 
+    pub fn install_buildtools(&mut self, loader: Loader) -> Task<Message> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let (j_sender, j_receiver) = std::sync::mpsc::channel();
+        let instance = self.instance().clone();
+
+        let command = Task::perform(
+            async move {
+                loaders::buildtools::install(instance, loader, Some(&sender), Some(&j_sender))
+                    .await
+                    .strerr()
+            },
+            Message::InstallBuildToolsEnd,
+        );
+
+        self.state = State::InstallBuildTools(MenuInstallBuildTools {
+            loader,
+            progress: ProgressBar::with_recv(receiver),
+            java_progress: ProgressBar::with_recv(j_receiver),
+            is_java_getting_installed: false,
+        });
+        command
+    }
+
     pub fn install_forge(&mut self, kind: ForgeKind) -> Task<Message> {
         let (f_sender, f_receiver) = std::sync::mpsc::channel();
         let (j_sender, j_receiver): (Sender<GenericProgress>, Receiver<GenericProgress>) =
@@ -0,0 +1,175 @@
+use iced::Task;
+use ql_core::{Instance, IntoStringError};
+use ql_servers::{BackupConfig, BackupReason, RestoreTarget};
+
+use crate::state::{BackupsMessage, InfoMessage, Launcher, MenuBackups, Message, State};
+
+impl Launcher {
+    pub fn update_backups(&mut self, msg: BackupsMessage) -> Task<Message> {
+        match msg {
+            BackupsMessage::Open => {
+                self.state = State::GenericMessage("Loading backups...".to_owned());
+                return load_backups(self.instance().clone());
+            }
+            BackupsMessage::Loaded(res) => match res {
+                Ok((snapshots, config)) => {
+                    if let State::Backups(menu) = &mut self.state {
+                        menu.snapshots = snapshots;
+                        menu.config = config;
+                        menu.is_working = false;
+                    } else {
+                        self.state = State::Backups(MenuBackups {
+                            snapshots,
+                            config,
+                            new_server_name: String::new(),
+                            is_working: false,
+                        });
+                    }
+                }
+                Err(err) => self.set_error(err),
+            },
+            BackupsMessage::Create => {
+                let State::Backups(menu) = &mut self.state else {
+                    return Task::none();
+                };
+                menu.is_working = true;
+                let instance = self.instance().clone();
+                let process = self.processes.get(&instance).map(|n| n.child.clone());
+                return Task::perform(
+                    async move {
+                        ql_servers::create_snapshot(
+                            &instance,
+                            process.as_ref(),
+                            BackupReason::Manual,
+                        )
+                        .await
+                        .strerr()
+                        .map(|_| ())
+                    },
+                    |n| BackupsMessage::Refresh(n).into(),
+                );
+            }
+            BackupsMessage::Delete(id) => {
+                if let State::Backups(menu) = &mut self.state {
+                    menu.is_working = true;
+                    let instance = self.instance().clone();
+                    return Task::perform(
+                        async move { ql_servers::delete_snapshot(&instance, &id).await.strerr() },
+                        |n| BackupsMessage::Refresh(n).into(),
+                    );
+                }
+            }
+            BackupsMessage::Refresh(res) => match res {
+                Ok(()) => return load_backups(self.instance().clone()),
+                Err(err) => self.set_error(err),
+            },
+            BackupsMessage::Restore(id) => {
+                return self.restore_backup(id, RestoreTarget::Live);
+            }
+            BackupsMessage::RestoreAsNew(id) => {
+                if let State::Backups(menu) = &self.state {
+                    let name = menu.new_server_name.clone();
+                    return self.restore_backup(id, RestoreTarget::NewServer(name));
+                }
+            }
+            BackupsMessage::Restored(res) => match res {
+                Ok(name) => {
+                    if name != self.instance().get_name() {
+                        self.selected_instance = Some(Instance::server(&name));
+                    }
+                    return self.go_to_main_menu(Some(InfoMessage::success(format!(
+                        "Restored backup into {name}"
+                    ))));
+                }
+                Err(err) => self.set_error(err),
+            },
+            BackupsMessage::NewServerNameChanged(name) => {
+                if let State::Backups(menu) = &mut self.state {
+                    menu.new_server_name = name;
+                }
+            }
+            BackupsMessage::IntervalChanged(n) => {
+                return self.edit_backup_config(|config| {
+                    if n.is_empty() {
+                        config.interval_minutes = None;
+                    } else if let Ok(n) = n.parse() {
+                        config.interval_minutes = Some(n);
+                    }
+                });
+            }
+            BackupsMessage::OnStopToggled(t) => {
+                return self.edit_backup_config(|config| config.on_stop = t);
+            }
+            BackupsMessage::KeepLastChanged(n) => {
+                return self.edit_backup_config(|config| {
+                    config.retention.keep_last = parse_count(&n, config.retention.keep_last);
+                });
+            }
+            BackupsMessage::KeepDailyChanged(n) => {
+                return self.edit_backup_config(|config| {
+                    config.retention.keep_daily = parse_count(&n, config.retention.keep_daily);
+                });
+            }
+            BackupsMessage::KeepWeeklyChanged(n) => {
+                return self.edit_backup_config(|config| {
+                    config.retention.keep_weekly = parse_count(&n, config.retention.keep_weekly);
+                });
+            }
+            BackupsMessage::ConfigSaved(res) => {
+                if let Err(err) = res {
+                    self.set_error(err);
+                }
+            }
+        }
+        Task::none()
+    }
+
+    fn restore_backup(&mut self, id: String, target: RestoreTarget) -> Task<Message> {
+        let State::Backups(menu) = &mut self.state else {
+            return Task::none();
+        };
+        menu.is_working = true;
+        let instance = self.instance().clone();
+        Task::perform(
+            async move {
+                ql_servers::restore_snapshot(&instance, &id, target)
+                    .await
+                    .strerr()
+            },
+            |n| BackupsMessage::Restored(n).into(),
+        )
+    }
+
+    /// Changes the backup config, saving it right away
+    /// (the scheduler of a running server picks it up).
+    fn edit_backup_config(&mut self, f: impl FnOnce(&mut BackupConfig)) -> Task<Message> {
+        let State::Backups(menu) = &mut self.state else {
+            return Task::none();
+        };
+        f(&mut menu.config);
+        let config = menu.config.clone();
+        let instance = self.instance().clone();
+        Task::perform(async move { config.save(&instance).await.strerr() }, |n| {
+            BackupsMessage::ConfigSaved(n).into()
+        })
+    }
+}
+
+fn load_backups(instance: Instance) -> Task<Message> {
+    Task::perform(
+        async move {
+            let snapshots = ql_servers::list_snapshots(&instance).await.strerr()?;
+            let config = BackupConfig::load(&instance).await.strerr()?;
+            Ok((snapshots, config))
+        },
+        |n| BackupsMessage::Loaded(n).into(),
+    )
+}
+
+fn parse_count(input: &str, old: usize) -> usize {
+    if input.is_empty() {
+        0
+    } else {
+        input.parse().unwrap_or(old)
+    }
+}
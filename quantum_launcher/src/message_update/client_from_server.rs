@@ -0,0 +1,96 @@
+use iced::Task;
+use ql_core::{Instance, IntoIoError, IntoStringError};
+
+use crate::state::{
+    ClientFromServerMessage, InfoMessage, Launcher, MenuClientFromServer, Message, State,
+};
+
+impl Launcher {
+    pub fn update_client_from_server(&mut self, msg: ClientFromServerMessage) -> Task<Message> {
+        match msg {
+            ClientFromServerMessage::Open => {
+                let server = self.instance().clone();
+                self.state = State::ClientFromServer(MenuClientFromServer {
+                    name: server.name.to_string(),
+                    server,
+                    companions: String::new(),
+                    is_working: false,
+                });
+            }
+            ClientFromServerMessage::NameInput(name) => {
+                if let State::ClientFromServer(menu) = &mut self.state {
+                    menu.name = name;
+                }
+            }
+            ClientFromServerMessage::CompanionsInput(companions) => {
+                if let State::ClientFromServer(menu) = &mut self.state {
+                    menu.companions = companions;
+                }
+            }
+            ClientFromServerMessage::CreateInstance => {
+                let State::ClientFromServer(menu) = &mut self.state else {
+                    return Task::none();
+                };
+                menu.is_working = true;
+                let server = menu.server.clone();
+                let name = menu.name.clone();
+                let companions = menu.companion_ids();
+                return Task::perform(
+                    async move {
+                        ql_packager::create_client_from_server(&server, name, companions, None)
+                            .await
+                            .strerr()
+                    },
+                    |n| ClientFromServerMessage::InstanceCreated(n).into(),
+                );
+            }
+            ClientFromServerMessage::ExportPack => {
+                let State::ClientFromServer(menu) = &mut self.state else {
+                    return Task::none();
+                };
+                let Some(path) = rfd::FileDialog::new()
+                    .set_title("Save modpack for players...")
+                    .add_filter("Modrinth modpack", &["mrpack"])
+                    .set_file_name(format!("{}.mrpack", menu.server.name))
+                    .save_file()
+                else {
+                    return Task::none();
+                };
+                menu.is_working = true;
+                let server = menu.server.clone();
+                let companions = menu.companion_ids();
+                return Task::perform(
+                    async move {
+                        let file =
+                            ql_mod_manager::store::export_modrinth_pack(&server, &companions)
+                                .await
+                                .strerr()?;
+                        tokio::fs::write(&path, file).await.path(&path).strerr()
+                    },
+                    |n| ClientFromServerMessage::PackExported(n).into(),
+                );
+            }
+            ClientFromServerMessage::InstanceCreated(Ok(created)) => {
+                self.selected_instance = Some(Instance::client(&created.name));
+                if !created.not_allowed.is_empty() {
+                    return self.go_to_curseforge_manual_download(created.not_allowed);
+                }
+                let message = if created.skipped_mods.is_empty() {
+                    "Created Instance".to_owned()
+                } else {
+                    format!(
+                        "Created Instance (left out {} server-only mods)",
+                        created.skipped_mods.len()
+                    )
+                };
+                return self.go_to_main_menu(Some(InfoMessage::success(message)));
+            }
+            ClientFromServerMessage::PackExported(Ok(())) => {
+                return self.go_to_main_menu(Some(InfoMessage::success("Exported Modpack")));
+            }
+            ClientFromServerMessage::InstanceCreated(Err(err))
+            | ClientFromServerMessage::PackExported(Err(err)) => self.set_error(err),
+        }
+        Task::none()
+    }
+}
@@ -109,6 +109,9 @@ impl Launcher {
             CreateInstanceMessage::ChangeAssetToggle(t) => iflet!(self, download_assets; {
                 *download_assets = t;
             }),
+            CreateInstanceMessage::EulaToggle(t) => iflet!(self, accept_eula; {
+                *accept_eula = t;
+            }),
             CreateInstanceMessage::Import => {
                 if let Some(file) = rfd::FileDialog::new()
                     .set_title("Select an instance...")
@@ -192,6 +195,7 @@ then go to "Mods->Add File""#,
             },
             instance_name: String::new(),
             download_assets: true,
+            accept_eula: false,
             search_box: String::new(),
             selected_categories: self.config.c_persistent().get_create_instance_filters(),
             kind,
@@ -203,7 +207,7 @@ then go to "Mods->Add File""#,
     }
 
     fn create_instance(&mut self) -> Task<Message> {
-        iflet!(self, instance_name, download_assets, selected_version, kind; {
+        iflet!(self, instance_name, download_assets, accept_eula, selected_version, kind; {
             let already_exists = {
                 let existing_instances = match kind {
                     InstanceKind::Client => self.client_list.as_ref(),
@@ -215,7 +219,7 @@ then go to "Mods->Add File""#,
                 })
             };
 
-            if already_exists {
+            if already_exists || (*kind == InstanceKind::Server && !*accept_eula) {
                 return Task::none();
             }
 
@@ -245,10 +249,15 @@ then go to "Mods->Add File""#,
                 InstanceKind::Server => Task::perform(
                     async move {
                         let sender = sender;
-                        ql_servers::create_server(instance_name.clone(), version, Some(&sender))
+                        let name =
+                            ql_servers::create_server(instance_name.clone(), version, Some(&sender))
+                                .await
+                                .strerr()?;
+                        let instance = Instance::server(&name);
+                        ql_servers::accept_eula(&instance.get_instance_path())
                             .await
-                            .strerr()
-                            .map(|n| Instance::server(&n))
+                            .strerr()?;
+                        Ok(instance)
                     },
                     |n| CreateInstanceMessage::End(n).into(),
                 ),
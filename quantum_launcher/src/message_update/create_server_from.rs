@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+
+use iced::Task;
+use ql_core::{Instance, IntoIoError, IntoStringError};
+use ql_servers::{CreatedServer, ImportMode};
+
+use crate::state::{
+    CreateServerFromMessage, InfoMessage, Launcher, MenuCreateServerFrom, Message, ServerSource,
+    State,
+};
+
+impl Launcher {
+    pub fn update_create_server_from(&mut self, msg: CreateServerFromMessage) -> Task<Message> {
+        match msg {
+            CreateServerFromMessage::OpenFromInstance => {
+                let client = self.instance().clone();
+                self.state = State::CreateServerFrom(MenuCreateServerFrom {
+                    name: format!("{} server", client.name),
+                    source: ServerSource::Client(client),
+                    accept_eula: false,
+                    is_working: false,
+                });
+            }
+            CreateServerFromMessage::OpenFromModpack => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .set_title("Select a modpack...")
+                    .add_filter("Modpack", &["mrpack", "zip"])
+                    .pick_file()
+                {
+                    let name = path
+                        .file_stem()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    self.state = State::CreateServerFrom(MenuCreateServerFrom {
+                        name,
+                        source: ServerSource::Modpack(path),
+                        accept_eula: false,
+                        is_working: false,
+                    });
+                }
+            }
+            CreateServerFromMessage::OpenFromFolder => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .set_title("Select a server folder...")
+                    .pick_folder()
+                {
+                    let name = path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    self.state = State::CreateServerFrom(MenuCreateServerFrom {
+                        name,
+                        source: ServerSource::Folder { path, adopt: false },
+                        accept_eula: false,
+                        is_working: false,
+                    });
+                }
+            }
+            CreateServerFromMessage::AdoptToggled(t) => {
+                if let State::CreateServerFrom(MenuCreateServerFrom {
+                    source: ServerSource::Folder { adopt, .. },
+                    ..
+                }) = &mut self.state
+                {
+                    *adopt = t;
+                }
+            }
+            CreateServerFromMessage::EulaToggle(t) => {
+                if let State::CreateServerFrom(menu) = &mut self.state {
+                    menu.accept_eula = t;
+                }
+            }
+            CreateServerFromMessage::NameInput(name) => {
+                if let State::CreateServerFrom(menu) = &mut self.state {
+                    menu.name = name;
+                }
+            }
+            CreateServerFromMessage::Start => {
+                let State::CreateServerFrom(menu) = &mut self.state else {
+                    return Task::none();
+                };
+                if menu.needs_eula() {
+                    return Task::none();
+                }
+                menu.is_working = true;
+                let name = menu.name.clone();
+                let source = menu.source.clone();
+                let accept_eula = menu.accept_eula;
+                return Task::perform(
+                    async move {
+                        let created = match source {
+                            ServerSource::Client(client) => {
+                                ql_servers::create_server_from_instance(&client, name, None).await
+                            }
+                            ServerSource::Modpack(path) => {
+                                let file = tokio::fs::read(&path).await.path(&path).strerr()?;
+                                ql_servers::create_server_from_modpack(name, file, None).await
+                            }
+                            ServerSource::Folder { path, adopt } => {
+                                let mode = if adopt {
+                                    ImportMode::Adopt
+                                } else {
+                                    ImportMode::Copy
+                                };
+                                ql_servers::import_server(&path, name, mode).await.map(
+                                    |(name, _)| CreatedServer {
+                                        name,
+                                        skipped_mods: Vec::new(),
+                                        not_allowed: HashSet::new(),
+                                    },
+                                )
+                            }
+                        }
+                        .strerr()?;
+                        if accept_eula {
+                            let dir = Instance::server(&created.name).get_instance_path();
+                            ql_servers::accept_eula(&dir).await.strerr()?;
+                        }
+                        Ok(created)
+                    },
+                    |n| CreateServerFromMessage::End(n).into(),
+                );
+            }
+            CreateServerFromMessage::End(Ok(created)) => {
+                self.selected_instance = Some(Instance::server(&created.name));
+                if !created.not_allowed.is_empty() {
+                    return self.go_to_curseforge_manual_download(created.not_allowed);
+                }
+                let is_import = matches!(
+                    &self.state,
+                    State::CreateServerFrom(MenuCreateServerFrom {
+                        source: ServerSource::Folder { .. },
+                        ..
+                    })
+                );
+                let message = if is_import {
+                    "Imported Server".to_owned()
+                } else if created.skipped_mods.is_empty() {
+                    "Created Server".to_owned()
+                } else {
+                    format!(
+                        "Created Server (left out {} client-only mods)",
+                        created.skipped_mods.len()
+                    )
+                };
+                return self.go_to_main_menu(Some(InfoMessage::success(message)));
+            }
+            CreateServerFromMessage::End(Err(err)) => self.set_error(err),
+        }
+        Task::none()
+    }
+}
@@ -125,6 +125,11 @@ impl Launcher {
             EditInstanceMessage::LoggingToggle(t) => iflet_config!(c: &mut self.state, config, {
                 config.enable_logger = Some(t);
             }),
+            EditInstanceMessage::LanBroadcastToggle(t) => {
+                iflet_config!(c: &mut self.state, config, {
+                    config.lan_broadcast = Some(t);
+                })
+            }
             EditInstanceMessage::JavaArgsModeChanged(mode) => {
                 iflet_config!(&mut self.state, global_java_args_enable, {
                     *global_java_args_enable = Some(mode);
@@ -500,6 +505,7 @@ impl EditInstanceMessage {
             EditInstanceMessage::MemoryChanged(_) |
             EditInstanceMessage::MemoryInputChanged(_) |
             EditInstanceMessage::LoggingToggle(_) |
+            EditInstanceMessage::LanBroadcastToggle(_) |
             EditInstanceMessage::SetMainClass(_, _) |
             EditInstanceMessage::JavaArgs(_) |
             EditInstanceMessage::JavaArgsModeChanged(_) |
@@ -0,0 +1,104 @@
+use iced::Task;
+use ql_core::IntoStringError;
+use ql_mod_manager::store::{self, ModrinthList};
+
+use crate::state::{
+    ImportCollectionMessage, InfoMessage, Launcher, MenuImportCollection, Message, ProgressBar,
+    State,
+};
+
+impl Launcher {
+    pub fn update_import_collection(&mut self, msg: ImportCollectionMessage) -> Task<Message> {
+        match msg {
+            ImportCollectionMessage::Open => {
+                let has_token = match store::get_modrinth_token() {
+                    Ok(n) => n.is_some(),
+                    Err(err) => {
+                        ql_core::err!("Couldn't read Modrinth token: {err}");
+                        false
+                    }
+                };
+                self.state = State::ImportCollection(MenuImportCollection {
+                    input: String::new(),
+                    token_input: String::new(),
+                    has_token,
+                    progress: None,
+                    result: None,
+                });
+            }
+            ImportCollectionMessage::InputChanged(n) => {
+                if let State::ImportCollection(menu) = &mut self.state {
+                    menu.input = n;
+                }
+            }
+            ImportCollectionMessage::TokenInputChanged(n) => {
+                if let State::ImportCollection(menu) = &mut self.state {
+                    menu.token_input = n;
+                }
+            }
+            ImportCollectionMessage::TokenSave => {
+                if let State::ImportCollection(menu) = &mut self.state {
+                    let token = menu.token_input.trim();
+                    if token.is_empty() {
+                        return Task::none();
+                    }
+                    if let Err(err) = store::set_modrinth_token(Some(token)) {
+                        self.set_error(err);
+                        return Task::none();
+                    }
+                    menu.token_input.clear();
+                    menu.has_token = true;
+                }
+            }
+            ImportCollectionMessage::TokenClear => {
+                if let Err(err) = store::set_modrinth_token(None) {
+                    self.set_error(err);
+                } else if let State::ImportCollection(menu) = &mut self.state {
+                    menu.has_token = false;
+                }
+            }
+            ImportCollectionMessage::ImportCollection => {
+                if let State::ImportCollection(menu) = &self.state {
+                    let Some(id) = store::parse_collection_id(&menu.input) else {
+                        self.set_error("Invalid Modrinth collection link or ID");
+                        return Task::none();
+                    };
+                    return self.import_modrinth_list(ModrinthList::Collection(id));
+                }
+            }
+            ImportCollectionMessage::ImportFollowed => {
+                return self.import_modrinth_list(ModrinthList::Followed);
+            }
+            ImportCollectionMessage::Done(res) => match res {
+                Ok(result) if result.incompatible.is_empty() => {
+                    return self.go_to_edit_mods_menu(Some(InfoMessage::success(format!(
+                        "Imported {} ({} installed)",
+                        result.name,
+                        result.installed.len()
+                    ))));
+                }
+                Ok(result) => {
+                    if let State::ImportCollection(menu) = &mut self.state {
+                        menu.progress = None;
+                        menu.result = Some(result);
+                    }
+                }
+                Err(err) => self.set_error(err),
+            },
+        }
+        Task::none()
+    }
+
+    fn import_modrinth_list(&mut self, list: ModrinthList) -> Task<Message> {
+        let State::ImportCollection(menu) = &mut self.state else {
+            return Task::none();
+        };
+        let (sender, receiver) = std::sync::mpsc::channel();
+        menu.progress = Some(ProgressBar::with_recv(receiver));
+
+        Task::perform(
+            store::import_modrinth_list(list, self.instance().clone(), Some(sender)),
+            |n| ImportCollectionMessage::Done(n.strerr()).into(),
+        )
+    }
+}
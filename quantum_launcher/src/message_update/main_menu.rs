@@ -1,4 +1,4 @@
-use std::process::ExitStatus;
+use std::{process::ExitStatus, time::Duration};
 
 use iced::{Task, futures::executor::block_on};
 use ql_core::{
@@ -6,18 +6,23 @@ use ql_core::{
     read_log::{Diagnostic, ReadError},
 };
 use ql_instances::auth::AccountData;
-use tokio::io::AsyncWriteExt;
+use ql_servers::{DiagnosticFix, ServerError};
 
 use crate::{
     config::{AfterLaunchBehavior, sidebar::SidebarSelection},
+    menu_renderer::{EULA_URL, back_to_launch_screen},
     message_handler::{SIDEBAR_LIMIT_LEFT, SIDEBAR_LIMIT_RIGHT},
     state::{
-        AutoSaveKind, GameProcess, InfoMessage, LaunchMessage, LaunchModal, LaunchTab, Launcher,
-        MainMenuMessage, MenuLaunch, Message, OFFLINE_ACCOUNT_NAME, ProgressBar, SidebarMessage,
-        State,
+        AutoSaveKind, GameProcess, InfoMessage, InfoMessageKind, LaunchMessage, LaunchModal,
+        LaunchTab, Launcher, MainMenuMessage, MenuLaunch, Message, OFFLINE_ACCOUNT_NAME,
+        ProgressBar, SidebarMessage, State,
     },
 };
 
+/// How long a server gets to shut down gracefully
+/// (saving the world) before it's killed
+pub(super) const SERVER_STOP_TIMEOUT: Duration = Duration::from_secs(60);
+
 impl Launcher {
     pub fn update_launch(&mut self, msg: LaunchMessage) -> Task<Message> {
         match msg {
@@ -31,6 +36,45 @@ impl Launcher {
             LaunchMessage::Start => self.launch_start(),
             LaunchMessage::End(result) => self.finish_launching(result),
             LaunchMessage::Kill => self.kill_selected_instance(),
+            LaunchMessage::ApplyFix(instance, fix) => {
+                Task::perform(async move { fix.apply(&instance).await.strerr() }, |n| {
+                    LaunchMessage::FixApplied(n).into()
+                })
+            }
+            LaunchMessage::FixApplied(res) => {
+                match res {
+                    Ok(msg) => {
+                        if let State::Launch(menu) = &mut self.state {
+                            menu.message = Some(InfoMessage::success(msg));
+                        }
+                    }
+                    Err(err) => self.set_error(err),
+                }
+                Task::none()
+            }
+            LaunchMessage::EulaNeeded(instance, retry) => {
+                self.java_recv = None;
+                self.state = State::ConfirmAction {
+                    msg1: format!("accept the Minecraft EULA for {}", instance.name),
+                    msg2: format!(
+                        "Servers only run if you agree to it: {EULA_URL}\nYour acceptance is recorded in the server's config."
+                    ),
+                    yes: LaunchMessage::AcceptEula(instance, retry).into(),
+                    no: back_to_launch_screen(None),
+                };
+                Task::none()
+            }
+            LaunchMessage::AcceptEula(instance, retry) => Task::perform(
+                async move {
+                    ql_servers::accept_eula(&instance.get_instance_path())
+                        .await
+                        .strerr()
+                },
+                move |n| match n {
+                    Ok(()) => Message::Multiple(vec![back_to_launch_screen(None), *retry.clone()]),
+                    Err(err) => Message::Error(err),
+                },
+            ),
         }
     }
 
@@ -65,17 +109,55 @@ impl Launcher {
                 self.launch_game(account_data)
             }
             InstanceKind::Server => {
-                let (sender, receiver) = std::sync::mpsc::channel();
-                self.java_recv = Some(ProgressBar::with_recv(receiver));
-
-                let server = selected_instance.name.clone();
-                Task::perform(ql_servers::run(server, Some(sender)), |n| {
-                    LaunchMessage::End(n.strerr()).into()
-                })
+                let instance = selected_instance.clone();
+                self.start_server(instance, LaunchMessage::Start.into(), None)
             }
         }
     }
 
+    /// Starts a server, unless it's running in the background
+    /// (`quantum_launcher server start`), asking to accept the EULA
+    /// if needed (sending `retry` after that).
+    ///
+    /// `then` is sent once the server has started.
+    pub(super) fn start_server(
+        &mut self,
+        instance: Instance,
+        retry: Message,
+        then: Option<Message>,
+    ) -> Task<Message> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        self.java_recv = Some(ProgressBar::with_recv(receiver));
+
+        let eula_instance = instance.clone();
+        Task::perform(
+            async move {
+                // Started with `quantum_launcher server start`
+                if let Some(state) = ql_servers::DaemonState::load(&instance).await? {
+                    return Err(ql_servers::DaemonError::AlreadyRunning(
+                        instance.name.to_string(),
+                        state.pid,
+                    )
+                    .into());
+                }
+                ql_servers::run(instance.name, Some(sender)).await
+            },
+            move |n| match n {
+                Err(ServerError::EulaNotAccepted(_)) => {
+                    LaunchMessage::EulaNeeded(eula_instance.clone(), Box::new(retry.clone())).into()
+                }
+                Ok(process) => {
+                    let started = LaunchMessage::End(Ok(process)).into();
+                    match &then {
+                        Some(then) => Message::Multiple(vec![started, then.clone()]),
+                        None => started,
+                    }
+                }
+                n => LaunchMessage::End(n.strerr()).into(),
+            },
+        )
+    }
+
     pub fn launch_game(&mut self, account_data: Option<AccountData>) -> Task<Message> {
         let username = if let Some(account_data) = &account_data {
             // Logged in account
@@ -123,13 +205,27 @@ impl Launcher {
         }) = &mut self.state
         {
             let has_crashed = !status.success();
-            if has_crashed {
-                let mut msg = format!("{kind} crashed! ({status})\nCheck \"Logs\" for more info");
+            // Servers often exit normally after failing to start
+            // (EULA not accepted, port in use, ...)
+            if has_crashed || diagnostic.is_some() {
+                let mut msg = if has_crashed {
+                    format!("{kind} crashed! ({status})\nCheck \"Logs\" for more info")
+                } else {
+                    format!("{kind} couldn't start!\nCheck \"Logs\" for more info")
+                };
+                let mut fix = None;
                 if let Some(diag) = diagnostic {
                     msg.push_str("\n\n");
                     msg.push_str(&diag.to_string());
+                    fix = DiagnosticFix::for_diagnostic(&diag, instance);
                 }
-                *message = Some(InfoMessage::error(msg));
+                *message = Some(InfoMessage {
+                    text: msg,
+                    kind: match fix {
+                        Some(fix) => InfoMessageKind::ErrorWithFix(instance.clone(), fix),
+                        None => InfoMessageKind::Error,
+                    },
+                });
             }
             if let Some(log) = self.logs.get_mut(instance) {
                 log.has_crashed = has_crashed;
@@ -139,7 +235,9 @@ impl Launcher {
             &mut None
         };
 
+        let mut stopped_by_user = false;
         if let Some(process) = self.processes.remove(instance) {
+            stopped_by_user = process.is_stopping;
             Self::read_game_logs(
                 &process,
                 instance,
@@ -149,7 +247,23 @@ impl Launcher {
             );
         }
 
-        self.rpc_game_update(instance.clone(), true)
+        let rpc_task = self.rpc_game_update(instance.clone(), true);
+        let Some(watchdog) = self.watchdogs.get(instance).cloned() else {
+            return rpc_task;
+        };
+        let instance = instance.clone();
+        let restart_task = Task::perform(
+            async move {
+                watchdog
+                    .restart_after_exit(&instance, status, stopped_by_user)
+                    .await
+            },
+            |n| match n {
+                Some(res) => LaunchMessage::End(res.strerr()).into(),
+                None => Message::Nothing,
+            },
+        );
+        Task::batch([rpc_task, restart_task])
     }
 
     fn finish_launching(&mut self, result: Result<LaunchedProcess, String>) -> Task<Message> {
@@ -159,18 +273,21 @@ impl Launcher {
             Ok(child) => {
                 let selected_instance = child.instance.clone();
 
-                let server_input = block_on(child.child.lock())
-                    .stdin
-                    .take()
-                    .map(|n| (n, false));
-
                 let (sender, receiver) = std::sync::mpsc::channel();
+                let (sender, events) = if selected_instance.is_server() {
+                    let (sender, events) = ql_servers::log_events(sender);
+                    (sender, Some(events))
+                } else {
+                    (sender, None)
+                };
                 self.processes.insert(
                     selected_instance.clone(),
                     GameProcess {
                         child: child.clone(),
                         receiver: Some(receiver),
-                        server_input,
+                        is_stopping: false,
+                        events,
+                        monitor: ql_servers::ServerMonitor::default(),
                     },
                 );
 
@@ -182,6 +299,15 @@ impl Launcher {
                 }
 
                 let version_presence_task = self.rpc_game_update(selected_instance.clone(), false);
+                if selected_instance.is_server() {
+                    tokio::spawn(ql_servers::backup_scheduler(child.clone()));
+                    let watchdog = self
+                        .watchdogs
+                        .entry(selected_instance.clone())
+                        .or_default()
+                        .clone();
+                    tokio::spawn(watchdog.restart_scheduler(child.clone()));
+                }
 
                 let log_task = Task::perform(
                     async move {
@@ -236,18 +362,22 @@ impl Launcher {
             }
             InstanceKind::Server => {
                 if let Some(GameProcess {
-                    server_input: Some((stdin, has_issued_stop_command)),
-                    child,
-                    ..
+                    child, is_stopping, ..
                 }) = self.processes.get_mut(instance)
                 {
-                    *has_issued_stop_command = true;
-                    if child.is_classic_server {
-                        _ = block_on(child.child.lock()).start_kill();
-                    } else {
-                        let future = stdin.write_all("stop\n".as_bytes());
-                        _ = block_on(future);
+                    let child = child.clone();
+                    if *is_stopping {
+                        info!("Force-killing server");
+                        return Task::perform(
+                            async move { child.kill().await.strerr() },
+                            Message::Done,
+                        );
                     }
+                    *is_stopping = true;
+                    return Task::perform(
+                        async move { child.stop(SERVER_STOP_TIMEOUT).await.strerr() },
+                        Message::Done,
+                    );
                 }
             }
         }
@@ -7,9 +7,8 @@ use std::{collections::HashSet, path::PathBuf};
 
 use crate::state::{
     AutoSaveKind, ExportModsTextMessage, InfoMessage, InfoMessageKind, Launcher,
-    ManageJarModsMessage, ManageModsMessage, MenuCurseforgeManualDownload, MenuEditJarMods,
-    MenuEditMods, MenuEditModsModal, MenuExportModsText, Message, ProgressBar, SelectedState,
-    State,
+    ManageJarModsMessage, ManageModsMessage, MenuEditJarMods, MenuEditMods, MenuEditModsModal,
+    MenuExportModsText, Message, ProgressBar, SelectedState, State,
 };
 
 impl Launcher {
@@ -82,10 +81,7 @@ impl Launcher {
             }
             ManageModsMessage::AddFileDone(Ok(not_allowed)) => {
                 if !not_allowed.is_empty() {
-                    self.state = State::CurseforgeManualDownload(MenuCurseforgeManualDownload {
-                        not_allowed,
-                        delete_mods: true,
-                    });
+                    return self.go_to_curseforge_manual_download(not_allowed);
                 }
                 return self.go_to_edit_mods_menu(None);
             }
@@ -294,6 +290,44 @@ impl Launcher {
                     menu.delete_mods = t;
                 }
             }
+            ManageModsMessage::CurseforgeManualScanned(res) => {
+                if let State::CurseforgeManualDownload(menu) = &mut self.state {
+                    menu.is_scanning = false;
+                    match res {
+                        Ok(installed) => {
+                            for entry in &installed {
+                                menu.not_allowed.remove(entry);
+                            }
+                            if menu.not_allowed.is_empty() {
+                                return self.go_to_edit_mods_menu(Some(InfoMessage::success(
+                                    "Added all manually downloaded files",
+                                )));
+                            }
+                        }
+                        Err(err) => self.set_error(err),
+                    }
+                }
+            }
+            ManageModsMessage::CurseforgeManualPickDir => {
+                return Task::perform(
+                    rfd::AsyncFileDialog::new()
+                        .set_title("Select your downloads folder")
+                        .pick_folder(),
+                    |n| {
+                        ManageModsMessage::CurseforgeManualDirPicked(n.map(|n| n.path().to_owned()))
+                            .into()
+                    },
+                );
+            }
+            ManageModsMessage::CurseforgeManualDirPicked(Some(dir)) => {
+                self.config.c_persistent().downloads_dir = Some(dir.clone());
+                self.autosave.remove(&AutoSaveKind::LauncherConfig);
+                if let State::CurseforgeManualDownload(menu) = &mut self.state {
+                    menu.set_downloads_dir(Some(dir));
+                    return menu.scan(self.selected_instance.clone().unwrap());
+                }
+            }
+            ManageModsMessage::CurseforgeManualDirPicked(None) => {}
             ManageModsMessage::RightClick(clicked_id) => {
                 if let State::EditMods(menu) = &mut self.state {
                     if let Some(MenuEditModsModal::RightClick(old_id, _)) = &menu.ui_state.modal {
@@ -894,7 +928,8 @@ impl ManageModsMessage {
             | ManageModsMessage::AddFileDone(_)
             | ManageModsMessage::ToggleSelected
             | ManageModsMessage::ToggleOne(_)
-            | ManageModsMessage::ToggleOneLocal(_) => true,
+            | ManageModsMessage::ToggleOneLocal(_)
+            | ManageModsMessage::CurseforgeManualScanned(_) => true,
 
             ManageModsMessage::SelectEnsure(_, _, _)
             | ManageModsMessage::SelectMod(_, _, _)
@@ -911,6 +946,8 @@ impl ManageModsMessage {
             | ManageModsMessage::SetModal(_)
             | ManageModsMessage::AddFileSelected(_, _, _)
             | ManageModsMessage::CurseforgeManualToggleDelete(_)
+            | ManageModsMessage::CurseforgeManualPickDir
+            | ManageModsMessage::CurseforgeManualDirPicked(_)
             | ManageModsMessage::SetInfoMessage(_) => false,
         }
     }
@@ -3,19 +3,36 @@ use std::path::Path;
 use frostmark::MarkState;
 use iced::{Task, futures::executor::block_on, widget::text_editor};
 use ql_core::{IntoStringError, Loader, OptifineUniqueVersion, err};
-use ql_mod_manager::{loaders, store};
+use ql_mod_manager::{
+    loaders::{
+        self,
+        paper::{BuildChannel, PaperProject, PaperVer},
+    },
+    store,
+};
 
 mod accounts;
+mod backups;
+mod client_from_server;
 mod create_instance;
+mod create_server_from;
 mod discord_rpc;
 mod edit_instance;
+mod import_collection;
 mod main_menu;
 mod manage_mods;
 mod mod_store;
+mod network;
+mod players;
 mod presets;
 mod recommended;
+mod server_list;
+mod server_properties;
+mod server_status;
 mod settings;
 mod shortcuts;
+mod watchdog;
+mod worlds;
 
 use crate::state::{
     self, GameLogMessage, InfoMessage, InstallFabricMessage, InstallOptifineMessage,
@@ -283,50 +300,60 @@ impl Launcher {
                 if let State::InstallPaper(MenuInstallPaper::Loaded { version, .. }) =
                     &mut self.state
                 {
-                    *version = v;
+                    *version = Some(v);
                 }
             }
             InstallPaperMessage::VersionsLoaded(res) => match res {
                 Ok(list) => {
-                    let Some(version) = list.first().cloned() else {
-                        self.set_error("No compatible Paper versions found");
-                        return Task::none();
-                    };
-                    self.state = State::InstallPaper(MenuInstallPaper::Loaded {
-                        version,
-                        versions: list,
-                    });
+                    if let State::InstallPaper(MenuInstallPaper::Loading {
+                        project,
+                        channel,
+                        game_version,
+                        ..
+                    }) = &mut self.state
+                    {
+                        self.state = State::InstallPaper(MenuInstallPaper::Loaded {
+                            project: *project,
+                            channel: *channel,
+                            game_version: std::mem::take(game_version),
+                            version: list.first().cloned(),
+                            versions: list,
+                        });
+                    }
                 }
                 Err(err) => self.set_error(err),
             },
-            InstallPaperMessage::ScreenOpen => {
+            InstallPaperMessage::ScreenOpen(project) => {
                 if let State::EditMods(menu) = &self.state {
-                    let (task, handle) = Task::perform(
-                        loaders::paper::get_list_of_versions(
-                            menu.file_data.details.get_id().to_owned(),
-                        ),
-                        |n| Message::InstallPaper(InstallPaperMessage::VersionsLoaded(n.strerr())),
-                    )
-                    .abortable();
-                    self.state = State::InstallPaper(MenuInstallPaper::Loading {
-                        _handle: handle.abort_on_drop(),
-                    });
-                    return task;
+                    let game_version = menu.file_data.details.get_id().to_owned();
+                    return self.load_paper_versions(project, BuildChannel::Stable, game_version);
+                }
+            }
+            InstallPaperMessage::ChannelSelected(channel) => {
+                if let State::InstallPaper(MenuInstallPaper::Loaded {
+                    project,
+                    game_version,
+                    ..
+                }) = &mut self.state
+                {
+                    let (project, game_version) = (*project, std::mem::take(game_version));
+                    return self.load_paper_versions(project, channel, game_version);
                 }
             }
             InstallPaperMessage::ButtonClicked => {
+                let State::InstallPaper(MenuInstallPaper::Loaded {
+                    project,
+                    version: Some(version),
+                    ..
+                }) = &self.state
+                else {
+                    return Task::none();
+                };
+                let (project, version) = (*project, version.clone());
                 let instance_name = self.instance().get_name().to_owned();
-                let version =
-                    if let State::InstallPaper(MenuInstallPaper::Loaded { version, .. }) =
-                        &self.state
-                    {
-                        Some(version.clone())
-                    } else {
-                        None
-                    };
-                self.state = State::InstallPaper(MenuInstallPaper::Installing);
+                self.state = State::InstallPaper(MenuInstallPaper::Installing(project));
                 return Task::perform(
-                    loaders::paper::install(instance_name, version.into()),
+                    loaders::paper::install(instance_name, project, PaperVer::Full(version)),
                     |n| Message::InstallPaper(InstallPaperMessage::End(n.strerr())),
                 );
             }
@@ -334,14 +361,40 @@ impl Launcher {
                 if let Err(err) = res {
                     self.set_error(err);
                 } else {
-                    return self
-                        .go_to_edit_mods_menu(Some(InfoMessage::success("Installed Paper")));
+                    let project = if let State::InstallPaper(menu) = &self.state {
+                        menu.project()
+                    } else {
+                        PaperProject::Paper
+                    };
+                    return self.go_to_edit_mods_menu(Some(InfoMessage::success(format!(
+                        "Installed {project}"
+                    ))));
                 }
             }
         }
         Task::none()
     }
 
+    fn load_paper_versions(
+        &mut self,
+        project: PaperProject,
+        channel: BuildChannel,
+        game_version: String,
+    ) -> Task<Message> {
+        let (task, handle) = Task::perform(
+            loaders::paper::get_list_of_versions(project, game_version.clone(), channel),
+            |n| Message::InstallPaper(InstallPaperMessage::VersionsLoaded(n.strerr())),
+        )
+        .abortable();
+        self.state = State::InstallPaper(MenuInstallPaper::Loading {
+            project,
+            channel,
+            game_version,
+            _handle: handle.abort_on_drop(),
+        });
+        task
+    }
+
     pub fn update_window_msg(&mut self, msg: WindowMessage) -> Task<Message> {
         match msg {
             WindowMessage::Dragged => iced::window::get_latest().and_then(iced::window::drag),
@@ -0,0 +1,167 @@
+use iced::Task;
+use ql_core::{Instance, IntoStringError, Loader, json::InstanceConfigJson};
+use ql_servers::NetworkConfig;
+
+use crate::{
+    message_update::main_menu::SERVER_STOP_TIMEOUT,
+    state::{Launcher, MenuNetwork, Message, NetworkMessage, State},
+};
+
+impl Launcher {
+    pub fn update_network(&mut self, msg: NetworkMessage) -> Task<Message> {
+        match msg {
+            NetworkMessage::Open => {
+                self.state = State::GenericMessage("Loading network...".to_owned());
+                let proxy = self.instance().clone();
+                let servers = self.server_list.clone().unwrap_or_default();
+                return Task::perform(load_network(proxy, servers), |n| {
+                    NetworkMessage::Loaded(n).into()
+                });
+            }
+            NetworkMessage::Loaded(res) => match res {
+                Ok((config, other_servers, is_bungeecord)) => {
+                    self.state = State::Network(MenuNetwork {
+                        config,
+                        other_servers,
+                        is_bungeecord,
+                        warnings: Vec::new(),
+                        is_working: false,
+                    });
+                }
+                Err(err) => self.set_error(err),
+            },
+            NetworkMessage::AddBackend(name) => {
+                if let State::Network(menu) = &mut self.state {
+                    menu.other_servers.retain(|n| *n != name);
+                    menu.config.backends.push(name);
+                }
+            }
+            NetworkMessage::RemoveBackend(i) => {
+                if let State::Network(menu) = &mut self.state {
+                    if i < menu.config.backends.len() {
+                        let name = menu.config.backends.remove(i);
+                        menu.other_servers.push(name);
+                        menu.other_servers.sort();
+                    }
+                }
+            }
+            NetworkMessage::MoveBackendUp(i) => {
+                if let State::Network(menu) = &mut self.state {
+                    if i > 0 && i < menu.config.backends.len() {
+                        menu.config.backends.swap(i, i - 1);
+                    }
+                }
+            }
+            NetworkMessage::ForwardingChanged(mode) => {
+                if let State::Network(menu) = &mut self.state {
+                    menu.config.forwarding = mode;
+                }
+            }
+            NetworkMessage::Apply(start) => {
+                let State::Network(menu) = &mut self.state else {
+                    return Task::none();
+                };
+                menu.is_working = true;
+                let config = menu.config.clone();
+                let proxy = self.instance().clone();
+                return Task::perform(
+                    async move {
+                        config.save(&proxy).await.strerr()?;
+                        ql_servers::apply_network(&proxy, &config).await.strerr()
+                    },
+                    move |n| NetworkMessage::Applied(n, start).into(),
+                );
+            }
+            NetworkMessage::Applied(res, start) => match res {
+                Ok(warnings) => {
+                    let State::Network(menu) = &mut self.state else {
+                        return Task::none();
+                    };
+                    menu.is_working = false;
+                    menu.warnings = warnings;
+                    if start {
+                        let config = menu.config.clone();
+                        return self.start_network(config.start_order(self.instance()));
+                    }
+                }
+                Err(err) => self.set_error(err),
+            },
+            NetworkMessage::Start(servers) => return self.start_network(servers),
+            NetworkMessage::Stop => return self.stop_network(),
+        }
+        Task::none()
+    }
+
+    /// Starts the servers of a network that aren't running, in
+    /// order (see [`NetworkConfig::start_order`]), the same way as
+    /// pressing "Play" on each of them. Stops at the first
+    /// one that fails.
+    fn start_network(&mut self, mut servers: Vec<Instance>) -> Task<Message> {
+        servers.retain(|n| !self.processes.contains_key(n));
+        if servers.is_empty() {
+            return Task::none();
+        }
+        let retry = NetworkMessage::Start(servers.clone()).into();
+        let server = servers.remove(0);
+        self.logs.remove(&server);
+        let then = (!servers.is_empty()).then(|| NetworkMessage::Start(servers).into());
+        self.start_server(server, retry, then)
+    }
+
+    fn stop_network(&mut self) -> Task<Message> {
+        let State::Network(menu) = &self.state else {
+            return Task::none();
+        };
+        let backends: Vec<Instance> = menu
+            .config
+            .backends
+            .iter()
+            .map(|n| Instance::server(n))
+            .collect();
+        let proxy = self.instance().clone();
+
+        let mut stop = |instance: &Instance| {
+            let process = self.processes.get_mut(instance)?;
+            process.is_stopping = true;
+            Some(process.child.clone())
+        };
+        let proxy = stop(&proxy);
+        let backends: Vec<_> = backends.iter().filter_map(&mut stop).collect();
+
+        Task::perform(
+            async move {
+                ql_servers::stop_network(proxy, backends, SERVER_STOP_TIMEOUT)
+                    .await
+                    .strerr()
+            },
+            Message::Done,
+        )
+    }
+}
+
+/// Loads the network of `proxy`, which of `servers` can be added
+/// to it (not already in it, and not proxies), and whether
+/// the proxy is BungeeCord.
+async fn load_network(
+    proxy: Instance,
+    servers: Vec<String>,
+) -> Result<(NetworkConfig, Vec<String>, bool), String> {
+    let config = NetworkConfig::load(&proxy).await.strerr()?;
+    let is_bungeecord =
+        InstanceConfigJson::read(&proxy).await.strerr()?.mod_type == Loader::BungeeCord;
+
+    let mut other_servers = Vec::new();
+    for name in servers {
+        if name == proxy.get_name() || config.backends.contains(&name) {
+            continue;
+        }
+        let is_proxy = InstanceConfigJson::read(&Instance::server(&name))
+            .await
+            .is_ok_and(|n| n.mod_type.is_proxy());
+        if !is_proxy {
+            other_servers.push(name);
+        }
+    }
+    other_servers.sort();
+    Ok((config, other_servers, is_bungeecord))
+}
@@ -0,0 +1,120 @@
+use iced::Task;
+use ql_core::IntoStringError;
+use ql_servers::{ListChange, PlayerLists};
+
+use crate::state::{Launcher, MenuPlayers, Message, PlayerListTab, PlayersMessage, State};
+
+impl Launcher {
+    pub fn update_players(&mut self, msg: PlayersMessage) -> Task<Message> {
+        match msg {
+            PlayersMessage::Open => {
+                self.state = State::GenericMessage("Loading players...".to_owned());
+                return self.load_player_lists();
+            }
+            PlayersMessage::Loaded(res) => match res {
+                Ok(lists) => {
+                    if let State::Players(menu) = &mut self.state {
+                        menu.lists = lists;
+                        menu.is_working = false;
+                    } else {
+                        self.state = State::Players(MenuPlayers {
+                            lists,
+                            tab: PlayerListTab::Whitelist,
+                            input: String::new(),
+                            reason: String::new(),
+                            is_working: false,
+                        });
+                    }
+                }
+                Err(err) => self.set_error(err),
+            },
+            PlayersMessage::TabChanged(tab) => {
+                if let State::Players(menu) = &mut self.state {
+                    menu.tab = tab;
+                }
+            }
+            PlayersMessage::InputChanged(input) => {
+                if let State::Players(menu) = &mut self.state {
+                    menu.input = input;
+                }
+            }
+            PlayersMessage::ReasonChanged(reason) => {
+                if let State::Players(menu) = &mut self.state {
+                    menu.reason = reason;
+                }
+            }
+            PlayersMessage::Add => {
+                let State::Players(menu) = &mut self.state else {
+                    return Task::none();
+                };
+                let name = menu.input.trim().to_owned();
+                let reason = Some(std::mem::take(&mut menu.reason));
+                let change = match menu.tab {
+                    PlayerListTab::Whitelist => ListChange::WhitelistAdd(name),
+                    PlayerListTab::Ops => ListChange::Op(name),
+                    PlayerListTab::BannedPlayers => ListChange::Ban { name, reason },
+                    PlayerListTab::BannedIps => ListChange::BanIp { ip: name, reason },
+                };
+                menu.input.clear();
+                return self.apply_list_change(change);
+            }
+            PlayersMessage::Remove(name) => {
+                let State::Players(menu) = &self.state else {
+                    return Task::none();
+                };
+                let change = match menu.tab {
+                    PlayerListTab::Whitelist => ListChange::WhitelistRemove(name),
+                    PlayerListTab::Ops => ListChange::Deop(name),
+                    PlayerListTab::BannedPlayers => ListChange::Pardon(name),
+                    PlayerListTab::BannedIps => ListChange::PardonIp(name),
+                };
+                return self.apply_list_change(change);
+            }
+            PlayersMessage::OpLevelChanged(name, level) => {
+                if let State::Players(menu) = &mut self.state {
+                    menu.is_working = true;
+                    let instance = self.instance().clone();
+                    return Task::perform(
+                        async move {
+                            ql_servers::set_op_level(&instance, &name, level)
+                                .await
+                                .strerr()
+                        },
+                        |n| PlayersMessage::Done(n).into(),
+                    );
+                }
+            }
+            PlayersMessage::Done(res) => match res {
+                Ok(()) => return self.load_player_lists(),
+                Err(err) => self.set_error(err),
+            },
+        }
+        Task::none()
+    }
+
+    fn load_player_lists(&self) -> Task<Message> {
+        let instance = self.instance().clone();
+        Task::perform(
+            async move { PlayerLists::load(&instance).await.strerr() },
+            |n| PlayersMessage::Loaded(n).into(),
+        )
+    }
+
+    /// Sent as a command if the server is running,
+    /// so it doesn't overwrite the change when it stops.
+    fn apply_list_change(&mut self, change: ListChange) -> Task<Message> {
+        if let State::Players(menu) = &mut self.state {
+            menu.is_working = true;
+        }
+        let instance = self.instance().clone();
+        let process = self.processes.get(&instance).map(|n| n.child.clone());
+        Task::perform(
+            async move {
+                ql_servers::apply_list_change(&instance, &change, process.as_ref())
+                    .await
+                    .strerr()
+            },
+            |n| PlayersMessage::Done(n).into(),
+        )
+    }
+}
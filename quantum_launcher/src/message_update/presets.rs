@@ -4,8 +4,7 @@ use ql_mod_manager::store::{QueryType, SelectedMod};
 use std::collections::HashSet;
 
 use crate::state::{
-    EditPresetsMessage, InfoMessage, Launcher, MenuCurseforgeManualDownload, MenuEditPresets,
-    Message, SelectedState, State,
+    EditPresetsMessage, InfoMessage, Launcher, MenuEditPresets, Message, SelectedState, State,
 };
 
 macro_rules! iflet_manage_preset {
@@ -79,12 +78,7 @@ impl Launcher {
                     if not_allowed.is_empty() {
                         self.go_to_edit_mods_menu(Some(InfoMessage::success("Imported mod preset")))
                     } else {
-                        self.state =
-                            State::CurseforgeManualDownload(MenuCurseforgeManualDownload {
-                                not_allowed,
-                                delete_mods: true,
-                            });
-                        Task::none()
+                        self.go_to_curseforge_manual_download(not_allowed)
                     }
                 }) {
                     Ok(n) => return n,
@@ -3,8 +3,7 @@ use ql_core::{Instance, IntoStringError, JsonFileError, json::InstanceConfigJson
 use ql_mod_manager::store::{ModId, RECOMMENDED_MODS, RecommendedMod};
 
 use crate::state::{
-    InfoMessage, Launcher, MenuCurseforgeManualDownload, MenuRecommendedMods, Message, ProgressBar,
-    RecommendedModMessage, State,
+    InfoMessage, Launcher, MenuRecommendedMods, Message, ProgressBar, RecommendedModMessage, State,
 };
 
 impl Launcher {
@@ -98,10 +97,7 @@ impl Launcher {
                             "Downloaded recommended mods",
                         )));
                     }
-                    self.state = State::CurseforgeManualDownload(MenuCurseforgeManualDownload {
-                        not_allowed,
-                        delete_mods: true,
-                    });
+                    return self.go_to_curseforge_manual_download(not_allowed);
                 }
                 Err(err) => self.set_error(err),
             },
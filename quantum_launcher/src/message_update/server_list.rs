@@ -0,0 +1,84 @@
+use iced::Task;
+use ql_core::{IntoStringError, nbt::servers_dat};
+
+use crate::state::{
+    InfoMessage, Launcher, MenuServerList, Message, ServerListMessage, ServerStatusMessage, State,
+};
+
+impl Launcher {
+    pub fn update_server_list(&mut self, msg: ServerListMessage) -> Task<Message> {
+        match msg {
+            ServerListMessage::Open => {
+                self.state = State::GenericMessage("Loading server list...".to_owned());
+                let instance = self.instance().clone();
+                return Task::perform(
+                    async move { servers_dat::load(&instance).await.strerr() },
+                    |n| ServerListMessage::Loaded(n).into(),
+                );
+            }
+            ServerListMessage::Loaded(res) => match res {
+                Ok(servers) => {
+                    self.state = State::ServerList(MenuServerList {
+                        servers,
+                        is_changed: false,
+                        is_working: false,
+                    });
+                    return Task::done(ServerStatusMessage::Refresh.into());
+                }
+                Err(err) => self.set_error(err),
+            },
+            ServerListMessage::Add => self.edit_server_list(|servers| {
+                servers.push(servers_dat::ServerEntry::new(
+                    "Minecraft Server".to_owned(),
+                    String::new(),
+                ));
+            }),
+            ServerListMessage::NameChanged(i, name) => self.edit_server_list(|servers| {
+                if let Some(server) = servers.get_mut(i) {
+                    server.name = name;
+                }
+            }),
+            ServerListMessage::AddressChanged(i, ip) => self.edit_server_list(|servers| {
+                if let Some(server) = servers.get_mut(i) {
+                    server.ip = ip.trim().to_owned();
+                }
+            }),
+            ServerListMessage::Remove(i) => self.edit_server_list(|servers| {
+                if i < servers.len() {
+                    servers.remove(i);
+                }
+            }),
+            ServerListMessage::MoveUp(i) => self.edit_server_list(|servers| {
+                if i > 0 && i < servers.len() {
+                    servers.swap(i, i - 1);
+                }
+            }),
+            ServerListMessage::Save => {
+                let State::ServerList(menu) = &mut self.state else {
+                    return Task::none();
+                };
+                menu.is_working = true;
+                let servers = menu.servers.clone();
+                let instance = self.instance().clone();
+                return Task::perform(
+                    async move { servers_dat::save(&instance, servers).await.strerr() },
+                    |n| ServerListMessage::Saved(n).into(),
+                );
+            }
+            ServerListMessage::Saved(res) => match res {
+                Ok(()) => {
+                    return self.go_to_main_menu(Some(InfoMessage::success("Saved server list")));
+                }
+                Err(err) => self.set_error(err),
+            },
+        }
+        Task::none()
+    }
+
+    fn edit_server_list(&mut self, f: impl FnOnce(&mut Vec<servers_dat::ServerEntry>)) {
+        if let State::ServerList(menu) = &mut self.state {
+            f(&mut menu.servers);
+            menu.is_changed = true;
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use iced::Task;
+use ql_core::{IntoStringError, json::VersionDetails};
+use ql_servers::{ServerProperties, known_properties};
+
+use crate::state::{
+    InfoMessage, Launcher, MenuServerProperties, Message, ServerPropertiesMessage, State,
+};
+
+impl Launcher {
+    pub fn update_server_properties(&mut self, msg: ServerPropertiesMessage) -> Task<Message> {
+        match msg {
+            ServerPropertiesMessage::Open => {
+                self.state = State::GenericMessage("Loading server.properties...".to_owned());
+                let dir = self.instance().get_instance_path();
+                return Task::perform(
+                    async move {
+                        let details = VersionDetails::load_from_path(&dir).await.strerr()?;
+                        let properties = ServerProperties::load_from_dir(&dir).await.strerr()?;
+                        Ok((properties, known_properties(&details).collect()))
+                    },
+                    |n| ServerPropertiesMessage::Loaded(n).into(),
+                );
+            }
+            ServerPropertiesMessage::Loaded(res) => match res {
+                Ok((properties, known)) => {
+                    self.state = State::ServerProperties(MenuServerProperties {
+                        is_new: properties.is_none(),
+                        properties: properties.unwrap_or_default(),
+                        known,
+                    });
+                }
+                Err(err) => self.set_error(err),
+            },
+            ServerPropertiesMessage::Edit(key, value) => {
+                if let State::ServerProperties(menu) = &mut self.state {
+                    menu.properties.set(&key, value);
+                }
+            }
+            ServerPropertiesMessage::Reset(key) => {
+                if let State::ServerProperties(menu) = &mut self.state {
+                    menu.properties.remove(&key);
+                }
+            }
+            ServerPropertiesMessage::Save => {
+                if let State::ServerProperties(menu) = &self.state {
+                    let properties = menu.properties.clone();
+                    let dir = self.instance().get_instance_path();
+                    return Task::perform(
+                        async move { properties.save_to_dir(&dir).await.strerr() },
+                        |n| ServerPropertiesMessage::Saved(n).into(),
+                    );
+                }
+            }
+            ServerPropertiesMessage::Saved(res) => match res {
+                Ok(()) => {
+                    return self
+                        .go_to_main_menu(Some(InfoMessage::success("Saved server.properties")));
+                }
+                Err(err) => self.set_error(err),
+            },
+        }
+        Task::none()
+    }
+}
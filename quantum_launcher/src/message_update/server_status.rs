@@ -0,0 +1,47 @@
+use iced::Task;
+use ql_core::IntoStringError;
+use ql_servers::ServerStatus;
+
+use crate::state::{Launcher, Message, ServerStatusMessage, State, StatusTarget};
+
+impl Launcher {
+    pub fn update_server_status(&mut self, msg: ServerStatusMessage) -> Task<Message> {
+        match msg {
+            ServerStatusMessage::Refresh => {
+                let mut targets: Vec<StatusTarget> = self
+                    .processes
+                    .iter()
+                    .filter(|(instance, process)| instance.is_server() && !process.is_stopping)
+                    .map(|(instance, _)| StatusTarget::Local(instance.clone()))
+                    .collect();
+                if let State::ServerList(menu) = &self.state {
+                    targets.extend(
+                        menu.servers
+                            .iter()
+                            .filter(|n| !n.ip.is_empty())
+                            .map(|n| StatusTarget::Address(n.ip.clone())),
+                    );
+                }
+                // Forget servers that aren't shown anymore
+                self.server_status.retain(|k, _| targets.contains(k));
+
+                return Task::batch(targets.into_iter().map(|target| {
+                    Task::perform(ping(target.clone()), move |n| {
+                        ServerStatusMessage::Loaded(target.clone(), n).into()
+                    })
+                }));
+            }
+            ServerStatusMessage::Loaded(target, res) => {
+                self.server_status.insert(target, res);
+            }
+        }
+        Task::none()
+    }
+}
+
+async fn ping(target: StatusTarget) -> Result<ServerStatus, String> {
+    match target {
+        StatusTarget::Local(instance) => ql_servers::ping_local(&instance).await.strerr(),
+        StatusTarget::Address(address) => ql_servers::ping(&address).await.strerr(),
+    }
+}
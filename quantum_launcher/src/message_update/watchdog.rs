@@ -0,0 +1,146 @@
+use chrono::NaiveTime;
+use iced::Task;
+use ql_core::IntoStringError;
+use ql_servers::WatchdogConfig;
+
+use crate::state::{Launcher, MenuWatchdog, Message, State, WatchdogMessage};
+
+/// Format of the daily restart time input
+const TIME_FORMAT: &str = "%H:%M";
+
+impl Launcher {
+    pub fn update_watchdog(&mut self, msg: WatchdogMessage) -> Task<Message> {
+        match msg {
+            WatchdogMessage::Open => {
+                self.state = State::GenericMessage("Loading...".to_owned());
+                let instance = self.instance().clone();
+                return Task::perform(
+                    async move {
+                        let config = WatchdogConfig::load(&instance).await.strerr()?;
+                        let history = ql_servers::restart_history(&instance).await.strerr()?;
+                        Ok((config, history))
+                    },
+                    |n| WatchdogMessage::Loaded(n).into(),
+                );
+            }
+            WatchdogMessage::Loaded(res) => match res {
+                Ok((config, history)) => {
+                    self.state = State::Watchdog(MenuWatchdog {
+                        daily_restart: config
+                            .daily_restart
+                            .map(|n| n.format(TIME_FORMAT).to_string())
+                            .unwrap_or_default(),
+                        warnings: config
+                            .warnings
+                            .iter()
+                            .map(u32::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        config,
+                        history,
+                    });
+                }
+                Err(err) => self.set_error(err),
+            },
+            WatchdogMessage::RestartOnCrashToggled(t) => {
+                return self.edit_watchdog_config(|config| {
+                    config.restart_on_crash = t;
+                    true
+                });
+            }
+            WatchdogMessage::MaxCrashesChanged(n) => {
+                return self.edit_watchdog_config(|config| parse_into(&n, &mut config.max_crashes));
+            }
+            WatchdogMessage::CrashWindowChanged(n) => {
+                return self.edit_watchdog_config(|config| {
+                    parse_into(&n, &mut config.crash_window_minutes)
+                });
+            }
+            WatchdogMessage::BackoffChanged(n) => {
+                return self
+                    .edit_watchdog_config(|config| parse_into(&n, &mut config.backoff_seconds));
+            }
+            WatchdogMessage::MaxBackoffChanged(n) => {
+                return self.edit_watchdog_config(|config| {
+                    parse_into(&n, &mut config.max_backoff_seconds)
+                });
+            }
+            WatchdogMessage::DailyRestartChanged(input) => {
+                let time = if input.trim().is_empty() {
+                    Some(None)
+                } else {
+                    NaiveTime::parse_from_str(input.trim(), TIME_FORMAT)
+                        .ok()
+                        .map(Some)
+                };
+                if let State::Watchdog(menu) = &mut self.state {
+                    menu.daily_restart = input;
+                }
+                // Only saved once it's a valid time
+                return self.edit_watchdog_config(|config| {
+                    let Some(time) = time else {
+                        return false;
+                    };
+                    config.daily_restart = time;
+                    true
+                });
+            }
+            WatchdogMessage::WarningsChanged(input) => {
+                let warnings: Option<Vec<u32>> = input
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|n| !n.is_empty())
+                    .map(|n| n.parse().ok())
+                    .collect();
+                if let State::Watchdog(menu) = &mut self.state {
+                    menu.warnings = input;
+                }
+                return self.edit_watchdog_config(|config| {
+                    let Some(mut warnings) = warnings else {
+                        return false;
+                    };
+                    warnings.sort_unstable_by(|a, b| b.cmp(a));
+                    warnings.dedup();
+                    config.warnings = warnings;
+                    true
+                });
+            }
+            WatchdogMessage::ConfigSaved(res) => {
+                if let Err(err) = res {
+                    self.set_error(err);
+                }
+            }
+        }
+        Task::none()
+    }
+
+    /// Changes the watchdog config, saving it right away if
+    /// `f` returns true (a running server picks it up).
+    fn edit_watchdog_config(
+        &mut self,
+        f: impl FnOnce(&mut WatchdogConfig) -> bool,
+    ) -> Task<Message> {
+        let State::Watchdog(menu) = &mut self.state else {
+            return Task::none();
+        };
+        if !f(&mut menu.config) {
+            return Task::none();
+        }
+        let config = menu.config.clone();
+        let instance = self.instance().clone();
+        Task::perform(async move { config.save(&instance).await.strerr() }, |n| {
+            WatchdogMessage::ConfigSaved(n).into()
+        })
+    }
+}
+
+fn parse_into(input: &str, value: &mut u32) -> bool {
+    if input.is_empty() {
+        *value = 0;
+    } else if let Ok(n) = input.parse() {
+        *value = n;
+    } else {
+        return false;
+    }
+    true
+}
@@ -0,0 +1,78 @@
+use iced::Task;
+use ql_core::{IntoStringError, nbt::level};
+
+use crate::state::{Launcher, MenuWorlds, Message, State, WorldsMessage};
+
+impl Launcher {
+    pub fn update_worlds(&mut self, msg: WorldsMessage) -> Task<Message> {
+        match msg {
+            WorldsMessage::Open => {
+                self.state = State::GenericMessage("Loading worlds...".to_owned());
+                return self.load_worlds();
+            }
+            WorldsMessage::Loaded(res) => match res {
+                Ok(worlds) => {
+                    self.state = State::Worlds(MenuWorlds {
+                        worlds,
+                        editing: None,
+                        is_working: false,
+                    });
+                }
+                Err(err) => self.set_error(err),
+            },
+            WorldsMessage::Edit(i) => {
+                if let State::Worlds(menu) = &mut self.state {
+                    menu.editing = menu.worlds.get(i).map(|n| (i, n.clone()));
+                }
+            }
+            WorldsMessage::EditCancel => {
+                if let State::Worlds(menu) = &mut self.state {
+                    menu.editing = None;
+                }
+            }
+            WorldsMessage::NameChanged(name) => self.edit_world(|n| n.name = name),
+            WorldsMessage::GameModeChanged(mode) => self.edit_world(|n| n.game_mode = mode),
+            WorldsMessage::DifficultyChanged(d) => self.edit_world(|n| n.difficulty = d),
+            WorldsMessage::HardcoreToggled(t) => self.edit_world(|n| n.hardcore = t),
+            WorldsMessage::CommandsToggled(t) => self.edit_world(|n| n.allow_commands = t),
+            WorldsMessage::Save => {
+                let State::Worlds(MenuWorlds {
+                    editing: Some((_, world)),
+                    is_working,
+                    ..
+                }) = &mut self.state
+                else {
+                    return Task::none();
+                };
+                *is_working = true;
+                let world = world.clone();
+                return Task::perform(async move { world.save().await.strerr() }, |n| {
+                    WorldsMessage::Saved(n).into()
+                });
+            }
+            WorldsMessage::Saved(res) => match res {
+                Ok(()) => return self.load_worlds(),
+                Err(err) => self.set_error(err),
+            },
+        }
+        Task::none()
+    }
+
+    fn load_worlds(&self) -> Task<Message> {
+        let instance = self.instance().clone();
+        Task::perform(
+            async move { level::list_worlds(&instance).await.strerr() },
+            |n| WorldsMessage::Loaded(n).into(),
+        )
+    }
+
+    fn edit_world(&mut self, f: impl FnOnce(&mut level::WorldInfo)) {
+        if let State::Worlds(MenuWorlds {
+            editing: Some((_, world)),
+            ..
+        }) = &mut self.state
+        {
+            f(world);
+        }
+    }
+}
@@ -21,7 +21,7 @@ use iced::{
     widget::{self, scrollable::AbsoluteOffset},
 };
 use ql_core::{
-    DownloadProgress, GenericProgress, Instance, InstanceKind, IntoStringError, ListEntry,
+    DownloadProgress, GenericProgress, Instance, InstanceKind, IntoStringError, ListEntry, Loader,
     OptifineUniqueVersion,
     file_utils::DirItem,
     flags::log_verbose,
@@ -30,14 +30,14 @@ use ql_core::{
     pt,
 };
 use ql_mod_manager::{
-    loaders::paper::PaperVersion,
+    loaders::paper::{BuildChannel, PaperProject, PaperVersion},
     store::{Category, LocalMod, SearchMod},
 };
 use ql_mod_manager::{
     loaders::{self, forge::ForgeInstallProgress, optifine::OptifineInstallProgress},
     store::{
-        CurseforgeNotAllowed, ModConfig, ModId, ModIndex, QueryType, RecommendedMod, SearchResult,
-        SelectedMod, StoreBackendType,
+        CurseforgeNotAllowed, ModConfig, ModId, ModIndex, ModListImport, QueryType, RecommendedMod,
+        SearchResult, SelectedMod, StoreBackendType,
     },
 };
 
@@ -481,6 +481,8 @@ pub enum InfoMessageKind {
     Success,
     AtPath(PathBuf),
     Error,
+    /// An error with a one-click fix
+    ErrorWithFix(Instance, ql_servers::DiagnosticFix),
 }
 
 #[derive(Debug, Clone)]
@@ -622,6 +624,8 @@ pub struct MenuCreateInstanceChoosing {
     pub selected_version: ListEntry,
     pub instance_name: String,
     pub download_assets: bool,
+    /// Servers only: the user agreed to the Minecraft EULA
+    pub accept_eula: bool,
 }
 
 pub enum MenuInstallFabric {
@@ -650,13 +654,37 @@ impl MenuInstallFabric {
 
 pub enum MenuInstallPaper {
     Loading {
+        project: PaperProject,
+        channel: BuildChannel,
+        game_version: String,
         _handle: iced::task::Handle,
     },
     Loaded {
-        version: PaperVersion,
+        project: PaperProject,
+        channel: BuildChannel,
+        game_version: String,
+        /// `None` if there are no builds in the channel
+        version: Option<PaperVersion>,
         versions: Vec<PaperVersion>,
     },
-    Installing,
+    Installing(PaperProject),
+}
+
+impl MenuInstallPaper {
+    pub fn project(&self) -> PaperProject {
+        match self {
+            MenuInstallPaper::Loading { project, .. }
+            | MenuInstallPaper::Loaded { project, .. }
+            | MenuInstallPaper::Installing(project) => *project,
+        }
+    }
+}
+
+pub struct MenuInstallBuildTools {
+    pub loader: Loader,
+    pub progress: ProgressBar<GenericProgress>,
+    pub java_progress: ProgressBar<GenericProgress>,
+    pub is_java_getting_installed: bool,
 }
 
 pub struct MenuInstallForge {
@@ -864,11 +892,251 @@ pub enum MenuWelcome {
 pub struct MenuCurseforgeManualDownload {
     pub not_allowed: HashSet<CurseforgeNotAllowed>,
     pub delete_mods: bool,
+
+    /// Watched for the blocked files, which are
+    /// added automatically as soon as they're downloaded
+    pub downloads_dir: Option<PathBuf>,
+    pub downloads_watcher: Option<FsWatcher>,
+    pub is_scanning: bool,
+}
+
+impl MenuCurseforgeManualDownload {
+    pub fn new(not_allowed: HashSet<CurseforgeNotAllowed>, downloads_dir: Option<PathBuf>) -> Self {
+        let mut menu = Self {
+            not_allowed,
+            delete_mods: true,
+            downloads_dir: None,
+            downloads_watcher: None,
+            is_scanning: false,
+        };
+        menu.set_downloads_dir(downloads_dir);
+        menu
+    }
+
+    pub fn set_downloads_dir(&mut self, dir: Option<PathBuf>) {
+        self.downloads_watcher = dir.as_ref().and_then(|n| FsWatcher::new(n).ok());
+        self.downloads_dir = dir;
+    }
+
+    /// Checks the downloads folder for any of the blocked files.
+    pub fn scan(&mut self, instance: Instance) -> Task<Message> {
+        let Some(dir) = self.downloads_dir.clone() else {
+            return Task::none();
+        };
+        self.is_scanning = true;
+        let pending = self.not_allowed.iter().cloned().collect();
+        Task::perform(
+            async move { ql_mod_manager::store::add_manual_downloads(instance, &dir, pending).await },
+            |n| ManageModsMessage::CurseforgeManualScanned(n.strerr()).into(),
+        )
+    }
+
+    pub fn tick(&mut self, instance: Instance) -> Task<Message> {
+        // Events pile up while scanning, and are handled next tick
+        if !self.is_scanning
+            && self
+                .downloads_watcher
+                .as_ref()
+                .is_some_and(FsWatcher::has_changed)
+        {
+            return self.scan(instance);
+        }
+        Task::none()
+    }
+}
+
+pub struct MenuImportCollection {
+    /// Collection ID or link
+    pub input: String,
+    pub token_input: String,
+    /// Whether a Modrinth token is saved in the keyring
+    pub has_token: bool,
+    pub progress: Option<ProgressBar<GenericProgress>>,
+    pub result: Option<ModListImport>,
+}
+
+pub struct MenuServerProperties {
+    pub properties: ql_servers::ServerProperties,
+    /// Known properties for this server's version
+    pub known: Vec<&'static ql_servers::PropertyInfo>,
+    /// `server.properties` doesn't exist yet
+    /// (it's created when the server is first run)
+    pub is_new: bool,
+}
+
+impl MenuServerProperties {
+    /// Values that don't match the schema, as `(key, problem)`
+    pub fn errors(&self) -> Vec<(&'static str, String)> {
+        self.known
+            .iter()
+            .filter_map(|info| {
+                let value = self.properties.get(info.key)?;
+                Some((info.key, info.kind.validate(value).err()?))
+            })
+            .collect()
+    }
+}
+
+pub struct MenuBackups {
+    /// Newest first
+    pub snapshots: Vec<ql_servers::Snapshot>,
+    pub config: ql_servers::BackupConfig,
+    /// Name for "Restore as new server"
+    pub new_server_name: String,
+    /// A backup/restore is in progress
+    pub is_working: bool,
+}
+
+pub struct MenuNetwork {
+    pub config: ql_servers::NetworkConfig,
+    /// Servers that aren't in the network yet (excluding proxies)
+    pub other_servers: Vec<String>,
+    /// BungeeCord only supports legacy forwarding
+    pub is_bungeecord: bool,
+    /// From the last time the configs were written
+    pub warnings: Vec<String>,
+    pub is_working: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerListTab {
+    Whitelist,
+    Ops,
+    BannedPlayers,
+    BannedIps,
+}
+
+impl PlayerListTab {
+    pub const ALL: &[Self] = &[
+        Self::Whitelist,
+        Self::Ops,
+        Self::BannedPlayers,
+        Self::BannedIps,
+    ];
+}
+
+impl std::fmt::Display for PlayerListTab {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Whitelist => "Whitelist",
+                Self::Ops => "Operators",
+                Self::BannedPlayers => "Banned Players",
+                Self::BannedIps => "Banned IPs",
+            }
+        )
+    }
+}
+
+pub struct MenuPlayers {
+    pub lists: ql_servers::PlayerLists,
+    pub tab: PlayerListTab,
+    /// Player name or IP to add
+    pub input: String,
+    /// Ban reason
+    pub reason: String,
+    pub is_working: bool,
+}
+
+pub struct MenuWatchdog {
+    pub config: ql_servers::WatchdogConfig,
+    /// Newest first
+    pub history: Vec<ql_servers::RestartEvent>,
+    /// Daily restart time being typed (`HH:MM`)
+    pub daily_restart: String,
+    /// Warning times being typed (seconds, comma-separated)
+    pub warnings: String,
+}
+
+pub struct MenuCreateServerFrom {
+    pub source: ServerSource,
+    pub name: String,
+    pub accept_eula: bool,
+    pub is_working: bool,
+}
+
+impl MenuCreateServerFrom {
+    /// Imported folders may have accepted the EULA already,
+    /// otherwise it's asked for when starting them.
+    pub fn needs_eula(&self) -> bool {
+        !self.accept_eula && !matches!(self.source, ServerSource::Folder { .. })
+    }
+}
+
+pub struct MenuClientFromServer {
+    pub server: Instance,
+    pub name: String,
+    /// Client-only mods to add, as comma-separated
+    /// Modrinth project IDs or slugs
+    pub companions: String,
+    pub is_working: bool,
+}
+
+impl MenuClientFromServer {
+    pub fn companion_ids(&self) -> Vec<String> {
+        self.companions
+            .split(',')
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+pub enum ServerSource {
+    Client(Instance),
+    /// Curseforge or Modrinth modpack file
+    Modpack(PathBuf),
+    /// Existing server, see [`ql_servers::import_server`]
+    Folder {
+        path: PathBuf,
+        adopt: bool,
+    },
+}
+
+pub struct MenuWorlds {
+    /// Most recently played first
+    pub worlds: Vec<ql_core::nbt::level::WorldInfo>,
+    /// Index and edited copy of the world being edited
+    pub editing: Option<(usize, ql_core::nbt::level::WorldInfo)>,
+    pub is_working: bool,
+}
+
+pub struct MenuServerList {
+    pub servers: Vec<ql_core::nbt::servers_dat::ServerEntry>,
+    /// There are unsaved changes
+    pub is_changed: bool,
+    pub is_working: bool,
 }
 
 pub struct MenuExportInstance {
     pub entries: Option<Vec<(DirItem, bool)>>,
     pub progress: Option<ProgressBar<GenericProgress>>,
+    pub format: ExportFormat,
+    pub compression: ql_packager::ExportCompression,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    QuantumLauncher,
+    MultiMC,
+}
+
+impl ExportFormat {
+    pub const ALL: &'static [Self] = &[Self::QuantumLauncher, Self::MultiMC];
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ExportFormat::QuantumLauncher => "QuantumLauncher",
+            ExportFormat::MultiMC => "MultiMC / Prism",
+        })
+    }
 }
 
 pub struct MenuLoginAlternate {
@@ -925,7 +1193,17 @@ pub enum State {
     EditJarMods(MenuEditJarMods),
     ImportModpack(ProgressBar<GenericProgress>),
     CurseforgeManualDownload(MenuCurseforgeManualDownload),
+    ImportCollection(MenuImportCollection),
     ExportInstance(MenuExportInstance),
+    ServerProperties(MenuServerProperties),
+    Backups(MenuBackups),
+    Network(MenuNetwork),
+    Worlds(MenuWorlds),
+    Players(MenuPlayers),
+    Watchdog(MenuWatchdog),
+    CreateServerFrom(MenuCreateServerFrom),
+    ClientFromServer(MenuClientFromServer),
+    ServerList(MenuServerList),
 
     Error {
         error: String,
@@ -952,6 +1230,7 @@ pub enum State {
     InstallPaper(MenuInstallPaper),
     InstallFabric(MenuInstallFabric),
     InstallForge(MenuInstallForge),
+    InstallBuildTools(MenuInstallBuildTools),
     InstallOptifine(MenuInstallOptifine),
 
     InstallJava,
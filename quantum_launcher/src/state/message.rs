@@ -6,7 +6,10 @@ use crate::{
         sidebar::{FolderId, SDragLocation, SidebarSelection},
     },
     message_handler::ForgeKind,
-    state::{InfoMessage, LaunchModal, MenuEditModsModal, SidebarScroll},
+    state::{
+        ExportFormat, InfoMessage, LaunchModal, MenuEditModsModal, PlayerListTab, SidebarScroll,
+        StatusTarget,
+    },
     stylesheet::styles::{LauncherThemeColor, LauncherThemeLightness},
 };
 use filthy_rich::PresenceClient;
@@ -23,10 +26,13 @@ use ql_instances::auth::{
     ms::{AuthCodeResponse, AuthTokenResponse},
 };
 use ql_mod_manager::{
-    loaders::{fabric, paper::PaperVersion},
+    loaders::{
+        fabric,
+        paper::{BuildChannel, PaperProject, PaperVersion},
+    },
     store::{
-        Category, CurseforgeNotAllowed, LocalMod, ModId, ModIndex, QueryType, RecommendedMod,
-        SearchMod, SearchResult, StoreBackendType,
+        Category, CurseforgeNotAllowed, LocalMod, ModId, ModIndex, ModListImport, QueryType,
+        RecommendedMod, SearchMod, SearchResult, StoreBackendType,
     },
 };
 
@@ -47,8 +53,9 @@ pub enum InstallPaperMessage {
     End(Res),
     VersionSelected(PaperVersion),
     VersionsLoaded(Res<Vec<PaperVersion>>),
+    ChannelSelected(BuildChannel),
     ButtonClicked,
-    ScreenOpen,
+    ScreenOpen(PaperProject),
 }
 
 #[derive(Debug, Clone)]
@@ -60,6 +67,7 @@ pub enum CreateInstanceMessage {
     VersionSelected(ListEntry),
     NameInput(String),
     ChangeAssetToggle(bool),
+    EulaToggle(bool),
     ChangeKind(InstanceKind),
 
     SearchInput(String),
@@ -86,6 +94,7 @@ pub enum EditInstanceMessage {
     MemoryChanged(f32),
     MemoryInputChanged(String),
     LoggingToggle(bool),
+    LanBroadcastToggle(bool),
     SetMainClass(Option<MainClassMode>, Option<String>),
 
     JavaArgs(ListMessage),
@@ -148,6 +157,9 @@ pub enum ManageModsMessage {
     SetContentFilter(Option<QueryType>),
 
     CurseforgeManualToggleDelete(bool),
+    CurseforgeManualScanned(Res<Vec<CurseforgeNotAllowed>>),
+    CurseforgeManualPickDir,
+    CurseforgeManualDirPicked(Option<PathBuf>),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -221,6 +233,169 @@ pub enum EditPresetsMessage {
     LoadComplete(Res<HashSet<CurseforgeNotAllowed>>),
 }
 
+#[derive(Debug, Clone)]
+pub enum ImportCollectionMessage {
+    Open,
+    InputChanged(String),
+    TokenInputChanged(String),
+    TokenSave,
+    TokenClear,
+    ImportCollection,
+    ImportFollowed,
+    Done(Res<ModListImport>),
+}
+
+#[derive(Debug, Clone)]
+pub enum NetworkMessage {
+    Open,
+    /// The network, the servers that can be added to it,
+    /// and whether the proxy is BungeeCord
+    Loaded(Res<(ql_servers::NetworkConfig, Vec<String>, bool)>),
+    AddBackend(String),
+    RemoveBackend(usize),
+    MoveBackendUp(usize),
+    ForwardingChanged(ql_servers::ForwardingMode),
+    /// Save the network and write the server configs.
+    /// The field represents whether to start the network afterwards.
+    Apply(bool),
+    /// Warnings, and whether to start the network
+    Applied(Res<Vec<String>>, bool),
+    /// Starts these servers one after another
+    /// (skipping running ones)
+    Start(Vec<Instance>),
+    Stop,
+}
+
+#[derive(Debug, Clone)]
+pub enum ServerStatusMessage {
+    /// Pings running servers, and the entries of
+    /// the server list if it's open
+    Refresh,
+    Loaded(StatusTarget, Res<ql_servers::ServerStatus>),
+}
+
+#[derive(Debug, Clone)]
+pub enum PlayersMessage {
+    Open,
+    Loaded(Res<ql_servers::PlayerLists>),
+    TabChanged(PlayerListTab),
+    InputChanged(String),
+    ReasonChanged(String),
+    Add,
+    /// Removes the player (or IP) from the current list
+    Remove(String),
+    OpLevelChanged(String, u8),
+    Done(Res),
+}
+
+#[derive(Debug, Clone)]
+pub enum WorldsMessage {
+    Open,
+    Loaded(Res<Vec<ql_core::nbt::level::WorldInfo>>),
+    Edit(usize),
+    EditCancel,
+    NameChanged(String),
+    GameModeChanged(ql_core::nbt::level::GameMode),
+    DifficultyChanged(ql_core::nbt::level::Difficulty),
+    HardcoreToggled(bool),
+    CommandsToggled(bool),
+    Save,
+    Saved(Res),
+}
+
+#[derive(Debug, Clone)]
+pub enum ServerListMessage {
+    Open,
+    Loaded(Res<Vec<ql_core::nbt::servers_dat::ServerEntry>>),
+    Add,
+    NameChanged(usize, String),
+    AddressChanged(usize, String),
+    Remove(usize),
+    MoveUp(usize),
+    Save,
+    Saved(Res),
+}
+
+#[derive(Debug, Clone)]
+pub enum BackupsMessage {
+    Open,
+    Loaded(Res<(Vec<ql_servers::Snapshot>, ql_servers::BackupConfig)>),
+    Create,
+    /// Restore the snapshot with this ID over the current worlds
+    Restore(String),
+    /// Restore the snapshot with this ID into a new server
+    RestoreAsNew(String),
+    /// Returns the name of the instance restored into
+    Restored(Res<String>),
+    Delete(String),
+    /// Reloads the snapshot list after creating/deleting
+    Refresh(Res),
+    NewServerNameChanged(String),
+    IntervalChanged(String),
+    OnStopToggled(bool),
+    KeepLastChanged(String),
+    KeepDailyChanged(String),
+    KeepWeeklyChanged(String),
+    ConfigSaved(Res),
+}
+
+#[derive(Debug, Clone)]
+pub enum WatchdogMessage {
+    Open,
+    Loaded(Res<(ql_servers::WatchdogConfig, Vec<ql_servers::RestartEvent>)>),
+    RestartOnCrashToggled(bool),
+    MaxCrashesChanged(String),
+    CrashWindowChanged(String),
+    BackoffChanged(String),
+    MaxBackoffChanged(String),
+    DailyRestartChanged(String),
+    WarningsChanged(String),
+    ConfigSaved(Res),
+}
+
+#[derive(Debug, Clone)]
+pub enum CreateServerFromMessage {
+    /// From the selected client instance
+    OpenFromInstance,
+    /// Asks for a modpack file first
+    OpenFromModpack,
+    /// Asks for an existing server folder first
+    OpenFromFolder,
+    NameInput(String),
+    AdoptToggled(bool),
+    EulaToggle(bool),
+    Start,
+    End(Res<ql_servers::CreatedServer>),
+}
+
+#[derive(Debug, Clone)]
+pub enum ClientFromServerMessage {
+    /// For the selected server
+    Open,
+    NameInput(String),
+    CompanionsInput(String),
+    CreateInstance,
+    /// Asks where to save the `.mrpack` first
+    ExportPack,
+    InstanceCreated(Res<ql_packager::ClientFromServer>),
+    PackExported(Res),
+}
+
+#[derive(Debug, Clone)]
+pub enum ServerPropertiesMessage {
+    Open,
+    Loaded(
+        Res<(
+            Option<ql_servers::ServerProperties>,
+            Vec<&'static ql_servers::PropertyInfo>,
+        )>,
+    ),
+    Edit(String, String),
+    Reset(String),
+    Save,
+    Saved(Res),
+}
+
 #[derive(Debug, Clone)]
 pub enum RecommendedModMessage {
     Open,
@@ -476,6 +651,12 @@ pub enum LaunchMessage {
     End(Res<LaunchedProcess>),
     Kill,
     GameExited(Res<(ExitStatus, Instance, Option<Diagnostic>)>),
+    ApplyFix(Instance, ql_servers::DiagnosticFix),
+    FixApplied(Res<String>),
+    /// The server can't start until the user accepts
+    /// the Minecraft EULA. The message starts it again.
+    EulaNeeded(Instance, Box<Message>),
+    AcceptEula(Instance, Box<Message>),
 }
 
 #[derive(Debug, Clone)]
@@ -507,6 +688,17 @@ pub enum Message {
     EditPresets(EditPresetsMessage),
     ExportMods(ExportModsTextMessage),
     RecommendedMods(RecommendedModMessage),
+    ImportCollection(ImportCollectionMessage),
+    ServerProperties(ServerPropertiesMessage),
+    Backups(BackupsMessage),
+    Network(NetworkMessage),
+    Worlds(WorldsMessage),
+    ServerList(ServerListMessage),
+    ServerStatus(ServerStatusMessage),
+    Players(PlayersMessage),
+    Watchdog(WatchdogMessage),
+    CreateServerFrom(CreateServerFromMessage),
+    ClientFromServer(ClientFromServerMessage),
     MainMenu(MainMenuMessage),
     Sidebar(SidebarMessage),
     ModDescription(ModDescriptionMessage),
@@ -521,6 +713,11 @@ pub enum Message {
 
     InstallForge(ForgeKind),
     InstallForgeEnd(Res),
+    /// Spigot or Bukkit
+    InstallBuildTools(Loader),
+    InstallBuildToolsEnd(Res),
+    InstallBungeeCord,
+    InstallBungeeCordEnd(Res),
     InstallPaper(InstallPaperMessage),
 
     UninstallLoaderConfirm(Box<Message>, Loader),
@@ -530,8 +727,10 @@ pub enum Message {
     #[allow(unused)]
     ExportInstanceOpen,
     ExportInstanceToggleItem(usize, bool),
+    ExportInstanceFormat(ExportFormat),
+    ExportInstanceCompression(ql_packager::ExportCompression),
     ExportInstanceStart,
-    ExportInstanceFinished(Res<Vec<u8>>),
+    ExportInstanceFinished(Res),
     ExportInstanceLoaded(Res<Vec<DirItem>>),
 
     CoreCopyError,
@@ -564,6 +763,11 @@ pub enum Message {
 
     ServerCommandEdit(String),
     ServerCommandSubmit,
+    /// RCON gives the output directly, instead of in the log
+    ServerCommandOutput(Res<(Instance, Option<String>)>),
+    /// Go through sent commands (`true` for older)
+    ServerCommandHistory(bool),
+    ServerCommandComplete,
 
     LicenseOpen,
     LicenseChangeTab(LicenseTab),
@@ -591,6 +795,17 @@ from_m!(InstallFabric, InstallFabricMessage);
 from_m!(EditPresets, EditPresetsMessage);
 from_m!(ExportMods, ExportModsTextMessage);
 from_m!(RecommendedMods, RecommendedModMessage);
+from_m!(ImportCollection, ImportCollectionMessage);
+from_m!(ServerProperties, ServerPropertiesMessage);
+from_m!(Backups, BackupsMessage);
+from_m!(Network, NetworkMessage);
+from_m!(Worlds, WorldsMessage);
+from_m!(ServerList, ServerListMessage);
+from_m!(ServerStatus, ServerStatusMessage);
+from_m!(Players, PlayersMessage);
+from_m!(Watchdog, WatchdogMessage);
+from_m!(CreateServerFrom, CreateServerFromMessage);
+from_m!(ClientFromServer, ClientFromServerMessage);
 from_m!(Account, AccountMessage);
 from_m!(CreateInstance, CreateInstanceMessage);
 from_m!(EditInstance, EditInstanceMessage);
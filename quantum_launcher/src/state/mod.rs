@@ -19,7 +19,6 @@ use ql_core::{
     request::{CLIENT, build_middleware},
 };
 use ql_instances::auth::{AccountData, AccountType, ms::CLIENT_ID};
-use tokio::process::ChildStdin;
 
 use crate::{
     config::{LauncherConfig, SIDEBAR_WIDTH},
@@ -48,6 +47,8 @@ pub struct InstanceLog {
     pub log: Vec<String>,
     pub has_crashed: bool,
     pub command: String,
+    /// Sent console commands (servers only)
+    pub history: ql_servers::CommandHistory,
 }
 
 pub struct Launcher {
@@ -81,6 +82,12 @@ pub struct Launcher {
 
     pub processes: HashMap<Instance, GameProcess>,
     pub logs: HashMap<Instance, InstanceLog>,
+    /// Last Server List Ping result of each server shown,
+    /// refreshed in the background
+    pub server_status: HashMap<StatusTarget, Res<ql_servers::ServerStatus>>,
+    /// Restarts servers after crashes and on schedule.
+    /// Kept across runs to detect crash loops
+    pub watchdogs: HashMap<Instance, ql_servers::Watchdog>,
 
     pub window_state: WindowState,
     pub keys_pressed: HashSet<iced::keyboard::Key>,
@@ -172,10 +179,32 @@ impl FsWatcher {
     }
 }
 
+/// A server whose status is pinged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StatusTarget {
+    /// A server of the launcher, while it's running
+    Local(Instance),
+    /// An entry of a client's server list
+    Address(String),
+}
+
 pub struct GameProcess {
     pub child: LaunchedProcess,
     pub receiver: Option<Receiver<LogLine>>,
-    pub server_input: Option<(ChildStdin, bool)>,
+    /// Whether a graceful `stop` was sent (servers only).
+    /// Killing again while stopping kills it forcefully.
+    pub is_stopping: bool,
+    /// Parsed from the logs (servers only)
+    pub events: Option<Receiver<ql_servers::ServerEvent>>,
+    pub monitor: ql_servers::ServerMonitor,
+}
+
+impl GameProcess {
+    pub fn read_events(&mut self) {
+        while let Some(event) = self.events.as_ref().and_then(|n| n.try_recv().ok()) {
+            self.monitor.apply(&event);
+        }
+    }
 }
 
 impl Launcher {
@@ -253,6 +282,8 @@ impl Launcher {
 
             logs: HashMap::new(),
             processes: HashMap::new(),
+            server_status: HashMap::new(),
+            watchdogs: HashMap::new(),
 
             keys_pressed: HashSet::new(),
 
@@ -322,6 +353,8 @@ impl Launcher {
 
             logs: HashMap::new(),
             processes: HashMap::new(),
+            server_status: HashMap::new(),
+            watchdogs: HashMap::new(),
             accounts: HashMap::new(),
             keys_pressed: HashSet::new(),
 
@@ -10,8 +10,8 @@ use ql_mod_manager::store::ModIndex;
 use crate::state::{
     AutoSaveKind, EditInstanceMessage, GameProcess, InstallModsMessage, InstanceLog, LaunchModal,
     LaunchTab, Launcher, LogState, ManageJarModsMessage, ManageModsMessage, MenuCreateInstance,
-    MenuEditMods, MenuExportInstance, MenuInstallFabric, MenuInstallOptifine, MenuLaunch,
-    MenuLoginMS, MenuModsDownload, MenuRecommendedMods, Message, State,
+    MenuEditMods, MenuExportInstance, MenuImportCollection, MenuInstallFabric, MenuInstallOptifine,
+    MenuLaunch, MenuLoginMS, MenuModsDownload, MenuRecommendedMods, Message, State,
 };
 use crate::{config::SIDEBAR_WIDTH, state::InfoMessage};
 
@@ -48,6 +48,7 @@ impl Launcher {
                 }
 
                 for (instance, process) in &mut self.processes {
+                    process.read_events();
                     let log_state = if let State::Launch(menu) = &mut self.state {
                         &mut menu.log_state
                     } else {
@@ -77,6 +78,10 @@ impl Launcher {
                 let instance = self.selected_instance.as_ref().unwrap();
                 return menu.tick(instance);
             }
+            State::CurseforgeManualDownload(menu) => {
+                let instance = self.selected_instance.clone().unwrap();
+                return menu.tick(instance);
+            }
             State::InstallFabric(menu) => {
                 if let MenuInstallFabric::Loaded {
                     progress: Some(progress),
@@ -86,6 +91,12 @@ impl Launcher {
                     progress.tick();
                 }
             }
+            State::InstallBuildTools(menu) => {
+                menu.progress.tick();
+                if menu.java_progress.tick() {
+                    menu.is_java_getting_installed = true;
+                }
+            }
             State::InstallForge(menu) => {
                 menu.forge_progress.tick();
                 if menu.java_progress.tick() {
@@ -155,6 +166,10 @@ impl Launcher {
             }
             State::AccountLoginProgress(progress)
             | State::ImportModpack(progress)
+            | State::ImportCollection(MenuImportCollection {
+                progress: Some(progress),
+                ..
+            })
             | State::ExportInstance(MenuExportInstance {
                 progress: Some(progress),
                 ..
@@ -167,16 +182,25 @@ impl Launcher {
             | State::LoginAlternate(_)
             | State::AccountLogin
             | State::ExportInstance(_)
+            | State::ImportCollection(_)
             | State::ConfirmAction { .. }
             | State::ChangeLog
             | State::Welcome(_)
             | State::License(_)
             | State::LoginMS(MenuLoginMS { .. })
             | State::GenericMessage(_)
-            | State::CurseforgeManualDownload(_)
             | State::LogUploadResult { .. }
             | State::InstallPaper(_)
             | State::CreateShortcut(_)
+            | State::ServerProperties(_)
+            | State::Backups(_)
+            | State::Network(_)
+            | State::Worlds(_)
+            | State::ServerList(_)
+            | State::Players(_)
+            | State::Watchdog(_)
+            | State::CreateServerFrom(_)
+            | State::ClientFromServer(_)
             | State::ModDescription(_)
             | State::ExportModsText(_) => {}
         }
@@ -316,6 +340,11 @@ impl Launcher {
                         log: vec![log_start],
                         has_crashed: false,
                         command: String::new(),
+                        history: if instance.is_server() {
+                            ql_servers::CommandHistory::load(&instance.get_instance_path())
+                        } else {
+                            ql_servers::CommandHistory::default()
+                        },
                     }
                 })
                 .log
@@ -402,7 +431,7 @@ impl MenuCreateInstance {
     }
 }
 
-fn update_log_render_state(log_state: Option<&mut LogState>, mut message: String) {
+pub fn update_log_render_state(log_state: Option<&mut LogState>, mut message: String) {
     if let Some(state) = log_state {
         use iced::widget::text_editor::{Action, Edit, Motion};
         // TODO: preserve selection
@@ -1,7 +1,6 @@
-use iced::{Task, futures::executor::block_on};
-use ql_core::{InstanceKind, IntoIoError, IntoStringError, err, file_utils::DirItem, info};
+use iced::Task;
+use ql_core::{InstanceKind, IntoStringError, err, file_utils::DirItem, info};
 use std::fmt::Write;
-use tokio::io::AsyncWriteExt;
 
 #[allow(unused)]
 use owo_colors::OwoColorize;
@@ -10,9 +9,9 @@ use owo_colors::OwoColorize;
 use crate::launcher_update::UpdateCheckInfo;
 use crate::{
     state::{
-        AutoSaveKind, CustomJarState, FsWatcher, GameProcess, InfoMessage, Launcher,
-        LauncherSettingsMessage, ManageModsMessage, MenuExportInstance, MenuLicense, MenuWelcome,
-        Message, ProgressBar, State, get_entries,
+        AutoSaveKind, CustomJarState, ExportFormat, FsWatcher, InfoMessage, Launcher,
+        LauncherSettingsMessage, ManageModsMessage, MenuExportInstance, MenuLaunch, MenuLicense,
+        MenuWelcome, Message, ProgressBar, State, get_entries,
     },
     stylesheet::styles::LauncherThemeLightness,
 };
@@ -46,6 +45,8 @@ impl Launcher {
 
             Message::UninstallLoaderEnd(Err(err))
             | Message::InstallForgeEnd(Err(err))
+            | Message::InstallBuildToolsEnd(Err(err))
+            | Message::InstallBungeeCordEnd(Err(err))
             | Message::CoreListLoaded(Err(err)) => self.set_error(err),
 
             Message::WelcomeContinueToTheme => {
@@ -62,6 +63,17 @@ impl Launcher {
             Message::ExportMods(msg) => return self.update_export_mods(msg),
             Message::ManageJarMods(msg) => return self.update_manage_jar_mods(msg),
             Message::RecommendedMods(msg) => return self.update_recommended_mods(msg),
+            Message::ImportCollection(msg) => return self.update_import_collection(msg),
+            Message::ServerProperties(msg) => return self.update_server_properties(msg),
+            Message::Backups(msg) => return self.update_backups(msg),
+            Message::Network(msg) => return self.update_network(msg),
+            Message::Worlds(msg) => return self.update_worlds(msg),
+            Message::ServerList(msg) => return self.update_server_list(msg),
+            Message::ServerStatus(msg) => return self.update_server_status(msg),
+            Message::Players(msg) => return self.update_players(msg),
+            Message::Watchdog(msg) => return self.update_watchdog(msg),
+            Message::CreateServerFrom(msg) => return self.update_create_server_from(msg),
+            Message::ClientFromServer(msg) => return self.update_client_from_server(msg),
             Message::Window(msg) => return self.update_window_msg(msg),
             Message::Notes(msg) => return self.update_notes(msg),
             Message::GameLog(msg) => return self.update_game_log(msg),
@@ -179,6 +191,35 @@ impl Launcher {
             Message::InstallForge(kind) => {
                 return self.install_forge(kind);
             }
+            Message::InstallBuildTools(loader) => {
+                return self.install_buildtools(loader);
+            }
+            Message::InstallBuildToolsEnd(Ok(())) => {
+                let loader = if let State::InstallBuildTools(menu) = &self.state {
+                    menu.loader.to_string()
+                } else {
+                    "loader".to_owned()
+                };
+                return self.go_to_edit_mods_menu(Some(InfoMessage::success(format!(
+                    "Installed {loader}"
+                ))));
+            }
+            Message::InstallBungeeCord => {
+                self.state = State::GenericMessage("Installing BungeeCord...".to_owned());
+                let instance = self.instance().clone();
+                return Task::perform(
+                    async move {
+                        ql_mod_manager::loaders::bungeecord::install(instance)
+                            .await
+                            .strerr()
+                    },
+                    Message::InstallBungeeCordEnd,
+                );
+            }
+            Message::InstallBungeeCordEnd(Ok(())) => {
+                return self
+                    .go_to_edit_mods_menu(Some(InfoMessage::success("Installed BungeeCord")));
+            }
             Message::InstallForgeEnd(Ok(())) => {
                 return self
                     .go_to_edit_mods_menu(Some(InfoMessage::success("Installed Forge/NeoForge")));
@@ -225,21 +266,72 @@ impl Launcher {
             Message::ServerCommandSubmit => {
                 let server = self.selected_instance.as_ref().unwrap();
                 debug_assert!(server.is_server());
-                if let (
-                    Some(log),
-                    Some(GameProcess {
-                        server_input: Some((stdin, _)),
-                        ..
-                    }),
-                ) = (self.logs.get_mut(server), self.processes.get_mut(server))
-                {
-                    let log_cloned = format!("{}\n", log.command);
-                    let future = stdin.write_all(log_cloned.as_bytes());
+                if let Some(log) = self.logs.get_mut(server) {
+                    let command = std::mem::take(&mut log.command);
                     // Make the input command visible in the log
-                    log.log.push(format!("> {}", log.command));
+                    log.log.push(format!("> {command}"));
+                    log.history.push(&command);
 
-                    log.command.clear();
-                    _ = block_on(future);
+                    // Falls back to RCON if there's no console
+                    let process = self.processes.get(server).map(|n| n.child.clone());
+                    let history = log.history.clone();
+                    let server = server.clone();
+                    return Task::perform(
+                        async move {
+                            let output = ql_servers::send_console_command(
+                                process.as_ref(),
+                                server.get_name(),
+                                &command,
+                            )
+                            .await
+                            .strerr()?;
+                            history.save(&server.get_instance_path()).await.strerr()?;
+                            Ok((server, output))
+                        },
+                        Message::ServerCommandOutput,
+                    );
+                }
+            }
+            Message::ServerCommandOutput(res) => match res {
+                Ok((server, Some(output))) if !output.is_empty() => {
+                    let output = format!("{}\n", output.trim_end());
+                    if let Some(log) = self.logs.get_mut(&server) {
+                        log.log.push(output.clone());
+                    }
+                    if let State::Launch(MenuLaunch { log_state, .. }) = &mut self.state {
+                        if self.selected_instance.as_ref() == Some(&server) {
+                            crate::tick::update_log_render_state(log_state.as_mut(), output);
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => self.set_error(err),
+            },
+            Message::ServerCommandHistory(older) => {
+                let server = self.selected_instance.as_ref().unwrap();
+                if let Some(log) = self.logs.get_mut(server) {
+                    let command = if older {
+                        log.history.older()
+                    } else {
+                        log.history.newer()
+                    };
+                    if let Some(command) = command {
+                        log.command = command.to_owned();
+                        return iced::widget::text_input::move_cursor_to_end(
+                            iced::widget::text_input::Id::new("MenuLaunch:server_command"),
+                        );
+                    }
+                }
+            }
+            Message::ServerCommandComplete => {
+                let server = self.selected_instance.as_ref().unwrap();
+                if let Some(log) = self.logs.get_mut(server) {
+                    if let Some(command) = ql_servers::complete_command(&log.command) {
+                        log.command = command;
+                        return iced::widget::text_input::move_cursor_to_end(
+                            iced::widget::text_input::Id::new("MenuLaunch:server_command"),
+                        );
+                    }
                 }
             }
             Message::CoreListLoaded(Ok((list, kind))) => {
@@ -285,6 +377,8 @@ impl Launcher {
                 self.state = State::ExportInstance(MenuExportInstance {
                     entries: None,
                     progress: None,
+                    format: ExportFormat::default(),
+                    compression: ql_packager::ExportCompression::default(),
                 });
                 return Task::perform(
                     ql_core::file_utils::read_filenames_from_dir(
@@ -344,12 +438,36 @@ impl Launcher {
                     }
                 }
             }
+            Message::ExportInstanceFormat(f) => {
+                if let State::ExportInstance(MenuExportInstance { format, .. }) = &mut self.state {
+                    *format = f;
+                }
+            }
+            Message::ExportInstanceCompression(c) => {
+                if let State::ExportInstance(MenuExportInstance { compression, .. }) =
+                    &mut self.state
+                {
+                    *compression = c;
+                }
+            }
             Message::ExportInstanceStart => {
                 if let State::ExportInstance(MenuExportInstance {
                     entries: Some(entries),
                     progress,
+                    format,
+                    compression,
                 }) = &mut self.state
                 {
+                    let instance = self.selected_instance.clone().unwrap();
+                    // Ask first, so the zip can be written straight to disk
+                    let Some(output) = rfd::FileDialog::new()
+                        .set_file_name(format!("{}.zip", instance.get_name()))
+                        .add_filter("Zip archive", &["zip"])
+                        .save_file()
+                    else {
+                        return Task::none();
+                    };
+
                     let (send, recv) = std::sync::mpsc::channel();
                     *progress = Some(ProgressBar::with_recv(recv));
 
@@ -358,26 +476,31 @@ impl Launcher {
                         .filter_map(|(n, b)| (!b).then_some(format!(".minecraft/{}", n.name)))
                         .collect();
 
-                    return Task::perform(
-                        ql_packager::export_instance(
-                            self.selected_instance.clone().unwrap(),
-                            exceptions,
-                            Some(send),
+                    return match format {
+                        ExportFormat::QuantumLauncher => Task::perform(
+                            ql_packager::export_instance(
+                                instance,
+                                exceptions,
+                                output,
+                                *compression,
+                                Some(send),
+                            ),
+                            |n| Message::ExportInstanceFinished(n.strerr()),
                         ),
-                        |n| Message::ExportInstanceFinished(n.strerr()),
-                    );
+                        ExportFormat::MultiMC => Task::perform(
+                            ql_packager::export_instance_multimc(
+                                instance,
+                                exceptions,
+                                output,
+                                Some(send),
+                            ),
+                            |n| Message::ExportInstanceFinished(n.strerr()),
+                        ),
+                    };
                 }
             }
             Message::ExportInstanceFinished(res) => match res {
-                Ok(bytes) => {
-                    if let Some(path) = rfd::FileDialog::new().save_file() {
-                        if let Err(err) = std::fs::write(&path, bytes).path(path) {
-                            self.set_error(err);
-                        } else {
-                            return self.go_to_main_menu(None);
-                        }
-                    }
-                }
+                Ok(()) => return self.go_to_main_menu(None),
                 Err(err) => self.set_error(err),
             },
             Message::LicenseOpen => {
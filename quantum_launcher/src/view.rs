@@ -166,11 +166,25 @@ impl Launcher {
             State::License(menu) => menu.view(),
             State::ExportModsText(menu) => menu.view(),
             State::InstallForge(menu) => menu.view(),
+            State::InstallBuildTools(menu) => menu.view(),
             #[cfg(feature = "auto_update")]
             State::UpdateFound(menu) => menu.view(),
             State::InstallOptifine(menu) => menu.view(),
             State::ManagePresets(menu) => menu.view(),
             State::RecommendedMods(menu) => menu.view(),
+            State::ImportCollection(menu) => menu.view(),
+            State::ServerProperties(menu) => menu.view(),
+            State::Backups(menu) => menu.view(
+                self.instance().is_server(),
+                self.processes.contains_key(self.instance()),
+            ),
+            State::Network(menu) => menu.view(self.instance(), &self.processes),
+            State::Worlds(menu) => menu.view(self.processes.contains_key(self.instance())),
+            State::ServerList(menu) => menu.view(&self.server_status),
+            State::Players(menu) => menu.view(self.processes.contains_key(self.instance())),
+            State::Watchdog(menu) => menu.view(),
+            State::CreateServerFrom(menu) => menu.view(),
+            State::ClientFromServer(menu) => menu.view(),
         };
 
         widget::mouse_area(if let State::Launch(_) = &self.state {